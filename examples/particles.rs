@@ -16,6 +16,11 @@ fn main() {
                 resizable: true,
             },
             vsync: Vsync::Disabled,
+            decorations: true,
+            multisampling: 0,
+            atlas_size: 1024,
+            atlas_max_size: 4096,
+            icon: None,
         },
         run,
     );
@@ -45,32 +50,21 @@ fn run(engine: &mut Engine) -> impl FnMut(InputMessage, &mut Engine) {
 
     move |event, engine| match event {
         InputMessage::CloseRequested => engine.stop(),
-        InputMessage::KeyPressed(key) => match key {
-            KeyboardButton::Escape => engine.stop(),
-            _ => {}
-        },
+        InputMessage::KeyPressed(KeyboardButton::Escape) => engine.stop(),
         InputMessage::CursorPressed {
-            button,
+            button: CursorButton::Left,
             ..
-        } => match button {
-            CursorButton::Left => is_dragging = true,
-            _ => {}
-        },
+        } => is_dragging = true,
         InputMessage::CursorReleased {
-            button,
+            button: CursorButton::Left,
             ..
-        } => match button {
-            CursorButton::Left => is_dragging = false,
-            _ => {}
-        },
+        } => is_dragging = false,
         InputMessage::CursorMoved {
             delta,
             ..
-        } => {
-            if is_dragging {
-                screen_transform.translation += delta / screen_transform.scale;
-                screen.set_transform(&screen_transform);
-            }
+        } if is_dragging => {
+            screen_transform.translation += delta.div_element_wise(screen_transform.scale);
+            screen.set_transform(&screen_transform);
         }
         InputMessage::CursorScroll(direction) => {
             match direction {
@@ -111,7 +105,7 @@ impl Particle {
         };
         let particle = Particle {
             pos: pos.truncate(),
-            velocity: velocity,
+            velocity,
             acceleration: Vector2::zero(),
         };
         (sprite, particle)
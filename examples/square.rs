@@ -1,4 +1,5 @@
 use core::time::Duration;
+use storm::cgmath::ElementWise;
 use storm::*;
 
 /// Run with: cargo run --example square --release
@@ -14,6 +15,11 @@ fn main() {
                 resizable: true,
             },
             vsync: Vsync::Disabled,
+            decorations: true,
+            multisampling: 0,
+            atlas_size: 1024,
+            atlas_max_size: 4096,
+            icon: None,
         },
         run,
     );
@@ -63,33 +69,25 @@ fn run(engine: &mut Engine) -> impl FnMut(InputMessage, &mut Engine) {
         InputMessage::KeyPressed(key) => match key {
             KeyboardButton::Escape => engine.stop(),
             KeyboardButton::Tab => {
-                screen_transform.scale = 1.0;
+                screen_transform.set_scale(1.0);
                 screen.set_transform(&screen_transform);
             }
             _ => {}
         },
         InputMessage::CursorPressed {
-            button,
+            button: CursorButton::Left,
             ..
-        } => match button {
-            CursorButton::Left => is_dragging = true,
-            _ => {}
-        },
+        } => is_dragging = true,
         InputMessage::CursorReleased {
-            button,
+            button: CursorButton::Left,
             ..
-        } => match button {
-            CursorButton::Left => is_dragging = false,
-            _ => {}
-        },
+        } => is_dragging = false,
         InputMessage::CursorMoved {
             delta,
             ..
-        } => {
-            if is_dragging {
-                screen_transform.translation += delta / screen_transform.scale;
-                screen.set_transform(&screen_transform);
-            }
+        } if is_dragging => {
+            screen_transform.translation += delta.div_element_wise(screen_transform.scale);
+            screen.set_transform(&screen_transform);
         }
         InputMessage::CursorScroll(direction) => {
             match direction {
@@ -16,6 +16,11 @@ fn main() {
                 resizable: true,
             },
             vsync: Vsync::Disabled,
+            decorations: true,
+            multisampling: 0,
+            atlas_size: 1024,
+            atlas_max_size: 4096,
+            icon: None,
         },
         run,
     );
@@ -31,8 +36,10 @@ fn run(engine: &mut Engine) -> impl FnMut(InputMessage, &mut Engine) {
     let texture_2 = texture_2.sub_texture(0, 0, 16, 16).unwrap();
 
     let mut sprites = Vec::new();
-    let mut sprite = Sprite::default();
-    sprite.texture = texture_1;
+    let mut sprite = Sprite {
+        texture: texture_1,
+        ..Sprite::default()
+    };
     sprites.push(sprite);
     sprite.texture = texture_1.mirror_x();
     sprite.pos.y -= 100.0;
@@ -52,10 +59,7 @@ fn run(engine: &mut Engine) -> impl FnMut(InputMessage, &mut Engine) {
 
     move |event, engine| match event {
         InputMessage::CloseRequested => engine.stop(),
-        InputMessage::KeyPressed(key) => match key {
-            KeyboardButton::Escape => engine.stop(),
-            _ => {}
-        },
+        InputMessage::KeyPressed(KeyboardButton::Escape) => engine.stop(),
         InputMessage::CursorPressed {
             ..
         } => {
@@ -0,0 +1,124 @@
+use crate::animation::sprite::SharedAnimation;
+use crate::animation::AnimationSystem;
+use crate::types::{Sprite, Text};
+use crate::utility::bad::UnsafeShared;
+use cgmath::*;
+
+/// Placement state shared between the animation system and the anchored text handle.
+pub struct SharedAnchor {
+    index: usize,
+    sprite: UnsafeShared<SharedAnimation>,
+    text: Text,
+    offset: Vector2<f32>,
+}
+
+impl SharedAnchor {
+    pub fn new(sprite: UnsafeShared<SharedAnimation>, text: Text, offset: Vector2<f32>) -> SharedAnchor {
+        let mut shared = SharedAnchor {
+            index: 0,
+            sprite,
+            text,
+            offset,
+        };
+        shared.update();
+        shared
+    }
+
+    pub fn set_index(&mut self, index: usize) {
+        self.index = index;
+    }
+
+    /// Moves the text to follow the sprite.
+    pub fn update(&mut self) {
+        self.text.pos = place(self.sprite.sprite(), &self.text, self.offset);
+    }
+}
+
+/// The position of text anchored to the top center of the sprite, after the sprite's scale. Text
+/// with a max width is laid out centered within it, so it's shifted left by half of it to center it
+/// over the sprite.
+fn place(sprite: &Sprite, text: &Text, offset: Vector2<f32>) -> Vector3<f32> {
+    let half_width = text.max_width.map(|width| width / 2.0).unwrap_or(0.0);
+    let half_size = Vector2::new(sprite.size.x as f32, sprite.size.y as f32) / 2.0;
+    Vector3::new(
+        sprite.pos.x + half_size.x - half_width + offset.x,
+        sprite.pos.y + half_size.y + half_size.y * sprite.scale.y.abs() + offset.y,
+        sprite.pos.z,
+    )
+}
+
+/// Text that follows an animated sprite, like a name tag or a health readout. The engine moves it
+/// to the sprite before each update, so `text` is always in place to pass to `Engine::text_append`.
+/// The text's bottom left corner sits on the top center of the sprite, moved by the offset, at the
+/// sprite's depth. Text with a max width is centered over the sprite instead. If the animated
+/// sprite is dropped, the text stays where it last was.
+pub struct AnchoredText {
+    system: UnsafeShared<AnimationSystem>,
+    shared: UnsafeShared<SharedAnchor>,
+}
+
+impl AnchoredText {
+    pub(crate) fn new(
+        system: UnsafeShared<AnimationSystem>,
+        shared: UnsafeShared<SharedAnchor>,
+    ) -> AnchoredText {
+        AnchoredText {
+            system,
+            shared,
+        }
+    }
+
+    /// The text, moved to the sprite.
+    pub fn text(&self) -> &Text {
+        &self.shared.text
+    }
+
+    /// The text, to change anything but its position. Any position set here is replaced the next
+    /// time the text follows the sprite.
+    pub fn text_mut(&mut self) -> &mut Text {
+        &mut self.shared.text
+    }
+
+    /// Sets the offset from the top center of the sprite, moving the text right away. Units are
+    /// measured in pixels.
+    pub fn set_offset(&mut self, offset: Vector2<f32>) {
+        self.shared.offset = offset;
+        self.shared.update();
+    }
+
+    /// Moves the text to the sprite right away, like after the sprite was moved during an update.
+    pub fn follow(&mut self) {
+        self.shared.update();
+    }
+}
+
+impl Drop for AnchoredText {
+    fn drop(&mut self) {
+        self.system.anchor_drop(self.shared.index);
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{colors, Texture};
+
+    #[test]
+    fn placement() {
+        let sprite = Sprite::new(
+            Vector3::new(10.0, 20.0, 0.5),
+            Vector2::new(32.0, 16.0),
+            Texture::default(),
+            colors::WHITE,
+            0.0,
+        );
+        let mut text = Text::default();
+        assert_eq!(place(&sprite, &text, Vector2::new(0.0, 4.0)), Vector3::new(26.0, 40.0, 0.5));
+        text.max_width = Some(100.0);
+        assert_eq!(place(&sprite, &text, Vector2::new(0.0, 4.0)), Vector3::new(-24.0, 40.0, 0.5));
+    }
+}
@@ -0,0 +1,224 @@
+use crate::animation::AnimationSystem;
+use crate::render::SpriteSlots;
+use crate::types::{AnimationMode, SpriteKeyframes};
+use crate::utility::bad::UnsafeShared;
+use crate::utility::pool::PoolHandle;
+
+/// Playback state shared between the animation system and the keyframed sprite handle.
+pub struct SharedKeyframes {
+    index: usize,
+    slots: UnsafeShared<SpriteSlots>,
+    sprite: PoolHandle,
+    keyframes: SpriteKeyframes,
+    mode: AnimationMode,
+    speed: f32,
+    playing: bool,
+    finished: bool,
+    forward: bool,
+    time: f32,
+}
+
+impl SharedKeyframes {
+    pub fn new(
+        slots: UnsafeShared<SpriteSlots>,
+        sprite: PoolHandle,
+        keyframes: SpriteKeyframes,
+        mode: AnimationMode,
+    ) -> SharedKeyframes {
+        let mut shared = SharedKeyframes {
+            index: 0,
+            slots,
+            sprite,
+            keyframes,
+            mode,
+            speed: 1.0,
+            playing: true,
+            finished: false,
+            forward: true,
+            time: 0.0,
+        };
+        shared.sync();
+        shared
+    }
+
+    pub fn set_index(&mut self, index: usize) {
+        self.index = index;
+    }
+
+    /// Advances playback and applies the tracks to the sprite.
+    pub fn update(&mut self, delta: f32) {
+        if !self.playing {
+            return;
+        }
+        let duration = self.keyframes.duration();
+        let step = delta * self.speed;
+        match self.mode {
+            AnimationMode::Once => {
+                self.time += step;
+                if self.time >= duration {
+                    self.time = duration;
+                    self.playing = false;
+                    self.finished = true;
+                }
+            }
+            AnimationMode::Loop => {
+                self.time = if duration > 0.0 {
+                    (self.time + step).rem_euclid(duration)
+                } else {
+                    0.0
+                };
+            }
+            AnimationMode::PingPong => {
+                self.time = if duration > 0.0 {
+                    // Folds the time back and forth across the duration.
+                    let position = if self.forward {
+                        self.time + step
+                    } else {
+                        2.0 * duration - self.time + step
+                    }
+                    .rem_euclid(2.0 * duration);
+                    self.forward = position < duration;
+                    if self.forward {
+                        position
+                    } else {
+                        2.0 * duration - position
+                    }
+                } else {
+                    0.0
+                };
+            }
+        }
+        self.sync();
+    }
+
+    /// Applies the tracks to the sprite. Playback stops if the sprite was removed.
+    fn sync(&mut self) {
+        match self.slots.sprite_mut(self.sprite) {
+            Some(sprite) => self.keyframes.apply(self.time, sprite),
+            None => self.playing = false,
+        }
+    }
+
+    fn restart(&mut self) {
+        self.time = 0.0;
+        self.forward = true;
+        self.playing = true;
+        self.finished = false;
+        self.sync();
+    }
+}
+
+/// Keyframed sprites drive the properties of a sprite created with `Layer::sprite_create` from
+/// keyframe tracks. The engine advances them automatically before each update, and only the
+/// properties with keyframes are changed, so the sprite can still be updated through the layer.
+/// Playback stops if the sprite is removed, and the sprite keeps its last values when this is
+/// dropped.
+pub struct KeyframedSprite {
+    system: UnsafeShared<AnimationSystem>,
+    shared: UnsafeShared<SharedKeyframes>,
+}
+
+impl KeyframedSprite {
+    pub(crate) fn new(
+        system: UnsafeShared<AnimationSystem>,
+        shared: UnsafeShared<SharedKeyframes>,
+    ) -> KeyframedSprite {
+        KeyframedSprite {
+            system,
+            shared,
+        }
+    }
+
+    /// Replaces the keyframes and restarts playback from the start.
+    pub fn set_keyframes(&mut self, keyframes: SpriteKeyframes) {
+        self.shared.keyframes = keyframes;
+        self.shared.restart();
+    }
+
+    /// Sets how playback proceeds after the last keyframe.
+    pub fn set_mode(&mut self, mode: AnimationMode) {
+        self.shared.mode = mode;
+    }
+
+    /// Sets the playback speed. 2.0 plays twice as fast. The default is 1.0.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.shared.speed = speed.max(0.0);
+    }
+
+    /// Resumes playback. If playback already finished, it starts over.
+    pub fn play(&mut self) {
+        if self.is_finished() {
+            self.shared.restart();
+        }
+        self.shared.playing = true;
+    }
+
+    /// Pauses playback at the current time.
+    pub fn pause(&mut self) {
+        self.shared.playing = false;
+    }
+
+    /// Starts playback over from the start.
+    pub fn restart(&mut self) {
+        self.shared.restart();
+    }
+
+    /// If the tracks are currently advancing.
+    pub fn is_playing(&self) -> bool {
+        self.shared.playing
+    }
+
+    /// If playback is in the Once mode and has stopped on the last keyframe.
+    pub fn is_finished(&self) -> bool {
+        self.shared.finished
+    }
+
+    /// How far playback is through the tracks, in seconds.
+    pub fn time(&self) -> f32 {
+        self.shared.time
+    }
+}
+
+impl Drop for KeyframedSprite {
+    fn drop(&mut self) {
+        self.system.keyframes_drop(self.shared.index);
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Keyframe, Sprite};
+    use cgmath::*;
+
+    fn positions(mode: AnimationMode, steps: usize) -> Vec<f32> {
+        let mut slots = UnsafeShared::new(SpriteSlots::new());
+        let token = slots.create(&Sprite::default());
+        let keyframes = SpriteKeyframes {
+            position: vec![
+                Keyframe::new(0.0, Vector3::new(0.0, 0.0, 0.0)),
+                Keyframe::new(2.0, Vector3::new(2.0, 0.0, 0.0)),
+            ],
+            ..SpriteKeyframes::default()
+        };
+        let handle = slots.handle(&token);
+        let mut shared = SharedKeyframes::new(slots.clone(), handle, keyframes, mode);
+        let mut seen = Vec::new();
+        for _ in 0..steps {
+            shared.update(0.75);
+            seen.push(slots.get(&token).pos.x);
+        }
+        seen
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(positions(AnimationMode::Once, 4), vec![0.75, 1.5, 2.0, 2.0]);
+        assert_eq!(positions(AnimationMode::Loop, 4), vec![0.75, 1.5, 0.25, 1.0]);
+        assert_eq!(positions(AnimationMode::PingPong, 4), vec![0.75, 1.5, 1.75, 1.0]);
+    }
+}
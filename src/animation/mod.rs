@@ -0,0 +1,123 @@
+mod anchor;
+mod keyframes;
+mod sprite;
+
+pub use self::anchor::AnchoredText;
+pub use self::keyframes::KeyframedSprite;
+pub use self::sprite::AnimatedSprite;
+
+use self::anchor::SharedAnchor;
+use self::keyframes::SharedKeyframes;
+use self::sprite::SharedAnimation;
+use crate::render::Layer;
+use crate::types::{AnimatedSpriteId, Animation, AnimationMode, Sprite, SpriteKeyframes, SpriteToken, Text};
+use crate::utility::bad::UnsafeShared;
+use cgmath::Vector2;
+
+/// Owns the playback state of every live animated and keyframed sprite and advances them once per
+/// update, then moves the text anchored to them.
+pub struct AnimationSystem {
+    sprites: Vec<UnsafeShared<SharedAnimation>>,
+    anchors: Vec<UnsafeShared<SharedAnchor>>,
+    keyframes: Vec<UnsafeShared<SharedKeyframes>>,
+    completed: Vec<AnimatedSpriteId>,
+    next_id: u64,
+}
+
+impl AnimationSystem {
+    pub fn new() -> UnsafeShared<AnimationSystem> {
+        UnsafeShared::new(AnimationSystem {
+            sprites: Vec::new(),
+            anchors: Vec::new(),
+            keyframes: Vec::new(),
+            completed: Vec::new(),
+            next_id: 0,
+        })
+    }
+
+    pub fn sprite_create(
+        system: &mut UnsafeShared<AnimationSystem>,
+        sprite: Sprite,
+        animation: &Animation,
+        mode: AnimationMode,
+    ) -> AnimatedSprite {
+        let id = AnimatedSpriteId(system.next_id);
+        system.next_id += 1;
+        let mut shared = UnsafeShared::new(SharedAnimation::new(id, sprite, animation, mode));
+        shared.set_index(system.sprites.len());
+        system.sprites.push(shared.clone());
+        AnimatedSprite::new(system.clone(), shared)
+    }
+
+    /// Unsubscribes the animated sprite at the given index from getting updates.
+    pub fn sprite_drop(&mut self, index: usize) {
+        self.sprites.swap_remove(index);
+        if let Some(sprite) = self.sprites.get_mut(index) {
+            sprite.set_index(index);
+        }
+    }
+
+    pub fn anchor_create(
+        system: &mut UnsafeShared<AnimationSystem>,
+        sprite: &AnimatedSprite,
+        text: Text,
+        offset: Vector2<f32>,
+    ) -> AnchoredText {
+        let mut shared = UnsafeShared::new(SharedAnchor::new(sprite.shared(), text, offset));
+        shared.set_index(system.anchors.len());
+        system.anchors.push(shared.clone());
+        AnchoredText::new(system.clone(), shared)
+    }
+
+    /// Unsubscribes the anchored text at the given index from following its sprite.
+    pub fn anchor_drop(&mut self, index: usize) {
+        self.anchors.swap_remove(index);
+        if let Some(anchor) = self.anchors.get_mut(index) {
+            anchor.set_index(index);
+        }
+    }
+
+    pub fn keyframes_create(
+        system: &mut UnsafeShared<AnimationSystem>,
+        layer: &Layer,
+        token: &SpriteToken,
+        keyframes: SpriteKeyframes,
+        mode: AnimationMode,
+    ) -> KeyframedSprite {
+        let slots = layer.slots();
+        let handle = slots.handle(token);
+        let mut shared = UnsafeShared::new(SharedKeyframes::new(slots, handle, keyframes, mode));
+        shared.set_index(system.keyframes.len());
+        system.keyframes.push(shared.clone());
+        KeyframedSprite::new(system.clone(), shared)
+    }
+
+    /// Unsubscribes the keyframed sprite at the given index from getting updates.
+    pub fn keyframes_drop(&mut self, index: usize) {
+        self.keyframes.swap_remove(index);
+        if let Some(keyframes) = self.keyframes.get_mut(index) {
+            keyframes.set_index(index);
+        }
+    }
+
+    /// Advances every animated sprite by the delta, measured in seconds, and moves anchored text
+    /// to its sprite.
+    pub fn update(&mut self, delta: f32) {
+        for sprite in &mut self.sprites {
+            if sprite.update(delta) {
+                self.completed.push(sprite.id());
+            }
+        }
+        for keyframes in &mut self.keyframes {
+            keyframes.update(delta);
+        }
+        for anchor in &mut self.anchors {
+            anchor.update();
+        }
+    }
+
+    /// Takes the ids of the sprites that completed since the last call.
+    pub fn take_completed(&mut self) -> Vec<AnimatedSpriteId> {
+        core::mem::take(&mut self.completed)
+    }
+}
@@ -0,0 +1,262 @@
+use crate::animation::AnimationSystem;
+use crate::types::{AnimatedSpriteId, Animation, AnimationFrame, AnimationMode, Sprite};
+use crate::utility::bad::UnsafeShared;
+
+/// Frames shorter than this are treated as this long, so an animation of empty frames can't stall
+/// the update.
+const MIN_FRAME_DURATION: f32 = 0.001;
+
+/// Playback state shared between the animation system and the animated sprite handle.
+pub struct SharedAnimation {
+    index: usize,
+    id: AnimatedSpriteId,
+    sprite: Sprite,
+    frames: Vec<AnimationFrame>,
+    mode: AnimationMode,
+    speed: f32,
+    playing: bool,
+    finished: bool,
+    frame: usize,
+    forward: bool,
+    elapsed: f32,
+}
+
+impl SharedAnimation {
+    pub fn new(
+        id: AnimatedSpriteId,
+        sprite: Sprite,
+        animation: &Animation,
+        mode: AnimationMode,
+    ) -> SharedAnimation {
+        let mut shared = SharedAnimation {
+            index: 0,
+            id,
+            sprite,
+            frames: animation.frames.clone(),
+            mode,
+            speed: 1.0,
+            playing: true,
+            finished: false,
+            frame: 0,
+            forward: true,
+            elapsed: 0.0,
+        };
+        shared.sync();
+        shared
+    }
+
+    pub fn set_index(&mut self, index: usize) {
+        self.index = index;
+    }
+
+    pub fn id(&self) -> AnimatedSpriteId {
+        self.id
+    }
+
+    pub fn sprite(&self) -> &Sprite {
+        &self.sprite
+    }
+
+    /// Advances playback. Returns true if the animation completed during this update.
+    pub fn update(&mut self, delta: f32) -> bool {
+        if !self.playing || self.frames.is_empty() {
+            return false;
+        }
+        self.elapsed += delta * self.speed;
+        let mut completed = false;
+        loop {
+            let duration = self.frames[self.frame].duration.max(MIN_FRAME_DURATION);
+            if self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
+            if !self.advance() {
+                self.playing = false;
+                self.finished = true;
+                self.elapsed = 0.0;
+                completed = true;
+                break;
+            }
+        }
+        self.sync();
+        completed
+    }
+
+    /// Moves to the next frame. Returns false if there is no next frame.
+    fn advance(&mut self) -> bool {
+        let last = self.frames.len() - 1;
+        match self.mode {
+            AnimationMode::Once => {
+                if self.frame == last {
+                    return false;
+                }
+                self.frame += 1;
+            }
+            AnimationMode::Loop => {
+                self.frame = if self.frame == last {
+                    0
+                } else {
+                    self.frame + 1
+                };
+            }
+            AnimationMode::PingPong => {
+                if last == 0 {
+                    return true;
+                }
+                if self.forward && self.frame == last {
+                    self.forward = false;
+                } else if !self.forward && self.frame == 0 {
+                    self.forward = true;
+                }
+                if self.forward {
+                    self.frame += 1;
+                } else {
+                    self.frame -= 1;
+                }
+            }
+        }
+        true
+    }
+
+    fn sync(&mut self) {
+        if let Some(frame) = self.frames.get(self.frame) {
+            self.sprite.texture = frame.texture;
+        }
+    }
+
+    fn restart(&mut self) {
+        self.frame = 0;
+        self.forward = true;
+        self.elapsed = 0.0;
+        self.playing = true;
+        self.finished = false;
+        self.sync();
+    }
+}
+
+/// Animated sprites cycle a sprite's texture through the frames of an animation. The engine
+/// advances them automatically before each update.
+pub struct AnimatedSprite {
+    system: UnsafeShared<AnimationSystem>,
+    shared: UnsafeShared<SharedAnimation>,
+}
+
+impl AnimatedSprite {
+    pub(crate) fn new(
+        system: UnsafeShared<AnimationSystem>,
+        shared: UnsafeShared<SharedAnimation>,
+    ) -> AnimatedSprite {
+        AnimatedSprite {
+            system,
+            shared,
+        }
+    }
+
+    pub(crate) fn shared(&self) -> UnsafeShared<SharedAnimation> {
+        self.shared.clone()
+    }
+
+    /// The id reported when this sprite completes.
+    pub fn id(&self) -> AnimatedSpriteId {
+        self.shared.id
+    }
+
+    /// The sprite with the texture of the current frame.
+    pub fn sprite(&self) -> &Sprite {
+        &self.shared.sprite
+    }
+
+    /// Sets the sprite being animated. Its texture is replaced by the texture of the current
+    /// frame.
+    pub fn set_sprite(&mut self, sprite: Sprite) {
+        self.shared.sprite = sprite;
+        self.shared.sync();
+    }
+
+    /// Replaces the animation and restarts playback from the first frame.
+    pub fn set_animation(&mut self, animation: &Animation) {
+        self.shared.frames = animation.frames.clone();
+        self.shared.restart();
+    }
+
+    /// Sets how playback proceeds after the last frame.
+    pub fn set_mode(&mut self, mode: AnimationMode) {
+        self.shared.mode = mode;
+    }
+
+    /// Sets the playback speed. 2.0 plays twice as fast. The default is 1.0.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.shared.speed = speed.max(0.0);
+    }
+
+    /// Resumes playback. If the animation already completed, it starts over.
+    pub fn play(&mut self) {
+        if self.is_finished() {
+            self.shared.restart();
+        }
+        self.shared.playing = true;
+    }
+
+    /// Pauses playback on the current frame.
+    pub fn pause(&mut self) {
+        self.shared.playing = false;
+    }
+
+    /// Starts playback over from the first frame.
+    pub fn restart(&mut self) {
+        self.shared.restart();
+    }
+
+    /// If the animation is currently advancing.
+    pub fn is_playing(&self) -> bool {
+        self.shared.playing
+    }
+
+    /// If the animation is in the Once mode and has stopped on its last frame.
+    pub fn is_finished(&self) -> bool {
+        self.shared.finished
+    }
+
+    /// The index of the current frame.
+    pub fn frame(&self) -> usize {
+        self.shared.frame
+    }
+}
+
+impl Drop for AnimatedSprite {
+    fn drop(&mut self) {
+        self.system.sprite_drop(self.shared.index);
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{colors, Texture};
+    use cgmath::*;
+
+    fn frames(mode: AnimationMode, steps: usize) -> (Vec<usize>, bool) {
+        let sprite =
+            Sprite::new(Vector3::zero(), Vector2::new(1.0, 1.0), Texture::default(), colors::WHITE, 0.0);
+        let animation = Animation::from_textures(&[Texture::default(); 3], 1.0);
+        let mut shared = SharedAnimation::new(AnimatedSpriteId(0), sprite, &animation, mode);
+        let mut completed = false;
+        let mut seen = vec![shared.frame];
+        for _ in 0..steps {
+            completed |= shared.update(1.0);
+            seen.push(shared.frame);
+        }
+        (seen, completed)
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(frames(AnimationMode::Once, 4), (vec![0, 1, 2, 2, 2], true));
+        assert_eq!(frames(AnimationMode::Loop, 4), (vec![0, 1, 2, 0, 1], false));
+        assert_eq!(frames(AnimationMode::PingPong, 6), (vec![0, 1, 2, 1, 0, 1, 2], false));
+    }
+}
@@ -0,0 +1,5 @@
+mod stream;
+mod tiled;
+
+pub use self::stream::*;
+pub use self::tiled::*;
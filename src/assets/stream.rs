@@ -0,0 +1,209 @@
+use crate::math::AABB2D;
+use cgmath::*;
+use hashbrown::HashMap;
+
+/// Loads and unloads the chunks of a world for a `WorldStream`.
+pub trait ChunkProvider {
+    /// The loaded form of a chunk, like a layer and the sprites drawn to it.
+    type Chunk;
+
+    /// Loads the chunk at the coordinate. In a looping world, `coord` is where the chunk is placed
+    /// and `source` is the chunk within the world to load, which are the same otherwise.
+    fn load(&mut self, coord: Vector2<i32>, source: Vector2<i32>) -> Self::Chunk;
+
+    /// Unloads a chunk that's no longer near the view.
+    fn unload(&mut self, coord: Vector2<i32>, chunk: Self::Chunk);
+}
+
+/// Streams a world split into a grid of equally sized chunks. As the view moves, chunks coming
+/// into range are loaded through the provider and chunks going out of range are unloaded. Chunk
+/// (0, 0) covers the area from the origin to the chunk size.
+pub struct WorldStream<P: ChunkProvider> {
+    provider: P,
+    chunk_size: Vector2<f32>,
+    load_margin: i32,
+    unload_margin: i32,
+    looping: Option<Vector2<i32>>,
+    chunks: HashMap<Vector2<i32>, P::Chunk>,
+}
+
+impl<P: ChunkProvider> WorldStream<P> {
+    /// Creates a stream with no chunks loaded. Chunks are loaded on the first update.
+    pub fn new(provider: P, chunk_size: Vector2<f32>) -> WorldStream<P> {
+        if chunk_size.x <= 0.0 || chunk_size.y <= 0.0 {
+            panic!("Chunk size must be positive.");
+        }
+        WorldStream {
+            provider,
+            chunk_size,
+            load_margin: 1,
+            unload_margin: 2,
+            looping: None,
+            chunks: HashMap::new(),
+        }
+    }
+
+    /// Sets how many chunks past the edge of the view are kept loaded. Chunks are loaded within
+    /// the load margin, and unloaded once they're outside of the unload margin, so moving back
+    /// and forth over a chunk border doesn't reload chunks. The unload margin is at least the
+    /// load margin. The defaults are 1 and 2.
+    pub fn set_margins(&mut self, load: u32, unload: u32) {
+        self.load_margin = load as i32;
+        self.unload_margin = unload.max(load) as i32;
+    }
+
+    /// Makes the world repeat every `size` chunks in each direction. Set this to None for a world
+    /// that doesn't repeat. Loaded chunks are kept, and reflect the new setting when reloaded.
+    pub fn set_looping(&mut self, size: Option<Vector2<u32>>) {
+        self.looping = size.map(|size| Vector2::new(size.x.max(1) as i32, size.y.max(1) as i32));
+    }
+
+    /// The size of a chunk in world units.
+    pub fn chunk_size(&self) -> Vector2<f32> {
+        self.chunk_size
+    }
+
+    /// The coordinate of the chunk containing the position.
+    pub fn chunk_at(&self, pos: Vector2<f32>) -> Vector2<i32> {
+        Vector2::new((pos.x / self.chunk_size.x).floor() as i32, (pos.y / self.chunk_size.y).floor() as i32)
+    }
+
+    /// The area covered by the chunk at the coordinate.
+    pub fn chunk_bounds(&self, coord: Vector2<i32>) -> AABB2D {
+        let min = Vector2::new(coord.x as f32 * self.chunk_size.x, coord.y as f32 * self.chunk_size.y);
+        let max = min + self.chunk_size;
+        AABB2D::new(min.x, min.y, max.x, max.y)
+    }
+
+    /// Loads chunks near the view and unloads chunks that are far from it. The view is in world
+    /// coordinates, which `LayerTransform::screen_to_layer` can convert the window's corners to.
+    pub fn update(&mut self, view: &AABB2D) {
+        let min = self.chunk_at(view.min);
+        let max = self.chunk_at(view.max);
+
+        let unload = self.unload_margin;
+        let far: Vec<Vector2<i32>> = self
+            .chunks
+            .keys()
+            .filter(|coord| {
+                coord.x < min.x - unload
+                    || coord.x > max.x + unload
+                    || coord.y < min.y - unload
+                    || coord.y > max.y + unload
+            })
+            .copied()
+            .collect();
+        for coord in far {
+            if let Some(chunk) = self.chunks.remove(&coord) {
+                self.provider.unload(coord, chunk);
+            }
+        }
+
+        let load = self.load_margin;
+        for y in min.y - load..=max.y + load {
+            for x in min.x - load..=max.x + load {
+                let coord = Vector2::new(x, y);
+                if !self.chunks.contains_key(&coord) {
+                    let chunk = self.provider.load(coord, self.source(coord));
+                    self.chunks.insert(coord, chunk);
+                }
+            }
+        }
+    }
+
+    /// Unloads every chunk.
+    pub fn clear(&mut self) {
+        for (coord, chunk) in self.chunks.drain() {
+            self.provider.unload(coord, chunk);
+        }
+    }
+
+    /// The chunk within the world that the coordinate shows. This wraps the coordinate in a
+    /// looping world.
+    pub fn source(&self, coord: Vector2<i32>) -> Vector2<i32> {
+        match self.looping {
+            Some(size) => Vector2::new(coord.x.rem_euclid(size.x), coord.y.rem_euclid(size.y)),
+            None => coord,
+        }
+    }
+
+    /// The loaded chunk at the coordinate.
+    pub fn get(&self, coord: Vector2<i32>) -> Option<&P::Chunk> {
+        self.chunks.get(&coord)
+    }
+
+    /// The loaded chunk at the coordinate.
+    pub fn get_mut(&mut self, coord: Vector2<i32>) -> Option<&mut P::Chunk> {
+        self.chunks.get_mut(&coord)
+    }
+
+    /// Iterates over the loaded chunks in no particular order.
+    pub fn chunks(&self) -> impl Iterator<Item = (Vector2<i32>, &P::Chunk)> {
+        self.chunks.iter().map(|(coord, chunk)| (*coord, chunk))
+    }
+
+    /// Iterates over the loaded chunks in no particular order.
+    pub fn chunks_mut(&mut self) -> impl Iterator<Item = (Vector2<i32>, &mut P::Chunk)> {
+        self.chunks.iter_mut().map(|(coord, chunk)| (*coord, chunk))
+    }
+
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    pub fn provider_mut(&mut self) -> &mut P {
+        &mut self.provider
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter {
+        loaded: Vec<Vector2<i32>>,
+        unloaded: Vec<Vector2<i32>>,
+    }
+
+    impl ChunkProvider for Counter {
+        type Chunk = Vector2<i32>;
+
+        fn load(&mut self, coord: Vector2<i32>, source: Vector2<i32>) -> Vector2<i32> {
+            self.loaded.push(coord);
+            source
+        }
+
+        fn unload(&mut self, coord: Vector2<i32>, _chunk: Vector2<i32>) {
+            self.unloaded.push(coord);
+        }
+    }
+
+    #[test]
+    fn streaming() {
+        let mut stream = WorldStream::new(Counter::default(), Vector2::new(10.0, 10.0));
+        stream.set_margins(0, 1);
+        stream.set_looping(Some(Vector2::new(4, 4)));
+
+        stream.update(&AABB2D::new(-5.0, 1.0, 5.0, 9.0));
+        assert_eq!(stream.provider().loaded.len(), 2);
+        assert_eq!(stream.get(Vector2::new(-1, 0)), Some(&Vector2::new(3, 0)));
+
+        // The chunk to the left is still within the unload margin.
+        stream.update(&AABB2D::new(1.0, 1.0, 9.0, 9.0));
+        assert_eq!(stream.provider().loaded.len(), 2);
+        assert!(stream.provider().unloaded.is_empty());
+
+        stream.update(&AABB2D::new(11.0, 1.0, 19.0, 9.0));
+        assert_eq!(stream.provider().loaded.len(), 3);
+        assert_eq!(stream.provider().unloaded, vec![Vector2::new(-1, 0)]);
+
+        stream.clear();
+        assert_eq!(stream.chunks().count(), 0);
+        assert_eq!(stream.provider().unloaded.len(), 3);
+    }
+}
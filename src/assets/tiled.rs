@@ -0,0 +1,501 @@
+use crate::math::{Grid, AABB2D};
+use crate::types::{Sprite, Texture, RGBA8};
+use cgmath::*;
+use xml::attribute::OwnedAttribute;
+use xml::reader::{EventReader, XmlEvent};
+
+const FLIP_HORIZONTAL: u32 = 0x8000_0000;
+const FLIP_VERTICAL: u32 = 0x4000_0000;
+const FLIP_DIAGONAL: u32 = 0x2000_0000;
+const FLIP_MASK: u32 = FLIP_HORIZONTAL | FLIP_VERTICAL | FLIP_DIAGONAL;
+
+/// A map made in the Tiled editor. Positions are converted from Tiled's coordinates so the bottom
+/// left corner of the map is the origin and Y points up, matching sprites.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledMap {
+    /// Width of the map in tiles.
+    pub width: u32,
+    /// Height of the map in tiles.
+    pub height: u32,
+    /// Width of a tile in pixels.
+    pub tile_width: u32,
+    /// Height of a tile in pixels.
+    pub tile_height: u32,
+    pub tilesets: Vec<TiledTileset>,
+    pub tile_layers: Vec<TiledTileLayer>,
+    pub object_layers: Vec<TiledObjectLayer>,
+}
+
+/// A tileset referenced by a map. Tilesets stored in separate files only have their first_gid and
+/// source set until loaded with `TiledTileset::load_tsx`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledTileset {
+    /// The global tile id of the first tile in the tileset.
+    pub first_gid: u32,
+    /// The path of the tileset file, if the tileset isn't embedded in the map.
+    pub source: Option<String>,
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tile_count: u32,
+    pub columns: u32,
+    /// Space between tiles in the image in pixels.
+    pub spacing: u32,
+    /// Space around the edge of the image in pixels.
+    pub margin: u32,
+    /// The path of the tileset image, relative to the file that defined the tileset.
+    pub image: Option<String>,
+}
+
+/// A layer of tiles. Tiles are stored in rows from the top of the map, as global tile ids with
+/// Tiled's flip flags in the upper bits. An id of 0 is an empty tile.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledTileLayer {
+    pub name: String,
+    pub visible: bool,
+    pub opacity: f32,
+    pub tiles: Vec<u32>,
+}
+
+/// A layer of free placed objects.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledObjectLayer {
+    pub name: String,
+    pub visible: bool,
+    pub objects: Vec<TiledObject>,
+}
+
+/// An object placed in an object layer. Rectangles, ellipses, and tile objects have a size, and
+/// points don't. Polygons and polylines are reduced to their position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledObject {
+    pub id: u32,
+    pub name: String,
+    /// The object's type, called class in newer versions of Tiled.
+    pub kind: String,
+    /// The bottom left corner of the object.
+    pub pos: Vector2<f32>,
+    pub size: Vector2<f32>,
+    /// If the object is a point.
+    pub point: bool,
+    /// The global tile id for tile objects.
+    pub gid: Option<u32>,
+}
+
+impl TiledObject {
+    /// The bounds of the object.
+    pub fn aabb(&self) -> AABB2D {
+        AABB2D::new(self.pos.x, self.pos.y, self.pos.x + self.size.x, self.pos.y + self.size.y)
+    }
+}
+
+impl TiledTileset {
+    /// Fills in the tileset from a separate .tsx file. If there is an issue parsing the file, this
+    /// function will panic.
+    pub fn load_tsx(&mut self, bytes: &[u8]) {
+        for event in EventReader::new(bytes) {
+            match event.expect("Unable to parse Tiled tileset.") {
+                XmlEvent::StartElement {
+                    name,
+                    attributes,
+                    ..
+                } => match name.local_name.as_str() {
+                    "tileset" => self.read_attributes(&attributes),
+                    "image" if self.image.is_none() => self.image = attr(&attributes, "source"),
+                    _ => {}
+                },
+                XmlEvent::EndElement {
+                    name,
+                } if name.local_name == "tileset" => break,
+                _ => {}
+            }
+        }
+    }
+
+    fn read_attributes(&mut self, attributes: &[OwnedAttribute]) {
+        self.name = attr(attributes, "name").unwrap_or_default();
+        self.tile_width = attr_num(attributes, "tilewidth", 0);
+        self.tile_height = attr_num(attributes, "tileheight", 0);
+        self.tile_count = attr_num(attributes, "tilecount", 0);
+        self.columns = attr_num(attributes, "columns", 0);
+        self.spacing = attr_num(attributes, "spacing", 0);
+        self.margin = attr_num(attributes, "margin", 0);
+    }
+
+    /// The region of the tileset image for a tile, where the texture covers the whole tileset
+    /// image.
+    fn tile_texture(&self, texture: &Texture, local_id: u32) -> Option<Texture> {
+        if self.columns == 0 {
+            return None;
+        }
+        let x = self.margin + (local_id % self.columns) * (self.tile_width + self.spacing);
+        let y = self.margin + (local_id / self.columns) * (self.tile_height + self.spacing);
+        texture.sub_texture(x as u16, y as u16, self.tile_width as u16, self.tile_height as u16).ok()
+    }
+}
+
+impl TiledMap {
+    /// Parses a map from a .tmx file. Tile data may be CSV, or base64 with no compression, zlib, or
+    /// gzip. Infinite maps aren't supported. If there is an issue parsing the map, this function
+    /// will panic.
+    pub fn from_tmx(bytes: &[u8]) -> TiledMap {
+        let mut map = TiledMap::default();
+        let mut tileset: Option<TiledTileset> = None;
+        let mut layer: Option<TiledTileLayer> = None;
+        let mut encoding = (None, None);
+        let mut data = String::new();
+        let mut in_data = false;
+        let mut objects: Option<TiledObjectLayer> = None;
+
+        for event in EventReader::new(bytes) {
+            match event.expect("Unable to parse Tiled map.") {
+                XmlEvent::StartElement {
+                    name,
+                    attributes,
+                    ..
+                } => match name.local_name.as_str() {
+                    "map" => {
+                        if attr(&attributes, "infinite").as_deref() == Some("1") {
+                            panic!("Infinite Tiled maps are unsupported.");
+                        }
+                        map.width = attr_num(&attributes, "width", 0);
+                        map.height = attr_num(&attributes, "height", 0);
+                        map.tile_width = attr_num(&attributes, "tilewidth", 0);
+                        map.tile_height = attr_num(&attributes, "tileheight", 0);
+                    }
+                    "tileset" => {
+                        let mut next = TiledTileset {
+                            first_gid: attr_num(&attributes, "firstgid", 1),
+                            source: attr(&attributes, "source"),
+                            ..TiledTileset::default()
+                        };
+                        if next.source.is_none() {
+                            next.read_attributes(&attributes);
+                        }
+                        tileset = Some(next);
+                    }
+                    "image" => {
+                        if let Some(tileset) = &mut tileset {
+                            if tileset.image.is_none() {
+                                tileset.image = attr(&attributes, "source");
+                            }
+                        }
+                    }
+                    "layer" => {
+                        layer = Some(TiledTileLayer {
+                            name: attr(&attributes, "name").unwrap_or_default(),
+                            visible: attr(&attributes, "visible").as_deref() != Some("0"),
+                            opacity: attr_num(&attributes, "opacity", 1.0),
+                            tiles: Vec::new(),
+                        });
+                    }
+                    "data" => {
+                        encoding = (attr(&attributes, "encoding"), attr(&attributes, "compression"));
+                        data.clear();
+                        in_data = true;
+                    }
+                    "chunk" => panic!("Infinite Tiled maps are unsupported."),
+                    "objectgroup" => {
+                        objects = Some(TiledObjectLayer {
+                            name: attr(&attributes, "name").unwrap_or_default(),
+                            visible: attr(&attributes, "visible").as_deref() != Some("0"),
+                            objects: Vec::new(),
+                        });
+                    }
+                    "object" => {
+                        if let Some(objects) = &mut objects {
+                            let gid = attr(&attributes, "gid").and_then(|gid| gid.parse().ok());
+                            let size = Vector2::new(
+                                attr_num(&attributes, "width", 0.0),
+                                attr_num(&attributes, "height", 0.0),
+                            );
+                            let x = attr_num(&attributes, "x", 0.0);
+                            let y = attr_num(&attributes, "y", 0.0);
+                            // Tiled measures down from the top. Tile objects are anchored at their
+                            // bottom left, and everything else at the top left.
+                            let map_height = (map.height * map.tile_height) as f32;
+                            let bottom = match gid {
+                                Some(_) => map_height - y,
+                                None => map_height - y - size.y,
+                            };
+                            objects.objects.push(TiledObject {
+                                id: attr_num(&attributes, "id", 0),
+                                name: attr(&attributes, "name").unwrap_or_default(),
+                                kind: attr(&attributes, "type")
+                                    .or_else(|| attr(&attributes, "class"))
+                                    .unwrap_or_default(),
+                                pos: Vector2::new(x, bottom),
+                                size,
+                                point: false,
+                                gid,
+                            });
+                        }
+                    }
+                    "point" => {
+                        if let Some(object) = objects.as_mut().and_then(|objects| objects.objects.last_mut())
+                        {
+                            object.point = true;
+                        }
+                    }
+                    _ => {}
+                },
+                XmlEvent::Characters(text) if in_data => data.push_str(&text),
+                XmlEvent::EndElement {
+                    name,
+                } => match name.local_name.as_str() {
+                    "tileset" => map.tilesets.extend(tileset.take()),
+                    "data" => {
+                        in_data = false;
+                        if let Some(layer) = &mut layer {
+                            layer.tiles = decode_tiles(&data, encoding.0.as_deref(), encoding.1.as_deref());
+                        }
+                    }
+                    "layer" => map.tile_layers.extend(layer.take()),
+                    "objectgroup" => map.object_layers.extend(objects.take()),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        map
+    }
+
+    /// Gets the grid the map's tiles are laid out on, matching the sprites from `tile_sprites`.
+    pub fn grid(&self) -> Grid {
+        Grid::new(Vector2::new(self.tile_width as f32, self.tile_height as f32))
+    }
+
+    /// Gets the index into a tile layer's tiles for a cell of the map's grid, or None if the cell
+    /// is outside the map.
+    pub fn tile_index(&self, cell: Vector2<i32>) -> Option<usize> {
+        if cell.x < 0 || cell.y < 0 || cell.x as u32 >= self.width || cell.y as u32 >= self.height {
+            return None;
+        }
+        let row = self.height - 1 - cell.y as u32;
+        Some((row * self.width + cell.x as u32) as usize)
+    }
+
+    /// Gets the global tile id, with flip flags, in a cell of a tile layer. Empty cells are 0, and
+    /// cells outside the map are None.
+    pub fn tile_at(&self, layer: usize, cell: Vector2<i32>) -> Option<u32> {
+        self.tile_index(cell).map(|index| self.tile_layers[layer].tiles[index])
+    }
+
+    /// Finds the tileset and local id for a global tile id, with flip flags removed.
+    fn tileset_for(&self, gid: u32) -> Option<(usize, u32)> {
+        let gid = gid & !FLIP_MASK;
+        self.tilesets
+            .iter()
+            .enumerate()
+            .filter(|(_, tileset)| tileset.first_gid <= gid)
+            .max_by_key(|(_, tileset)| tileset.first_gid)
+            .map(|(index, tileset)| (index, gid - tileset.first_gid))
+    }
+
+    /// Creates sprites for a tile layer. Textures are given per tileset, in the same order as
+    /// `tilesets`, each covering the tileset's whole image. Empty tiles and tiles from tilesets
+    /// without a texture are skipped. This function appends sprites to the end of the output
+    /// buffer.
+    pub fn tile_sprites(&self, layer: usize, textures: &[Texture], depth: f32, output: &mut Vec<Sprite>) {
+        let layer = &self.tile_layers[layer];
+        let color = RGBA8::new_raw(255, 255, 255, (layer.opacity.clamp(0.0, 1.0) * 255.0) as u8);
+        for (index, &gid) in layer.tiles.iter().enumerate() {
+            if gid & !FLIP_MASK == 0 {
+                continue;
+            }
+            let (tileset_index, local_id) = match self.tileset_for(gid) {
+                Some(found) => found,
+                None => continue,
+            };
+            let tileset = &self.tilesets[tileset_index];
+            let texture =
+                match textures.get(tileset_index).and_then(|texture| tileset.tile_texture(texture, local_id))
+                {
+                    Some(texture) => texture,
+                    None => continue,
+                };
+            let (texture, rotation) = apply_flips(texture, gid);
+            let column = index as u32 % self.width;
+            let row = index as u32 / self.width;
+            let pos = Vector3::new(
+                (column * self.tile_width) as f32,
+                ((self.height - 1 - row) * self.tile_height) as f32,
+                depth,
+            );
+            let size = Vector2::new(tileset.tile_width as f32, tileset.tile_height as f32);
+            output.push(Sprite::new(pos, size, texture, color, rotation));
+        }
+    }
+}
+
+/// Applies Tiled's flip flags. The diagonal flip is a transpose, which is done as a vertical flip of
+/// the texture followed by a quarter turn clockwise. Returns the texture and rotation in turns.
+fn apply_flips(texture: Texture, gid: u32) -> (Texture, f32) {
+    let mut flip_x = false;
+    let mut flip_y = false;
+    let mut rotation = 0.0;
+    if gid & FLIP_DIAGONAL != 0 {
+        flip_y = true;
+        rotation = 0.75;
+        // After the quarter turn, the flips of the sprite map to the other axis of the texture.
+        flip_y ^= gid & FLIP_HORIZONTAL != 0;
+        flip_x ^= gid & FLIP_VERTICAL != 0;
+    } else {
+        flip_x ^= gid & FLIP_HORIZONTAL != 0;
+        flip_y ^= gid & FLIP_VERTICAL != 0;
+    }
+    let texture = if flip_x {
+        texture.mirror_y()
+    } else {
+        texture
+    };
+    let texture = if flip_y {
+        texture.mirror_x()
+    } else {
+        texture
+    };
+    (texture, rotation)
+}
+
+fn attr(attributes: &[OwnedAttribute], key: &str) -> Option<String> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name.local_name == key)
+        .map(|attribute| attribute.value.clone())
+}
+
+fn attr_num<T: core::str::FromStr>(attributes: &[OwnedAttribute], key: &str, default: T) -> T {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name.local_name == key)
+        .and_then(|attribute| attribute.value.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+fn decode_tiles(data: &str, encoding: Option<&str>, compression: Option<&str>) -> Vec<u32> {
+    match encoding {
+        Some("csv") => data
+            .split(',')
+            .map(|value| value.trim().parse().expect("Tiled layer has an invalid CSV tile."))
+            .collect(),
+        Some("base64") => {
+            let bytes = base64_decode(data);
+            let bytes = match compression {
+                None => bytes,
+                Some("zlib") => {
+                    inflate::inflate_bytes_zlib(&bytes).expect("Unable to decompress Tiled layer.")
+                }
+                Some("gzip") => {
+                    inflate::inflate_bytes(gzip_payload(&bytes)).expect("Unable to decompress Tiled layer.")
+                }
+                Some(other) => panic!("Tiled layer compression {} is unsupported.", other),
+            };
+            bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+        }
+        _ => panic!("Tiled layers must use CSV or base64 encoding."),
+    }
+}
+
+fn base64_decode(data: &str) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in data.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => continue,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    output
+}
+
+/// Skips the gzip header, returning the raw deflate stream.
+fn gzip_payload(bytes: &[u8]) -> &[u8] {
+    if bytes.len() < 18 || bytes[0] != 0x1f || bytes[1] != 0x8b {
+        panic!("Tiled layer has an invalid gzip header.");
+    }
+    let flags = bytes[3];
+    let mut position = 10;
+    if flags & 0x04 != 0 {
+        position += 2 + u16::from_le_bytes([bytes[10], bytes[11]]) as usize;
+    }
+    for flag in [0x08, 0x10] {
+        if flags & flag != 0 {
+            while bytes.get(position).copied().unwrap_or(0) != 0 {
+                position += 1;
+            }
+            position += 1;
+        }
+    }
+    if flags & 0x02 != 0 {
+        position += 2;
+    }
+    bytes.get(position..bytes.len() - 8).expect("Tiled layer has an invalid gzip header.")
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.5" orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <tileset firstgid="1" name="tiles" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+  <image source="tiles.png" width="32" height="32"/>
+ </tileset>
+ <tileset firstgid="5" source="other.tsx"/>
+ <layer id="1" name="ground" width="2" height="2">
+  <data encoding="csv">
+1,0,
+2147483650,5
+</data>
+ </layer>
+ <layer id="2" name="packed" width="2" height="2" opacity="0.5">
+  <data encoding="base64">AQAAAAIAAAADAAAABAAAAA==</data>
+ </layer>
+ <objectgroup id="3" name="spawns">
+  <object id="1" name="door" type="exit" x="4" y="8" width="8" height="4"/>
+  <object id="2" name="start" x="16" y="16">
+   <point/>
+  </object>
+ </objectgroup>
+</map>"#;
+
+    #[test]
+    fn parse_tmx() {
+        let map = TiledMap::from_tmx(MAP.as_bytes());
+        assert_eq!((map.width, map.height, map.tile_width, map.tile_height), (2, 2, 16, 16));
+        assert_eq!(map.tilesets.len(), 2);
+        assert_eq!(map.tilesets[0].image.as_deref(), Some("tiles.png"));
+        assert_eq!(map.tilesets[1].source.as_deref(), Some("other.tsx"));
+        assert_eq!(map.tile_layers[0].tiles, vec![1, 0, FLIP_HORIZONTAL | 2, 5]);
+        assert_eq!(map.tile_layers[1].tiles, vec![1, 2, 3, 4]);
+        assert_eq!(map.tile_layers[1].opacity, 0.5);
+        assert_eq!(map.tileset_for(FLIP_HORIZONTAL | 2), Some((0, 1)));
+        assert_eq!(map.tileset_for(6), Some((1, 1)));
+        assert_eq!(map.grid().cell_at(Vector2::new(20.0, 4.0)), Vector2::new(1, 0));
+        assert_eq!(map.tile_at(0, Vector2::new(1, 0)), Some(5));
+        assert_eq!(map.tile_at(0, Vector2::new(0, 1)), Some(1));
+        assert_eq!(map.tile_at(0, Vector2::new(2, 0)), None);
+
+        let objects = &map.object_layers[0].objects;
+        assert_eq!(objects[0].kind, "exit");
+        assert_eq!(objects[0].aabb(), AABB2D::new(4.0, 20.0, 12.0, 24.0));
+        assert!(objects[1].point);
+        assert_eq!(objects[1].pos, Vector2::new(16.0, 16.0));
+    }
+}
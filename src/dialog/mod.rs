@@ -0,0 +1,294 @@
+//! Native message boxes and file dialogs. These use the tools each platform already ships with, so
+//! no extra windowing dependency is needed. Dialogs block the calling thread until they're closed,
+//! and the engine's window doesn't process events in the meantime.
+
+use std::path::PathBuf;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::process::Command;
+
+/// How serious a message is, which picks the message box's icon.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MessageLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A named group of file extensions a file dialog can limit the shown files to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FileFilter<'a> {
+    /// The name of the group, like "Images".
+    pub name: &'a str,
+    /// Extensions without the leading dot, like "png".
+    pub extensions: &'a [&'a str],
+}
+
+/// Shows a message box with an OK button and waits for it to be closed. If the platform has no way
+/// to show one, the message is printed to stderr instead. This is meant for fatal errors, so it can
+/// be called before the engine starts.
+pub fn message_box(title: &str, message: &str, level: MessageLevel) {
+    if !platform::message_box(title, message, level) {
+        eprintln!("{}: {}", title, message);
+    }
+}
+
+/// Asks the user to pick an existing file. Returns None if the dialog was cancelled or can't be
+/// shown, which is always the case on the web. An empty filter list shows every file.
+pub fn open_file(title: &str, filters: &[FileFilter]) -> Option<PathBuf> {
+    platform::open_file(title, filters)
+}
+
+/// Asks the user where to save a file, starting with the given file name. Returns None if the
+/// dialog was cancelled or can't be shown, which is always the case on the web. The user is asked
+/// before an existing file is chosen.
+pub fn save_file(title: &str, default_name: &str, filters: &[FileFilter]) -> Option<PathBuf> {
+    platform::save_file(title, default_name, filters)
+}
+
+/// Runs a dialog command and returns what it printed, without the trailing newline. None if the
+/// command couldn't start or the dialog was cancelled.
+#[cfg(not(target_arch = "wasm32"))]
+fn run(command: &mut Command) -> Option<String> {
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    Some(text.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+/// Picks a path out of a dialog's output. Empty output means nothing was picked.
+#[cfg(not(target_arch = "wasm32"))]
+fn path(output: Option<String>) -> Option<PathBuf> {
+    output.filter(|path| !path.is_empty()).map(PathBuf::from)
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Linux and BSD
+// ////////////////////////////////////////////////////////////////////////////
+
+/// Uses zenity, falling back to kdialog, which cover GNOME and KDE desktops.
+#[cfg(all(unix, not(target_os = "macos"), not(target_arch = "wasm32")))]
+mod platform {
+    use super::*;
+
+    pub fn message_box(title: &str, message: &str, level: MessageLevel) -> bool {
+        let kind = match level {
+            MessageLevel::Info => "--info",
+            MessageLevel::Warning => "--warning",
+            MessageLevel::Error => "--error",
+        };
+        let zenity =
+            Command::new("zenity").args([kind, "--no-markup", "--title", title, "--text", message]).status();
+        if let Ok(status) = zenity {
+            return status.code().is_some();
+        }
+        let kind = match level {
+            MessageLevel::Info => "--msgbox",
+            MessageLevel::Warning => "--sorry",
+            MessageLevel::Error => "--error",
+        };
+        Command::new("kdialog").args([kind, message, "--title", title]).status().is_ok()
+    }
+
+    pub fn open_file(title: &str, filters: &[FileFilter]) -> Option<PathBuf> {
+        dialog(title, None, filters)
+    }
+
+    pub fn save_file(title: &str, default_name: &str, filters: &[FileFilter]) -> Option<PathBuf> {
+        dialog(title, Some(default_name), filters)
+    }
+
+    fn dialog(title: &str, save: Option<&str>, filters: &[FileFilter]) -> Option<PathBuf> {
+        let mut zenity = Command::new("zenity");
+        zenity.args(["--file-selection", "--title", title]);
+        if let Some(name) = save {
+            zenity.args(["--save", "--confirm-overwrite", "--filename", name]);
+        }
+        for filter in filters {
+            zenity.arg("--file-filter").arg(format!("{} | {}", filter.name, patterns(filter, " ")));
+        }
+        match zenity.output() {
+            Ok(output) if output.status.code().is_some() => {
+                return path(String::from_utf8(output.stdout).ok().map(|text| text.trim_end().to_string()));
+            }
+            _ => {}
+        }
+
+        let mut kdialog = Command::new("kdialog");
+        let filter: Vec<String> =
+            filters.iter().map(|filter| format!("{}|{}", patterns(filter, " "), filter.name)).collect();
+        match save {
+            Some(name) => kdialog.args(["--getsavefilename", name]),
+            None => kdialog.args(["--getopenfilename", "."]),
+        };
+        kdialog.arg(filter.join("\n")).args(["--title", title]);
+        path(run(&mut kdialog))
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// macOS
+// ////////////////////////////////////////////////////////////////////////////
+
+/// Uses AppleScript through osascript.
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    pub fn message_box(title: &str, message: &str, level: MessageLevel) -> bool {
+        let icon = match level {
+            MessageLevel::Info => "note",
+            MessageLevel::Warning => "caution",
+            MessageLevel::Error => "stop",
+        };
+        let script = format!(
+            "display dialog {} with title {} buttons {{\"OK\"}} default button 1 with icon {}",
+            quote(message),
+            quote(title),
+            icon
+        );
+        Command::new("osascript").args(["-e", &script]).status().is_ok()
+    }
+
+    pub fn open_file(title: &str, filters: &[FileFilter]) -> Option<PathBuf> {
+        let mut script = format!("POSIX path of (choose file with prompt {}", quote(title));
+        let types: Vec<String> =
+            filters.iter().flat_map(|filter| filter.extensions.iter()).map(|ext| quote(ext)).collect();
+        if !types.is_empty() {
+            script += &format!(" of type {{{}}}", types.join(", "));
+        }
+        script += ")";
+        path(run(Command::new("osascript").args(["-e", &script])))
+    }
+
+    pub fn save_file(title: &str, default_name: &str, _filters: &[FileFilter]) -> Option<PathBuf> {
+        let script = format!(
+            "POSIX path of (choose file name with prompt {} default name {})",
+            quote(title),
+            quote(default_name)
+        );
+        path(run(Command::new("osascript").args(["-e", &script])))
+    }
+
+    /// Quotes text as an AppleScript string.
+    fn quote(text: &str) -> String {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Windows
+// ////////////////////////////////////////////////////////////////////////////
+
+/// Uses Windows Forms through PowerShell.
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use std::os::windows::process::CommandExt;
+
+    /// Keeps PowerShell from opening a console window.
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    pub fn message_box(title: &str, message: &str, level: MessageLevel) -> bool {
+        let icon = match level {
+            MessageLevel::Info => "Information",
+            MessageLevel::Warning => "Warning",
+            MessageLevel::Error => "Error",
+        };
+        let script = format!(
+            "[System.Windows.Forms.MessageBox]::Show({}, {}, 'OK', '{}') | Out-Null",
+            quote(message),
+            quote(title),
+            icon
+        );
+        run(&mut powershell(&script)).is_some()
+    }
+
+    pub fn open_file(title: &str, filters: &[FileFilter]) -> Option<PathBuf> {
+        dialog("OpenFileDialog", title, None, filters)
+    }
+
+    pub fn save_file(title: &str, default_name: &str, filters: &[FileFilter]) -> Option<PathBuf> {
+        dialog("SaveFileDialog", title, Some(default_name), filters)
+    }
+
+    fn dialog(kind: &str, title: &str, name: Option<&str>, filters: &[FileFilter]) -> Option<PathBuf> {
+        let mut script =
+            format!("$dialog = New-Object System.Windows.Forms.{}; $dialog.Title = {};", kind, quote(title));
+        if let Some(name) = name {
+            script += &format!(" $dialog.FileName = {};", quote(name));
+        }
+        if !filters.is_empty() {
+            let filter: Vec<String> =
+                filters.iter().map(|filter| format!("{}|{}", filter.name, patterns(filter, ";"))).collect();
+            script += &format!(" $dialog.Filter = {};", quote(&filter.join("|")));
+        }
+        script += " if ($dialog.ShowDialog() -eq 'OK') { $dialog.FileName }";
+        path(run(&mut powershell(&script)))
+    }
+
+    fn powershell(script: &str) -> Command {
+        let mut command = Command::new("powershell");
+        command
+            .args(["-NoProfile", "-NonInteractive", "-Command"])
+            .arg(format!("Add-Type -AssemblyName System.Windows.Forms; {}", script))
+            .creation_flags(CREATE_NO_WINDOW);
+        command
+    }
+
+    /// Quotes text as a PowerShell string, which can't expand variables.
+    fn quote(text: &str) -> String {
+        format!("'{}'", text.replace('\'', "''"))
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Web
+// ////////////////////////////////////////////////////////////////////////////
+
+/// Browsers only offer alerts. File pickers can't block, so they aren't supported.
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use super::*;
+
+    pub fn message_box(title: &str, message: &str, _level: MessageLevel) -> bool {
+        match web_sys::window() {
+            Some(window) => window.alert_with_message(&format!("{}\n\n{}", title, message)).is_ok(),
+            None => false,
+        }
+    }
+
+    pub fn open_file(_title: &str, _filters: &[FileFilter]) -> Option<PathBuf> {
+        None
+    }
+
+    pub fn save_file(_title: &str, _default_name: &str, _filters: &[FileFilter]) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Formats a filter's extensions as glob patterns, like "*.png *.jpg".
+fn patterns(filter: &FileFilter, separator: &str) -> String {
+    filter.extensions.iter().map(|ext| format!("*.{}", ext)).collect::<Vec<_>>().join(separator)
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_patterns() {
+        let filter = FileFilter {
+            name: "Images",
+            extensions: &["png", "qoi"],
+        };
+        assert_eq!(patterns(&filter, " "), "*.png *.qoi");
+        assert_eq!(patterns(&filter, ";"), "*.png;*.qoi");
+    }
+}
@@ -1,19 +1,152 @@
-use crate::input::{InputMessage, ScrollDirection};
+use crate::input::{
+    CursorButton, DroppedFile, InputMessage, KeyboardModifiers, ScrollDirection, ShortcutTrigger,
+};
+use crate::photo::PhotoKey;
 use crate::Engine;
 use cgmath::prelude::*;
 use cgmath::*;
-use winit::event::WindowEvent;
+use hashbrown::HashMap;
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceEvent, TouchPhase, WindowEvent};
+
+/// A step of dragging files onto the window.
+pub(crate) enum FileDrop {
+    Hovered,
+    Cancelled,
+    Dropped(DroppedFile),
+}
 
 pub struct InputConverter {
     window_size: Vector2<f32>,
+    /// The cursor in window pixels, measured from the center of the window.
+    cursor_window: Vector2<f32>,
+    /// The cursor in the pixels layers are laid out in, which differ from window pixels while a
+    /// virtual resolution is set.
     cursor_pos: Vector2<f32>,
+    touches: HashMap<u64, Vector2<f32>>,
+    modifiers: KeyboardModifiers,
+    /// Buttons pressed in the window that haven't been released yet. While any are held, the
+    /// cursor is captured by the window.
+    captured: Vec<CursorButton>,
+    /// Buttons released early because the window lost focus. Their real release is dropped.
+    released: Vec<CursorButton>,
+    /// If the cursor left the window while captured, and the CursorLeft message is waiting for the
+    /// capture to end.
+    left: bool,
+    /// Device motion keeps arriving on some platforms while the window isn't focused.
+    focused: bool,
+    /// If files are being dragged over the window. Platforms report each dragged file separately.
+    hovered: bool,
+    /// If photo mode took the last key press, so the character it types is kept from the game too.
+    /// This covers the toggle key that leaves photo mode.
+    photo_key: bool,
 }
 
 impl InputConverter {
     pub fn new(window_size: Vector2<f32>) -> InputConverter {
         InputConverter {
             window_size,
+            cursor_window: Vector2::zero(),
             cursor_pos: Vector2::zero(),
+            touches: HashMap::new(),
+            modifiers: KeyboardModifiers::empty(),
+            captured: Vec::new(),
+            released: Vec::new(),
+            left: false,
+            focused: true,
+            hovered: false,
+            photo_key: false,
+        }
+    }
+
+    fn to_screen(&self, position: PhysicalPosition<f64>) -> Vector2<f32> {
+        Vector2::new(
+            position.x as f32 - (self.window_size.x / 2.0),
+            -position.y as f32 + (self.window_size.y / 2.0),
+        )
+    }
+
+    fn shortcut<T: 'static + FnMut(InputMessage, &mut Engine)>(
+        &self,
+        trigger: ShortcutTrigger,
+        event_handler: &mut T,
+        engine: &mut Engine,
+    ) {
+        if let Some(name) = engine.shortcut_find(self.modifiers, trigger) {
+            event_handler(InputMessage::Shortcut(name), engine);
+        }
+    }
+
+    /// Ends the capture of a released button. Once no buttons are held, a CursorLeft message held
+    /// back during the capture is sent.
+    fn release<T: 'static + FnMut(InputMessage, &mut Engine)>(
+        &mut self,
+        button: CursorButton,
+        event_handler: &mut T,
+        engine: &mut Engine,
+    ) {
+        self.captured.retain(|captured| *captured != button);
+        if self.captured.is_empty() && self.left {
+            self.left = false;
+            engine.window_cursor_moved(None);
+            event_handler(InputMessage::CursorLeft, engine);
+        }
+    }
+
+    /// Converts raw device input, which is only sent as relative cursor motion while the cursor is
+    /// grabbed.
+    pub fn push_device<T: 'static + FnMut(InputMessage, &mut Engine)>(
+        &mut self,
+        event: DeviceEvent,
+        event_handler: &mut T,
+        engine: &mut Engine,
+    ) {
+        if let DeviceEvent::MouseMotion {
+            delta: (x, y),
+        } = event
+        {
+            if self.focused && engine.cursor_grabbed() {
+                event_handler(
+                    InputMessage::CursorMotion {
+                        delta: Vector2::new(x as f32, -y as f32),
+                    },
+                    engine,
+                );
+            }
+        }
+    }
+
+    /// Converts a step of dragging files onto the window.
+    pub(crate) fn push_file<T: 'static + FnMut(InputMessage, &mut Engine)>(
+        &mut self,
+        event: FileDrop,
+        event_handler: &mut T,
+        engine: &mut Engine,
+    ) {
+        match event {
+            FileDrop::Hovered => {
+                if !self.hovered {
+                    self.hovered = true;
+                    event_handler(InputMessage::FileHovered, engine);
+                }
+            }
+            FileDrop::Cancelled => {
+                if self.hovered {
+                    self.hovered = false;
+                    event_handler(InputMessage::FileHoverCancelled, engine);
+                }
+            }
+            FileDrop::Dropped(file) => {
+                self.hovered = false;
+                engine.dropped_file_set(Some(file));
+                event_handler(
+                    InputMessage::FileDropped {
+                        pos: self.cursor_pos,
+                    },
+                    engine,
+                );
+                engine.dropped_file_set(None);
+            }
         }
     }
 
@@ -36,10 +169,41 @@ impl InputConverter {
             } => {
                 engine.window_check_resize();
             }
+            WindowEvent::Focused(true) => {
+                self.focused = true;
+                // Some platforms drop the grab while the window is in the background.
+                engine.cursor_grab_restore();
+            }
+            WindowEvent::Focused(false) => {
+                self.focused = false;
+                // The platform may never send the releases of buttons held while focus is lost, so
+                // they're released now to end any drags.
+                for button in self.captured.clone() {
+                    self.released.push(button);
+                    event_handler(
+                        InputMessage::CursorReleased {
+                            button,
+                            pos: self.cursor_pos,
+                        },
+                        engine,
+                    );
+                    self.release(button, event_handler, engine);
+                }
+            }
 
             // Keyboard
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                event_handler(InputMessage::ModifiersChanged(modifiers), engine);
+            }
             WindowEvent::ReceivedCharacter(char) => {
-                event_handler(InputMessage::ReceivedCharacter(char), engine);
+                engine.key_layout_character(char);
+                if !core::mem::take(&mut self.photo_key) && !engine.photo_mode_active() {
+                    event_handler(InputMessage::ReceivedCharacter(char), engine);
+                    if is_text(char) {
+                        event_handler(InputMessage::Character(char), engine);
+                    }
+                }
             }
             WindowEvent::KeyboardInput {
                 input,
@@ -48,10 +212,24 @@ impl InputConverter {
                 if let Some(keycode) = input.virtual_keycode {
                     match input.state {
                         winit::event::ElementState::Pressed => {
-                            event_handler(InputMessage::KeyPressed(keycode), engine);
+                            engine.key_layout_pressed(keycode, self.modifiers);
+                            let photo_key = engine.photo_key_pressed(keycode);
+                            self.photo_key = !matches!(photo_key, PhotoKey::Ignored);
+                            match photo_key {
+                                PhotoKey::Ignored => {
+                                    self.shortcut(ShortcutTrigger::Key(keycode), event_handler, engine);
+                                    event_handler(InputMessage::KeyPressed(keycode), engine);
+                                }
+                                PhotoKey::Consumed => {}
+                                PhotoKey::Toggled(active) => {
+                                    event_handler(InputMessage::PhotoModeChanged(active), engine)
+                                }
+                            }
                         }
                         winit::event::ElementState::Released => {
-                            event_handler(InputMessage::KeyReleased(keycode), engine);
+                            if !engine.photo_key_released(keycode) {
+                                event_handler(InputMessage::KeyReleased(keycode), engine);
+                            }
                         }
                     }
                 }
@@ -62,12 +240,15 @@ impl InputConverter {
                 position,
                 ..
             } => {
-                let cursor_pos = Vector2::new(
-                    position.x as f32 - (self.window_size.x / 2.0),
-                    -position.y as f32 + (self.window_size.y / 2.0),
-                );
+                engine.window_region_cursor_moved(position);
+                self.cursor_window = self.to_screen(position);
+                let cursor_pos = engine.window_to_virtual(self.cursor_window);
                 let delta = cursor_pos - self.cursor_pos;
                 self.cursor_pos = cursor_pos;
+                engine.window_cursor_moved(Some(cursor_pos));
+                if engine.photo_cursor_moved(delta) {
+                    return;
+                }
                 event_handler(
                     InputMessage::CursorMoved {
                         pos: self.cursor_pos,
@@ -84,15 +265,20 @@ impl InputConverter {
                     winit::event::MouseScrollDelta::LineDelta(x, y) => (x, y),
                     winit::event::MouseScrollDelta::PixelDelta(pos) => (pos.x as f32, pos.y as f32),
                 };
+                let mut send = |direction: ScrollDirection, engine: &mut Engine| {
+                    if !engine.photo_cursor_scroll(direction) {
+                        event_handler(InputMessage::CursorScroll(direction), engine);
+                    }
+                };
                 if x < 0.0 {
-                    event_handler(InputMessage::CursorScroll(ScrollDirection::Left), engine);
+                    send(ScrollDirection::Left, engine);
                 } else if x > 0.0 {
-                    event_handler(InputMessage::CursorScroll(ScrollDirection::Right), engine);
+                    send(ScrollDirection::Right, engine);
                 }
                 if y < 0.0 {
-                    event_handler(InputMessage::CursorScroll(ScrollDirection::Down), engine);
+                    send(ScrollDirection::Down, engine);
                 } else if y > 0.0 {
-                    event_handler(InputMessage::CursorScroll(ScrollDirection::Up), engine);
+                    send(ScrollDirection::Up, engine);
                 }
             }
             WindowEvent::MouseInput {
@@ -101,6 +287,17 @@ impl InputConverter {
                 ..
             } => match state {
                 winit::event::ElementState::Pressed => {
+                    if engine.photo_cursor_pressed(button) {
+                        return;
+                    }
+                    if button == CursorButton::Left && engine.window_region_pressed(&self.cursor_window) {
+                        return;
+                    }
+                    self.released.retain(|released| *released != button);
+                    if engine.cursor_capture_enabled() && !self.captured.contains(&button) {
+                        self.captured.push(button);
+                    }
+                    self.shortcut(ShortcutTrigger::Cursor(button), event_handler, engine);
                     event_handler(
                         InputMessage::CursorPressed {
                             button,
@@ -110,6 +307,16 @@ impl InputConverter {
                     );
                 }
                 winit::event::ElementState::Released => {
+                    if button == CursorButton::Left {
+                        engine.window_region_released();
+                    }
+                    if engine.photo_cursor_released(button) {
+                        return;
+                    }
+                    if let Some(index) = self.released.iter().position(|released| *released == button) {
+                        self.released.swap_remove(index);
+                        return;
+                    }
                     event_handler(
                         InputMessage::CursorReleased {
                             button,
@@ -117,19 +324,92 @@ impl InputConverter {
                         },
                         engine,
                     );
+                    self.release(button, event_handler, engine);
                 }
             },
             WindowEvent::CursorEntered {
                 ..
             } => {
+                // Returning during a capture means the cursor never left as far as messages go.
+                if self.left {
+                    self.left = false;
+                    return;
+                }
                 event_handler(InputMessage::CursorEntered, engine);
             }
             WindowEvent::CursorLeft {
                 ..
             } => {
+                if !self.captured.is_empty() {
+                    self.left = true;
+                    return;
+                }
+                engine.window_cursor_moved(None);
                 event_handler(InputMessage::CursorLeft, engine);
             }
+
+            // Files
+            WindowEvent::HoveredFile(_) => self.push_file(FileDrop::Hovered, event_handler, engine),
+            WindowEvent::HoveredFileCancelled => self.push_file(FileDrop::Cancelled, event_handler, engine),
+            WindowEvent::DroppedFile(path) => {
+                self.push_file(FileDrop::Dropped(DroppedFile::Path(path)), event_handler, engine)
+            }
+
+            // Touch
+            WindowEvent::Touch(touch) => {
+                let id = touch.id;
+                let pos = engine.window_to_virtual(self.to_screen(touch.location));
+                match touch.phase {
+                    TouchPhase::Started => {
+                        self.touches.insert(id, pos);
+                        event_handler(
+                            InputMessage::TouchStarted {
+                                id,
+                                pos,
+                            },
+                            engine,
+                        );
+                    }
+                    TouchPhase::Moved => {
+                        let last = self.touches.insert(id, pos).unwrap_or(pos);
+                        event_handler(
+                            InputMessage::TouchMoved {
+                                id,
+                                pos,
+                                delta: pos - last,
+                            },
+                            engine,
+                        );
+                    }
+                    TouchPhase::Ended => {
+                        self.touches.remove(&id);
+                        event_handler(
+                            InputMessage::TouchEnded {
+                                id,
+                                pos,
+                            },
+                            engine,
+                        );
+                    }
+                    TouchPhase::Cancelled => {
+                        self.touches.remove(&id);
+                        event_handler(
+                            InputMessage::TouchCancelled {
+                                id,
+                                pos,
+                            },
+                            engine,
+                        );
+                    }
+                }
+            }
             _ => {}
         }
     }
 }
+
+/// If the character is typed text, rather than a control character or a private use character
+/// some platforms send for keys like the arrows.
+fn is_text(char: char) -> bool {
+    !char.is_control() && !('\u{E000}'..='\u{F8FF}').contains(&char)
+}
@@ -0,0 +1,135 @@
+use super::RawEvent;
+use crate::input::{GamepadAxis, GamepadButton, GamepadId};
+use crate::time::Instant;
+use core::time::Duration;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read};
+use std::os::unix::fs::OpenOptionsExt;
+
+/// The number of joystick device nodes checked for gamepads.
+const DEVICES: usize = 16;
+/// How often to check for newly connected gamepads.
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+// Event types from linux/joystick.h.
+const JS_EVENT_BUTTON: u8 = 0x01;
+const JS_EVENT_AXIS: u8 = 0x02;
+const JS_EVENT_INIT: u8 = 0x80;
+
+/// Reads gamepads through the Linux joystick interface at /dev/input/js*. Buttons and axes are
+/// mapped using the xpad layout, which most gamepad drivers follow.
+pub struct Backend {
+    devices: Vec<Option<File>>,
+    last_scan: Option<Instant>,
+}
+
+impl Backend {
+    pub fn new() -> Backend {
+        Backend {
+            devices: (0..DEVICES).map(|_| None).collect(),
+            last_scan: None,
+        }
+    }
+
+    pub fn poll(&mut self, events: &mut Vec<RawEvent>) {
+        if self.last_scan.map(|last| last.elapsed() < SCAN_INTERVAL) != Some(true) {
+            self.last_scan = Some(Instant::now());
+            self.scan(events);
+        }
+
+        for (index, device) in self.devices.iter_mut().enumerate() {
+            let id = GamepadId(index as u32);
+            let mut disconnected = false;
+            if let Some(file) = device {
+                let mut buffer = [0u8; 8];
+                loop {
+                    match file.read(&mut buffer) {
+                        Ok(8) => Self::convert(id, &buffer, events),
+                        Ok(0) => {
+                            disconnected = true;
+                            break;
+                        }
+                        Ok(_) => break,
+                        Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                        Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                        Err(_) => {
+                            disconnected = true;
+                            break;
+                        }
+                    }
+                }
+            }
+            if disconnected {
+                *device = None;
+                events.push(RawEvent::Disconnected(id));
+            }
+        }
+    }
+
+    fn scan(&mut self, events: &mut Vec<RawEvent>) {
+        for (index, device) in self.devices.iter_mut().enumerate() {
+            if device.is_some() {
+                continue;
+            }
+            let path = format!("/dev/input/js{}", index);
+            if let Ok(file) = OpenOptions::new().read(true).custom_flags(libc::O_NONBLOCK).open(path) {
+                *device = Some(file);
+                events.push(RawEvent::Connected(GamepadId(index as u32)));
+            }
+        }
+    }
+
+    fn convert(id: GamepadId, buffer: &[u8; 8], events: &mut Vec<RawEvent>) {
+        // struct js_event { u32 time; s16 value; u8 type; u8 number; }
+        let value = i16::from_ne_bytes([buffer[4], buffer[5]]);
+        let kind = buffer[6] & !JS_EVENT_INIT;
+        let number = buffer[7];
+        match kind {
+            JS_EVENT_BUTTON => events.push(RawEvent::Button(id, Self::button(number), value != 0)),
+            JS_EVENT_AXIS => {
+                let axis = Self::axis(number);
+                let value = (value as f32 / i16::MAX as f32).max(-1.0);
+                let value = match axis {
+                    // Triggers rest at -1.
+                    GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => (value + 1.0) / 2.0,
+                    // The joystick interface is positive down.
+                    GamepadAxis::LeftStickY | GamepadAxis::RightStickY | GamepadAxis::DPadY => -value,
+                    _ => value,
+                };
+                events.push(RawEvent::Axis(id, axis, value));
+            }
+            _ => {}
+        }
+    }
+
+    fn button(number: u8) -> GamepadButton {
+        match number {
+            0 => GamepadButton::South,
+            1 => GamepadButton::East,
+            2 => GamepadButton::West,
+            3 => GamepadButton::North,
+            4 => GamepadButton::LeftBumper,
+            5 => GamepadButton::RightBumper,
+            6 => GamepadButton::Select,
+            7 => GamepadButton::Start,
+            8 => GamepadButton::Mode,
+            9 => GamepadButton::LeftStick,
+            10 => GamepadButton::RightStick,
+            number => GamepadButton::Unknown(number),
+        }
+    }
+
+    fn axis(number: u8) -> GamepadAxis {
+        match number {
+            0 => GamepadAxis::LeftStickX,
+            1 => GamepadAxis::LeftStickY,
+            2 => GamepadAxis::LeftTrigger,
+            3 => GamepadAxis::RightStickX,
+            4 => GamepadAxis::RightStickY,
+            5 => GamepadAxis::RightTrigger,
+            6 => GamepadAxis::DPadX,
+            7 => GamepadAxis::DPadY,
+            number => GamepadAxis::Unknown(number),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+use self::linux::Backend;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm;
+#[cfg(target_arch = "wasm32")]
+use self::wasm::Backend;
+
+#[cfg(not(any(target_os = "linux", target_arch = "wasm32")))]
+mod unsupported;
+#[cfg(not(any(target_os = "linux", target_arch = "wasm32")))]
+use self::unsupported::Backend;
+
+use crate::input::{GamepadAxis, GamepadButton, GamepadId, InputMessage};
+use crate::Engine;
+use hashbrown::HashMap;
+
+/// Events reported by a platform backend before dead zones are applied.
+pub(crate) enum RawEvent {
+    Connected(GamepadId),
+    Disconnected(GamepadId),
+    Button(GamepadId, GamepadButton, bool),
+    Axis(GamepadId, GamepadAxis, f32),
+}
+
+pub struct GamepadConverter {
+    backend: Backend,
+    events: Vec<RawEvent>,
+    axes: HashMap<(GamepadId, GamepadAxis), f32>,
+}
+
+impl GamepadConverter {
+    pub fn new() -> GamepadConverter {
+        GamepadConverter {
+            backend: Backend::new(),
+            events: Vec::new(),
+            axes: HashMap::new(),
+        }
+    }
+
+    pub fn poll<T: 'static + FnMut(InputMessage, &mut Engine)>(
+        &mut self,
+        event_handler: &mut T,
+        engine: &mut Engine,
+    ) {
+        self.backend.poll(&mut self.events);
+        for event in self.events.drain(..) {
+            match event {
+                RawEvent::Connected(id) => event_handler(InputMessage::GamepadConnected(id), engine),
+                RawEvent::Disconnected(id) => {
+                    self.axes.retain(|(axis_id, _), _| *axis_id != id);
+                    event_handler(InputMessage::GamepadDisconnected(id), engine);
+                }
+                RawEvent::Button(id, button, true) => {
+                    event_handler(
+                        InputMessage::GamepadPressed {
+                            id,
+                            button,
+                        },
+                        engine,
+                    );
+                }
+                RawEvent::Button(id, button, false) => {
+                    event_handler(
+                        InputMessage::GamepadReleased {
+                            id,
+                            button,
+                        },
+                        engine,
+                    );
+                }
+                RawEvent::Axis(id, axis, value) => {
+                    let value = engine.gamepad_axis_settings_value(axis).apply(value);
+                    let last = self.axes.insert((id, axis), value).unwrap_or(0.0);
+                    if last != value {
+                        event_handler(
+                            InputMessage::GamepadAxis {
+                                id,
+                                axis,
+                                value,
+                            },
+                            engine,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
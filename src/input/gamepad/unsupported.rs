@@ -0,0 +1,12 @@
+use super::RawEvent;
+
+/// Gamepads aren't supported on this platform yet, so no events are reported.
+pub struct Backend;
+
+impl Backend {
+    pub fn new() -> Backend {
+        Backend
+    }
+
+    pub fn poll(&mut self, _events: &mut Vec<RawEvent>) {}
+}
@@ -0,0 +1,126 @@
+use super::RawEvent;
+use crate::input::{GamepadAxis, GamepadButton, GamepadId};
+use wasm_bindgen::JsCast;
+
+struct PadState {
+    buttons: Vec<bool>,
+    axes: Vec<f32>,
+}
+
+/// Polls gamepads through the browser's Gamepad API. Gamepads using the standard mapping report
+/// named buttons and axes.
+pub struct Backend {
+    pads: Vec<Option<PadState>>,
+}
+
+impl Backend {
+    pub fn new() -> Backend {
+        Backend {
+            pads: Vec::new(),
+        }
+    }
+
+    pub fn poll(&mut self, events: &mut Vec<RawEvent>) {
+        let gamepads = match web_sys::window().unwrap().navigator().get_gamepads() {
+            Ok(gamepads) => gamepads,
+            Err(_) => return,
+        };
+        if self.pads.len() < gamepads.length() as usize {
+            self.pads.resize_with(gamepads.length() as usize, || None);
+        }
+
+        for index in 0..self.pads.len() {
+            let id = GamepadId(index as u32);
+            let gamepad = gamepads
+                .get(index as u32)
+                .dyn_into::<web_sys::Gamepad>()
+                .ok()
+                .filter(|gamepad| gamepad.connected());
+            let gamepad = match gamepad {
+                Some(gamepad) => gamepad,
+                None => {
+                    if self.pads[index].take().is_some() {
+                        events.push(RawEvent::Disconnected(id));
+                    }
+                    continue;
+                }
+            };
+            let pad = self.pads[index].get_or_insert_with(|| {
+                events.push(RawEvent::Connected(id));
+                PadState {
+                    buttons: Vec::new(),
+                    axes: Vec::new(),
+                }
+            });
+
+            let buttons = gamepad.buttons();
+            pad.buttons.resize(buttons.length() as usize, false);
+            for number in 0..buttons.length() {
+                let button = match buttons.get(number).dyn_into::<web_sys::GamepadButton>() {
+                    Ok(button) => button,
+                    Err(_) => continue,
+                };
+                let pressed = button.pressed();
+                if pad.buttons[number as usize] != pressed {
+                    pad.buttons[number as usize] = pressed;
+                    events.push(RawEvent::Button(id, Self::button(number as u8), pressed));
+                }
+                // Triggers are analog buttons in the standard mapping.
+                match number {
+                    6 => events.push(RawEvent::Axis(id, GamepadAxis::LeftTrigger, button.value() as f32)),
+                    7 => events.push(RawEvent::Axis(id, GamepadAxis::RightTrigger, button.value() as f32)),
+                    _ => {}
+                }
+            }
+
+            let axes = gamepad.axes();
+            pad.axes.resize(axes.length() as usize, 0.0);
+            for number in 0..axes.length() {
+                let value = axes.get(number).as_f64().unwrap_or(0.0) as f32;
+                if pad.axes[number as usize] != value {
+                    pad.axes[number as usize] = value;
+                    let axis = Self::axis(number as u8);
+                    let value = match axis {
+                        // The Gamepad API is positive down.
+                        GamepadAxis::LeftStickY | GamepadAxis::RightStickY => -value,
+                        _ => value,
+                    };
+                    events.push(RawEvent::Axis(id, axis, value));
+                }
+            }
+        }
+    }
+
+    fn button(number: u8) -> GamepadButton {
+        match number {
+            0 => GamepadButton::South,
+            1 => GamepadButton::East,
+            2 => GamepadButton::West,
+            3 => GamepadButton::North,
+            4 => GamepadButton::LeftBumper,
+            5 => GamepadButton::RightBumper,
+            6 => GamepadButton::LeftTrigger,
+            7 => GamepadButton::RightTrigger,
+            8 => GamepadButton::Select,
+            9 => GamepadButton::Start,
+            10 => GamepadButton::LeftStick,
+            11 => GamepadButton::RightStick,
+            12 => GamepadButton::DPadUp,
+            13 => GamepadButton::DPadDown,
+            14 => GamepadButton::DPadLeft,
+            15 => GamepadButton::DPadRight,
+            16 => GamepadButton::Mode,
+            number => GamepadButton::Unknown(number),
+        }
+    }
+
+    fn axis(number: u8) -> GamepadAxis {
+        match number {
+            0 => GamepadAxis::LeftStickX,
+            1 => GamepadAxis::LeftStickY,
+            2 => GamepadAxis::RightStickX,
+            3 => GamepadAxis::RightStickY,
+            number => GamepadAxis::Unknown(number),
+        }
+    }
+}
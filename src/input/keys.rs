@@ -0,0 +1,250 @@
+use crate::input::{KeyboardButton, KeyboardModifiers};
+use hashbrown::HashMap;
+
+/// Where a key is on the keyboard, for keys that appear more than once.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum KeyLocation {
+    /// The key only appears once, or is the main copy, like the digits on the top row.
+    Standard,
+    Left,
+    Right,
+    Numpad,
+}
+
+/// What a key means, without where it is. The 1 on the top row and the 1 on the numpad are both
+/// `Digit(1)`, and the left and right shift keys are both `Shift`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum KeySymbol {
+    Digit(u8),
+    /// An uppercase letter from A to Z.
+    Letter(char),
+    /// A punctuation or math key. Contains the character the key is named for, which is what it
+    /// types on a US layout. Other layouts can type something else, see `Engine::key_char`.
+    Symbol(char),
+    Shift,
+    Control,
+    Alt,
+    /// The Windows, Command, or Super key.
+    Logo,
+    Enter,
+    /// Any other key, like the arrows or function keys.
+    Other(KeyboardButton),
+}
+
+/// A key split into what it means and where it is, so bindings can treat the numpad and top row
+/// digits, or left and right modifiers, as the same key or as different keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyId {
+    pub symbol: KeySymbol,
+    pub location: KeyLocation,
+}
+
+impl KeyId {
+    pub fn new(key: KeyboardButton) -> KeyId {
+        use KeyboardButton::*;
+        let (symbol, location) = match key {
+            Key1 | Key2 | Key3 | Key4 | Key5 | Key6 | Key7 | Key8 | Key9 => {
+                (KeySymbol::Digit(key as u8 - Key1 as u8 + 1), KeyLocation::Standard)
+            }
+            Key0 => (KeySymbol::Digit(0), KeyLocation::Standard),
+            A | B | C | D | E | F | G | H | I | J | K | L | M | N | O | P | Q | R | S | T | U | V | W | X
+            | Y | Z => (KeySymbol::Letter((b'A' + (key as u8 - A as u8)) as char), KeyLocation::Standard),
+            Numpad0 | Numpad1 | Numpad2 | Numpad3 | Numpad4 | Numpad5 | Numpad6 | Numpad7 | Numpad8
+            | Numpad9 => (KeySymbol::Digit(key as u8 - Numpad0 as u8), KeyLocation::Numpad),
+            NumpadAdd => (KeySymbol::Symbol('+'), KeyLocation::Numpad),
+            NumpadSubtract => (KeySymbol::Symbol('-'), KeyLocation::Numpad),
+            NumpadMultiply => (KeySymbol::Symbol('*'), KeyLocation::Numpad),
+            NumpadDivide => (KeySymbol::Symbol('/'), KeyLocation::Numpad),
+            NumpadDecimal => (KeySymbol::Symbol('.'), KeyLocation::Numpad),
+            NumpadComma => (KeySymbol::Symbol(','), KeyLocation::Numpad),
+            NumpadEquals => (KeySymbol::Symbol('='), KeyLocation::Numpad),
+            NumpadEnter => (KeySymbol::Enter, KeyLocation::Numpad),
+            Return => (KeySymbol::Enter, KeyLocation::Standard),
+            LShift => (KeySymbol::Shift, KeyLocation::Left),
+            RShift => (KeySymbol::Shift, KeyLocation::Right),
+            LControl => (KeySymbol::Control, KeyLocation::Left),
+            RControl => (KeySymbol::Control, KeyLocation::Right),
+            LAlt => (KeySymbol::Alt, KeyLocation::Left),
+            RAlt => (KeySymbol::Alt, KeyLocation::Right),
+            LWin => (KeySymbol::Logo, KeyLocation::Left),
+            RWin => (KeySymbol::Logo, KeyLocation::Right),
+            _ => match us_symbol(key) {
+                Some(symbol) => (KeySymbol::Symbol(symbol), KeyLocation::Standard),
+                None => (KeySymbol::Other(key), KeyLocation::Standard),
+            },
+        };
+        KeyId {
+            symbol,
+            location,
+        }
+    }
+
+    /// The character the key types on a US layout without modifiers, if it types one.
+    pub fn us_char(&self) -> Option<char> {
+        match self.symbol {
+            KeySymbol::Digit(digit) => Some((b'0' + digit) as char),
+            KeySymbol::Letter(letter) => Some(letter.to_ascii_lowercase()),
+            KeySymbol::Symbol(symbol) => Some(symbol),
+            _ => None,
+        }
+    }
+}
+
+/// The character a punctuation key is named for.
+fn us_symbol(key: KeyboardButton) -> Option<char> {
+    use KeyboardButton::*;
+    Some(match key {
+        Apostrophe => '\'',
+        Asterisk => '*',
+        At => '@',
+        Backslash => '\\',
+        Caret => '^',
+        Colon => ':',
+        Comma => ',',
+        Equals => '=',
+        Grave => '`',
+        LBracket => '[',
+        Minus => '-',
+        Period => '.',
+        Plus => '+',
+        RBracket => ']',
+        Semicolon => ';',
+        Slash => '/',
+        Underline => '_',
+        Yen => '¥',
+        _ => return None,
+    })
+}
+
+/// A label for a key that doesn't type a character.
+fn named_label(id: KeyId) -> String {
+    use KeyboardButton::*;
+    let side = match id.location {
+        KeyLocation::Left => "Left ",
+        KeyLocation::Right => "Right ",
+        KeyLocation::Numpad => "Num ",
+        KeyLocation::Standard => "",
+    };
+    let name = match id.symbol {
+        KeySymbol::Shift => "Shift",
+        KeySymbol::Control => "Ctrl",
+        KeySymbol::Alt => "Alt",
+        KeySymbol::Logo => "Super",
+        KeySymbol::Enter => "Enter",
+        KeySymbol::Other(key) => match key {
+            Escape => "Esc",
+            Back => "Backspace",
+            Tab => "Tab",
+            Space => "Space",
+            Left => "Left",
+            Right => "Right",
+            Up => "Up",
+            Down => "Down",
+            Insert => "Insert",
+            Delete => "Delete",
+            Home => "Home",
+            End => "End",
+            PageUp => "Page Up",
+            PageDown => "Page Down",
+            Capital => "Caps Lock",
+            Numlock => "Num Lock",
+            Scroll => "Scroll Lock",
+            Snapshot => "Print Screen",
+            Pause => "Pause",
+            Apps => "Menu",
+            _ => return format!("{:?}", key),
+        },
+        _ => return String::new(),
+    };
+    format!("{}{}", side, name)
+}
+
+/// Learns the characters keys type on the current keyboard layout, by pairing each key press with
+/// the character received right after it. Only presses without modifiers are learned, since
+/// modifiers change the character.
+pub(crate) struct KeyLayout {
+    chars: HashMap<KeyboardButton, char>,
+    pressed: Option<KeyboardButton>,
+}
+
+impl KeyLayout {
+    pub fn new() -> KeyLayout {
+        KeyLayout {
+            chars: HashMap::new(),
+            pressed: None,
+        }
+    }
+
+    pub fn key_pressed(&mut self, key: KeyboardButton, modifiers: KeyboardModifiers) {
+        self.pressed = if modifiers.is_empty() {
+            Some(key)
+        } else {
+            None
+        };
+    }
+
+    pub fn received_character(&mut self, char: char) {
+        if let Some(key) = self.pressed.take() {
+            if !char.is_control() && !char.is_whitespace() {
+                self.chars.insert(key, char);
+            }
+        }
+    }
+
+    /// The character the key types without modifiers. Keys that haven't been typed yet fall back
+    /// to what they type on a US layout.
+    pub fn char(&self, key: KeyboardButton) -> Option<char> {
+        self.chars.get(&key).copied().or_else(|| KeyId::new(key).us_char())
+    }
+
+    /// A short label for the key, like "Q", "Num 1", or "Left Shift".
+    pub fn label(&self, key: KeyboardButton) -> String {
+        let id = KeyId::new(key);
+        match self.char(key) {
+            Some(char) => {
+                let char: String = char.to_uppercase().collect();
+                match id.location {
+                    KeyLocation::Numpad => format!("Num {}", char),
+                    _ => char,
+                }
+            }
+            None => named_label(id),
+        }
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identities() {
+        let top = KeyId::new(KeyboardButton::Key1);
+        let numpad = KeyId::new(KeyboardButton::Numpad1);
+        assert_eq!(top.symbol, numpad.symbol);
+        assert_eq!((top.location, numpad.location), (KeyLocation::Standard, KeyLocation::Numpad));
+        assert_eq!(KeyId::new(KeyboardButton::Key0).symbol, KeySymbol::Digit(0));
+        assert_eq!(KeyId::new(KeyboardButton::Q).symbol, KeySymbol::Letter('Q'));
+        assert_eq!(KeyId::new(KeyboardButton::RShift).location, KeyLocation::Right);
+
+        // An AZERTY layout types a, not q, with the Q key.
+        let mut layout = KeyLayout::new();
+        assert_eq!(layout.label(KeyboardButton::Q), "Q");
+        layout.key_pressed(KeyboardButton::Q, KeyboardModifiers::empty());
+        layout.received_character('a');
+        assert_eq!(layout.char(KeyboardButton::Q), Some('a'));
+        assert_eq!(layout.label(KeyboardButton::Q), "A");
+
+        // Shifted characters aren't learned.
+        layout.key_pressed(KeyboardButton::Key2, KeyboardModifiers::SHIFT);
+        layout.received_character('@');
+        assert_eq!(layout.label(KeyboardButton::Key2), "2");
+        assert_eq!(layout.label(KeyboardButton::Numpad2), "Num 2");
+        assert_eq!(layout.label(KeyboardButton::LControl), "Left Ctrl");
+        assert_eq!(layout.label(KeyboardButton::F5), "F5");
+    }
+}
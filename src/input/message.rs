@@ -1,6 +1,10 @@
+use crate::types::AnimatedSpriteId;
 use cgmath::*;
+use core::time::Duration;
+use std::path::PathBuf;
 
 // Re-exports.
+pub use winit::event::ModifiersState as KeyboardModifiers;
 pub use winit::event::MouseButton as CursorButton;
 pub use winit::event::VirtualKeyCode as KeyboardButton;
 
@@ -10,12 +14,25 @@ pub use winit::event::VirtualKeyCode as KeyboardButton;
 pub enum InputMessage {
     /// The window has requested it close.
     CloseRequested,
-    /// Received a character.
+    /// Received a character. This includes control characters, like backspace and enter. Use
+    /// Character for text input.
     ReceivedCharacter(char),
+    /// Typed text, one character at a time, sent just after its ReceivedCharacter message.
+    /// Characters follow the keyboard layout and held modifiers, and text composed in an IME
+    /// arrives once it's committed. Control characters and the platform's private characters for
+    /// keys like the arrows aren't sent. Composition in progress isn't reported yet, since the
+    /// windowing library doesn't provide it, but the IME's candidate window can be placed with
+    /// `Engine::window_ime_position`.
+    Character(char),
     /// Keyboard press event.
     KeyPressed(KeyboardButton),
     /// Keyboard release event.
     KeyReleased(KeyboardButton),
+    /// The held modifier keys changed.
+    ModifiersChanged(KeyboardModifiers),
+    /// A registered shortcut was pressed. Contains the name of its action. This is sent just before
+    /// the KeyPressed or CursorPressed message of the key or button that completed it.
+    Shortcut(&'static str),
     /// Cursor press event. Contains the button pressed and the position it was pressed at.
     CursorPressed {
         /// Button pressed.
@@ -40,12 +57,104 @@ pub enum InputMessage {
         /// Change from last position.
         delta: Vector2<f32>,
     },
-    /// Cursor left the bounds of the window event.
+    /// Relative cursor movement, sent while the cursor is grabbed, see `Engine::cursor_grab`.
+    /// Deltas come from the device, so they keep coming when the cursor is held in place or stopped
+    /// at the window's edge. They aren't scaled by a virtual resolution, and Y points up.
+    CursorMotion {
+        /// Change since the last motion, in the device's units, which are usually close to pixels.
+        delta: Vector2<f32>,
+    },
+    /// Cursor left the bounds of the window event. If a cursor button is held, this waits until
+    /// it's released, see `Engine::cursor_capture`.
     CursorLeft,
     /// Cursor entered the bounds of the window event.
     CursorEntered,
+    /// A finger touched the screen.
+    TouchStarted {
+        /// Identifies the finger until it's lifted.
+        id: u64,
+        /// Position of the touch, in the same coordinates as cursor events. Use
+        /// `Layer::screen_to_layer` to get the position within a layer.
+        pos: Vector2<f32>,
+    },
+    /// A finger moved on the screen.
+    TouchMoved {
+        /// Identifies the finger until it's lifted.
+        id: u64,
+        /// Current position of the touch.
+        pos: Vector2<f32>,
+        /// Change from the last position of this touch.
+        delta: Vector2<f32>,
+    },
+    /// A finger was lifted from the screen.
+    TouchEnded {
+        /// Identifies the finger. The id may be reused by a later touch.
+        id: u64,
+        /// Position the touch ended at.
+        pos: Vector2<f32>,
+    },
+    /// A touch was cancelled by the platform, like when the window loses focus.
+    TouchCancelled {
+        /// Identifies the finger. The id may be reused by a later touch.
+        id: u64,
+        /// Last position of the touch.
+        pos: Vector2<f32>,
+    },
+    /// Files are being dragged over the window. Sent once when the drag enters the window.
+    FileHovered,
+    /// Files dragged over the window left it without being dropped.
+    FileHoverCancelled,
+    /// A file was dropped on the window. Several files dropped together send one message each.
+    /// Get the file with `Engine::dropped_file` while handling this message.
+    FileDropped {
+        /// The last known cursor position. Platforms don't report the cursor during a drag, so
+        /// this can be where the cursor was before the drag entered the window.
+        pos: Vector2<f32>,
+    },
     /// Window resized event. Contains the new dimensions of the window.
     WindowResized(Vector2<f32>),
+    /// A gamepad was connected. Gamepads are only read on Linux and the web, so gamepad messages
+    /// are never sent on Windows or macOS.
+    GamepadConnected(GamepadId),
+    /// A gamepad was disconnected. Only sent on Linux and the web.
+    GamepadDisconnected(GamepadId),
+    /// Gamepad button press event. Only sent on Linux and the web.
+    GamepadPressed {
+        /// Gamepad the button belongs to.
+        id: GamepadId,
+        /// Button pressed.
+        button: GamepadButton,
+    },
+    /// Gamepad button release event. Only sent on Linux and the web.
+    GamepadReleased {
+        /// Gamepad the button belongs to.
+        id: GamepadId,
+        /// Button released.
+        button: GamepadButton,
+    },
+    /// Gamepad axis event. Sent when the value of the axis changes after the dead zone is applied.
+    /// Only sent on Linux and the web.
+    GamepadAxis {
+        /// Gamepad the axis belongs to.
+        id: GamepadId,
+        /// Axis that changed.
+        axis: GamepadAxis,
+        /// Sticks range from [-1, 1], with positive values being up and right. Triggers range from
+        /// [0, 1].
+        value: f32,
+    },
+    /// Photo mode was entered (true) or left (false).
+    PhotoModeChanged(bool),
+    /// An animated sprite playing in the Once mode reached the end of its last frame.
+    AnimationCompleted(AnimatedSpriteId),
+    /// Several frames in a row took longer than the frame budget. This is sent again each time the
+    /// same number of further frames run over.
+    FrameBudgetExceeded {
+        /// The budget that was exceeded.
+        budget: Duration,
+        /// How many frames in a row have been over budget.
+        frames: u32,
+    },
     /// This event is useful as a place to put your code that should be run after all state-changing
     /// events have been handled and you want to do stuff (updating state, performing calculations,
     /// etc) that happens as the "main body" of your event loop. The value is the time passed since
@@ -53,6 +162,18 @@ pub enum InputMessage {
     Update(f32),
 }
 
+/// A file dropped on the window.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DroppedFile {
+    /// The path to the file, on native platforms.
+    Path(PathBuf),
+    /// The file's name and contents, on the web, where pages can't see paths.
+    Bytes {
+        name: String,
+        bytes: Vec<u8>,
+    },
+}
+
 /// A cursor wheel movement. Some mice have left and right scroll options.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ScrollDirection {
@@ -65,3 +186,54 @@ pub enum ScrollDirection {
     /// Cursor wheel scrolled right.
     Right,
 }
+
+/// Identifies a connected gamepad. Ids are reused after a gamepad disconnects.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GamepadId(pub(crate) u32);
+
+impl GamepadId {
+    /// The platform's index for the gamepad.
+    pub fn index(&self) -> u32 {
+        self.0
+    }
+}
+
+/// A gamepad button. Face buttons are named by their position, so South is A on an Xbox layout
+/// and Cross on a PlayStation layout.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    /// A button without a known mapping. Contains the platform's button number.
+    Unknown(u8),
+}
+
+/// A gamepad axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+    DPadX,
+    DPadY,
+    /// An axis without a known mapping. Contains the platform's axis number.
+    Unknown(u8),
+}
@@ -1,5 +1,13 @@
 mod converter;
+mod gamepad;
+mod keys;
 mod message;
+mod shortcut;
 
 pub use self::converter::*;
+pub(crate) use self::gamepad::GamepadConverter;
+pub(crate) use self::keys::KeyLayout;
+pub use self::keys::{KeyId, KeyLocation, KeySymbol};
 pub use self::message::*;
+pub(crate) use self::shortcut::ShortcutRegistry;
+pub use self::shortcut::{Shortcut, ShortcutTrigger};
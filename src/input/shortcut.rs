@@ -0,0 +1,112 @@
+use crate::input::{CursorButton, KeyboardButton, KeyboardModifiers};
+use hashbrown::HashMap;
+
+/// What completes a shortcut once its modifiers are held.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ShortcutTrigger {
+    Key(KeyboardButton),
+    Cursor(CursorButton),
+}
+
+/// A key or cursor button pressed while exactly a set of modifiers is held, like Ctrl+S or
+/// Shift+Click. Left and right modifier keys are treated the same.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub modifiers: KeyboardModifiers,
+    pub trigger: ShortcutTrigger,
+}
+
+impl Shortcut {
+    /// A shortcut completed by a key.
+    pub fn key(modifiers: KeyboardModifiers, key: KeyboardButton) -> Shortcut {
+        Shortcut {
+            modifiers,
+            trigger: ShortcutTrigger::Key(key),
+        }
+    }
+
+    /// A shortcut completed by a cursor button.
+    pub fn cursor(modifiers: KeyboardModifiers, button: CursorButton) -> Shortcut {
+        Shortcut {
+            modifiers,
+            trigger: ShortcutTrigger::Cursor(button),
+        }
+    }
+}
+
+/// Maps shortcuts to the names of the actions they fire.
+pub(crate) struct ShortcutRegistry {
+    bindings: HashMap<Shortcut, &'static str>,
+}
+
+impl ShortcutRegistry {
+    pub fn new() -> ShortcutRegistry {
+        ShortcutRegistry {
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Binds the shortcut to the action. If a different action already has the shortcut, nothing
+    /// changes and that action's name is returned as the error.
+    pub fn register(&mut self, name: &'static str, shortcut: Shortcut) -> Result<(), &'static str> {
+        match self.bindings.get(&shortcut) {
+            Some(&existing) if existing != name => Err(existing),
+            _ => {
+                self.bindings.insert(shortcut, name);
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes every shortcut bound to the action.
+    pub fn unregister(&mut self, name: &str) {
+        self.bindings.retain(|_, action| *action != name);
+    }
+
+    /// The shortcuts bound to the action.
+    pub fn shortcuts(&self, name: &str) -> Vec<Shortcut> {
+        self.bindings.iter().filter(|(_, action)| **action == name).map(|(shortcut, _)| *shortcut).collect()
+    }
+
+    /// The action fired by the trigger with the modifiers held, if any. Modifiers must match
+    /// exactly, so Ctrl+Shift+S doesn't fire a Ctrl+S shortcut.
+    pub fn find(&self, modifiers: KeyboardModifiers, trigger: ShortcutTrigger) -> Option<&'static str> {
+        self.bindings
+            .get(&Shortcut {
+                modifiers,
+                trigger,
+            })
+            .copied()
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflicts() {
+        let mut registry = ShortcutRegistry::new();
+        let save = Shortcut::key(KeyboardModifiers::CTRL, KeyboardButton::S);
+        let save_as = Shortcut::key(KeyboardModifiers::CTRL | KeyboardModifiers::SHIFT, KeyboardButton::S);
+
+        assert_eq!(registry.register("save", save), Ok(()));
+        assert_eq!(registry.register("save", save), Ok(()));
+        assert_eq!(registry.register("save_as", save), Err("save"));
+        assert_eq!(registry.register("save_as", save_as), Ok(()));
+
+        let trigger = ShortcutTrigger::Key(KeyboardButton::S);
+        assert_eq!(registry.find(KeyboardModifiers::CTRL, trigger), Some("save"));
+        assert_eq!(registry.find(save_as.modifiers, trigger), Some("save_as"));
+        assert_eq!(registry.find(KeyboardModifiers::empty(), trigger), None);
+
+        registry.unregister("save");
+        assert_eq!(registry.find(KeyboardModifiers::CTRL, trigger), None);
+        assert_eq!(registry.register("save_as", save), Ok(()));
+        assert_eq!(registry.shortcuts("save_as").len(), 2);
+    }
+}
@@ -1,28 +1,54 @@
+//! Storm is a simple 2D renderer designed for performance, with an OpenGL backend for Windows,
+//! Linux, macOS, and the web.
+//!
+//! # Platform support
+//!
+//! Rendering, windowing, keyboard, cursor, and touch input work on every platform. Gamepads are
+//! only read on Linux, from the joystick devices, and on the web, through the Gamepad API. On
+//! Windows and macOS no gamepad messages are sent and the `Engine::gamepad_*` settings have no
+//! effect.
+
 #![allow(dead_code, non_camel_case_types, non_snake_case)]
 
 #[macro_use]
 pub extern crate log;
 extern crate alloc;
 
+pub mod assets;
+pub mod dialog;
 pub mod math;
 pub mod time;
 
+pub use crate::animation::{AnchoredText, AnimatedSprite, KeyframedSprite};
 pub use crate::input::*;
-pub use crate::render::{ClearMode, Layer};
+pub use crate::particle::{FloatingText, ParticleEmitter};
+pub use crate::render::{
+    ClearMode, Layer, LightMap, MeshLayer, PixelReadback, PrimitiveLayer, RenderTarget, ShapeLayer,
+    SpriteSender,
+};
 pub use crate::types::*;
+pub use crate::utility::pool::{Pool, PoolHandle};
 pub use cgmath;
 
+mod animation;
 mod input;
+mod particle;
+mod photo;
 mod render;
 mod text;
 mod texture;
 mod types;
 mod utility;
 
-use crate::input::InputConverter;
+use crate::animation::AnimationSystem;
+use crate::particle::ParticleSystem;
+use crate::photo::{PhotoKey, PhotoMode};
 use crate::render::Renderer;
-use crate::time::{Instant, Timer};
+use crate::time::{FrameBudget, FrameHistory, FrameSpans, Instant, Watchdog};
+use crate::utility::bad::UnsafeShared;
+use cgmath::Vector3;
 use core::time::Duration;
+use hashbrown::HashMap;
 use winit::event::Event;
 use winit::event_loop::ControlFlow;
 
@@ -30,11 +56,32 @@ use winit::event_loop::ControlFlow;
 /// API on this type. The engine is send, and can be moved between threads.
 pub struct Engine {
     render: Renderer,
+    particles: UnsafeShared<ParticleSystem>,
+    animations: UnsafeShared<AnimationSystem>,
+    photo: PhotoMode,
+    /// If clearing the photo mode settings left photo mode, so PhotoModeChanged is sent next frame.
+    photo_left: bool,
+    shortcuts: ShortcutRegistry,
     stop: bool,
     control_flow: Option<ControlFlow>,
     last_update: Instant,
     wait_next: Instant,
     wait_periodic: Option<Duration>,
+    watchdog: Option<Watchdog>,
+    frame_history: FrameHistory,
+    spans: FrameSpans,
+    frame_budget: Option<FrameBudget>,
+    quality: QualitySettings,
+    pixel_art: bool,
+    cursor_capture: bool,
+    cursor_grab: bool,
+    time_scale: f32,
+    paused: bool,
+    step: Option<f32>,
+    gamepad_axis_default: GamepadAxisSettings,
+    gamepad_axes: HashMap<GamepadAxis, GamepadAxisSettings>,
+    key_layout: KeyLayout,
+    dropped_file: Option<DroppedFile>,
 }
 
 impl Engine {
@@ -47,17 +94,37 @@ impl Engine {
         let event_loop = winit::event_loop::EventLoop::new();
         let render = Renderer::new(&desc, &event_loop);
         let mut input = InputConverter::new(render.window_logical_size());
+        let mut gamepads = GamepadConverter::new();
         let mut engine = Engine {
             render,
+            particles: ParticleSystem::new(),
+            animations: AnimationSystem::new(),
+            photo: PhotoMode::new(),
+            photo_left: false,
+            shortcuts: ShortcutRegistry::new(),
             stop: false,
             control_flow: Some(ControlFlow::Poll),
             last_update: Instant::now(),
             wait_next: Instant::now(),
             wait_periodic: None,
+            watchdog: None,
+            frame_history: FrameHistory::new(),
+            spans: FrameSpans::new(),
+            frame_budget: None,
+            quality: QualitySettings::default(),
+            pixel_art: false,
+            cursor_capture: true,
+            cursor_grab: false,
+            time_scale: 1.0,
+            paused: false,
+            step: None,
+            gamepad_axis_default: GamepadAxisSettings::default(),
+            gamepad_axes: HashMap::new(),
+            key_layout: KeyLayout::new(),
+            dropped_file: None,
         };
         info!("Starting handler");
         let mut event_handler = event_handler_creator(&mut engine);
-        let mut update_timer = Timer::new("InputMessage::Update");
         info!("Starting loop");
         event_loop.run(move |event, _, control_flow| {
             match event {
@@ -65,7 +132,17 @@ impl Engine {
                     event,
                     ..
                 } => {
+                    engine.spans.begin(FramePhase::Input);
                     input.push(event, &mut event_handler, &mut engine);
+                    engine.spans.end(FramePhase::Input);
+                }
+                Event::DeviceEvent {
+                    event,
+                    ..
+                } => {
+                    engine.spans.begin(FramePhase::Input);
+                    input.push_device(event, &mut event_handler, &mut engine);
+                    engine.spans.end(FramePhase::Input);
                 }
                 Event::MainEventsCleared => {
                     let now = Instant::now();
@@ -74,15 +151,65 @@ impl Engine {
                             engine.wait_next = now + duration;
                             engine.control_flow = Some(ControlFlow::WaitUntil(engine.wait_next));
                         }
-                        let delta = (now - engine.last_update).as_secs_f32();
-                        update_timer.start();
+                        engine.frame_history.push(now - engine.last_update);
+                        if let Some(budget) = &mut engine.frame_budget {
+                            if let Some(frames) = budget.frame(now - engine.last_update) {
+                                let budget = budget.budget();
+                                event_handler(
+                                    InputMessage::FrameBudgetExceeded {
+                                        budget,
+                                        frames,
+                                    },
+                                    &mut engine,
+                                );
+                            }
+                        }
+                        engine.spans.begin(FramePhase::Input);
+                        gamepads.poll(&mut event_handler, &mut engine);
+                        for drop in engine.render.window_file_drops() {
+                            input.push_file(drop, &mut event_handler, &mut engine);
+                        }
+                        if core::mem::take(&mut engine.photo_left) {
+                            event_handler(InputMessage::PhotoModeChanged(false), &mut engine);
+                        }
+                        engine.spans.end(FramePhase::Input);
+                        let delta = if engine.photo.is_active() {
+                            0.0
+                        } else if let Some(step) = engine.render.window_record_step() {
+                            step
+                        } else {
+                            (now - engine.last_update).as_secs_f32()
+                        };
+                        let delta = engine.time_delta(delta);
+                        engine.spans.begin(FramePhase::Simulation);
+                        engine.particles.update(delta);
+                        engine.animations.update(delta);
+                        for id in engine.animations.take_completed() {
+                            event_handler(InputMessage::AnimationCompleted(id), &mut engine);
+                        }
+                        engine.spans.end(FramePhase::Simulation);
+                        engine.spans.begin(FramePhase::Update);
+                        engine.render.window_camera(engine.photo.camera());
+                        engine.render.frame_begin();
                         event_handler(InputMessage::Update(delta), &mut engine);
+                        engine.spans.end(FramePhase::Update);
+                        engine.spans.begin(FramePhase::Draw);
+                        if engine.render.stats_overlay_enabled() {
+                            let stats = engine.stats();
+                            engine.render.stats_overlay_update(&stats);
+                        }
+                        let presented = Instant::now();
                         engine.render.window_swap_buffers();
-                        update_timer.stop();
+                        engine.spans.end(FramePhase::Draw);
+                        engine.spans.frame_end();
+                        if let Some(watchdog) = &mut engine.watchdog {
+                            watchdog.frame(presented - now, presented.elapsed());
+                        }
                         engine.last_update = now;
                     }
                 }
                 Event::LoopDestroyed => {
+                    engine.render.window_record(None);
                     info!("Stopped engine");
                     engine.stop = true;
                 }
@@ -97,10 +224,247 @@ impl Engine {
         });
     }
 
+    /// Applies the pause and time scale to a frame's delta.
+    fn time_delta(&mut self, delta: f32) -> f32 {
+        let step = self.step.take();
+        if self.paused {
+            step.unwrap_or(0.0)
+        } else {
+            delta * self.time_scale
+        }
+    }
+
     pub(crate) fn window_check_resize(&mut self) {
         self.render.window_check_resize();
     }
 
+    pub(crate) fn cursor_capture_enabled(&self) -> bool {
+        self.cursor_capture
+    }
+
+    pub(crate) fn cursor_grab_restore(&mut self) {
+        if self.cursor_grab {
+            self.render.window_cursor_grab(true);
+        }
+    }
+
+    pub(crate) fn window_to_virtual(&self, pos: cgmath::Vector2<f32>) -> cgmath::Vector2<f32> {
+        self.render.window_to_virtual(pos)
+    }
+
+    pub(crate) fn window_region_pressed(&mut self, pos: &cgmath::Vector2<f32>) -> bool {
+        self.render.window_region_pressed(pos)
+    }
+
+    pub(crate) fn window_region_cursor_moved(&mut self, cursor: winit::dpi::PhysicalPosition<f64>) {
+        self.render.window_region_cursor_moved(cursor);
+    }
+
+    pub(crate) fn window_cursor_moved(&mut self, pos: Option<cgmath::Vector2<f32>>) {
+        self.render.window_cursor_moved(pos);
+    }
+
+    pub(crate) fn window_region_released(&mut self) {
+        self.render.window_region_released();
+    }
+
+    pub(crate) fn photo_key_pressed(&mut self, key: KeyboardButton) -> PhotoKey {
+        let result = self.photo.key_pressed(key);
+        if let PhotoKey::Consumed = result {
+            if self.photo.capture_key() == Some(key) {
+                if let Some(path) = self.photo.capture_path() {
+                    self.render.window_screenshot(path);
+                }
+            }
+        }
+        result
+    }
+
+    pub(crate) fn photo_key_released(&mut self, key: KeyboardButton) -> bool {
+        self.photo.key_released(key)
+    }
+
+    pub(crate) fn photo_cursor_pressed(&mut self, button: CursorButton) -> bool {
+        self.photo.cursor_pressed(button)
+    }
+
+    pub(crate) fn photo_cursor_released(&mut self, button: CursorButton) -> bool {
+        self.photo.cursor_released(button)
+    }
+
+    pub(crate) fn photo_cursor_moved(&mut self, delta: cgmath::Vector2<f32>) -> bool {
+        self.photo.cursor_moved(delta)
+    }
+
+    pub(crate) fn photo_cursor_scroll(&mut self, direction: ScrollDirection) -> bool {
+        self.photo.cursor_scroll(direction)
+    }
+
+    pub(crate) fn key_layout_pressed(&mut self, key: KeyboardButton, modifiers: KeyboardModifiers) {
+        self.key_layout.key_pressed(key, modifiers);
+    }
+
+    pub(crate) fn key_layout_character(&mut self, char: char) {
+        self.key_layout.received_character(char);
+    }
+
+    pub(crate) fn dropped_file_set(&mut self, file: Option<DroppedFile>) {
+        self.dropped_file = file;
+    }
+
+    pub(crate) fn shortcut_find(
+        &self,
+        modifiers: KeyboardModifiers,
+        trigger: ShortcutTrigger,
+    ) -> Option<&'static str> {
+        self.shortcuts.find(modifiers, trigger)
+    }
+
+    pub(crate) fn gamepad_axis_settings_value(&self, axis: GamepadAxis) -> GamepadAxisSettings {
+        self.gamepad_axes.get(&axis).copied().unwrap_or(self.gamepad_axis_default)
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Animation
+    // ////////////////////////////////////////////////////////
+
+    /// Creates a new animated sprite that plays the animation on the sprite's texture. The engine
+    /// advances it before each update. In the Once mode, an AnimationCompleted message is sent when
+    /// it reaches the end. The sprite stops being advanced when it's dropped.
+    pub fn animated_sprite_create(
+        &mut self,
+        sprite: Sprite,
+        animation: &Animation,
+        mode: AnimationMode,
+    ) -> AnimatedSprite {
+        AnimationSystem::sprite_create(&mut self.animations, sprite, animation, mode)
+    }
+
+    /// Anchors text to an animated sprite, like a name tag or a damage number. The engine moves the
+    /// text to the sprite before each update. See `AnchoredText` for where it's placed.
+    pub fn anchored_text_create(
+        &mut self,
+        sprite: &AnimatedSprite,
+        text: Text,
+        offset: cgmath::Vector2<f32>,
+    ) -> AnchoredText {
+        AnimationSystem::anchor_create(&mut self.animations, sprite, text, offset)
+    }
+
+    /// Drives a sprite created with `Layer::sprite_create` from keyframe tracks. The engine
+    /// advances the tracks before each update and writes the tracked properties into the layer's
+    /// sprite. The sprite stops being driven when the keyframed sprite is dropped. Tokens remember
+    /// the layer that created them, so if the token is from a different layer than the one given,
+    /// this function will panic.
+    pub fn keyframed_sprite_create(
+        &mut self,
+        layer: &Layer,
+        token: &SpriteToken,
+        keyframes: SpriteKeyframes,
+        mode: AnimationMode,
+    ) -> KeyframedSprite {
+        AnimationSystem::keyframes_create(&mut self.animations, layer, token, keyframes, mode)
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Gamepad
+    // ////////////////////////////////////////////////////////
+
+    /// Sets the dead zone applied to gamepad axes, from [0, 1). Axis values closer to rest than the
+    /// dead zone are reported as 0, and the remaining range is rescaled to still reach 1. The
+    /// default is 0.1. Axes with their own settings are unaffected. Gamepads are only read on Linux
+    /// and the web, so this has no effect on Windows or macOS.
+    pub fn gamepad_deadzone(&mut self, deadzone: f32) {
+        self.gamepad_axis_default.deadzone = deadzone.clamp(0.0, 0.99);
+    }
+
+    /// Sets the dead zone, saturation, and response curve used by axes without their own
+    /// settings. Only used on Linux and the web, where gamepads are read.
+    pub fn gamepad_axis_default(&mut self, settings: GamepadAxisSettings) {
+        self.gamepad_axis_default = settings;
+    }
+
+    /// Sets the dead zone, saturation, and response curve for one axis on every gamepad. Set this
+    /// to None to use the default settings for the axis again. Only used on Linux and the web,
+    /// where gamepads are read.
+    pub fn gamepad_axis_settings(&mut self, axis: GamepadAxis, settings: Option<GamepadAxisSettings>) {
+        match settings {
+            Some(settings) => self.gamepad_axes.insert(axis, settings),
+            None => self.gamepad_axes.remove(&axis),
+        };
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Input
+    // ////////////////////////////////////////////////////////
+
+    /// Binds a shortcut to a named action. Pressing the shortcut's key or cursor button while
+    /// exactly its modifiers are held sends a Shortcut message with the name. An action can have
+    /// several shortcuts. If the shortcut is already bound to a different action, nothing changes
+    /// and the name of that action is returned as the error.
+    pub fn shortcut_register(&mut self, name: &'static str, shortcut: Shortcut) -> Result<(), &'static str> {
+        self.shortcuts.register(name, shortcut)
+    }
+
+    /// Removes every shortcut bound to the action.
+    pub fn shortcut_unregister(&mut self, name: &str) {
+        self.shortcuts.unregister(name);
+    }
+
+    /// Gets the shortcuts bound to the action, like for showing them in a menu.
+    pub fn shortcut_get(&self, name: &str) -> Vec<Shortcut> {
+        self.shortcuts.shortcuts(name)
+    }
+
+    /// The character the key types on the player's keyboard layout without modifiers, like for
+    /// showing rebindable controls. Platforms don't report the layout up front, so it's learned as
+    /// keys are typed. Keys that haven't been typed yet fall back to what they type on a US layout.
+    /// Returns None for keys that don't type a character. See `KeyId` for what a key means
+    /// regardless of layout.
+    pub fn key_char(&self, key: KeyboardButton) -> Option<char> {
+        self.key_layout.char(key)
+    }
+
+    /// A short label for the key, like "Q", "Num 1", or "Left Shift". Keys that type a character
+    /// are labeled with it, see `key_char`.
+    pub fn key_label(&self, key: KeyboardButton) -> String {
+        self.key_layout.label(key)
+    }
+
+    /// The file of the FileDropped message being handled. Native platforms give the file's path,
+    /// and the web gives its name and contents. Returns None outside of a FileDropped message.
+    pub fn dropped_file(&self) -> Option<&DroppedFile> {
+        self.dropped_file.as_ref()
+    }
+
+    /// Sets if the window captures the cursor while a cursor button is held, so a drag started in
+    /// the window keeps getting CursorMoved and CursorReleased messages after the cursor leaves it.
+    /// During a capture, CursorLeft is held back until the last button is released, and is dropped
+    /// if the cursor comes back first. If the window loses focus during a capture, the held buttons
+    /// are released right away. Browsers only report the cursor over the canvas, so moves outside
+    /// it aren't sent on the web. The default is true.
+    pub fn cursor_capture(&mut self, enabled: bool) {
+        self.cursor_capture = enabled;
+    }
+
+    /// Grabs the cursor, keeping it in the window and sending its relative movement as
+    /// CursorMotion messages, like for camera controls or dragging without hitting the edge of the
+    /// screen. Pair this with `WindowCursor::Hidden` for mouse look. macOS locks the cursor in
+    /// place, and other platforms confine it to the window. Returns false if the platform couldn't
+    /// change the grab. The cursor can't be grabbed on the web. The default is false.
+    pub fn cursor_grab(&mut self, grab: bool) -> bool {
+        let changed = self.render.window_cursor_grab(grab);
+        if changed {
+            self.cursor_grab = grab;
+        }
+        changed
+    }
+
+    /// If the cursor is grabbed, see `cursor_grab`.
+    pub fn cursor_grabbed(&self) -> bool {
+        self.cursor_grab
+    }
+
     // ////////////////////////////////////////////////////////
     // Layer
     // ////////////////////////////////////////////////////////
@@ -111,26 +475,264 @@ impl Engine {
         self.render.layer_create()
     }
 
+    // ////////////////////////////////////////////////////////
+    // Light
+    // ////////////////////////////////////////////////////////
+
+    /// Creates a new light map. Drawing a light map darkens everything drawn before it, except
+    /// where it's lit by its lights. Occluders block light and cast shadows.
+    pub fn light_map_create(&mut self) -> LightMap {
+        self.render.light_map_create()
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Mesh
+    // ////////////////////////////////////////////////////////
+
+    /// Creates a new mesh layer. Mesh layers draw textured triangles from vertices and indices you
+    /// provide, for geometry that sprites can't express, like skinned characters and trails.
+    pub fn mesh_layer_create(&mut self) -> MeshLayer {
+        self.render.mesh_layer_create()
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Particle
+    // ////////////////////////////////////////////////////////
+
+    /// Creates a new particle emitter at the given position. The engine simulates the emitter's
+    /// particles before each update, and they're available as sprites through the emitter. The
+    /// emitter stops being simulated when it's dropped.
+    pub fn particle_emitter_create(
+        &mut self,
+        pos: Vector3<f32>,
+        settings: ParticleSettings,
+    ) -> ParticleEmitter {
+        ParticleSystem::emitter_create(&mut self.particles, pos, settings)
+    }
+
+    /// Creates a new floating text spawner, for text that rises, fades out, and removes itself,
+    /// like damage numbers. The engine moves its texts before each update, and they're available
+    /// through the spawner to pass to `text_append`. The texts stop moving when it's dropped.
+    pub fn floating_text_create(&mut self, settings: FloatingTextSettings) -> FloatingText {
+        ParticleSystem::floating_create(&mut self.particles, settings)
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Photo Mode
+    // ////////////////////////////////////////////////////////
+
+    /// Enables the built-in photo mode with the given settings, or disables it with None. While
+    /// photo mode is active, updates are sent with a delta of 0 and particles and animations are
+    /// paused, the view is moved with the cursor, layers marked as photo hidden aren't drawn, and
+    /// keyboard, character, and cursor input is kept from the game. A PhotoModeChanged message is
+    /// sent when photo mode is entered or left with the toggle key, and on the next frame if
+    /// disabling photo mode here leaves it. Disabled by default.
+    pub fn photo_mode(&mut self, settings: Option<PhotoModeSettings>) {
+        if self.photo.set_settings(settings) {
+            self.photo_left = true;
+        }
+    }
+
+    /// Enters or leaves photo mode. This has no effect unless photo mode has settings.
+    pub fn photo_mode_set_active(&mut self, active: bool) {
+        self.photo.set_active(active);
+    }
+
+    /// If photo mode is active.
+    pub fn photo_mode_active(&self) -> bool {
+        self.photo.is_active()
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Primitive
+    // ////////////////////////////////////////////////////////
+
+    /// Creates a new primitive layer. Primitive layers draw colored lines, polylines, convex
+    /// polygons, circles, rings, and rounded rectangles, which is useful for debug drawing, interface
+    /// panels, and vector styled games. Circles, rings, and rounded rectangles have smooth edges at
+    /// any radius without needing a texture.
+    pub fn primitive_layer_create(&mut self) -> PrimitiveLayer {
+        self.render.primitive_layer_create()
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Render Target
+    // ////////////////////////////////////////////////////////
+
+    /// Creates a new offscreen render target with the given size in pixels.
+    pub fn render_target_create(&mut self, width: u32, height: u32) -> RenderTarget {
+        self.render.render_target_create(width, height)
+    }
+
+    /// Sets where layers are drawn and what gets cleared. When set to a render target, drawing
+    /// goes into the target with its origin at the center of the target. When set to None, drawing
+    /// goes to the window. Reset this to None before the end of the update.
+    pub fn render_target_bind(&mut self, target: Option<&mut RenderTarget>) {
+        self.render.render_target_bind(target);
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Post Processing
+    // ////////////////////////////////////////////////////////
+
+    /// Adds a fullscreen post processing pass to the end of the pass list. While any pass is
+    /// enabled, each frame is drawn offscreen and then run through the enabled passes in the order
+    /// they were added, with the last pass drawing to the window.
+    ///
+    /// The fragment shader is GLSL ES 3.00. It receives `in vec2 v_uv`, the previous pass's output
+    /// as `uniform sampler2D tex`, and the window size in pixels as `uniform vec2 resolution`. If
+    /// there is an issue compiling the shader, this function will panic.
+    pub fn post_pass_add(&mut self, fragment_shader: &str) -> PostPassToken {
+        self.render.post_pass_add(fragment_shader)
+    }
+
+    /// Sets a uniform on a post processing pass. The value is kept and applied every frame.
+    pub fn post_pass_uniform(&mut self, token: &PostPassToken, name: &str, value: UniformValue) {
+        self.render.post_pass_uniform(token, name, value);
+    }
+
+    /// Enables or disables a post processing pass. Passes are enabled when added.
+    pub fn post_pass_enabled(&mut self, token: &PostPassToken, enabled: bool) {
+        self.render.post_pass_enabled(token, enabled);
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Quality
+    // ////////////////////////////////////////////////////////
+
+    /// Applies quality settings for multisampling, bloom, light resolution, particle limits, and
+    /// texture filtering together. These take effect on the next frame, and can be changed at any
+    /// time.
+    pub fn quality(&mut self, settings: QualitySettings) {
+        self.particles.set_limit(settings.particle_limit);
+        self.render.quality(&settings);
+        self.quality = settings;
+    }
+
+    /// Applies the settings of a quality preset. This is the same as calling quality with the
+    /// preset's settings.
+    pub fn quality_preset(&mut self, preset: QualityPreset) {
+        self.quality(preset.settings());
+    }
+
+    /// The quality settings in use. The default is `QualitySettings::default()`.
+    pub fn quality_settings(&self) -> QualitySettings {
+        self.quality
+    }
+
+    /// Sets if everything is drawn as crisp pixel art. While enabled, layers sample with nearest
+    /// filtering whatever their sampling settings, atlas mipmaps aren't generated, and each
+    /// layer's transform is snapped so one unit covers a whole number of physical pixels and the
+    /// layer's origin lands on a pixel corner. Scales round to the nearest whole number of pixels,
+    /// and never below one. The default is false.
+    pub fn pixel_art(&mut self, enabled: bool) {
+        self.pixel_art = enabled;
+        self.render.pixel_art(enabled);
+    }
+
+    /// If pixel art mode is enabled.
+    pub fn pixel_art_enabled(&self) -> bool {
+        self.pixel_art
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Shape
+    // ////////////////////////////////////////////////////////
+
+    /// Creates a new shape layer. Shape layers draw shapes made of circles, boxes, and capsules
+    /// from distance functions, so they're smooth at any scale and need no textures.
+    pub fn shape_layer_create(&mut self) -> ShapeLayer {
+        self.render.shape_layer_create()
+    }
+
     // ////////////////////////////////////////////////////////
     // String
     // ////////////////////////////////////////////////////////
 
-    /// Creates a new font from bytes. If there is an issue loading the font, this function will
-    /// panic.
+    /// Creates a new font from the bytes of a TTF or OTF file. Glyphs from every font share the
+    /// texture atlas, and are cached separately per font and size. If there is an issue loading the
+    /// font, this function will panic.
     pub fn font_create(&mut self, bytes: &[u8]) -> FontToken {
         self.render.font_create(bytes)
     }
 
+    /// Creates a new font from a TTF or OTF file on disk. Returns None if the file can't be read,
+    /// which is always the case on the web. If the file isn't a valid font, this function will
+    /// panic.
+    pub fn font_create_path(&mut self, path: &std::path::Path) -> Option<FontToken> {
+        self.render.font_create_path(path)
+    }
+
+    /// Creates a new bitmap font from an AngelCode BMFont text descriptor (.fnt) and its page
+    /// images, in page id order. The glyphs are copied into the texture atlas. Text drawn with a
+    /// bitmap font is pixel exact when its scale equals the size the font was generated at. Spans
+    /// in the text only change the color, and distance field text isn't supported. If the
+    /// descriptor or a page can't be read, this function will panic.
+    pub fn font_create_bitmap(&mut self, fnt: &[u8], pages: &[&[u8]], format: TextureFormat) -> FontToken {
+        self.render.font_create_bitmap(fnt, pages, format)
+    }
+
+    /// Lists the family names of the fonts installed on the system, sorted and without duplicates.
+    /// This scans the platform's font directories each call. The web has no system fonts, so this
+    /// is always empty there.
+    pub fn font_system_families(&self) -> Vec<String> {
+        self.render.font_system_families()
+    }
+
+    /// Creates a new font from an installed system font, matching the family name without regard
+    /// to case. The regular style is preferred when the family has several. Returns None if no
+    /// installed font matches.
+    pub fn font_create_system(&mut self, family: &str) -> Option<FontToken> {
+        self.render.font_create_system(family)
+    }
+
     /// Rasterizes text into sprites. This function appends sprites to the end of the output buffer.
+    /// Text drawn with outline fonts is shaped, so Arabic letters join and combining marks sit on
+    /// their letters, and right to left runs such as Arabic and Hebrew are reordered for display.
+    /// Bitmap fonts always lay text out left to right.
+    #[allow(clippy::ptr_arg)]
     pub fn text_append(&mut self, descs: &Vec<Text>, output: &mut Vec<Sprite>) {
         self.render.text_append(descs, output)
     }
 
     /// Rasterizes text into sprites. This function appends sprites to the end of the output buffer.
+    #[allow(clippy::ptr_arg)]
     pub fn text_clear(&mut self, descs: &Vec<Text>, output: &mut Vec<Sprite>) {
         self.render.text_clear(descs, output)
     }
 
+    /// Finds the link of the text under the position, returning its id. The position is in the
+    /// same space as the text's position, so use `LayerTransform::screen_to_layer` for cursor
+    /// positions. Links hidden by reveal can still be hit.
+    pub fn text_hit_test(&mut self, desc: &Text, pos: cgmath::Vector2<f32>) -> Option<u32> {
+        self.render.text_hit_test(desc, pos)
+    }
+
+    /// Lays out text without drawing it and returns the box of each character, in string order.
+    /// Useful for placing a caret or highlighting a selection. Boxes of right to left characters
+    /// run right to left, and the later characters of a ligature have no width.
+    pub fn text_glyphs(&mut self, desc: &Text) -> Vec<TextGlyph> {
+        self.render.text_glyphs(desc)
+    }
+
+    /// Finds the byte index in the text's string of the caret position closest to the position,
+    /// like where a click in a text box should place the caret. The position is in the same space
+    /// as the text's position. Returns the string's length if the caret belongs at the end.
+    pub fn text_index_at(&mut self, desc: &Text, pos: cgmath::Vector2<f32>) -> usize {
+        self.render.text_index_at(desc, pos)
+    }
+
+    /// Measures text without drawing it, using the same layout as rasterizing. Returns the size of
+    /// the box around every line, which is useful for sizing buttons and centering labels.
+    pub fn text_measure(&mut self, desc: &Text) -> cgmath::Vector2<f32> {
+        self.render.text_measure(desc).size
+    }
+
+    /// Measures text without drawing it, including where the text is and the size of each line.
+    pub fn text_metrics(&mut self, desc: &Text) -> TextMetrics {
+        self.render.text_measure(desc)
+    }
+
     // ////////////////////////////////////////////////////////
     // Texture
     // ////////////////////////////////////////////////////////
@@ -141,6 +743,94 @@ impl Engine {
         self.render.texture_create(bytes, format)
     }
 
+    /// Creates a new texture from bytes, like `texture_create`, but returns an error instead of
+    /// panicking if the texture atlas is full and can't grow any larger. If the bytes can't be
+    /// decoded, this function will panic.
+    pub fn texture_try_create(
+        &mut self,
+        bytes: &[u8],
+        format: TextureFormat,
+    ) -> Result<Texture, &'static str> {
+        self.render.texture_try_create(bytes, format)
+    }
+
+    /// Creates textures from several files at once, returning them in the same order. Files are
+    /// decoded in parallel on a thread per core, and each texture is uploaded while later files
+    /// are still decoding, which cuts load times when starting up with many images. On the web,
+    /// files are decoded one at a time. If there is an issue loading any of the textures, this
+    /// function will panic.
+    pub fn texture_create_batch(&mut self, files: &[(&[u8], TextureFormat)]) -> Vec<Texture> {
+        self.render.texture_create_batch(files)
+    }
+
+    /// Creates a new texture from pixels, in rows from top to bottom. If the number of pixels
+    /// doesn't match the size, or either dimension is 0, this function will panic.
+    pub fn texture_create_raw(&mut self, width: u32, height: u32, pixels: &[RGBA8]) -> Texture {
+        self.render.texture_create_raw(width, height, pixels)
+    }
+
+    /// Creates a new texture by rasterizing an SVG file. The texture's size is the file's size
+    /// multiplied by the scale, so the same file can be loaded at several resolutions. Paths, basic
+    /// shapes, groups, transforms, solid fills and strokes, and opacity are supported. Gradients,
+    /// clipping, masks, and text are skipped. If there is an issue loading the file, or the scale
+    /// isn't positive, this function will panic.
+    pub fn texture_create_svg(&mut self, bytes: &[u8], scale: f32) -> Texture {
+        self.render.texture_create_svg(bytes, scale)
+    }
+
+    /// Creates textures for every frame of an Aseprite file, and animation clips for each of its
+    /// tags. Visible layers are flattened with normal blending. Reverse tags become clips with their
+    /// frames reversed, and ping-pong tags use the PingPong mode. If there is an issue loading the
+    /// file, this function will panic.
+    pub fn texture_create_aseprite(&mut self, bytes: &[u8]) -> SpriteSheet {
+        self.render.texture_create_aseprite(bytes)
+    }
+
+    /// Overwrites part of a texture with pixels, in rows from top to bottom. The offset is in
+    /// pixels from the top left of the texture, and only the region is uploaded. This is meant for
+    /// textures that change often, like video frames. If the region falls outside of the texture,
+    /// or the number of pixels doesn't match the size, this function will panic.
+    pub fn texture_update(
+        &mut self,
+        texture: Texture,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        pixels: &[RGBA8],
+    ) {
+        self.render.texture_update(texture, x, y, width, height, pixels);
+    }
+
+    /// Sets if mipmaps are generated for the texture atlas, which the trilinear and anisotropic
+    /// texture filters need. Mipmaps are regenerated each time textures are created or updated,
+    /// so this adds a cost to those. The default is false.
+    pub fn texture_mipmaps(&mut self, mipmaps: bool) {
+        self.render.texture_mipmaps(mipmaps);
+    }
+
+    /// Registers a name for a texture, replacing any texture already registered under the name.
+    /// Names are free form, like "player/idle_0".
+    pub fn texture_register(&mut self, name: &str, texture: Texture) {
+        self.render.texture_register(name, texture);
+    }
+
+    /// Removes a name registered for a texture. The texture itself is unaffected.
+    pub fn texture_unregister(&mut self, name: &str) {
+        self.render.texture_unregister(name);
+    }
+
+    /// Gets the texture registered under the name.
+    pub fn texture_get(&self, name: &str) -> Option<Texture> {
+        self.render.texture_get(name)
+    }
+
+    /// Registers every frame of a sprite sheet. Frames are registered as "prefix/index", and the
+    /// frames of each clip as "prefix/clip_index", with indices counted from 0.
+    pub fn texture_register_sheet(&mut self, prefix: &str, sheet: &SpriteSheet) {
+        self.render.texture_register_sheet(prefix, sheet);
+    }
+
     // ////////////////////////////////////////////////////////
     // Window
     // ////////////////////////////////////////////////////////
@@ -150,12 +840,144 @@ impl Engine {
         self.render.window_title(title);
     }
 
+    /// Sets the cursor shown over the window: one of the platform's cursors, no cursor, or a custom
+    /// image. Custom images are a software cursor, not a platform cursor. The windowing library
+    /// can't set cursor images, so the engine hides the platform's cursor and draws the image in a
+    /// layer. That cursor lags the pointer by a frame and isn't shown outside the window. The
+    /// default is `CursorShape::Default`.
+    pub fn window_cursor(&mut self, cursor: &WindowCursor) {
+        self.render.window_cursor(cursor);
+    }
+
+    /// Sets the icon shown in the window's title bar and the taskbar. None restores the platform's
+    /// default icon. This has no effect on the web.
+    pub fn window_icon(&mut self, icon: Option<&WindowIcon>) {
+        self.render.window_icon(icon);
+    }
+
+    /// Gets the safe area of the window. On the web, this is read from the page's CSS safe area
+    /// insets. Native platforms report no insets unless overridden.
+    pub fn window_safe_area(&self) -> SafeArea {
+        self.render.window_safe_area()
+    }
+
+    /// Overrides the safe area of the window, like to account for TV overscan. Set this to None to
+    /// use the platform's safe area.
+    pub fn window_safe_area_override(&mut self, safe_area: Option<SafeArea>) {
+        self.render.window_safe_area_override(safe_area);
+    }
+
     /// Sets the display mode of the window.
     pub fn window_display_mode(&mut self, display_mode: DisplayMode) {
         self.render.window_display_mode(display_mode);
     }
 
-    /// Sets the clear color for the window.
+    /// Maximizes the window, or restores it to its previous size. This has no effect on the web.
+    pub fn window_maximized(&mut self, maximized: bool) {
+        self.render.window_maximized(maximized);
+    }
+
+    /// Gets where the window sits on the desktop, to save and restore with
+    /// `window_restore_placement` the next time the window is opened. The window's platform can't
+    /// be asked if it's maximized, so only maximizing done with `window_maximized` is reported.
+    /// Returns None on the web, or if the platform can't report the window's position.
+    pub fn window_placement(&self) -> Option<WindowPlacement> {
+        self.render.window_placement()
+    }
+
+    /// Moves and resizes the window to a placement from `window_placement`. If the placement's
+    /// monitor is no longer connected, the window is placed on the primary monitor instead, and
+    /// it's kept inside the monitor if the monitor got smaller. This has no effect on the web.
+    pub fn window_restore_placement(&mut self, placement: &WindowPlacement) {
+        self.render.window_restore_placement(placement);
+    }
+
+    /// Draws every frame at a fixed resolution and scales it to fit the window with the given
+    /// policy, or draws at the window's size with None. While set, layers are laid out in virtual
+    /// pixels, cursor and touch positions are reported in virtual pixels, and the safe area is
+    /// ignored. The default is None.
+    pub fn window_virtual_resolution(&mut self, resolution: Option<VirtualResolution>) {
+        self.render.window_virtual_resolution(resolution);
+    }
+
+    /// Sets the regions of the window that move or resize it when pressed with the left cursor
+    /// button, for windows created without decorations. Later regions take priority where regions
+    /// overlap. Presses that land in a region aren't sent as CursorPressed messages. This has no
+    /// effect on the web.
+    pub fn window_regions(&mut self, regions: &[WindowRegion]) {
+        self.render.window_regions(regions);
+    }
+
+    /// Saves the window's contents to a PNG file once the current frame finishes drawing. This
+    /// has no effect on the web.
+    pub fn window_screenshot(&mut self, path: &std::path::Path) {
+        self.render.window_screenshot(path.to_path_buf());
+    }
+
+    /// Moves the IME's candidate window, like to the text cursor of a focused text box, so the
+    /// candidates for CJK input show up next to the text. The position is in the same units as
+    /// cursor positions. Browsers place the candidate window themselves, so this has no effect on
+    /// the web.
+    pub fn window_ime_position(&mut self, pos: cgmath::Vector2<f32>) {
+        self.render.window_ime_position(pos);
+    }
+
+    /// Reads an area of the window without waiting on the GPU, like for color picking. The area is
+    /// in physical pixels from the top left of the window, and is read once the current frame
+    /// finishes drawing, without the stats overlay. Poll the returned readback each frame; the
+    /// pixels are usually ready a frame or two later.
+    pub fn window_read_pixels(
+        &mut self,
+        pos: cgmath::Vector2<u32>,
+        size: cgmath::Vector2<u32>,
+    ) -> PixelReadback {
+        self.render.window_readback(pos, size)
+    }
+
+    /// Starts piping each presented frame to an external video encoder, or stops recording with
+    /// None. Stopping waits for the encoder to finish the file. With fixed timing, updates are sent
+    /// with a delta of one video frame while recording. Recording stops if the window is resized.
+    /// This has no effect on the web.
+    pub fn window_record(&mut self, settings: Option<VideoSettings>) {
+        self.render.window_record(settings.as_ref());
+    }
+
+    /// If a video is being recorded.
+    pub fn window_recording(&self) -> bool {
+        self.render.window_recording()
+    }
+
+    /// Starts keeping the last few seconds of presented frames in memory, or stops and drops them
+    /// with None. Frames are read back from the window at the replay's frame rate, so keeping a
+    /// replay costs a little each kept frame. Save the frames with `window_replay_save`.
+    pub fn window_replay(&mut self, settings: Option<ReplaySettings>) {
+        self.render.window_replay(settings.as_ref());
+    }
+
+    /// Saves the kept replay frames, as a GIF file or as a directory of PNG files. Encoding runs on
+    /// a background thread, and the frames are still kept afterwards. This has no effect on the
+    /// web.
+    pub fn window_replay_save(&mut self, path: &std::path::Path, format: ReplayFormat) {
+        self.render.window_replay_save(path.to_path_buf(), format);
+    }
+
+    /// The refresh rate of the display the window is on, in Hz, like for picking a default frame
+    /// cap. This is the highest rate the display supports at its current resolution, since the
+    /// platform doesn't report the rate in use. None if it's unknown, which it always is on web.
+    pub fn window_refresh_rate(&self) -> Option<f32> {
+        self.render.window_refresh_rate()
+    }
+
+    /// If the last present waited for the display, like with vsync enabled. A present that waits
+    /// even with vsync disabled means the compositor is throttling the window, and frame times well
+    /// over the refresh interval while this is true usually mean the window is hidden or occluded.
+    /// This is always true on web, where the browser paces frames.
+    pub fn window_vsync_limited(&self) -> bool {
+        self.render.window_vsync_limited()
+    }
+
+    /// Sets the clear color for the window. Render targets use this color too unless they set
+    /// their own. The default is black.
     pub fn clear_color(&mut self, clear_color: RGBA8) {
         self.render.clear_color(clear_color);
     }
@@ -197,4 +1019,105 @@ impl Engine {
     pub fn wait_periodic(&mut self, duration: Option<Duration>) {
         self.wait_periodic = duration;
     }
+
+    /// Logs a warning when several frames in a row take longer than the budget to update and
+    /// present, naming the slower of the two phases. Set this to None to disable the watchdog. It's
+    /// disabled by default.
+    pub fn watchdog(&mut self, budget: Option<Duration>) {
+        self.watchdog = budget.map(Watchdog::new);
+    }
+
+    /// The number of frames started so far. Each Update message starts a new frame, so the first
+    /// update is frame 1.
+    pub fn frame_number(&self) -> u64 {
+        self.render.frame_number()
+    }
+
+    /// Recent frame times, measured from one update to the next.
+    pub fn frame_history(&self) -> &FrameHistory {
+        &self.frame_history
+    }
+
+    /// Sets how many frames the frame history keeps. The default is 120.
+    pub fn frame_history_capacity(&mut self, capacity: usize) {
+        self.frame_history.set_capacity(capacity);
+    }
+
+    /// Gets a snapshot of how the engine is performing. Frame times are over the frame history.
+    pub fn stats(&self) -> EngineStats {
+        let frame_time = self.frame_history.average();
+        let (draw_calls, sprites) = self.render.draw_counts();
+        let (atlas_size, atlas_occupancy) = self.render.atlas_stats();
+        EngineStats {
+            fps: if frame_time.is_zero() {
+                0.0
+            } else {
+                1.0 / frame_time.as_secs_f32()
+            },
+            frame_time,
+            frame_time_p50: self.frame_history.percentile(0.5),
+            frame_time_p99: self.frame_history.percentile(0.99),
+            draw_calls,
+            sprites,
+            atlas_size,
+            atlas_occupancy,
+            gpu_timings: self.render.gpu_timings(),
+            phase_timings: self.spans.timings(),
+        }
+    }
+
+    /// Sets a function called when each phase of the frame loop begins and ends, so the phases can
+    /// be forwarded to an external profiler, like as spans. Phase times are also reported in
+    /// `EngineStats::phase_timings` and logged at trace level. Set this to None to stop calling it.
+    pub fn stats_span_hook(&mut self, hook: Option<fn(FramePhase, SpanEvent)>) {
+        self.spans.set_hook(hook);
+    }
+
+    /// Times each layer and post processing pass on the GPU with timer queries, reported in
+    /// `EngineStats::gpu_timings`. Timing has a small cost, so it's disabled by default. Returns
+    /// false if timer queries aren't supported, like in browsers without the disjoint timer query
+    /// extension.
+    pub fn stats_gpu_timing(&mut self, enabled: bool) -> bool {
+        self.render.gpu_timing(enabled)
+    }
+
+    /// Shows the engine's stats in the top left corner of the window, over everything else. The
+    /// overlay isn't included in screenshots or videos, and is hidden in photo mode. The default
+    /// is false.
+    pub fn stats_overlay(&mut self, enabled: bool) {
+        self.render.stats_overlay(enabled);
+    }
+
+    /// Sends InputMessage::FrameBudgetExceeded when the given number of frames in a row take longer
+    /// than the budget, so the game can scale its quality down. Set this to None to stop checking.
+    /// It's disabled by default.
+    pub fn frame_budget(&mut self, budget: Option<Duration>, frames: u32) {
+        self.frame_budget = budget.map(|budget| FrameBudget::new(budget, frames));
+    }
+
+    /// Sets how fast time passes for the game. The delta sent with updates, and the one particles,
+    /// animations, and floating text advance by, is multiplied by the scale. Negative scales are
+    /// treated as 0. The default is 1.
+    pub fn time_scale(&mut self, scale: f32) {
+        self.time_scale = scale.max(0.0);
+    }
+
+    /// Pauses or resumes time. While paused, updates are still sent and frames are still drawn,
+    /// but with a delta of 0, so particles, animations, and floating text hold still. The default
+    /// is false.
+    pub fn time_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// If time is paused.
+    pub fn time_is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Advances the next frame by exactly the delta while time is paused, like for stepping
+    /// through an effect one frame at a time. The time scale isn't applied. This has no effect
+    /// while time isn't paused.
+    pub fn time_step(&mut self, delta: f32) {
+        self.step = Some(delta.max(0.0));
+    }
 }
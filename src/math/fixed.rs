@@ -0,0 +1,365 @@
+use cgmath::*;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+const FRACTION_BITS: u32 = 16;
+/// Sine polynomial coefficients for a quarter turn, as 16.16 values. The polynomial is
+/// z * (A - z^2 * (B - z^2 * C)), which is exact at 0 and a quarter turn.
+const SIN_A: i64 = 102943; // pi / 2, rounded down so the sum is exactly one
+const SIN_B: i64 = 42047; // pi - 5 / 2
+const SIN_C: i64 = 4640; // pi / 2 - 3 / 2
+/// A quarter turn in the 14 bits used for sine.
+const QUARTER: i64 = 1 << 14;
+
+/// A 16.16 fixed-point number, for simulations that must give identical results on every
+/// platform, like lockstep multiplayer. Every operation is integer math, so results don't depend
+/// on the platform's floating point behavior. Values range from about -32768 to 32768 with a
+/// precision of 1/65536. Multiplication rounds toward negative infinity, division rounds toward
+/// zero, overflow wraps, and division by zero panics.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    pub const ZERO: Fixed = Fixed(0);
+    pub const ONE: Fixed = Fixed(1 << FRACTION_BITS);
+    pub const HALF: Fixed = Fixed(1 << (FRACTION_BITS - 1));
+    pub const MIN: Fixed = Fixed(i32::MIN);
+    pub const MAX: Fixed = Fixed(i32::MAX);
+
+    /// Creates a value from its raw bits, in 1/65536ths.
+    pub const fn from_raw(raw: i32) -> Fixed {
+        Fixed(raw)
+    }
+
+    /// The raw bits, in 1/65536ths. Useful for hashing or sending the simulation's state.
+    pub const fn raw(self) -> i32 {
+        self.0
+    }
+
+    pub const fn from_int(value: i16) -> Fixed {
+        Fixed((value as i32) << FRACTION_BITS)
+    }
+
+    /// Creates a value from a ratio of integers, like 3/4, without going through floats.
+    pub fn from_ratio(numerator: i32, denominator: i32) -> Fixed {
+        Fixed((((numerator as i64) << FRACTION_BITS) / denominator as i64) as i32)
+    }
+
+    /// Converts a float, rounding to the nearest 1/65536th. Converting is deterministic, but the
+    /// float itself might not be if it came from float math, so only convert constants and
+    /// configuration into the simulation.
+    pub fn from_f32(value: f32) -> Fixed {
+        Fixed((value * (1 << FRACTION_BITS) as f32).round() as i32)
+    }
+
+    /// Converts to a float, like for rendering.
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / (1 << FRACTION_BITS) as f32
+    }
+
+    /// Rounds toward negative infinity to a whole number.
+    pub fn floor(self) -> Fixed {
+        Fixed(self.0 & !((1 << FRACTION_BITS) - 1))
+    }
+
+    /// The whole part, rounded toward negative infinity.
+    pub fn to_int(self) -> i32 {
+        self.0 >> FRACTION_BITS
+    }
+
+    pub fn abs(self) -> Fixed {
+        Fixed(self.0.wrapping_abs())
+    }
+
+    pub fn min(self, other: Fixed) -> Fixed {
+        Ord::min(self, other)
+    }
+
+    pub fn max(self, other: Fixed) -> Fixed {
+        Ord::max(self, other)
+    }
+
+    pub fn clamp(self, min: Fixed, max: Fixed) -> Fixed {
+        Ord::clamp(self, min, max)
+    }
+
+    /// The square root, rounded down. Negative values give 0.
+    pub fn sqrt(self) -> Fixed {
+        if self.0 <= 0 {
+            return Fixed::ZERO;
+        }
+        Fixed(isqrt((self.0 as u64) << FRACTION_BITS) as i32)
+    }
+
+    /// The sine of an angle measured in turns. Accurate to about 0.0005.
+    pub fn sin(self) -> Fixed {
+        // The fraction of a turn, in 16 bits.
+        let turn = (self.0 & 0xFFFF) as i64;
+        let quadrant = turn >> 14;
+        let offset = turn & (QUARTER - 1);
+        let z = if quadrant & 1 == 0 {
+            offset
+        } else {
+            QUARTER - offset
+        };
+        let z2 = (z * z) >> 14;
+        let value = (z * (SIN_A - ((z2 * (SIN_B - ((z2 * SIN_C) >> 14))) >> 14))) >> 14;
+        Fixed(if quadrant < 2 {
+            value as i32
+        } else {
+            -value as i32
+        })
+    }
+
+    /// The cosine of an angle measured in turns. Accurate to about 0.0005.
+    pub fn cos(self) -> Fixed {
+        Fixed(self.0.wrapping_add(1 << (FRACTION_BITS - 2))).sin()
+    }
+}
+
+/// The integer square root, rounded down.
+fn isqrt(value: u64) -> u64 {
+    let mut remainder = value;
+    let mut root = 0u64;
+    let mut bit = 1u64 << 62;
+    while bit > value {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if remainder >= root + bit {
+            remainder -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0.wrapping_add(other.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0.wrapping_sub(other.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+    fn mul(self, other: Fixed) -> Fixed {
+        Fixed(((self.0 as i64 * other.0 as i64) >> FRACTION_BITS) as i32)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Fixed;
+    fn div(self, other: Fixed) -> Fixed {
+        Fixed((((self.0 as i64) << FRACTION_BITS) / other.0 as i64) as i32)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+    fn neg(self) -> Fixed {
+        Fixed(self.0.wrapping_neg())
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, other: Fixed) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, other: Fixed) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for Fixed {
+    fn mul_assign(&mut self, other: Fixed) {
+        *self = *self * other;
+    }
+}
+
+impl DivAssign for Fixed {
+    fn div_assign(&mut self, other: Fixed) {
+        *self = *self / other;
+    }
+}
+
+/// A 2D vector of fixed-point numbers. See `Fixed`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FixedVec2 {
+    pub x: Fixed,
+    pub y: Fixed,
+}
+
+impl FixedVec2 {
+    pub const ZERO: FixedVec2 = FixedVec2::new(Fixed::ZERO, Fixed::ZERO);
+
+    pub const fn new(x: Fixed, y: Fixed) -> FixedVec2 {
+        FixedVec2 {
+            x,
+            y,
+        }
+    }
+
+    pub fn from_int(x: i16, y: i16) -> FixedVec2 {
+        FixedVec2::new(Fixed::from_int(x), Fixed::from_int(y))
+    }
+
+    /// Converts a float vector. See `Fixed::from_f32`.
+    pub fn from_f32(vector: Vector2<f32>) -> FixedVec2 {
+        FixedVec2::new(Fixed::from_f32(vector.x), Fixed::from_f32(vector.y))
+    }
+
+    /// Converts to a float vector, like for a sprite's position.
+    pub fn to_f32(self) -> Vector2<f32> {
+        Vector2::new(self.x.to_f32(), self.y.to_f32())
+    }
+
+    pub fn dot(self, other: FixedVec2) -> Fixed {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn length_squared(self) -> Fixed {
+        self.dot(self)
+    }
+
+    /// The length. Vectors longer than about 181 overflow the squared length, so scale large
+    /// vectors down first.
+    pub fn length(self) -> Fixed {
+        self.length_squared().sqrt()
+    }
+
+    /// Rotates counterclockwise by an angle measured in turns.
+    pub fn rotate(self, turns: Fixed) -> FixedVec2 {
+        let (sin, cos) = (turns.sin(), turns.cos());
+        FixedVec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+}
+
+impl Add for FixedVec2 {
+    type Output = FixedVec2;
+    fn add(self, other: FixedVec2) -> FixedVec2 {
+        FixedVec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for FixedVec2 {
+    type Output = FixedVec2;
+    fn sub(self, other: FixedVec2) -> FixedVec2 {
+        FixedVec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<Fixed> for FixedVec2 {
+    type Output = FixedVec2;
+    fn mul(self, scale: Fixed) -> FixedVec2 {
+        FixedVec2::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl Div<Fixed> for FixedVec2 {
+    type Output = FixedVec2;
+    fn div(self, scale: Fixed) -> FixedVec2 {
+        FixedVec2::new(self.x / scale, self.y / scale)
+    }
+}
+
+impl Neg for FixedVec2 {
+    type Output = FixedVec2;
+    fn neg(self) -> FixedVec2 {
+        FixedVec2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for FixedVec2 {
+    fn add_assign(&mut self, other: FixedVec2) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for FixedVec2 {
+    fn sub_assign(&mut self, other: FixedVec2) {
+        *self = *self - other;
+    }
+}
+
+/// A 2D transform of fixed-point numbers: a scale, then a rotation, then a translation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FixedTransform {
+    pub translation: FixedVec2,
+    /// Counterclockwise rotation, in turns.
+    pub rotation: Fixed,
+    pub scale: FixedVec2,
+}
+
+impl Default for FixedTransform {
+    fn default() -> FixedTransform {
+        FixedTransform {
+            translation: FixedVec2::ZERO,
+            rotation: Fixed::ZERO,
+            scale: FixedVec2::new(Fixed::ONE, Fixed::ONE),
+        }
+    }
+}
+
+impl FixedTransform {
+    pub fn transform_point(&self, point: FixedVec2) -> FixedVec2 {
+        let scaled = FixedVec2::new(point.x * self.scale.x, point.y * self.scale.y);
+        scaled.rotate(self.rotation) + self.translation
+    }
+
+    /// Converts to a float matrix, like for `Layer::set_transform_matrix`.
+    pub fn to_matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation.to_f32().extend(0.0))
+            * Matrix4::from_angle_z(Rad(self.rotation.to_f32() * core::f32::consts::TAU))
+            * Matrix4::from_nonuniform_scale(self.scale.x.to_f32(), self.scale.y.to_f32(), 1.0)
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = Fixed::from_ratio(3, 2);
+        let b = Fixed::from_int(-2);
+        assert_eq!((a * b).to_f32(), -3.0);
+        assert_eq!((b / a).raw(), -87381);
+        assert_eq!((a + b).floor(), Fixed::from_int(-1));
+        assert_eq!(Fixed::from_int(2).sqrt().raw(), 92681);
+        assert_eq!(FixedVec2::from_int(3, 4).length(), Fixed::from_int(5));
+
+        // Exact at the quarter turns, and close in between.
+        assert_eq!(Fixed::from_ratio(1, 4).sin(), Fixed::ONE);
+        assert_eq!(Fixed::from_ratio(1, 2).cos(), -Fixed::ONE);
+        assert_eq!(Fixed::ZERO.sin(), Fixed::ZERO);
+        for step in -64..64 {
+            let turns = Fixed::from_ratio(step, 48);
+            let exact = (turns.to_f32() * core::f32::consts::TAU).sin();
+            assert!((turns.sin().to_f32() - exact).abs() < 0.0005);
+        }
+
+        let transform = FixedTransform {
+            translation: FixedVec2::from_int(10, 0),
+            rotation: Fixed::from_ratio(1, 4),
+            scale: FixedVec2::from_int(2, 2),
+        };
+        assert_eq!(transform.transform_point(FixedVec2::from_int(1, 0)), FixedVec2::from_int(10, 2));
+    }
+}
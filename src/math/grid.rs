@@ -0,0 +1,90 @@
+use crate::math::AABB2D;
+use cgmath::*;
+
+/// A grid of equally sized cells. Cell (0, 0) has its bottom left corner at the origin, and cell
+/// coordinates grow with X and Y like positions do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Grid {
+    /// Bottom left corner of cell (0, 0).
+    pub origin: Vector2<f32>,
+    /// Width and height of a cell.
+    pub cell_size: Vector2<f32>,
+}
+
+impl Grid {
+    /// Creates a grid with the given cell size, with its origin at (0, 0).
+    pub fn new(cell_size: Vector2<f32>) -> Grid {
+        Grid {
+            origin: Vector2::zero(),
+            cell_size,
+        }
+    }
+
+    /// Moves the point to the nearest grid line intersection.
+    pub fn snap(&self, point: Vector2<f32>) -> Vector2<f32> {
+        let local = (point - self.origin).div_element_wise(self.cell_size);
+        self.origin + Vector2::new(local.x.round(), local.y.round()).mul_element_wise(self.cell_size)
+    }
+
+    /// Moves the point to the center of the cell it's in.
+    pub fn snap_center(&self, point: Vector2<f32>) -> Vector2<f32> {
+        self.cell_center(self.cell_at(point))
+    }
+
+    /// Gets the cell the point is in. Points on an edge between cells belong to the cell above or
+    /// to the right.
+    pub fn cell_at(&self, point: Vector2<f32>) -> Vector2<i32> {
+        let local = (point - self.origin).div_element_wise(self.cell_size);
+        Vector2::new(local.x.floor() as i32, local.y.floor() as i32)
+    }
+
+    /// Gets the bottom left corner of the cell.
+    pub fn cell_pos(&self, cell: Vector2<i32>) -> Vector2<f32> {
+        self.origin + cell.cast::<f32>().unwrap().mul_element_wise(self.cell_size)
+    }
+
+    /// Gets the center of the cell.
+    pub fn cell_center(&self, cell: Vector2<i32>) -> Vector2<f32> {
+        self.cell_pos(cell) + self.cell_size / 2.0
+    }
+
+    /// Gets the area the cell covers.
+    pub fn cell_aabb(&self, cell: Vector2<i32>) -> AABB2D {
+        let min = self.cell_pos(cell);
+        let max = min + self.cell_size;
+        AABB2D::new(min.x, min.y, max.x, max.y)
+    }
+
+    /// Iterates over every cell the area touches, row by row from the bottom left. Like
+    /// `AABB2D::intersects`, touching an edge counts.
+    pub fn cells_in(&self, area: &AABB2D) -> impl Iterator<Item = Vector2<i32>> {
+        let min = self.cell_at(area.min);
+        let max = self.cell_at(area.max);
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| Vector2::new(x, y)))
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells() {
+        let grid = Grid {
+            origin: Vector2::new(8.0, 0.0),
+            cell_size: Vector2::new(16.0, 32.0),
+        };
+        assert_eq!(grid.cell_at(Vector2::new(7.0, 31.0)), Vector2::new(-1, 0));
+        assert_eq!(grid.cell_at(Vector2::new(24.0, 32.0)), Vector2::new(1, 1));
+        assert_eq!(grid.snap(Vector2::new(17.0, 15.0)), Vector2::new(24.0, 0.0));
+        assert_eq!(grid.snap_center(Vector2::new(17.0, 15.0)), Vector2::new(16.0, 16.0));
+        assert_eq!(grid.cell_aabb(Vector2::new(1, -1)), AABB2D::new(24.0, -32.0, 40.0, 0.0));
+
+        let cells: Vec<_> = grid.cells_in(&AABB2D::new(10.0, 10.0, 24.0, 20.0)).collect();
+        assert_eq!(cells, vec![Vector2::new(0, 0), Vector2::new(1, 0)]);
+    }
+}
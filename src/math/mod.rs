@@ -1,5 +1,9 @@
 mod aabb;
+mod fixed;
+mod grid;
 mod trigonometry;
 
 pub use self::aabb::*;
+pub use self::fixed::*;
+pub use self::grid::*;
 pub use self::trigonometry::*;
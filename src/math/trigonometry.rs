@@ -1,6 +1,6 @@
-const PI: f32 = 3.141_592_653_589_793_238f32;
-const PI_2: f32 = 1.570_796_326_794_896_619f32;
-const PI_NEG_2: f32 = -1.570_796_326_794_896_619f32;
+const PI: f32 = core::f32::consts::PI;
+const PI_2: f32 = core::f32::consts::FRAC_PI_2;
+const PI_NEG_2: f32 = -core::f32::consts::FRAC_PI_2;
 const CONST: f32 = 0.28087f32; // Trial and error
 
 /// Average error of 0.00231 radians.
@@ -0,0 +1,181 @@
+use crate::particle::ParticleSystem;
+use crate::types::{ParticleSettings, Sprite};
+use crate::utility::bad::UnsafeShared;
+use crate::utility::random::Random;
+use cgmath::*;
+
+struct Particle {
+    pos: Vector2<f32>,
+    velocity: Vector2<f32>,
+    age: f32,
+}
+
+/// Simulation state shared between the particle system and the emitter handle.
+pub struct SharedEmitter {
+    index: usize,
+    pos: Vector3<f32>,
+    settings: ParticleSettings,
+    emitting: bool,
+    pending: f32,
+    /// Multiplier on the max particles from the quality settings.
+    limit: f32,
+    random: Random,
+    particles: Vec<Particle>,
+    sprites: Vec<Sprite>,
+}
+
+impl SharedEmitter {
+    pub fn new(pos: Vector3<f32>, settings: ParticleSettings, seed: u32, limit: f32) -> SharedEmitter {
+        SharedEmitter {
+            index: 0,
+            pos,
+            settings,
+            emitting: true,
+            pending: 0.0,
+            limit,
+            random: Random::new(seed),
+            particles: Vec::new(),
+            sprites: Vec::new(),
+        }
+    }
+
+    pub fn set_index(&mut self, index: usize) {
+        self.index = index;
+    }
+
+    pub fn set_limit(&mut self, limit: f32) {
+        self.limit = limit;
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        let settings = self.settings;
+
+        // Age and move the live particles.
+        let mut index = 0;
+        while index < self.particles.len() {
+            let particle = &mut self.particles[index];
+            particle.age += delta;
+            if particle.age >= settings.lifetime {
+                self.particles.swap_remove(index);
+                self.sprites.swap_remove(index);
+                continue;
+            }
+            particle.velocity += settings.gravity * delta;
+            particle.pos += particle.velocity * delta;
+            index += 1;
+        }
+
+        // Spawn new particles at the rate, carrying the fractional remainder between updates.
+        if self.emitting {
+            self.pending += settings.rate * delta;
+            let count = self.pending as usize;
+            self.pending -= count as f32;
+            self.spawn(count);
+        }
+
+        for (particle, sprite) in self.particles.iter().zip(self.sprites.iter_mut()) {
+            *sprite = Self::sprite(&settings, self.pos.z, particle);
+        }
+    }
+
+    fn spawn(&mut self, count: usize) {
+        let settings = &self.settings;
+        let max = settings.max_particles.min(((settings.max_particles as f32 * self.limit) as usize).max(1));
+        let available = max.saturating_sub(self.particles.len());
+        for _ in 0..count.min(available) {
+            let variance = Vector2::new(
+                settings.velocity_variance.x * self.random.next_signed_f32(),
+                settings.velocity_variance.y * self.random.next_signed_f32(),
+            );
+            let particle = Particle {
+                pos: self.pos.truncate(),
+                velocity: settings.velocity + variance,
+                age: 0.0,
+            };
+            self.sprites.push(Self::sprite(settings, self.pos.z, &particle));
+            self.particles.push(particle);
+        }
+    }
+
+    fn sprite(settings: &ParticleSettings, depth: f32, particle: &Particle) -> Sprite {
+        let t = if settings.lifetime > 0.0 {
+            particle.age / settings.lifetime
+        } else {
+            1.0
+        };
+        let size = settings.size_start.lerp(settings.size_end, t);
+        let color = settings.color_start.lerp(settings.color_end, t);
+        let pos = particle.pos - size / 2.0;
+        Sprite::new(pos.extend(depth), size, settings.texture, color, 0.0)
+    }
+}
+
+/// Particle emitters spawn and simulate particles automatically each update. The particles are
+/// exposed as sprites to be drawn by a layer.
+pub struct ParticleEmitter {
+    system: UnsafeShared<ParticleSystem>,
+    shared: UnsafeShared<SharedEmitter>,
+}
+
+impl ParticleEmitter {
+    pub(crate) fn new(
+        system: UnsafeShared<ParticleSystem>,
+        shared: UnsafeShared<SharedEmitter>,
+    ) -> ParticleEmitter {
+        ParticleEmitter {
+            system,
+            shared,
+        }
+    }
+
+    /// The sprites for the current particles. These are updated each update, so they should be
+    /// set on a layer every frame.
+    pub fn sprites(&self) -> &[Sprite] {
+        &self.shared.sprites
+    }
+
+    /// The number of live particles.
+    pub fn len(&self) -> usize {
+        self.shared.particles.len()
+    }
+
+    /// If there are no live particles.
+    pub fn is_empty(&self) -> bool {
+        self.shared.particles.is_empty()
+    }
+
+    /// Sets the position new particles spawn at. The z component is used as the depth of every
+    /// particle. Live particles are not moved.
+    pub fn set_position(&mut self, pos: Vector3<f32>) {
+        self.shared.pos = pos;
+    }
+
+    /// Replaces the settings of the emitter. Live particles adopt the new settings.
+    pub fn set_settings(&mut self, settings: ParticleSettings) {
+        self.shared.settings = settings;
+    }
+
+    /// If the emitter should continuously spawn particles at its rate. Live particles finish
+    /// their lifetime either way. The default is true.
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.shared.emitting = emitting;
+        self.shared.pending = 0.0;
+    }
+
+    /// Spawns the given number of particles immediately, up to the particle limit.
+    pub fn burst(&mut self, count: usize) {
+        self.shared.spawn(count);
+    }
+
+    /// Removes all live particles.
+    pub fn clear(&mut self) {
+        self.shared.particles.clear();
+        self.shared.sprites.clear();
+    }
+}
+
+impl Drop for ParticleEmitter {
+    fn drop(&mut self) {
+        self.system.emitter_drop(self.shared.index);
+    }
+}
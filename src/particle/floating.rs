@@ -0,0 +1,190 @@
+use crate::particle::ParticleSystem;
+use crate::types::{FloatingTextSettings, Text, RGBA8};
+use crate::utility::bad::UnsafeShared;
+
+struct Floating {
+    /// The text as it was spawned.
+    text: Text,
+    age: f32,
+}
+
+/// Simulation state shared between the particle system and the floating text handle.
+pub struct SharedFloating {
+    index: usize,
+    settings: FloatingTextSettings,
+    floating: Vec<Floating>,
+    texts: Vec<Text>,
+}
+
+impl SharedFloating {
+    pub fn new(settings: FloatingTextSettings) -> SharedFloating {
+        SharedFloating {
+            index: 0,
+            settings,
+            floating: Vec::new(),
+            texts: Vec::new(),
+        }
+    }
+
+    pub fn set_index(&mut self, index: usize) {
+        self.index = index;
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        let settings = self.settings;
+        let mut index = 0;
+        while index < self.floating.len() {
+            let floating = &mut self.floating[index];
+            floating.age += delta;
+            if floating.age >= settings.lifetime {
+                self.floating.swap_remove(index);
+                self.texts.swap_remove(index);
+                continue;
+            }
+            self.texts[index] = Self::text(&settings, floating);
+            index += 1;
+        }
+    }
+
+    fn spawn(&mut self, text: Text) {
+        let floating = Floating {
+            text,
+            age: 0.0,
+        };
+        self.texts.push(Self::text(&self.settings, &floating));
+        self.floating.push(floating);
+    }
+
+    /// The spawned text moved, faded, and scaled for its age.
+    fn text(settings: &FloatingTextSettings, floating: &Floating) -> Text {
+        let t = progress(floating.age, settings.lifetime);
+        let fade = if t <= settings.fade_start {
+            1.0
+        } else {
+            1.0 - progress(t - settings.fade_start, 1.0 - settings.fade_start)
+        };
+        let pop =
+            1.0 + (settings.pop - 1.0) * (1.0 - ease_out(progress(floating.age, settings.pop_duration)));
+        let scale = |scale: u32| ((scale as f32 * pop).round() as u32).max(1);
+        let faded =
+            |color: RGBA8| RGBA8::new_raw(color.r, color.g, color.b, (color.a as f32 * fade).round() as u8);
+
+        let mut text = floating.text.clone();
+        let rise = settings.rise * ease_out(t);
+        text.pos = floating.text.pos + rise.extend(0.0);
+        text.scale = scale(text.scale);
+        text.color = faded(text.color);
+        if let Some(background) = &mut text.background {
+            background.color = faded(background.color);
+        }
+        for span in &mut text.spans {
+            span.color = span.color.map(faded);
+            span.scale = span.scale.map(scale);
+        }
+        text
+    }
+}
+
+/// How far the time is through the duration, from 0 to 1. Empty durations are already done.
+fn progress(time: f32, duration: f32) -> f32 {
+    if duration > 0.0 {
+        (time / duration).clamp(0.0, 1.0)
+    } else {
+        1.0
+    }
+}
+
+/// Cubic ease out, which starts fast and slows to a stop.
+fn ease_out(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// Floating text spawns short lived text that rises, fades out, and removes itself, like damage
+/// numbers. The engine moves the text each update, so the current texts should be passed to
+/// `Engine::text_append` every frame.
+pub struct FloatingText {
+    system: UnsafeShared<ParticleSystem>,
+    shared: UnsafeShared<SharedFloating>,
+}
+
+impl FloatingText {
+    pub(crate) fn new(
+        system: UnsafeShared<ParticleSystem>,
+        shared: UnsafeShared<SharedFloating>,
+    ) -> FloatingText {
+        FloatingText {
+            system,
+            shared,
+        }
+    }
+
+    /// Spawns text starting at its own position, scale, and color.
+    pub fn spawn(&mut self, text: Text) {
+        self.shared.spawn(text);
+    }
+
+    /// The live texts, moved, faded, and scaled for their age.
+    pub fn texts(&self) -> &[Text] {
+        &self.shared.texts
+    }
+
+    /// The number of live texts.
+    pub fn len(&self) -> usize {
+        self.shared.texts.len()
+    }
+
+    /// If there are no live texts.
+    pub fn is_empty(&self) -> bool {
+        self.shared.texts.is_empty()
+    }
+
+    /// Replaces the settings. Live texts adopt the new settings.
+    pub fn set_settings(&mut self, settings: FloatingTextSettings) {
+        self.shared.settings = settings;
+    }
+
+    /// Removes all live texts.
+    pub fn clear(&mut self) {
+        self.shared.floating.clear();
+        self.shared.texts.clear();
+    }
+}
+
+impl Drop for FloatingText {
+    fn drop(&mut self) {
+        self.system.floating_drop(self.shared.index);
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::colors;
+    use cgmath::*;
+
+    #[test]
+    fn lifetime() {
+        let mut shared = SharedFloating::new(FloatingTextSettings::default());
+        shared.spawn(Text {
+            color: colors::RED,
+            ..Text::default()
+        });
+        assert_eq!(shared.texts[0].scale, 36);
+        assert_eq!(shared.texts[0].color.a, 255);
+
+        shared.update(0.5);
+        assert_eq!(shared.texts[0].scale, 24);
+        assert_eq!(shared.texts[0].color.a, 255);
+        assert_eq!(shared.texts[0].pos, Vector3::new(0.0, 42.0, 0.0));
+
+        shared.update(0.25);
+        assert_eq!(shared.texts[0].color.a, 128);
+
+        shared.update(0.25);
+        assert!(shared.texts.is_empty());
+    }
+}
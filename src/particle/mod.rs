@@ -0,0 +1,89 @@
+mod emitter;
+mod floating;
+
+pub use self::emitter::ParticleEmitter;
+pub use self::floating::FloatingText;
+
+use self::emitter::SharedEmitter;
+use self::floating::SharedFloating;
+use crate::types::{FloatingTextSettings, ParticleSettings};
+use crate::utility::bad::UnsafeShared;
+use crate::utility::random::Random;
+use cgmath::*;
+
+/// Owns the simulation side of every live particle emitter and floating text, and steps them once
+/// per update.
+pub struct ParticleSystem {
+    emitters: Vec<UnsafeShared<SharedEmitter>>,
+    floating: Vec<UnsafeShared<SharedFloating>>,
+    random: Random,
+    limit: f32,
+}
+
+impl ParticleSystem {
+    pub fn new() -> UnsafeShared<ParticleSystem> {
+        UnsafeShared::new(ParticleSystem {
+            emitters: Vec::new(),
+            floating: Vec::new(),
+            random: Random::new(0x2545_f491),
+            limit: 1.0,
+        })
+    }
+
+    pub fn emitter_create(
+        system: &mut UnsafeShared<ParticleSystem>,
+        pos: Vector3<f32>,
+        settings: ParticleSettings,
+    ) -> ParticleEmitter {
+        let seed = system.random.next_u32();
+        let mut shared = UnsafeShared::new(SharedEmitter::new(pos, settings, seed, system.limit));
+        shared.set_index(system.emitters.len());
+        system.emitters.push(shared.clone());
+        ParticleEmitter::new(system.clone(), shared)
+    }
+
+    /// Unsubscribes the emitter at the given index from getting updates.
+    pub fn emitter_drop(&mut self, index: usize) {
+        self.emitters.swap_remove(index);
+        if let Some(emitter) = self.emitters.get_mut(index) {
+            emitter.set_index(index);
+        }
+    }
+
+    pub fn floating_create(
+        system: &mut UnsafeShared<ParticleSystem>,
+        settings: FloatingTextSettings,
+    ) -> FloatingText {
+        let mut shared = UnsafeShared::new(SharedFloating::new(settings));
+        shared.set_index(system.floating.len());
+        system.floating.push(shared.clone());
+        FloatingText::new(system.clone(), shared)
+    }
+
+    /// Unsubscribes the floating text at the given index from getting updates.
+    pub fn floating_drop(&mut self, index: usize) {
+        self.floating.swap_remove(index);
+        if let Some(floating) = self.floating.get_mut(index) {
+            floating.set_index(index);
+        }
+    }
+
+    /// Multiplies the max particles of every emitter, including ones created later. Live
+    /// particles over the new limit finish their lifetime.
+    pub fn set_limit(&mut self, limit: f32) {
+        self.limit = limit.max(0.0);
+        for emitter in &mut self.emitters {
+            emitter.set_limit(self.limit);
+        }
+    }
+
+    /// Advances every emitter and floating text by the delta, measured in seconds.
+    pub fn update(&mut self, delta: f32) {
+        for emitter in &mut self.emitters {
+            emitter.update(delta);
+        }
+        for floating in &mut self.floating {
+            floating.update(delta);
+        }
+    }
+}
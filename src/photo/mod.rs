@@ -0,0 +1,263 @@
+mod replay;
+mod video;
+
+pub use self::replay::ReplayRecorder;
+pub use self::video::VideoRecorder;
+
+use crate::input::{CursorButton, KeyboardButton, ScrollDirection};
+use crate::types::PhotoModeSettings;
+use cgmath::*;
+use std::path::PathBuf;
+
+/// Zoom multiplier applied per step of the cursor wheel.
+const ZOOM_STEP: f32 = 1.1;
+
+/// The result of passing a key press to photo mode.
+pub enum PhotoKey {
+    /// Photo mode didn't handle the key.
+    Ignored,
+    /// Photo mode handled the key, and it shouldn't be sent to the game.
+    Consumed,
+    /// Photo mode was entered or left.
+    Toggled(bool),
+}
+
+/// Tracks the state of the built-in photo mode. The view is moved by a camera matrix that the
+/// renderer applies to layers drawn to the window.
+pub struct PhotoMode {
+    settings: Option<PhotoModeSettings>,
+    active: bool,
+    pan: Vector2<f32>,
+    zoom: f32,
+    dragging: bool,
+    captures: u32,
+}
+
+impl PhotoMode {
+    pub fn new() -> PhotoMode {
+        PhotoMode {
+            settings: None,
+            active: false,
+            pan: Vector2::zero(),
+            zoom: 1.0,
+            dragging: false,
+            captures: 0,
+        }
+    }
+
+    /// Replaces the settings. Returns true if this left photo mode.
+    pub fn set_settings(&mut self, settings: Option<PhotoModeSettings>) -> bool {
+        self.settings = settings;
+        if self.settings.is_none() && self.active {
+            self.set_active(false);
+            return true;
+        }
+        false
+    }
+
+    pub fn capture_key(&self) -> Option<KeyboardButton> {
+        self.settings.as_ref().map(|settings| settings.capture_key)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Enters or leaves photo mode. The view is reset each time.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active && self.settings.is_some();
+        self.pan = Vector2::zero();
+        self.zoom = 1.0;
+        self.dragging = false;
+    }
+
+    /// The matrix to move the view by, if photo mode is active.
+    pub fn camera(&self) -> Option<Matrix4<f32>> {
+        if !self.active {
+            return None;
+        }
+        // Depth isn't scaled, so zooming doesn't clip or compress it.
+        let scale = Matrix4::from_nonuniform_scale(self.zoom, self.zoom, 1.0);
+        Some(scale * Matrix4::from_translation(self.pan.extend(0.0)))
+    }
+
+    /// The path to save the next screenshot to. Names are unique within a run.
+    pub fn capture_path(&mut self) -> Option<PathBuf> {
+        let directory = &self.settings.as_ref()?.directory;
+        self.captures += 1;
+        let name = format!("screenshot-{}-{}.png", unix_seconds(), self.captures);
+        Some(directory.join(name))
+    }
+
+    pub fn key_pressed(&mut self, key: KeyboardButton) -> PhotoKey {
+        let (toggle_key, capture_key) = match &self.settings {
+            Some(settings) => (settings.toggle_key, settings.capture_key),
+            None => return PhotoKey::Ignored,
+        };
+        if key == toggle_key {
+            self.set_active(!self.active);
+            PhotoKey::Toggled(self.active)
+        } else if key == capture_key || self.active {
+            PhotoKey::Consumed
+        } else {
+            PhotoKey::Ignored
+        }
+    }
+
+    /// Returns true if the key should be hidden from the game.
+    pub fn key_released(&self, key: KeyboardButton) -> bool {
+        match &self.settings {
+            Some(settings) => self.active || key == settings.toggle_key || key == settings.capture_key,
+            None => false,
+        }
+    }
+
+    /// Returns true if the button should be hidden from the game.
+    pub fn cursor_pressed(&mut self, button: CursorButton) -> bool {
+        if self.active && button == CursorButton::Left {
+            self.dragging = true;
+        }
+        self.active
+    }
+
+    /// Returns true if the button should be hidden from the game.
+    pub fn cursor_released(&mut self, button: CursorButton) -> bool {
+        if button == CursorButton::Left {
+            self.dragging = false;
+        }
+        self.active
+    }
+
+    /// Returns true if the movement should be hidden from the game.
+    pub fn cursor_moved(&mut self, delta: Vector2<f32>) -> bool {
+        if let (true, Some(settings)) = (self.dragging, &self.settings) {
+            let limit = settings.pan_limit;
+            let pan = self.pan + delta / self.zoom;
+            self.pan = Vector2::new(pan.x.clamp(-limit.x, limit.x), pan.y.clamp(-limit.y, limit.y));
+        }
+        self.active
+    }
+
+    /// Returns true if the scroll should be hidden from the game.
+    pub fn cursor_scroll(&mut self, direction: ScrollDirection) -> bool {
+        if let (true, Some(settings)) = (self.active, &self.settings) {
+            let zoom = match direction {
+                ScrollDirection::Up => self.zoom * ZOOM_STEP,
+                ScrollDirection::Down => self.zoom / ZOOM_STEP,
+                _ => self.zoom,
+            };
+            self.zoom = zoom.clamp(settings.zoom_min, settings.zoom_max);
+        }
+        self.active
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_seconds() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn unix_seconds() -> u64 {
+    0
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active() -> PhotoMode {
+        let mut photo = PhotoMode::new();
+        photo.set_settings(Some(PhotoModeSettings {
+            pan_limit: Vector2::new(100.0, 50.0),
+            zoom_min: 0.5,
+            zoom_max: 2.0,
+            ..PhotoModeSettings::default()
+        }));
+        assert!(matches!(photo.key_pressed(KeyboardButton::F10), PhotoKey::Toggled(true)));
+        photo
+    }
+
+    #[test]
+    fn pan() {
+        let mut photo = active();
+        // Moving without dragging doesn't pan, but is still hidden from the game.
+        assert!(photo.cursor_moved(Vector2::new(10.0, 10.0)));
+        assert_eq!(photo.pan, Vector2::zero());
+
+        assert!(photo.cursor_pressed(CursorButton::Left));
+        photo.cursor_moved(Vector2::new(40.0, -20.0));
+        assert_eq!(photo.pan, Vector2::new(40.0, -20.0));
+        photo.cursor_moved(Vector2::new(500.0, -500.0));
+        assert_eq!(photo.pan, Vector2::new(100.0, -50.0));
+
+        // Pans are scaled by the zoom, so the view follows the cursor.
+        photo.cursor_moved(Vector2::new(-100.0, 0.0));
+        photo.cursor_scroll(ScrollDirection::Up);
+        photo.cursor_moved(Vector2::new(-11.0, 0.0));
+        assert!((photo.pan.x - -10.0).abs() < 0.001);
+
+        photo.cursor_released(CursorButton::Left);
+        photo.cursor_moved(Vector2::new(30.0, 0.0));
+        assert!((photo.pan.x - -10.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn zoom() {
+        let mut photo = active();
+        for _ in 0..20 {
+            photo.cursor_scroll(ScrollDirection::Up);
+        }
+        assert_eq!(photo.zoom, 2.0);
+        for _ in 0..20 {
+            photo.cursor_scroll(ScrollDirection::Down);
+        }
+        assert_eq!(photo.zoom, 0.5);
+
+        // Leaving and entering resets the view.
+        photo.key_pressed(KeyboardButton::F10);
+        assert!(!photo.is_active());
+        assert_eq!(photo.camera(), None);
+        assert!(!photo.cursor_scroll(ScrollDirection::Up));
+        photo.key_pressed(KeyboardButton::F10);
+        assert_eq!(photo.zoom, 1.0);
+        assert_eq!(photo.camera(), Some(Matrix4::identity()));
+    }
+
+    #[test]
+    fn depth() {
+        let mut photo = PhotoMode::new();
+        photo.set_settings(Some(PhotoModeSettings {
+            zoom_max: 4.0,
+            ..PhotoModeSettings::default()
+        }));
+        photo.set_active(true);
+        for _ in 0..20 {
+            photo.cursor_scroll(ScrollDirection::Up);
+        }
+        assert_eq!(photo.zoom, 4.0);
+        let point = photo.camera().unwrap() * Vector4::new(10.0, 20.0, 0.5, 1.0);
+        assert_eq!(point, Vector4::new(40.0, 80.0, 0.5, 1.0));
+    }
+
+    #[test]
+    fn keys() {
+        let mut photo = PhotoMode::new();
+        assert!(matches!(photo.key_pressed(KeyboardButton::F10), PhotoKey::Ignored));
+        photo.set_settings(Some(PhotoModeSettings::default()));
+        assert!(matches!(photo.key_pressed(KeyboardButton::A), PhotoKey::Ignored));
+        assert!(matches!(photo.key_pressed(KeyboardButton::F12), PhotoKey::Consumed));
+        photo.key_pressed(KeyboardButton::F10);
+        assert!(matches!(photo.key_pressed(KeyboardButton::A), PhotoKey::Consumed));
+        assert!(photo.key_released(KeyboardButton::A));
+
+        // Removing the settings leaves photo mode.
+        assert!(photo.set_settings(None));
+        assert!(!photo.is_active());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::texture::{formats, Image};
+use crate::time::Instant;
+use crate::types::{ReplayFormat, ReplaySettings};
+use crate::RGBA8;
+use core::time::Duration;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+/// Keeps the most recent presented frames in a ring buffer, dropping the oldest as new ones come
+/// in.
+pub struct ReplayRecorder {
+    frames: VecDeque<Image>,
+    capacity: usize,
+    downscale: u32,
+    frame_rate: u32,
+    interval: Duration,
+    next: Instant,
+}
+
+impl ReplayRecorder {
+    pub fn new(settings: &ReplaySettings) -> ReplayRecorder {
+        let frame_rate = settings.frame_rate.max(1);
+        ReplayRecorder {
+            frames: VecDeque::new(),
+            capacity: ((settings.seconds.max(0.0) * frame_rate as f32).ceil() as usize).max(1),
+            downscale: settings.downscale.max(1),
+            frame_rate,
+            interval: Duration::from_secs_f32(1.0 / frame_rate as f32),
+            next: Instant::now(),
+        }
+    }
+
+    /// If the presented frame should be kept to stay at the frame rate.
+    pub fn due(&self) -> bool {
+        self.due_at(Instant::now())
+    }
+
+    fn due_at(&self, now: Instant) -> bool {
+        now >= self.next
+    }
+
+    /// Keeps a presented frame. Frames from before a resize are dropped, since every frame in a
+    /// clip has the same size.
+    pub fn push(&mut self, image: &Image) {
+        self.push_at(image, Instant::now());
+    }
+
+    /// Keeps a frame presented at the given time. Frames are kept an interval apart, and after a
+    /// stall the next frame is an interval from now instead of catching up.
+    fn push_at(&mut self, image: &Image, now: Instant) {
+        self.next += self.interval;
+        if self.next < now {
+            self.next = now + self.interval;
+        }
+        let frame = downscale(image, self.downscale);
+        if let Some(last) = self.frames.back() {
+            if last.width() != frame.width() || last.height() != frame.height() {
+                self.frames.clear();
+            }
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// Writes the kept frames in the background. The frames are still kept afterwards.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: PathBuf, format: ReplayFormat) {
+        if self.frames.is_empty() {
+            warn!("No replay frames have been kept yet, not saving {}.", path.display());
+            return;
+        }
+        let frames: Vec<Image> = self.frames.iter().cloned().collect();
+        let delay = (100.0 / self.frame_rate as f32).round().max(1.0) as u16;
+        std::thread::spawn(move || match write(&path, &frames, format, delay) {
+            Ok(()) => info!("Saved {} replay frames to {}", frames.len(), path.display()),
+            Err(error) => warn!("Unable to save replay to {}: {}", path.display(), error),
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self, path: PathBuf, _format: ReplayFormat) {
+        warn!("Replays can't be saved to {} on the web.", path.display());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(path: &std::path::Path, frames: &[Image], format: ReplayFormat, delay: u16) -> std::io::Result<()> {
+    match format {
+        ReplayFormat::Gif => {
+            if let Some(directory) = path.parent() {
+                std::fs::create_dir_all(directory)?;
+            }
+            std::fs::write(path, formats::gif::write(frames, delay))
+        }
+        ReplayFormat::Png => {
+            std::fs::create_dir_all(path)?;
+            for (index, frame) in frames.iter().enumerate() {
+                std::fs::write(path.join(format!("frame-{:04}.png", index)), formats::png::write(frame))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Shrinks the image by a whole factor, averaging each block of pixels. Pixels past the last full
+/// block are dropped.
+fn downscale(image: &Image, factor: u32) -> Image {
+    if factor == 1 {
+        return image.clone();
+    }
+    let (width, height) = ((image.width() / factor).max(1), (image.height() / factor).max(1));
+    let mut pixels = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0u32; 4];
+            let mut count = 0;
+            for sy in y * factor..((y + 1) * factor).min(image.height()) {
+                for sx in x * factor..((x + 1) * factor).min(image.width()) {
+                    let pixel = image.get(sx, sy);
+                    sum[0] += pixel.r as u32;
+                    sum[1] += pixel.g as u32;
+                    sum[2] += pixel.b as u32;
+                    sum[3] += pixel.a as u32;
+                    count += 1;
+                }
+            }
+            let average = |total: u32| ((total + count / 2) / count) as u8;
+            pixels.push(RGBA8::new_raw(average(sum[0]), average(sum[1]), average(sum[2]), average(sum[3])));
+        }
+    }
+    Image::from_vec(pixels, width, height)
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring() {
+        let settings = ReplaySettings {
+            seconds: 0.2,
+            frame_rate: 10,
+            downscale: 2,
+        };
+        let mut replay = ReplayRecorder::new(&settings);
+        assert!(replay.due());
+        for shade in 0..3 {
+            replay.push(&Image::from_color(RGBA8::new_raw(shade, 0, 0, 255), 4, 2));
+        }
+        // The oldest frame is dropped, and frames are shrunk.
+        assert_eq!(replay.frames.len(), 2);
+        assert_eq!(replay.frames[0].as_slice(), &[RGBA8::new_raw(1, 0, 0, 255); 2][..]);
+        assert!(!replay.due());
+
+        // A resize starts over.
+        replay.push(&Image::from_color(RGBA8::new_raw(3, 0, 0, 255), 8, 8));
+        assert_eq!(replay.frames.len(), 1);
+        assert_eq!(replay.frames[0].width(), 4);
+    }
+
+    #[test]
+    fn pacing() {
+        let settings = ReplaySettings {
+            seconds: 1.0,
+            frame_rate: 10,
+            downscale: 1,
+        };
+        let mut replay = ReplayRecorder::new(&settings);
+        let image = Image::from_color(RGBA8::new_raw(0, 0, 0, 255), 1, 1);
+        let start = replay.next;
+        let millis = |millis: u64| start + Duration::from_millis(millis);
+        assert!(replay.due_at(start));
+
+        // The interval is 1/10th of a second, rounded from a float.
+        replay.push_at(&image, start);
+        assert!(!replay.due_at(millis(99)));
+        assert!(replay.due_at(millis(101)));
+
+        // Frames presented a little late don't push the schedule back.
+        replay.push_at(&image, millis(130));
+        assert!(replay.due_at(millis(201)));
+
+        // After a stall, frames aren't kept back to back to catch up.
+        replay.push_at(&image, millis(1000));
+        assert!(!replay.due_at(millis(1050)));
+        assert!(replay.due_at(millis(1101)));
+    }
+}
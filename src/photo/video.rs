@@ -0,0 +1,144 @@
+use crate::texture::Image;
+use crate::time::Instant;
+use crate::types::{VideoSettings, VideoTiming};
+use core::time::Duration;
+use std::io::Write;
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// Pipes frames into an encoder process as raw RGBA video.
+pub struct VideoRecorder {
+    child: Child,
+    stdin: ChildStdin,
+    width: u32,
+    height: u32,
+    frame_rate: u32,
+    timing: VideoTiming,
+    started: Instant,
+    frames: u64,
+    buffer: Vec<u8>,
+}
+
+impl VideoRecorder {
+    /// Starts the encoder for frames of the given size. Returns None if it can't be started.
+    pub fn start(settings: &VideoSettings, width: u32, height: u32) -> Option<VideoRecorder> {
+        let frame_rate = settings.frame_rate.max(1);
+        let spawned = Command::new(&settings.program)
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .arg("-video_size")
+            .arg(format!("{}x{}", width, height))
+            .arg("-framerate")
+            .arg(frame_rate.to_string())
+            .args(["-i", "-"])
+            .args(&settings.arguments)
+            .arg(&settings.output)
+            .stdin(Stdio::piped())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(error) => {
+                warn!("Unable to start video encoder {}: {}", settings.program.display(), error);
+                return None;
+            }
+        };
+        let stdin = child.stdin.take()?;
+        info!("Recording video to {}", settings.output.display());
+        Some(VideoRecorder {
+            child,
+            stdin,
+            width,
+            height,
+            frame_rate,
+            timing: settings.timing,
+            started: Instant::now(),
+            frames: 0,
+            buffer: Vec::with_capacity((width * height * 4) as usize),
+        })
+    }
+
+    /// The update delta to use while recording, if the recording has a fixed timing.
+    pub fn frame_step(&self) -> Option<f32> {
+        match self.timing {
+            VideoTiming::Fixed => Some(1.0 / self.frame_rate as f32),
+            VideoTiming::RealTime => None,
+        }
+    }
+
+    /// Sends a presented frame to the encoder. Returns false if recording can't continue.
+    pub fn write(&mut self, image: &Image) -> bool {
+        if image.width() != self.width || image.height() != self.height {
+            warn!("The window was resized, stopping the video recording.");
+            return false;
+        }
+        let count = frames_due(self.timing, self.frame_rate, self.started.elapsed(), self.frames);
+        if count == 0 {
+            return true;
+        }
+        self.buffer.clear();
+        for pixel in image.as_slice() {
+            self.buffer.extend_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+        }
+        for _ in 0..count {
+            if let Err(error) = self.stdin.write_all(&self.buffer) {
+                warn!("Unable to write to the video encoder, stopping the recording: {}", error);
+                return false;
+            }
+        }
+        self.frames += count;
+        true
+    }
+
+    /// Closes the encoder's input and waits for it to finish writing the video.
+    pub fn finish(self) {
+        let VideoRecorder {
+            mut child,
+            stdin,
+            frames,
+            ..
+        } = self;
+        drop(stdin);
+        match child.wait() {
+            Ok(status) if status.success() => info!("Finished recording {} video frames", frames),
+            Ok(status) => warn!("Video encoder exited with {}", status),
+            Err(error) => warn!("Unable to wait for the video encoder: {}", error),
+        }
+    }
+}
+
+/// The number of video frames to write for a presented frame, given how long the recording has run
+/// and how many frames were written so far. With real time timing, a frame is repeated when the
+/// game falls behind the frame rate, and dropped when it runs ahead.
+fn frames_due(timing: VideoTiming, frame_rate: u32, elapsed: Duration, written: u64) -> u64 {
+    match timing {
+        VideoTiming::Fixed => 1,
+        VideoTiming::RealTime => {
+            let due = (elapsed.as_secs_f64() * frame_rate as f64) as u64 + 1;
+            due.saturating_sub(written)
+        }
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pacing() {
+        let millis = Duration::from_millis;
+        // Fixed timing writes every frame once, however long it took.
+        assert_eq!(frames_due(VideoTiming::Fixed, 30, millis(500), 0), 1);
+
+        let due =
+            |elapsed: u64, written: u64| frames_due(VideoTiming::RealTime, 10, millis(elapsed), written);
+        // The first frame is written right away.
+        assert_eq!(due(0, 0), 1);
+        // A frame presented before the next one is due is dropped.
+        assert_eq!(due(50, 1), 0);
+        assert_eq!(due(100, 1), 1);
+        // After a stall, the frame is repeated to cover the missed time.
+        assert_eq!(due(450, 2), 3);
+    }
+}
@@ -3,12 +3,15 @@ use crate::render::vertex::*;
 use crate::render::OpenGLState;
 use crate::utility::bad::UnsafeShared;
 use core::marker::PhantomData;
+use core::ops::Range;
 
 pub struct Buffer<T: VertexDescription + Copy> {
     state: UnsafeShared<OpenGLState>,
     vbo: resource::Buffer,
     vao: resource::VertexArray,
     vertices: usize,
+    /// Bytes allocated for the buffer on the GPU, which clearing doesn't free.
+    allocated: usize,
     buffer_type: BufferBindingTarget,
     phantom: PhantomData<T>,
 }
@@ -28,6 +31,7 @@ impl<T: VertexDescription + Copy> Buffer<T> {
             vbo,
             vao,
             vertices: 0,
+            allocated: 0,
             buffer_type,
             phantom: PhantomData,
         }
@@ -37,15 +41,40 @@ impl<T: VertexDescription + Copy> Buffer<T> {
         self.vertices
     }
 
+    /// Bytes allocated for the buffer on the GPU.
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
     pub fn clear(&mut self) {
         self.vertices = 0;
     }
 
-    pub fn set(&mut self, items: &Vec<T>) {
+    pub fn set(&mut self, items: &[T]) {
         self.vertices = items.len();
         if self.vertices > 0 {
             self.state.gl.bind_buffer(self.buffer_type, Some(self.vbo));
             self.state.gl.buffer_data(self.buffer_type, items, BufferUsage::StaticDraw);
+            self.allocated = self.vertices * T::VERTEX_SIZE;
+        }
+    }
+
+    /// Sets the items, only uploading the given range of them if the buffer is already large
+    /// enough. When it isn't, the buffer is reallocated with room to grow.
+    pub fn patch(&mut self, items: &[T], range: Range<usize>) {
+        self.vertices = items.len();
+        if self.vertices == 0 {
+            return;
+        }
+        self.state.gl.bind_buffer(self.buffer_type, Some(self.vbo));
+        let needed = self.vertices * T::VERTEX_SIZE;
+        if needed > self.allocated {
+            self.allocated = self.vertices.next_power_of_two() * T::VERTEX_SIZE;
+            self.state.gl.buffer_data_empty(self.buffer_type, self.allocated as i32, BufferUsage::StaticDraw);
+            self.state.gl.buffer_sub_data(self.buffer_type, 0, items);
+        } else {
+            let range = range.start.min(items.len())..range.end.min(items.len());
+            self.state.gl.buffer_sub_data(self.buffer_type, range.start * T::VERTEX_SIZE, &items[range]);
         }
     }
 
@@ -55,6 +84,14 @@ impl<T: VertexDescription + Copy> Buffer<T> {
             self.state.gl.draw_arrays_instanced(DrawMode::TriangleStrip, 0, 4, self.vertices as i32);
         }
     }
+
+    /// Draws the items as the corners of triangles instead of as instances.
+    pub fn draw_triangles(&self) {
+        if self.vertices > 0 {
+            self.state.gl.bind_vertex_array(Some(self.vao));
+            self.state.gl.draw_arrays(DrawMode::Triangles, 0, self.vertices as i32);
+        }
+    }
 }
 
 impl<T: VertexDescription + Copy> Drop for Buffer<T> {
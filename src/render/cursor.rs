@@ -0,0 +1,63 @@
+use crate::render::Layer;
+use crate::types::{colors, Sprite, Texture};
+use cgmath::*;
+
+/// A software cursor: a cursor image drawn by the engine in its own layer, because winit 0.24 can't
+/// set cursor images. It's drawn where the cursor was when the frame started, so it lags the
+/// platform's pointer by a frame, and it isn't drawn outside the window.
+pub(crate) struct SoftwareCursor {
+    layer: Layer,
+    texture: Texture,
+    size: Vector2<f32>,
+    hotspot: Vector2<f32>,
+    pos: Option<Vector2<f32>>,
+    dirty: bool,
+}
+
+impl SoftwareCursor {
+    pub fn new(
+        layer: Layer,
+        texture: Texture,
+        size: Vector2<f32>,
+        hotspot: Vector2<f32>,
+        pos: Option<Vector2<f32>>,
+    ) -> SoftwareCursor {
+        SoftwareCursor {
+            layer,
+            texture,
+            size,
+            hotspot,
+            pos,
+            dirty: true,
+        }
+    }
+
+    pub fn into_layer(self) -> Layer {
+        self.layer
+    }
+
+    /// Moves the cursor, in the same units as cursor positions. None hides it, like when the cursor
+    /// leaves the window.
+    pub fn set_pos(&mut self, pos: Option<Vector2<f32>>) {
+        if self.pos != pos {
+            self.pos = pos;
+            self.dirty = true;
+        }
+    }
+
+    pub fn draw(&mut self) {
+        if self.dirty {
+            self.dirty = false;
+            match self.pos {
+                Some(pos) => {
+                    // The hotspot is measured from the top left, and Y points up.
+                    let corner = Vector2::new(pos.x - self.hotspot.x, pos.y + self.hotspot.y - self.size.y);
+                    let sprite = Sprite::new(corner.extend(0.0), self.size, self.texture, colors::WHITE, 0.0);
+                    self.layer.set_sprites(&vec![sprite]);
+                }
+                None => self.layer.clear_sprites(),
+            }
+        }
+        self.layer.draw();
+    }
+}
@@ -0,0 +1,93 @@
+use crate::render::raw::{resource, OpenGL};
+use crate::types::{GpuPass, GpuTiming};
+use core::time::Duration;
+use std::collections::VecDeque;
+
+/// Frames of queries kept waiting on the GPU. Older frames are dropped unread rather than waiting
+/// on them.
+const MAX_PENDING: usize = 4;
+
+/// Times parts of each frame on the GPU with timer queries. Query results are read once the GPU
+/// has them, a few frames after they were issued, so reading never stalls the frame.
+pub(crate) struct GpuTimers {
+    free: Vec<resource::Query>,
+    /// Queries issued this frame.
+    current: Vec<(GpuPass, resource::Query)>,
+    active: bool,
+    paused: bool,
+    pending: VecDeque<Vec<(GpuPass, resource::Query)>>,
+    latest: Vec<GpuTiming>,
+}
+
+impl GpuTimers {
+    pub fn new() -> GpuTimers {
+        GpuTimers {
+            free: Vec::new(),
+            current: Vec::new(),
+            active: false,
+            paused: false,
+            pending: VecDeque::new(),
+            latest: Vec::new(),
+        }
+    }
+
+    /// Starts timing a pass. Passes can't be nested, so this does nothing while another pass is
+    /// being timed.
+    pub fn begin(&mut self, gl: &OpenGL, pass: GpuPass) {
+        if self.active || self.paused {
+            return;
+        }
+        let query = self.free.pop().unwrap_or_else(|| gl.create_query());
+        gl.begin_timer_query(query);
+        self.current.push((pass, query));
+        self.active = true;
+    }
+
+    /// Pauses timing, so passes begun while paused aren't timed.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn end(&mut self, gl: &OpenGL) {
+        if self.active {
+            gl.end_timer_query();
+            self.active = false;
+        }
+    }
+
+    /// Finishes the frame's timings, and reads the oldest frames the GPU is done with.
+    pub fn frame_end(&mut self, gl: &OpenGL) {
+        self.end(gl);
+        self.pending.push_back(core::mem::take(&mut self.current));
+        while let Some(frame) = self.pending.front() {
+            let ready = frame.last().map(|(_, query)| gl.query_available(*query)).unwrap_or(true);
+            if !ready && self.pending.len() <= MAX_PENDING {
+                break;
+            }
+            let frame = self.pending.pop_front().unwrap();
+            if ready {
+                self.latest = frame
+                    .iter()
+                    .map(|(pass, query)| GpuTiming {
+                        pass: *pass,
+                        time: Duration::from_nanos(gl.query_result(*query) as u64),
+                    })
+                    .collect();
+            }
+            self.free.extend(frame.into_iter().map(|(_, query)| query));
+        }
+    }
+
+    /// The timings of the most recent frame the GPU finished.
+    pub fn latest(&self) -> &[GpuTiming] {
+        &self.latest
+    }
+
+    pub fn delete(&mut self, gl: &OpenGL) {
+        self.end(gl);
+        let pending = self.pending.drain(..).flatten().chain(self.current.drain(..));
+        for query in self.free.drain(..).chain(pending.map(|(_, query)| query)) {
+            gl.delete_query(query);
+        }
+    }
+}
@@ -1,7 +1,17 @@
+use crate::math::AABB2D;
 use crate::render::buffer::Buffer;
-use crate::render::raw::BufferBindingTarget;
+use crate::render::raw::{BufferBindingTarget, Capability, DepthTest, TextureUnit};
+use crate::render::submit::{SpriteReceiver, SpriteSender};
+use crate::render::target::RenderTarget;
+use crate::render::texture_handle::TextureHandle;
+use crate::render::trail::Trail;
 use crate::render::OpenGLState;
-use crate::types::{LayerTransform, Sprite};
+use crate::render::SpriteSlots;
+use crate::types::colors::WHITE;
+use crate::types::{
+    ColorGrade, GpuPass, GradientSprite, LayerInfo, LayerTransform, ScreenOrigin, Sprite, SpriteInstance,
+    SpriteSort, SpriteToken, TextureSampling, RGBA8,
+};
 use crate::utility::bad::UnsafeShared;
 use cgmath::*;
 
@@ -17,22 +27,95 @@ pub struct SharedLayer {
     index: usize,
     transform: Matrix4<f32>,
     ortho: Matrix4<f32>,
+    safe_ortho: Matrix4<f32>,
+    safe_area: bool,
+    screen: Option<ScreenOrigin>,
     ortho_transform: Matrix4<f32>,
 }
 
 impl SharedLayer {
+    pub fn new(ortho: &Matrix4<f32>, safe_ortho: &Matrix4<f32>) -> SharedLayer {
+        SharedLayer {
+            index: 0,
+            transform: IDENTITY_MATRIX,
+            ortho: *ortho,
+            safe_ortho: *safe_ortho,
+            safe_area: false,
+            screen: None,
+            ortho_transform: *ortho,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The matrix to draw with. A bound render target replaces the window's projection, and a
+    /// camera is applied under the layer's transform when drawing to the window. Screen space
+    /// layers ignore the camera, and move their origin to a corner of whatever they're drawn to.
+    pub fn ortho_transform(
+        &self,
+        target_ortho: Option<&Matrix4<f32>>,
+        camera: Option<&Matrix4<f32>>,
+    ) -> Matrix4<f32> {
+        if let Some(origin) = self.screen {
+            let ortho = target_ortho.copied().unwrap_or_else(|| self.ortho());
+            let corner = match ortho.invert() {
+                Some(inverse) => (inverse * origin.ndc().extend(0.0).extend(1.0)).truncate(),
+                None => Vector3::zero(),
+            };
+            return ortho * Matrix4::from_translation(corner.truncate().extend(0.0)) * self.transform;
+        }
+        match (target_ortho, camera) {
+            (Some(ortho), _) => ortho * self.transform,
+            (None, Some(camera)) => self.ortho() * camera * self.transform,
+            (None, None) => self.ortho_transform,
+        }
+    }
+
+    /// Converts a position in screen coordinates into the layer's coordinates, by undoing the
+    /// matrix the layer is drawn to the window with.
+    pub fn screen_to_layer(&self, pos: Vector2<f32>, camera: Option<&Matrix4<f32>>) -> Vector2<f32> {
+        match self.ortho_transform(None, camera).invert() {
+            Some(inverse) => (inverse * self.ortho * pos.extend(0.0).extend(1.0)).truncate().truncate(),
+            None => pos,
+        }
+    }
+
+    fn ortho(&self) -> Matrix4<f32> {
+        if self.safe_area {
+            self.safe_ortho
+        } else {
+            self.ortho
+        }
+    }
+
     pub fn set_index(&mut self, index: usize) {
         self.index = index;
     }
 
-    pub fn set_ortho(&mut self, ortho: &Matrix4<f32>) {
+    pub fn set_ortho(&mut self, ortho: &Matrix4<f32>, safe_ortho: &Matrix4<f32>) {
         self.ortho = *ortho;
-        self.ortho_transform = self.ortho * self.transform;
+        self.safe_ortho = *safe_ortho;
+        self.update();
     }
 
     pub fn set_transform_matrix(&mut self, transform: &Matrix4<f32>) {
         self.transform = *transform;
-        self.ortho_transform = self.ortho * self.transform;
+        self.update();
+    }
+
+    pub fn set_safe_area(&mut self, safe_area: bool) {
+        self.safe_area = safe_area;
+        self.update();
+    }
+
+    pub fn set_screen_space(&mut self, origin: Option<ScreenOrigin>) {
+        self.screen = origin;
+    }
+
+    fn update(&mut self) {
+        self.ortho_transform = self.ortho() * self.transform;
     }
 }
 
@@ -42,55 +125,488 @@ pub struct Layer {
     shared: UnsafeShared<SharedLayer>,
     is_visible: bool,
     sprites: Buffer<Sprite>,
+    /// Alternate sprites keyed by the scale they're used below, sorted by that scale.
+    lods: Vec<(f32, Buffer<Sprite>)>,
+    gradient_sprites: Buffer<GradientSprite>,
+    /// Sprites managed one at a time with tokens, and the buffer they're uploaded to.
+    slots: UnsafeShared<SpriteSlots>,
+    slot_sprites: Buffer<Sprite>,
+    /// Batches submitted from other threads, and the buffer they're uploaded to.
+    submitted: Option<(SpriteReceiver, Buffer<Sprite>)>,
+    template: Sprite,
+    instances: Buffer<SpriteInstance>,
+    scale: f32,
+    photo_hidden: bool,
+    sampling: TextureSampling,
+    grade: Option<ColorGrade>,
+    texture: Option<UnsafeShared<TextureHandle>>,
+    trail: Option<Trail>,
+    sort: SpriteSort,
+    depth_test: bool,
+    depth_write: bool,
+    /// Every sprite from `set_sprites` while culling, since only the visible ones are uploaded.
+    cull: Option<Culling>,
+    /// The frame the contents last changed on.
+    updated: Option<u64>,
 }
 
 impl Layer {
     pub(crate) fn new(
         state: UnsafeShared<OpenGLState>,
         ortho: &Matrix4<f32>,
+        safe_ortho: &Matrix4<f32>,
     ) -> (UnsafeShared<SharedLayer>, Layer) {
-        let shared = UnsafeShared::new(SharedLayer {
-            index: 0,
-            transform: IDENTITY_MATRIX,
-            ortho: *ortho,
-            ortho_transform: *ortho,
-        });
+        let shared = UnsafeShared::new(SharedLayer::new(ortho, safe_ortho));
         let layer = Layer {
             state: state.clone(),
             shared: shared.clone(),
             is_visible: true,
-            sprites: Buffer::new(state, BufferBindingTarget::ArrayBuffer),
+            sprites: Buffer::new(state.clone(), BufferBindingTarget::ArrayBuffer),
+            lods: Vec::new(),
+            gradient_sprites: Buffer::new(state.clone(), BufferBindingTarget::ArrayBuffer),
+            slots: UnsafeShared::new(SpriteSlots::new()),
+            slot_sprites: Buffer::new(state.clone(), BufferBindingTarget::ArrayBuffer),
+            submitted: None,
+            template: Sprite::default(),
+            instances: Buffer::new(state, BufferBindingTarget::ArrayBuffer),
+            scale: 1.0,
+            photo_hidden: false,
+            sampling: TextureSampling::default(),
+            grade: None,
+            texture: None,
+            trail: None,
+            sort: SpriteSort::None,
+            depth_test: true,
+            depth_write: true,
+            cull: None,
+            updated: None,
         };
         (shared, layer)
     }
 
     pub fn draw(&mut self) {
-        if self.is_visible && self.sprites.len() > 0 {
-            self.state.shader_ortho(&self.shared.ortho_transform);
-            self.sprites.draw();
+        if self.photo_hidden && self.state.camera().is_some() {
+            return;
+        }
+        if !self.is_visible {
+            return;
+        }
+        self.state.gpu_timer_begin(GpuPass::Layer(self.shared.index()));
+        // Trails keep fading while the layer is empty.
+        if let Some(trail) = &mut self.trail {
+            trail.begin();
+        }
+        self.draw_sprites();
+        if let Some(trail) = &mut self.trail {
+            trail.end();
+        }
+        self.state.gpu_timer_end();
+    }
+
+    fn draw_sprites(&mut self) {
+        if let Some(range) = self.slots.take_dirty() {
+            self.slot_sprites.patch(self.slots.sprites(), range);
+        }
+        if let Some((receiver, buffer)) = &mut self.submitted {
+            if let Some(sprites) = receiver.receive() {
+                match self.sort.sorted(&sprites, |sprite| sprite.pos) {
+                    Some(sorted) => buffer.set(&sorted),
+                    None => buffer.set(&sprites),
+                }
+                self.updated = Some(self.state.frame());
+            }
+        }
+        let submitted = self.submitted.as_ref().map(|(_, buffer)| buffer);
+        let ortho_transform = self
+            .state
+            .pixel_snap(self.shared.ortho_transform(self.state.target_ortho(), self.state.camera()));
+        if let Some(cull) = &mut self.cull {
+            if let Some(sprites) = cull.update(&ortho_transform) {
+                self.sprites.set(&sprites);
+            }
+        }
+        let scale = self.scale;
+        let sprites = match self.lods.iter().find(|(max_scale, _)| scale < *max_scale) {
+            Some((_, sprites)) => sprites,
+            None => &self.sprites,
+        };
+        let submitted_len = submitted.map(|buffer| buffer.len()).unwrap_or(0);
+        if sprites.len() > 0
+            || self.gradient_sprites.len() > 0
+            || self.slot_sprites.len() > 0
+            || submitted_len > 0
+            || self.instances.len() > 0
+        {
+            self.state.shader_ortho(&ortho_transform);
+            let unit = match &self.texture {
+                Some(texture) => {
+                    texture.bind();
+                    self.state.shader_texture(TextureUnit::Target);
+                    TextureUnit::Target
+                }
+                None => TextureUnit::Atlas,
+            };
+            let custom = self.sampling != TextureSampling::default();
+            if custom {
+                self.state.shader_sampling(unit, &self.sampling);
+            }
+            if let Some(grade) = &self.grade {
+                self.state.shader_grade(grade);
+            }
+            // Sorted sprites overlap in draw order where their depths tie.
+            let sorted = self.sort != SpriteSort::None;
+            if sorted {
+                self.state.gl.depth_func(DepthTest::LessEqual);
+            }
+            if !self.depth_test {
+                self.state.gl.disable(Capability::DepthTest);
+            }
+            if !self.depth_write {
+                self.state.gl.depth_mask(false);
+            }
+            self.corner_attributes(&[WHITE; 4]);
+            sprites.draw();
+            if self.gradient_sprites.len() > 0 {
+                self.gradient_sprites.draw();
+                self.corner_attributes(&[WHITE; 4]);
+            }
+            self.slot_sprites.draw();
+            if let Some(submitted) = submitted {
+                submitted.draw();
+            }
+            if self.instances.len() > 0 {
+                self.template_attributes();
+                self.instances.draw();
+            }
+            if sorted {
+                self.state.gl.depth_func(DepthTest::Less);
+            }
+            if !self.depth_test {
+                self.state.gl.enable(Capability::DepthTest);
+            }
+            if !self.depth_write {
+                self.state.gl.depth_mask(true);
+            }
+            if custom {
+                self.state.shader_sampling(unit, &TextureSampling::default());
+            }
+            if self.grade.is_some() {
+                self.state.shader_grade(&ColorGrade::default());
+            }
+            if unit == TextureUnit::Target {
+                self.state.shader_texture(TextureUnit::Atlas);
+            }
         }
     }
 
     /// Sets the sprites that will be drawn.
+    #[allow(clippy::ptr_arg)]
     pub fn set_sprites(&mut self, sprites: &Vec<Sprite>) {
-        self.sprites.set(sprites);
+        let sorted = self.sort.sorted(sprites, |sprite| sprite.pos);
+        match &mut self.cull {
+            Some(cull) => cull.set(sorted.unwrap_or_else(|| sprites.to_vec())),
+            None => match sorted {
+                Some(sorted) => self.sprites.set(&sorted),
+                None => self.sprites.set(sprites),
+            },
+        }
+        self.touch();
     }
 
     /// Clears all the sprites, drawing nothing.
     pub fn clear_sprites(&mut self) {
         self.sprites.clear();
+        if let Some(cull) = &mut self.cull {
+            cull.set(Vec::new());
+        }
+        self.touch();
+    }
+
+    /// Adds a sprite that can be updated or removed on its own with the returned token. Only the
+    /// sprites that changed are uploaded when the layer is next drawn, which is much cheaper than
+    /// setting every sprite again for large scenes that mostly hold still. These sprites are drawn
+    /// after the layer's other sprites, whatever its sort, and before its instances.
+    pub fn sprite_create(&mut self, sprite: &Sprite) -> SpriteToken {
+        self.touch();
+        self.slots.create(sprite)
+    }
+
+    /// Gets a sprite added with `sprite_create`. If the token is from another layer, this function
+    /// will panic.
+    pub fn sprite_get(&self, token: &SpriteToken) -> &Sprite {
+        self.slots.get(token)
+    }
+
+    /// Replaces a sprite added with `sprite_create`. If the token is from another layer, this
+    /// function will panic.
+    pub fn sprite_update(&mut self, token: &SpriteToken, sprite: &Sprite) {
+        self.slots.update(token, sprite);
+        self.touch();
+    }
+
+    /// Removes a sprite added with `sprite_create`. The last sprite added this way takes its place
+    /// in the draw order. If the token is from another layer, this function will panic.
+    pub fn sprite_remove(&mut self, token: SpriteToken) {
+        self.slots.remove(token);
+        self.touch();
+    }
+
+    /// The sprites created with `sprite_create`, shared so keyframed sprites can change them.
+    pub(crate) fn slots(&self) -> UnsafeShared<SpriteSlots> {
+        self.slots.clone()
+    }
+
+    /// Creates a sender for submitting sprites to this layer from other threads, see
+    /// `SpriteSender`. Submitted sprites are picked up when the layer is drawn, and are drawn after
+    /// its created sprites and before its instances. They're sorted with the layer's sort when
+    /// they're picked up.
+    pub fn sprite_sender(&mut self) -> SpriteSender {
+        let state = self.state.clone();
+        let (receiver, _) = self.submitted.get_or_insert_with(|| {
+            (SpriteReceiver::new(), Buffer::new(state, BufferBindingTarget::ArrayBuffer))
+        });
+        receiver.sender()
+    }
+
+    /// Sets sprites with a color for each corner, see `GradientSprite`. They're drawn after the
+    /// layer's sprites, and replace any previously set. They aren't culled or drawn with
+    /// alternate sets from `set_sprites_lod`.
+    pub fn set_gradient_sprites(&mut self, sprites: &[GradientSprite]) {
+        match self.sort.sorted(sprites, |gradient| gradient.sprite.pos) {
+            Some(sorted) => self.gradient_sprites.set(&sorted),
+            None => self.gradient_sprites.set(sprites),
+        }
+        self.touch();
+    }
+
+    /// Clears all the gradient sprites.
+    pub fn clear_gradient_sprites(&mut self) {
+        self.gradient_sprites.clear();
+        self.touch();
+    }
+
+    /// Sets sprites that share every property of the template except position, color, and
+    /// rotation, which come from each instance. The template's own position, color, and rotation
+    /// are ignored. Instances are drawn after the layer's sprites, and replace any previously set.
+    pub fn set_instances(&mut self, template: &Sprite, instances: &[SpriteInstance]) {
+        self.template = *template;
+        match self.sort.sorted(instances, |instance| instance.pos) {
+            Some(sorted) => self.instances.set(&sorted),
+            None => self.instances.set(instances),
+        }
+        self.touch();
+    }
+
+    /// Clears all the instances.
+    pub fn clear_instances(&mut self) {
+        self.instances.clear();
+        self.touch();
+    }
+
+    /// Sets the attributes that instances don't store to the template's values.
+    fn template_attributes(&self) {
+        let normal = |value: u16| value as f32 / 65535.0;
+        let template = &self.template;
+        let gl = &self.state.gl;
+        gl.vertex_attrib_4f(1, normal(template.size.x), normal(template.size.y), 0.0, 1.0);
+        let uv = template.texture.0;
+        gl.vertex_attrib_4f(2, normal(uv.x), normal(uv.y), normal(uv.z), normal(uv.w));
+        gl.vertex_attrib_4f(5, template.visible as u8 as f32, 0.0, 0.0, 1.0);
+        gl.vertex_attrib_4f(6, template.effect as u8 as f32, 0.0, 0.0, 1.0);
+        gl.vertex_attrib_4f(7, normal(template.effect_progress), 0.0, 0.0, 1.0);
+        gl.vertex_attrib_4f(8, template.depth_bias as f32, 0.0, 0.0, 1.0);
+        gl.vertex_attrib_4f(9, template.scale.x, template.scale.y, 0.0, 1.0);
+        gl.vertex_attrib_4f(10, template.flip_x as u8 as f32, template.flip_y as u8 as f32, 0.0, 1.0);
+    }
+
+    /// Sets the corner color attributes, which only gradient sprites store, to the given colors.
+    fn corner_attributes(&self, colors: &[RGBA8; 4]) {
+        let channel = |value: u8| value as f32 / 255.0;
+        for (index, color) in colors.iter().enumerate() {
+            let (r, g, b, a) = (channel(color.r), channel(color.g), channel(color.b), channel(color.a));
+            self.state.gl.vertex_attrib_4f(11 + index as u32, r, g, b, a);
+        }
+    }
+
+    /// Sets alternate sprites to draw while the layer's scale is below the given scale, like a
+    /// simplified view of a map when zoomed out. When several sets apply, the one with the smallest
+    /// scale is drawn. Setting sprites for a scale that already has a set replaces it.
+    pub fn set_sprites_lod(&mut self, max_scale: f32, sprites: &[Sprite]) {
+        let index = match self.lods.iter().position(|(scale, _)| *scale >= max_scale) {
+            Some(index) if self.lods[index].0 == max_scale => index,
+            Some(index) => {
+                self.lods.insert(
+                    index,
+                    (max_scale, Buffer::new(self.state.clone(), BufferBindingTarget::ArrayBuffer)),
+                );
+                index
+            }
+            None => {
+                self.lods
+                    .push((max_scale, Buffer::new(self.state.clone(), BufferBindingTarget::ArrayBuffer)));
+                self.lods.len() - 1
+            }
+        };
+        match self.sort.sorted(sprites, |sprite| sprite.pos) {
+            Some(sorted) => self.lods[index].1.set(&sorted),
+            None => self.lods[index].1.set(sprites),
+        }
+        self.touch();
+    }
+
+    /// Removes every alternate sprite set, so the layer always draws its sprites.
+    pub fn clear_sprites_lod(&mut self) {
+        self.lods.clear();
+        self.touch();
+    }
+
+    /// Converts a position in screen coordinates, like the positions in cursor and touch messages,
+    /// into this layer's coordinates. Unlike `LayerTransform::screen_to_layer`, this accounts for
+    /// everything the layer is drawn with: the camera, the safe area, a screen space origin, and a
+    /// transform set as a matrix.
+    pub fn screen_to_layer(&self, pos: Vector2<f32>) -> Vector2<f32> {
+        self.shared.screen_to_layer(pos, self.state.camera())
+    }
+
+    /// Gets what the layer holds, like for finding heavy layers or sprite lists that keep growing.
+    /// Text is counted in the sprites it was appended as.
+    pub fn info(&self) -> LayerInfo {
+        let lods = self.lods.iter().map(|(_, sprites)| sprites);
+        LayerInfo {
+            sprites: match &self.cull {
+                Some(cull) => cull.sprites.len(),
+                None => self.sprites.len(),
+            },
+            gradient_sprites: self.gradient_sprites.len(),
+            created_sprites: self.slots.len(),
+            submitted_sprites: self.submitted.as_ref().map(|(receiver, _)| receiver.len()).unwrap_or(0),
+            instances: self.instances.len(),
+            lod_sprites: lods.clone().map(|sprites| sprites.len()).sum(),
+            gpu_bytes: self.sprites.allocated()
+                + self.gradient_sprites.allocated()
+                + self.slot_sprites.allocated()
+                + self.submitted.as_ref().map(|(_, buffer)| buffer.allocated()).unwrap_or(0)
+                + self.instances.allocated()
+                + lods.map(|sprites| sprites.allocated()).sum::<usize>(),
+            updated_frame: self.updated,
+        }
+    }
+
+    /// Records that the contents changed this frame.
+    fn touch(&mut self) {
+        self.updated = Some(self.state.frame());
     }
 
-    /// Sets the transformation matrix used when drawing this.
+    /// Sets the transformation matrix used when drawing this. The scale used to pick alternate
+    /// sprites is the transform's X scale.
     pub fn set_transform(&mut self, transform: &LayerTransform) {
+        self.scale = transform.scale.x;
         self.shared.set_transform_matrix(&transform.to_matrix());
     }
 
-    /// Sets the transformation matrix used when drawing this.
+    /// Sets the transformation matrix used when drawing this. The scale used to pick alternate
+    /// sprites is the length of the matrix's X axis.
     pub fn set_transform_matrix(&mut self, transform: &Matrix4<f32>) {
+        self.scale = transform.x.truncate().magnitude();
         self.shared.set_transform_matrix(transform);
     }
 
+    /// Sets the texture the sprites in this layer sample from. When set to a render target, sprite
+    /// textures are relative to the target, see `RenderTarget::texture`. When set to None, sprites
+    /// sample the engine's texture atlas. The default is None. A layer shouldn't be drawn into the
+    /// same render target it samples from.
+    pub fn set_texture_source(&mut self, target: Option<&RenderTarget>) {
+        self.texture = target.map(|target| target.texture_handle());
+    }
+
+    /// Sets how the sprites in this layer sample their textures. The default is nearest filtering
+    /// with textures stretched to fit. Pixel art mode always samples with nearest filtering.
+    pub fn set_sampling(&mut self, sampling: TextureSampling) {
+        self.sampling = sampling;
+    }
+
+    /// Sets color adjustments applied to everything the layer draws, without touching its sprites.
+    /// Set this to None to draw colors unchanged. The default is None.
+    pub fn set_color_grade(&mut self, grade: Option<ColorGrade>) {
+        self.grade = grade;
+    }
+
+    /// Sets the order sprites, instances, and alternate sprites are drawn in. Sorting happens when
+    /// they're set, so this applies to everything set afterward. The default is `SpriteSort::None`.
+    pub fn set_sort(&mut self, sort: SpriteSort) {
+        self.sort = sort;
+    }
+
+    /// Sets if the layer's sprites are hidden behind what's already been drawn at a higher depth.
+    /// Without the depth test, sprites are drawn over everything in draw order, and don't write
+    /// their depth either. The default is true.
+    pub fn set_depth_test(&mut self, depth_test: bool) {
+        self.depth_test = depth_test;
+    }
+
+    /// Sets if the layer's sprites write their depth, hiding what's drawn behind them afterward.
+    /// Translucent layers like particles and glows can turn this off to still be hidden behind
+    /// solid sprites, without cutting holes in each other or in sprites drawn after them. The
+    /// default is true.
+    pub fn set_depth_write(&mut self, depth_write: bool) {
+        self.depth_write = depth_write;
+    }
+
+    /// Sets if sprites from `set_sprites` that are entirely outside the visible area are skipped,
+    /// so large scrolling worlds only pay for the sprites on screen. The layer keeps a copy of its
+    /// sprites and uploads the ones near the visible area, uploading again once the view moves far
+    /// enough. Created, submitted, and alternate sprites and instances are always drawn. Turning
+    /// culling on clears the sprites already set, so set it before setting them. The default is
+    /// false.
+    pub fn set_culling(&mut self, culling: bool) {
+        match (culling, self.cull.take()) {
+            (true, None) => {
+                self.sprites.clear();
+                self.cull = Some(Culling::new());
+            }
+            (true, cull) => self.cull = cull,
+            (false, Some(cull)) => self.sprites.set(&cull.sprites),
+            (false, None) => {}
+        }
+    }
+
+    /// Sets a motion trail, where what the layer drew on earlier frames lingers and fades out
+    /// behind what it draws now. The decay is the fraction of the trail's opacity kept each time
+    /// the layer is drawn, so higher values make longer trails. The trail is drawn over the
+    /// framebuffer without depth testing against what's already there. Set this to None to
+    /// remove the trail. The default is None.
+    pub fn set_trail(&mut self, decay: Option<f32>) {
+        match (decay, &mut self.trail) {
+            (Some(decay), Some(trail)) => trail.set_decay(decay),
+            (Some(decay), None) => self.trail = Some(Trail::new(self.state.clone(), decay)),
+            (None, _) => self.trail = None,
+        }
+    }
+
+    /// If the layer should be laid out within the window's safe area. When enabled, the origin of
+    /// the layer is the center of the safe area instead of the center of the window, so content
+    /// anchored to the edges stays clear of notches and overscan. Render targets ignore this. The
+    /// default is false.
+    pub fn set_safe_area(&mut self, safe_area: bool) {
+        self.shared.set_safe_area(safe_area);
+    }
+
+    /// Makes the layer screen space, for interfaces like HUDs. Screen space layers ignore the photo
+    /// mode camera and are laid out in window pixels, or virtual pixels with a virtual resolution,
+    /// with the origin at the given corner instead of the center. Y still points up, so content
+    /// anchored to a top corner has negative Y. The corners are the window's, so the safe area
+    /// doesn't apply. In a render target, the corners are the target's. Set this to None to lay
+    /// the layer out normally. The default is None.
+    pub fn set_screen_space(&mut self, origin: Option<ScreenOrigin>) {
+        self.shared.set_screen_space(origin);
+    }
+
+    /// If the layer should be hidden while photo mode is active, like for interface layers. The
+    /// default is false.
+    pub fn set_photo_hidden(&mut self, photo_hidden: bool) {
+        self.photo_hidden = photo_hidden;
+    }
+
     /// If the renderer should render this layer or not when draw is called.
     pub fn set_visible(&mut self, is_visible: bool) {
         self.is_visible = is_visible;
@@ -99,6 +615,101 @@ impl Layer {
 
 impl Drop for Layer {
     fn drop(&mut self) {
-        self.state.layer_drop(self.shared.index);
+        self.state.layer_drop(self.shared.index());
+    }
+}
+
+/// The sprites of a culled layer, and the area the uploaded ones were picked from.
+struct Culling {
+    sprites: Vec<Sprite>,
+    area: Option<AABB2D>,
+}
+
+impl Culling {
+    fn new() -> Culling {
+        Culling {
+            sprites: Vec::new(),
+            area: None,
+        }
+    }
+
+    fn set(&mut self, sprites: Vec<Sprite>) {
+        self.sprites = sprites;
+        self.area = None;
+    }
+
+    /// Picks the sprites to upload if the visible area has left the area they were last picked
+    /// from. The area is padded by half the visible size on every side, so small movements don't
+    /// upload anything.
+    fn update(&mut self, ortho_transform: &Matrix4<f32>) -> Option<Vec<Sprite>> {
+        let visible = visible_area(ortho_transform)?;
+        if let Some(area) = &self.area {
+            if area.contains(&visible) {
+                return None;
+            }
+        }
+        let pad = (visible.max - visible.min) / 2.0;
+        let area = AABB2D {
+            min: visible.min - pad,
+            max: visible.max + pad,
+        };
+        self.area = Some(area);
+        Some(self.sprites.iter().filter(|sprite| sprite.bounds().intersects(&area)).copied().collect())
+    }
+}
+
+/// Gets the area of the layer's coordinates that's drawn on screen.
+fn visible_area(ortho_transform: &Matrix4<f32>) -> Option<AABB2D> {
+    let inverse = ortho_transform.invert()?;
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)];
+    let mut area = AABB2D::new(f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for (x, y) in corners {
+        let pos = (inverse * Vector4::new(x, y, 0.0, 1.0)).truncate().truncate();
+        area.min = Vector2::new(area.min.x.min(pos.x), area.min.y.min(pos.y));
+        area.max = Vector2::new(area.max.x.max(pos.x), area.max.y.max(pos.y));
+    }
+    Some(area)
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::matrix_from_bounds;
+    use crate::types::LayerTransform;
+
+    fn close(a: Vector2<f32>, b: Vector2<f32>) -> bool {
+        (a - b).magnitude() < 0.001
+    }
+
+    #[test]
+    fn screen_to_layer() {
+        let ortho = matrix_from_bounds(&Vector2::new(800.0, 600.0));
+        let mut shared = SharedLayer::new(&ortho, &ortho);
+        let touch = Vector2::new(100.0, -46.0);
+        assert!(close(shared.screen_to_layer(touch, None), touch));
+
+        let mut transform = LayerTransform::new();
+        transform.translation = Vector2::new(10.0, 20.0);
+        transform.pivot = Vector2::new(100.0, -50.0);
+        transform.scale = Vector2::new(2.0, 4.0);
+        transform.rotation = 0.25;
+        shared.set_transform_matrix(&transform.to_matrix());
+        let pos = shared.screen_to_layer(touch, None);
+        assert!(close(pos, Vector2::new(91.0, -70.0)));
+        assert!(close(pos, transform.screen_to_layer(touch)));
+
+        // The camera is applied under the layer's transform.
+        let camera = Matrix4::from_translation(Vector3::new(-50.0, 0.0, 0.0));
+        let moved = shared.screen_to_layer(touch, Some(&camera));
+        assert!(close(moved, transform.screen_to_layer(touch + Vector2::new(50.0, 0.0))));
+
+        // Screen space layers ignore the camera and put their origin in a corner.
+        shared.set_transform_matrix(&IDENTITY_MATRIX);
+        shared.set_screen_space(Some(ScreenOrigin::BottomLeft));
+        assert!(close(shared.screen_to_layer(Vector2::new(-400.0, -300.0), Some(&camera)), Vector2::zero()));
     }
 }
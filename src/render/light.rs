@@ -0,0 +1,277 @@
+use crate::render::layer::SharedLayer;
+use crate::render::raw::{
+    resource, BlendFactor, Capability, DrawMode, FramebufferBindingTarget, TextureUnit,
+};
+use crate::render::shader;
+use crate::render::target::RenderTarget;
+use crate::render::OpenGLState;
+use crate::types::{LayerTransform, Light, Occluder, RGBA8};
+use crate::utility::bad::UnsafeShared;
+use cgmath::*;
+
+const MAX_LIGHTS: usize = 32;
+const MAX_OCCLUDERS: usize = 128;
+
+struct LightUniforms {
+    inverse: Option<resource::UniformLocation>,
+    ambient: Option<resource::UniformLocation>,
+    light_count: Option<resource::UniformLocation>,
+    occluder_count: Option<resource::UniformLocation>,
+    light_shape: Option<resource::UniformLocation>,
+    light_color: Option<resource::UniformLocation>,
+    light_cone: Option<resource::UniformLocation>,
+    occluders: Option<resource::UniformLocation>,
+}
+
+/// Light maps darken everything drawn before them by a shadow mask built from lights and
+/// occluders. Lights and occluders share a coordinate space with layers, so a light map with the
+/// same transform as a layer lines up with its sprites. Below full light resolution, lights are
+/// computed into a smaller target that's stretched over the frame.
+pub struct LightMap {
+    state: UnsafeShared<OpenGLState>,
+    shared: UnsafeShared<SharedLayer>,
+    program: resource::Program,
+    vao: resource::VertexArray,
+    uniforms: LightUniforms,
+    copy: resource::Program,
+    copy_texture: Option<resource::UniformLocation>,
+    target: Option<RenderTarget>,
+    is_visible: bool,
+    ambient: RGBA8,
+    light_shape: Vec<f32>,
+    light_color: Vec<f32>,
+    light_cone: Vec<f32>,
+    occluders: Vec<f32>,
+}
+
+impl LightMap {
+    pub(crate) fn new(
+        state: UnsafeShared<OpenGLState>,
+        ortho: &Matrix4<f32>,
+        safe_ortho: &Matrix4<f32>,
+    ) -> (UnsafeShared<SharedLayer>, LightMap) {
+        let shared = UnsafeShared::new(SharedLayer::new(ortho, safe_ortho));
+        let gl = &state.gl;
+        let program = gl.shader_program(shader::post::VERTEX, shader::light::FRAGMENT);
+        let uniforms = LightUniforms {
+            inverse: gl.get_uniform_location(program, "inverse"),
+            ambient: gl.get_uniform_location(program, "ambient"),
+            light_count: gl.get_uniform_location(program, "light_count"),
+            occluder_count: gl.get_uniform_location(program, "occluder_count"),
+            light_shape: gl.get_uniform_location(program, "light_shape"),
+            light_color: gl.get_uniform_location(program, "light_color"),
+            light_cone: gl.get_uniform_location(program, "light_cone"),
+            occluders: gl.get_uniform_location(program, "occluders"),
+        };
+        let vao = gl.create_vertex_array();
+        let copy = gl.shader_program(shader::post::VERTEX, shader::post::COPY);
+        let copy_texture = gl.get_uniform_location(copy, "tex");
+        let light_map = LightMap {
+            state,
+            shared: shared.clone(),
+            program,
+            vao,
+            uniforms,
+            copy,
+            copy_texture,
+            target: None,
+            is_visible: true,
+            ambient: RGBA8::new_raw(0, 0, 0, 255),
+            light_shape: Vec::new(),
+            light_color: Vec::new(),
+            light_cone: Vec::new(),
+            occluders: Vec::new(),
+        };
+        (shared, light_map)
+    }
+
+    /// Multiplies everything drawn so far by the light map.
+    pub fn draw(&mut self) {
+        if !self.is_visible {
+            return;
+        }
+        let ortho_transform = self
+            .state
+            .pixel_snap(self.shared.ortho_transform(self.state.target_ortho(), self.state.camera()));
+        let inverse = match ortho_transform.invert() {
+            Some(inverse) => inverse,
+            None => return,
+        };
+        let (framebuffer, size) = self.state.framebuffer();
+        let resolution = self.state.light_resolution();
+        if resolution < 1.0 {
+            let reduced = (size * resolution).map(|value| value.ceil().max(1.0));
+            if self.target.as_ref().map(|target| target.size()) != Some(reduced) {
+                self.target = Some(RenderTarget::new(self.state.clone(), reduced.x as u32, reduced.y as u32));
+            }
+        } else {
+            self.target = None;
+        }
+
+        let gl = &self.state.gl;
+        let uniforms = &self.uniforms;
+        gl.disable(Capability::DepthTest);
+        gl.blend_func(BlendFactor::DstColor, BlendFactor::Zero);
+        gl.bind_vertex_array(Some(self.vao));
+        gl.use_program(Some(self.program));
+        gl.uniform_matrix_4fv(uniforms.inverse.as_ref(), false, inverse.as_ref());
+        let ambient: Vector4<f32> = self.ambient.into();
+        gl.uniform_4f(uniforms.ambient.as_ref(), ambient.x, ambient.y, ambient.z, ambient.w);
+        gl.uniform_1i(uniforms.light_count.as_ref(), (self.light_shape.len() / 4) as i32);
+        gl.uniform_1i(uniforms.occluder_count.as_ref(), (self.occluders.len() / 4) as i32);
+        if !self.light_shape.is_empty() {
+            gl.uniform_4fv(uniforms.light_shape.as_ref(), &self.light_shape);
+            gl.uniform_4fv(uniforms.light_color.as_ref(), &self.light_color);
+            gl.uniform_4fv(uniforms.light_cone.as_ref(), &self.light_cone);
+        }
+        if !self.occluders.is_empty() {
+            gl.uniform_4fv(uniforms.occluders.as_ref(), &self.occluders);
+        }
+        match &self.target {
+            Some(target) => {
+                let reduced = target.size();
+                gl.disable(Capability::Blend);
+                gl.bind_framebuffer(FramebufferBindingTarget::Framebuffer, Some(target.framebuffer()));
+                gl.viewport(0, 0, reduced.x as i32, reduced.y as i32);
+                gl.draw_arrays(DrawMode::Triangles, 0, 3);
+
+                gl.bind_framebuffer(FramebufferBindingTarget::Framebuffer, framebuffer);
+                gl.viewport(0, 0, size.x as i32, size.y as i32);
+                gl.enable(Capability::Blend);
+                gl.use_program(Some(self.copy));
+                target.texture_handle().bind();
+                let unit = (TextureUnit::Target as u32 - TextureUnit::Atlas as u32) as i32;
+                gl.uniform_1i(self.copy_texture.as_ref(), unit);
+                gl.bind_sampler(TextureUnit::Target, Some(self.state.linear_sampler()));
+                gl.draw_arrays(DrawMode::Triangles, 0, 3);
+                gl.bind_sampler(TextureUnit::Target, None);
+            }
+            None => gl.draw_arrays(DrawMode::Triangles, 0, 3),
+        }
+
+        gl.enable(Capability::DepthTest);
+        gl.blend_func(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha);
+        self.state.shader_bind();
+    }
+
+    /// Sets the lights. Up to 32 lights are supported, and any more are ignored.
+    pub fn set_lights(&mut self, lights: &[Light]) {
+        if lights.len() > MAX_LIGHTS {
+            warn!("Light maps support up to {} lights, ignoring {}.", MAX_LIGHTS, lights.len() - MAX_LIGHTS);
+        }
+        self.light_shape.clear();
+        self.light_color.clear();
+        self.light_cone.clear();
+        for light in lights.iter().take(MAX_LIGHTS) {
+            let (shape, color, cone) = pack(light);
+            self.light_shape.extend_from_slice(&shape);
+            self.light_color.extend_from_slice(&color);
+            self.light_cone.extend_from_slice(&cone);
+        }
+    }
+
+    /// Sets the occluders that cast shadows. Up to 128 occluders are supported, and any more are
+    /// ignored.
+    pub fn set_occluders(&mut self, occluders: &[Occluder]) {
+        if occluders.len() > MAX_OCCLUDERS {
+            warn!(
+                "Light maps support up to {} occluders, ignoring {}.",
+                MAX_OCCLUDERS,
+                occluders.len() - MAX_OCCLUDERS
+            );
+        }
+        self.occluders.clear();
+        for occluder in occluders.iter().take(MAX_OCCLUDERS) {
+            self.occluders.extend_from_slice(&[
+                occluder.start.x,
+                occluder.start.y,
+                occluder.end.x,
+                occluder.end.y,
+            ]);
+        }
+    }
+
+    /// Sets the light applied everywhere, even in shadow. The default is black.
+    pub fn set_ambient(&mut self, ambient: RGBA8) {
+        self.ambient = ambient;
+    }
+
+    /// Sets the transformation matrix used when drawing this.
+    pub fn set_transform(&mut self, transform: &LayerTransform) {
+        self.shared.set_transform_matrix(&transform.to_matrix());
+    }
+
+    /// Sets the transformation matrix used when drawing this.
+    pub fn set_transform_matrix(&mut self, transform: &Matrix4<f32>) {
+        self.shared.set_transform_matrix(transform);
+    }
+
+    /// If the light map should be laid out within the window's safe area, like
+    /// `Layer::set_safe_area`. The default is false.
+    pub fn set_safe_area(&mut self, safe_area: bool) {
+        self.shared.set_safe_area(safe_area);
+    }
+
+    /// If the renderer should render this light map or not when draw is called.
+    pub fn set_visible(&mut self, is_visible: bool) {
+        self.is_visible = is_visible;
+    }
+}
+
+impl Drop for LightMap {
+    fn drop(&mut self) {
+        self.state.layer_drop(self.shared.index());
+        self.state.gl.delete_program(self.program);
+        self.state.gl.delete_program(self.copy);
+        self.state.gl.delete_vertex_array(self.vao);
+    }
+}
+
+/// Packs a light into its shape, color, and cone uniforms. The cone is the direction the light
+/// points and the cosine of half its spread, which the shader compares against.
+fn pack(light: &Light) -> ([f32; 4], [f32; 4], [f32; 4]) {
+    let color: Vector4<f32> = light.color.into();
+    let angle = core::f32::consts::PI * 2.0 * light.direction;
+    let half_spread = core::f32::consts::PI * light.spread.min(1.0);
+    (
+        [light.pos.x, light.pos.y, light.radius, 0.0],
+        [color.x, color.y, color.z, color.w],
+        [angle.cos(), angle.sin(), half_spread.cos(), 0.0],
+    )
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [f32; 4], b: [f32; 4]) -> bool {
+        a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < 0.0001)
+    }
+
+    #[test]
+    fn packing() {
+        let light = Light {
+            pos: Vector2::new(10.0, 20.0),
+            color: RGBA8::new_raw(255, 0, 0, 255),
+            radius: 50.0,
+            direction: 0.25,
+            spread: 0.5,
+        };
+        let (shape, color, cone) = pack(&light);
+        assert_eq!(shape, [10.0, 20.0, 50.0, 0.0]);
+        assert_eq!(color, [1.0, 0.0, 0.0, 1.0]);
+        // Pointing up with a quarter turn on each side of the direction.
+        assert!(close(cone, [0.0, 1.0, 0.0, 0.0]));
+
+        // Spreads past a full turn light everything in range.
+        let point = Light {
+            spread: 3.0,
+            ..light
+        };
+        assert!(close(pack(&point).2, [0.0, 1.0, -1.0, 0.0]));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::render::layer::SharedLayer;
+use crate::render::raw::{resource, BufferBindingTarget, BufferUsage, Capability, DrawMode, IndiceType};
+use crate::render::shader;
+use crate::render::vertex::VertexDescription;
+use crate::render::OpenGLState;
+use crate::types::{LayerTransform, MeshVertex, Texture};
+use crate::utility::bad::UnsafeShared;
+use cgmath::*;
+
+/// Mesh layers draw triangles from vertices and indices you provide, textured from the engine's
+/// texture atlas. This covers geometry quads can't, like skinned characters, deformable terrain,
+/// and trails. Meshes share a coordinate space and depth with layers, so a mesh layer with the
+/// same transform as a layer lines up with its sprites.
+pub struct MeshLayer {
+    state: UnsafeShared<OpenGLState>,
+    shared: UnsafeShared<SharedLayer>,
+    program: resource::Program,
+    uniform_ortho: Option<resource::UniformLocation>,
+    uniform_texture: Option<resource::UniformLocation>,
+    uniform_atlas_scale: Option<resource::UniformLocation>,
+    vao: resource::VertexArray,
+    vertices: resource::Buffer,
+    indices: resource::Buffer,
+    count: usize,
+    is_visible: bool,
+}
+
+impl MeshLayer {
+    pub(crate) fn new(
+        state: UnsafeShared<OpenGLState>,
+        ortho: &Matrix4<f32>,
+        safe_ortho: &Matrix4<f32>,
+    ) -> (UnsafeShared<SharedLayer>, MeshLayer) {
+        let shared = UnsafeShared::new(SharedLayer::new(ortho, safe_ortho));
+        let gl = &state.gl;
+        let program = gl.shader_program(shader::mesh::VERTEX, shader::mesh::FRAGMENT);
+        let uniform_ortho = gl.get_uniform_location(program, "ortho");
+        let uniform_texture = gl.get_uniform_location(program, "tex");
+        let uniform_atlas_scale = gl.get_uniform_location(program, "atlas_scale");
+
+        // The index buffer binding is part of the vertex array's state.
+        let vao = gl.create_vertex_array();
+        gl.bind_vertex_array(Some(vao));
+        let vertices = gl.create_buffer();
+        gl.bind_buffer(BufferBindingTarget::ArrayBuffer, Some(vertices));
+        gl.buffer_data_empty(BufferBindingTarget::ArrayBuffer, 0, BufferUsage::StaticDraw);
+        MeshVertex::configure_vertex_attribute(gl);
+        let indices = gl.create_buffer();
+        gl.bind_buffer(BufferBindingTarget::ElementArrayBuffer, Some(indices));
+        gl.buffer_data_empty(BufferBindingTarget::ElementArrayBuffer, 0, BufferUsage::StaticDraw);
+        gl.bind_vertex_array(None);
+
+        let mesh_layer = MeshLayer {
+            state: state.clone(),
+            shared: shared.clone(),
+            program,
+            uniform_ortho,
+            uniform_texture,
+            uniform_atlas_scale,
+            vao,
+            vertices,
+            indices,
+            count: 0,
+            is_visible: true,
+        };
+        (shared, mesh_layer)
+    }
+
+    /// Draws the mesh, depth tested against everything drawn so far.
+    pub fn draw(&mut self) {
+        if !self.is_visible || self.count == 0 {
+            return;
+        }
+        let ortho_transform = self
+            .state
+            .pixel_snap(self.shared.ortho_transform(self.state.target_ortho(), self.state.camera()));
+        let gl = &self.state.gl;
+        // Triangles can be in either winding, so none can be culled.
+        gl.disable(Capability::CullFace);
+        gl.use_program(Some(self.program));
+        gl.uniform_matrix_4fv(self.uniform_ortho.as_ref(), false, ortho_transform.as_ref());
+        // Units are counted from the atlas's.
+        gl.uniform_1i(self.uniform_texture.as_ref(), 0);
+        gl.uniform_1f(self.uniform_atlas_scale.as_ref(), self.state.atlas_scale());
+        gl.bind_vertex_array(Some(self.vao));
+        gl.draw_elements(DrawMode::Triangles, self.count as i32, IndiceType::UnsignedInt, 0);
+
+        gl.enable(Capability::CullFace);
+        self.state.shader_bind();
+    }
+
+    /// Sets the mesh, replacing any previously set. Every three indices make a triangle out of the
+    /// vertices they point to, in either winding. The vertices' UVs are relative to the texture.
+    /// If the number of indices isn't a multiple of three, or an index is past the end of the
+    /// vertices, this function will panic.
+    pub fn set_mesh(&mut self, texture: &Texture, vertices: &[MeshVertex], indices: &[u32]) {
+        if indices.len() % 3 != 0 {
+            panic!("Expected a multiple of 3 mesh indices, got {}.", indices.len());
+        }
+        if let Some(index) = indices.iter().find(|&&index| index as usize >= vertices.len()) {
+            panic!("Mesh index {} is past the end of {} vertices.", index, vertices.len());
+        }
+        let vertices: Vec<MeshVertex> = vertices.iter().map(|vertex| atlas_vertex(texture, vertex)).collect();
+        let gl = &self.state.gl;
+        gl.bind_vertex_array(Some(self.vao));
+        gl.bind_buffer(BufferBindingTarget::ArrayBuffer, Some(self.vertices));
+        gl.buffer_data(BufferBindingTarget::ArrayBuffer, &vertices, BufferUsage::StaticDraw);
+        gl.bind_buffer(BufferBindingTarget::ElementArrayBuffer, Some(self.indices));
+        gl.buffer_data(BufferBindingTarget::ElementArrayBuffer, indices, BufferUsage::StaticDraw);
+        gl.bind_vertex_array(None);
+        self.count = indices.len();
+    }
+
+    /// Clears the mesh, drawing nothing.
+    pub fn clear_mesh(&mut self) {
+        self.count = 0;
+    }
+
+    /// Sets the transformation matrix used when drawing this.
+    pub fn set_transform(&mut self, transform: &LayerTransform) {
+        self.shared.set_transform_matrix(&transform.to_matrix());
+    }
+
+    /// Sets the transformation matrix used when drawing this.
+    pub fn set_transform_matrix(&mut self, transform: &Matrix4<f32>) {
+        self.shared.set_transform_matrix(transform);
+    }
+
+    /// If the mesh layer should be laid out within the window's safe area, like
+    /// `Layer::set_safe_area`. The default is false.
+    pub fn set_safe_area(&mut self, safe_area: bool) {
+        self.shared.set_safe_area(safe_area);
+    }
+
+    /// If the renderer should render this mesh layer or not when draw is called.
+    pub fn set_visible(&mut self, is_visible: bool) {
+        self.is_visible = is_visible;
+    }
+}
+
+impl Drop for MeshLayer {
+    fn drop(&mut self) {
+        self.state.layer_drop(self.shared.index());
+        self.state.gl.delete_program(self.program);
+        self.state.gl.delete_vertex_array(self.vao);
+        self.state.gl.delete_buffer(self.vertices);
+        self.state.gl.delete_buffer(self.indices);
+    }
+}
+
+/// Moves the vertex's UV from the texture's space into the atlas's.
+fn atlas_vertex(texture: &Texture, vertex: &MeshVertex) -> MeshVertex {
+    let rect = texture.0.cast::<f32>().unwrap() / 65536.0;
+    MeshVertex {
+        uv: Vector2::new(rect.x + (rect.y - rect.x) * vertex.uv.x, rect.z + (rect.w - rect.z) * vertex.uv.y),
+        ..*vertex
+    }
+}
@@ -1,24 +1,59 @@
 mod buffer;
+mod cursor;
+mod gpu_timer;
 mod layer;
+mod light;
+mod mesh;
+mod post;
+mod primitive;
 mod raw;
+mod readback;
 mod shader;
+mod shape;
+mod slots;
 mod state;
+mod submit;
+mod target;
 mod texture_handle;
+mod trail;
 mod vertex;
 mod window;
 
-use self::raw::{OpenGL, TextureUnit};
+use self::cursor::SoftwareCursor;
+use self::post::PostProcessor;
+use self::raw::{Capability, OpenGL, PixelFormat, PixelType, TextureUnit};
 use self::state::OpenGLState;
 use self::texture_handle::*;
 use self::window::*;
+use crate::input::FileDrop;
+use crate::photo::{ReplayRecorder, VideoRecorder};
 use crate::text::*;
 use crate::texture::*;
+use crate::time::Instant;
 use crate::types::*;
 use crate::utility::bad::UnsafeShared;
 use cgmath::*;
+use core::time::Duration;
+use hashbrown::HashMap;
+use std::path::PathBuf;
 
 pub use self::layer::Layer;
+pub use self::light::LightMap;
+pub use self::mesh::MeshLayer;
+pub use self::primitive::PrimitiveLayer;
 pub use self::raw::ClearMode;
+pub use self::readback::PixelReadback;
+pub use self::shape::ShapeLayer;
+pub(crate) use self::slots::SpriteSlots;
+pub use self::submit::SpriteSender;
+pub use self::target::RenderTarget;
+
+/// Presents that block for at least this long are waiting on the display, since presents that
+/// don't wait return almost immediately.
+const VSYNC_WAIT: Duration = Duration::from_millis(1);
+/// Text scale and distance from the window's corner of the stats overlay.
+const OVERLAY_SCALE: u32 = 16;
+const OVERLAY_MARGIN: f32 = 8.0;
 
 pub fn matrix_from_bounds(bounds: &Vector2<f32>) -> Matrix4<f32> {
     let w = bounds.x / 2.0;
@@ -26,14 +61,41 @@ pub fn matrix_from_bounds(bounds: &Vector2<f32>) -> Matrix4<f32> {
     ortho(-w.floor(), w.ceil(), -h.floor(), h.ceil(), -1.0, 1.0)
 }
 
+/// Like matrix_from_bounds, but the origin is moved to the center of the safe area.
+pub fn matrix_from_safe_area(bounds: &Vector2<f32>, safe_area: &SafeArea) -> Matrix4<f32> {
+    let offset = Vector3::new(
+        ((safe_area.left - safe_area.right) / 2.0).round(),
+        ((safe_area.bottom - safe_area.top) / 2.0).round(),
+        0.0,
+    );
+    matrix_from_bounds(bounds) * Matrix4::from_translation(offset)
+}
+
 pub struct Renderer {
     window: OpenGLWindow,
     state: UnsafeShared<OpenGLState>,
     texture_atlas: TextureHandle,
+    post: PostProcessor,
     matrix_bounds: Matrix4<f32>,
+    matrix_safe_area: Matrix4<f32>,
+    safe_area_override: Option<SafeArea>,
     logical_size: Vector2<f32>,
     atlas: TextureAtlas,
     text_cache: TextCache,
+    texture_names: HashMap<String, Texture>,
+    screenshot: Option<PathBuf>,
+    video: Option<VideoRecorder>,
+    replay: Option<ReplayRecorder>,
+    /// How long the last present blocked for.
+    present_wait: Duration,
+    /// Draw calls and instances drawn for the last presented frame.
+    draw_counts: (u32, u32),
+    overlay: Option<Layer>,
+    cursor: Option<SoftwareCursor>,
+    /// Textures for cursor images, so setting the same image again doesn't add it to the atlas.
+    cursor_textures: Vec<(CursorImage, Texture)>,
+    /// The cursor's position in virtual pixels, or None if it's outside the window.
+    cursor_pos: Option<Vector2<f32>>,
 }
 
 impl Renderer {
@@ -41,19 +103,36 @@ impl Renderer {
         let (window, gl) = OpenGLWindow::new(desc, event_loop);
 
         let gl = OpenGL::new(gl);
-        let state = UnsafeShared::new(OpenGLState::new(gl));
+        let mut state = UnsafeShared::new(OpenGLState::new(gl));
 
         let texture_atlas = TextureHandle::new(state.clone(), TextureUnit::Atlas);
+        let post = PostProcessor::new(state.clone());
         let logical_size = window.logical_size();
+        let matrix_bounds = matrix_from_bounds(&logical_size);
+        let matrix_safe_area = matrix_from_safe_area(&logical_size, &window.safe_area());
+        state.resize(&window.physical_size(), &matrix_bounds, &matrix_safe_area);
 
         Renderer {
             window,
-            state: state,
+            state,
             texture_atlas,
-            matrix_bounds: matrix_from_bounds(&logical_size),
+            post,
+            matrix_bounds,
+            matrix_safe_area,
+            safe_area_override: None,
             logical_size,
-            atlas: TextureAtlas::new(),
+            atlas: TextureAtlas::new(desc.atlas_size, desc.atlas_max_size),
             text_cache: TextCache::new(),
+            texture_names: HashMap::new(),
+            screenshot: None,
+            video: None,
+            replay: None,
+            present_wait: Duration::ZERO,
+            draw_counts: (0, 0),
+            overlay: None,
+            cursor: None,
+            cursor_textures: Vec::new(),
+            cursor_pos: None,
         }
     }
 
@@ -62,11 +141,109 @@ impl Renderer {
     // ////////////////////////////////////////////////////////
 
     pub fn layer_create(&mut self) -> Layer {
-        let (a, b) = Layer::new(self.state.clone(), &self.matrix_bounds);
+        let (a, b) = Layer::new(self.state.clone(), &self.matrix_bounds, &self.matrix_safe_area);
+        self.state.layer_add(a);
+        b
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Light
+    // ////////////////////////////////////////////////////////
+
+    pub fn light_map_create(&mut self) -> LightMap {
+        let (a, b) = LightMap::new(self.state.clone(), &self.matrix_bounds, &self.matrix_safe_area);
+        self.state.layer_add(a);
+        b
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Mesh
+    // ////////////////////////////////////////////////////////
+
+    pub fn mesh_layer_create(&mut self) -> MeshLayer {
+        let (a, b) = MeshLayer::new(self.state.clone(), &self.matrix_bounds, &self.matrix_safe_area);
+        self.state.layer_add(a);
+        b
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Primitive
+    // ////////////////////////////////////////////////////////
+
+    pub fn primitive_layer_create(&mut self) -> PrimitiveLayer {
+        let (a, b) = PrimitiveLayer::new(self.state.clone(), &self.matrix_bounds, &self.matrix_safe_area);
+        self.state.layer_add(a);
+        b
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Shape
+    // ////////////////////////////////////////////////////////
+
+    pub fn shape_layer_create(&mut self) -> ShapeLayer {
+        let (a, b) = ShapeLayer::new(self.state.clone(), &self.matrix_bounds, &self.matrix_safe_area);
         self.state.layer_add(a);
         b
     }
 
+    // ////////////////////////////////////////////////////////
+    // Render Target
+    // ////////////////////////////////////////////////////////
+
+    pub fn render_target_create(&mut self, width: u32, height: u32) -> RenderTarget {
+        RenderTarget::new(self.state.clone(), width, height)
+    }
+
+    pub fn render_target_bind(&mut self, target: Option<&mut RenderTarget>) {
+        match target {
+            Some(target) => target.bind(),
+            None => self.state.target_unbind(),
+        }
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Post Processing
+    // ////////////////////////////////////////////////////////
+
+    pub fn post_pass_add(&mut self, fragment_shader: &str) -> PostPassToken {
+        PostPassToken::new(self.post.pass_add(fragment_shader))
+    }
+
+    pub fn post_pass_uniform(&mut self, token: &PostPassToken, name: &str, value: UniformValue) {
+        self.post.pass_uniform(token.key(), name, value);
+    }
+
+    pub fn post_pass_enabled(&mut self, token: &PostPassToken, enabled: bool) {
+        self.post.pass_enabled(token.key(), enabled);
+    }
+
+    // ////////////////////////////////////////////////////////
+    // Quality
+    // ////////////////////////////////////////////////////////
+
+    pub fn quality(&mut self, settings: &QualitySettings) {
+        // WebGL has no switch for multisampling, it's set when the context is created.
+        if cfg!(not(target_arch = "wasm32")) {
+            if settings.multisampling {
+                self.state.gl.enable(Capability::Multisample);
+            } else {
+                self.state.gl.disable(Capability::Multisample);
+            }
+        }
+        self.post.bloom_enabled(settings.bloom);
+        self.state.light_resolution_set(settings.light_resolution);
+        self.state.filter_limit_set(settings.texture_filter);
+    }
+
+    pub fn pixel_art(&mut self, enabled: bool) {
+        // Mipmaps aren't kept up to date in pixel art mode.
+        let stale = !self.state.mipmaps();
+        self.state.pixel_art_set(enabled);
+        if stale && self.state.mipmaps() {
+            self.texture_atlas.generate_mipmaps();
+        }
+    }
+
     // ////////////////////////////////////////////////////////
     // String
     // ////////////////////////////////////////////////////////
@@ -75,14 +252,63 @@ impl Renderer {
         FontToken::new(self.text_cache.add_font_bytes(bytes))
     }
 
-    pub fn text_append(&mut self, descs: &Vec<Text>, output: &mut Vec<Sprite>) {
+    pub fn font_create_path(&mut self, path: &std::path::Path) -> Option<FontToken> {
+        match std::fs::read(path) {
+            Ok(bytes) => Some(self.font_create(&bytes)),
+            Err(error) => {
+                warn!("Unable to read font {}: {}", path.display(), error);
+                None
+            }
+        }
+    }
+
+    pub fn font_create_bitmap(&mut self, fnt: &[u8], pages: &[&[u8]], format: TextureFormat) -> FontToken {
+        let pages: Vec<Image> = pages.iter().map(|bytes| Image::from_raw(bytes, format)).collect();
+        let key = self.text_cache.add_bitmap_font(&mut self.atlas, fnt, &pages);
+        self.texture_sync();
+        FontToken::new_bitmap(key)
+    }
+
+    pub fn font_system_families(&self) -> Vec<String> {
+        let mut families: Vec<String> = system_fonts().into_iter().map(|font| font.family).collect();
+        families.sort_unstable();
+        families.dedup();
+        families
+    }
+
+    pub fn font_create_system(&mut self, family: &str) -> Option<FontToken> {
+        let fonts: Vec<SystemFont> =
+            system_fonts().into_iter().filter(|font| font.family.eq_ignore_ascii_case(family)).collect();
+        let font =
+            fonts.iter().find(|font| font.style.eq_ignore_ascii_case("Regular")).or_else(|| fonts.first())?;
+        let path = font.path.clone();
+        self.font_create_path(&path)
+    }
+
+    pub fn text_append(&mut self, descs: &[Text], output: &mut Vec<Sprite>) {
         for desc in descs {
             self.text_cache.rasterize(&mut self.atlas, desc, output);
         }
         self.texture_sync();
     }
 
-    pub fn text_clear(&mut self, descs: &Vec<Text>, output: &mut Vec<Sprite>) {
+    pub fn text_hit_test(&mut self, desc: &Text, pos: Vector2<f32>) -> Option<u32> {
+        self.text_cache.hit_test(desc, pos)
+    }
+
+    pub fn text_glyphs(&mut self, desc: &Text) -> Vec<TextGlyph> {
+        self.text_cache.glyphs(desc)
+    }
+
+    pub fn text_index_at(&mut self, desc: &Text, pos: Vector2<f32>) -> usize {
+        self.text_cache.index_at(desc, pos)
+    }
+
+    pub fn text_measure(&mut self, desc: &Text) -> TextMetrics {
+        self.text_cache.measure(desc)
+    }
+
+    pub fn text_clear(&mut self, descs: &[Text], output: &mut Vec<Sprite>) {
         unsafe { output.set_len(0) };
         for desc in descs {
             self.text_cache.rasterize(&mut self.atlas, desc, output);
@@ -101,9 +327,160 @@ impl Renderer {
         Texture(uv)
     }
 
+    pub fn texture_try_create(
+        &mut self,
+        bytes: &[u8],
+        format: TextureFormat,
+    ) -> Result<Texture, &'static str> {
+        let image = Image::from_raw(bytes, format);
+        let uv = self.atlas.try_add(image)?;
+        self.texture_sync();
+        Ok(Texture(uv))
+    }
+
+    pub fn texture_create_batch(&mut self, files: &[(&[u8], TextureFormat)]) -> Vec<Texture> {
+        // Anything already pending goes up first, so each region below can be uploaded alone.
+        self.texture_sync();
+        let mut textures = vec![Texture::default(); files.len()];
+        let atlas = &mut self.atlas;
+        let texture_atlas = &mut self.texture_atlas;
+        decode_batch(files, |index, image| {
+            let (uv, x, y) = match atlas.add_uploaded(&image) {
+                Ok(added) => added,
+                Err(error) => panic!("{}", error),
+            };
+            // Once the atlas grows, it's uploaded whole below.
+            if !atlas.is_dirty() {
+                texture_atlas.set_region(x, y, &image);
+            }
+            textures[index] = Texture(uv);
+        });
+        if self.atlas.is_dirty() {
+            self.texture_sync();
+        } else if !files.is_empty() && self.state.mipmaps() {
+            self.texture_atlas.generate_mipmaps();
+        }
+        textures
+    }
+
+    pub fn texture_create_raw(&mut self, width: u32, height: u32, pixels: &[RGBA8]) -> Texture {
+        if width == 0 || height == 0 {
+            panic!("Texture dimensions must be non-zero, got {}x{}.", width, height);
+        }
+        let len = (width as usize).checked_mul(height as usize).expect("Texture is too large.");
+        if pixels.len() != len {
+            panic!("Expected {} pixels for a {}x{} texture, got {}.", len, width, height, pixels.len());
+        }
+        let image = Image::from_vec(pixels.to_vec(), width, height);
+        let uv = self.atlas.add(image);
+        self.texture_sync();
+        Texture(uv)
+    }
+
+    pub fn texture_create_svg(&mut self, bytes: &[u8], scale: f32) -> Texture {
+        let image = formats::svg::read(bytes, scale);
+        let uv = self.atlas.add(image);
+        self.texture_sync();
+        Texture(uv)
+    }
+
+    pub fn texture_create_aseprite(&mut self, bytes: &[u8]) -> SpriteSheet {
+        let file = formats::aseprite::read(bytes);
+        let frames: Vec<AnimationFrame> = file
+            .frames
+            .into_iter()
+            .map(|frame| {
+                AnimationFrame::new(Texture(self.atlas.add(frame.image)), frame.duration as f32 / 1000.0)
+            })
+            .collect();
+        self.texture_sync();
+        let clips = file
+            .tags
+            .into_iter()
+            .filter(|tag| tag.from <= tag.to && tag.to < frames.len())
+            .map(|tag| {
+                let mut clip = frames[tag.from..=tag.to].to_vec();
+                if tag.direction == 1 || tag.direction == 3 {
+                    clip.reverse();
+                }
+                AnimationClip {
+                    name: tag.name,
+                    animation: Animation::new(clip),
+                    mode: if tag.direction >= 2 {
+                        AnimationMode::PingPong
+                    } else {
+                        AnimationMode::Loop
+                    },
+                }
+            })
+            .collect();
+        SpriteSheet {
+            animation: Animation::new(frames),
+            clips,
+        }
+    }
+
+    pub fn texture_update(
+        &mut self,
+        texture: Texture,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        pixels: &[RGBA8],
+    ) {
+        let len = (width as usize).checked_mul(height as usize).expect("Texture update is too large.");
+        if pixels.len() != len {
+            panic!("Expected {} pixels for a {}x{} update, got {}.", len, width, height, pixels.len());
+        }
+        // Pending additions are uploaded first so only the region needs to be sent after.
+        self.texture_sync();
+        let image = Image::from_vec(pixels.to_vec(), width, height);
+        let (x, y) = self.atlas.update(texture.0, x, y, &image);
+        self.texture_atlas.set_region(x, y, &image);
+        if self.state.mipmaps() {
+            self.texture_atlas.generate_mipmaps();
+        }
+    }
+
+    pub fn texture_register(&mut self, name: &str, texture: Texture) {
+        self.texture_names.insert(String::from(name), texture);
+    }
+
+    pub fn texture_unregister(&mut self, name: &str) {
+        self.texture_names.remove(name);
+    }
+
+    pub fn texture_get(&self, name: &str) -> Option<Texture> {
+        self.texture_names.get(name).copied()
+    }
+
+    pub fn texture_register_sheet(&mut self, prefix: &str, sheet: &SpriteSheet) {
+        for (index, frame) in sheet.animation.frames.iter().enumerate() {
+            self.texture_names.insert(format!("{}/{}", prefix, index), frame.texture);
+        }
+        for clip in &sheet.clips {
+            for (index, frame) in clip.animation.frames.iter().enumerate() {
+                self.texture_names.insert(format!("{}/{}_{}", prefix, clip.name, index), frame.texture);
+            }
+        }
+    }
+
     pub fn texture_sync(&mut self) {
         if let Some(atlas) = self.atlas.sync() {
             self.texture_atlas.set_texture(atlas);
+            self.state.atlas_scale_set((MAX_SIZE / atlas.width()) as f32);
+            if self.state.mipmaps() {
+                self.texture_atlas.generate_mipmaps();
+            }
+        }
+    }
+
+    pub fn texture_mipmaps(&mut self, mipmaps: bool) {
+        let stale = !self.state.mipmaps();
+        self.state.mipmaps_set(mipmaps);
+        if stale && self.state.mipmaps() {
+            self.texture_atlas.generate_mipmaps();
         }
     }
 
@@ -115,17 +492,278 @@ impl Renderer {
         let new_logical_size = self.window.logical_size();
         if self.logical_size != new_logical_size {
             self.logical_size = new_logical_size;
-            let new_physical_size = self.window.physical_size();
-            self.matrix_bounds = matrix_from_bounds(&new_logical_size);
+            trace!(
+                "Window resized: Physical({:?}) Logical({:?})",
+                self.window.physical_size(),
+                new_logical_size
+            );
+            self.window_update_bounds();
+        }
+    }
 
-            trace!("Window resized: Physical({:?}) Logical({:?})", new_physical_size, new_logical_size);
+    fn window_update_bounds(&mut self) {
+        let new_physical_size = self.window.physical_size();
+        match self.state.resolution() {
+            // The safe area is in window pixels, so it doesn't apply to a virtual resolution.
+            Some(resolution) => {
+                self.matrix_bounds = matrix_from_bounds(&resolution.size());
+                self.matrix_safe_area = self.matrix_bounds;
+            }
+            None => {
+                self.matrix_bounds = matrix_from_bounds(&self.logical_size);
+                self.matrix_safe_area = matrix_from_safe_area(&self.logical_size, &self.window_safe_area());
+            }
+        }
+        self.state.resize(&new_physical_size, &self.matrix_bounds, &self.matrix_safe_area);
+    }
 
-            self.state.resize(&new_physical_size, &self.matrix_bounds);
+    pub fn window_safe_area(&self) -> SafeArea {
+        match self.safe_area_override {
+            Some(safe_area) => safe_area,
+            None => self.window.safe_area(),
         }
     }
 
-    pub fn window_swap_buffers(&self) {
+    pub fn window_safe_area_override(&mut self, safe_area: Option<SafeArea>) {
+        self.safe_area_override = safe_area;
+        self.window_update_bounds();
+    }
+
+    pub fn window_virtual_resolution(&mut self, resolution: Option<VirtualResolution>) {
+        self.state.resolution_set(resolution);
+        self.window_update_bounds();
+    }
+
+    /// Converts a position in window pixels, measured from the center of the window, into the
+    /// virtual resolution's pixels. Positions are unchanged without a virtual resolution.
+    pub fn window_to_virtual(&self, pos: Vector2<f32>) -> Vector2<f32> {
+        match self.state.resolution() {
+            Some(resolution) => resolution.to_virtual(self.state.viewport(), pos),
+            None => pos,
+        }
+    }
+
+    /// Converts a position in the virtual resolution's pixels into window pixels, measured from the
+    /// center of the window. The inverse of `window_to_virtual`.
+    pub fn window_from_virtual(&self, pos: Vector2<f32>) -> Vector2<f32> {
+        match self.state.resolution() {
+            Some(resolution) => resolution.to_window(self.state.viewport(), pos),
+            None => pos,
+        }
+    }
+
+    pub fn frame_begin(&mut self) {
+        self.state.frame_advance();
+        self.post.frame_begin();
+    }
+
+    pub fn frame_number(&self) -> u64 {
+        self.state.frame()
+    }
+
+    pub fn window_swap_buffers(&mut self) {
+        self.post.frame_end();
+        self.state.gpu_timer_frame_end();
+        self.draw_counts = self.state.gl.take_draw_counts();
+        self.state.readback_frame_end();
+        if let Some(path) = self.screenshot.take() {
+            self.window_save_screenshot(path);
+        }
+        let mut image = None;
+        if let Some(mut video) = self.video.take() {
+            let image = image.get_or_insert_with(|| self.window_read_pixels());
+            if video.write(image) {
+                self.video = Some(video);
+            } else {
+                video.finish();
+            }
+        }
+        if let Some(mut replay) = self.replay.take() {
+            if replay.due() {
+                replay.push(image.get_or_insert_with(|| self.window_read_pixels()));
+            }
+            self.replay = Some(replay);
+        }
+        // The overlay is drawn over everything, and isn't counted, timed, or captured.
+        if let Some(overlay) = &mut self.overlay {
+            self.state.gl.clear(ClearMode::DEPTH);
+            self.state.gpu_timer_pause(true);
+            overlay.draw();
+            self.state.gpu_timer_pause(false);
+            self.state.gl.take_draw_counts();
+        }
+        if let Some(cursor) = &mut self.cursor {
+            self.state.gl.clear(ClearMode::DEPTH);
+            self.state.gpu_timer_pause(true);
+            cursor.draw();
+            self.state.gpu_timer_pause(false);
+            self.state.gl.take_draw_counts();
+        }
+        let start = Instant::now();
         self.window.swap_buffers();
+        self.present_wait = start.elapsed();
+    }
+
+    pub fn draw_counts(&self) -> (u32, u32) {
+        self.draw_counts
+    }
+
+    pub fn atlas_stats(&self) -> (u32, f32) {
+        (self.atlas.size(), self.atlas.occupancy())
+    }
+
+    pub fn gpu_timing(&mut self, enabled: bool) -> bool {
+        self.state.gpu_timing_set(enabled)
+    }
+
+    pub fn gpu_timings(&self) -> Vec<GpuTiming> {
+        self.state.gpu_timings().to_vec()
+    }
+
+    pub fn stats_overlay(&mut self, enabled: bool) {
+        if !enabled {
+            self.overlay = None;
+        } else if self.overlay.is_none() {
+            let mut overlay = self.layer_create();
+            overlay.set_photo_hidden(true);
+            self.overlay = Some(overlay);
+        }
+    }
+
+    pub fn stats_overlay_enabled(&self) -> bool {
+        self.overlay.is_some()
+    }
+
+    /// Lays the stats out in the top left corner of the overlay.
+    pub fn stats_overlay_update(&mut self, stats: &EngineStats) {
+        let mut overlay = match self.overlay.take() {
+            Some(overlay) => overlay,
+            None => return,
+        };
+        let bounds = match self.state.resolution() {
+            Some(resolution) => resolution.size(),
+            None => self.logical_size,
+        };
+        let mut sprites = Vec::new();
+        for (index, line) in stats.lines().into_iter().enumerate() {
+            let pos = Vector3::new(
+                -bounds.x / 2.0 + OVERLAY_MARGIN,
+                bounds.y / 2.0 - OVERLAY_MARGIN - OVERLAY_SCALE as f32 * (index + 1) as f32,
+                0.0,
+            );
+            let text = Text {
+                string: line,
+                pos,
+                scale: OVERLAY_SCALE,
+                color: colors::WHITE,
+                background: Some(TextBackground::new(RGBA8::new_raw(0, 0, 0, 160), 2.0, 0.0)),
+                ..Text::default()
+            };
+            self.text_append(&[text], &mut sprites);
+        }
+        overlay.set_sprites(&sprites);
+        self.overlay = Some(overlay);
+    }
+
+    pub fn window_refresh_rate(&self) -> Option<f32> {
+        self.window.refresh_rate()
+    }
+
+    /// Browsers always pace frames to the display.
+    pub fn window_vsync_limited(&self) -> bool {
+        cfg!(target_arch = "wasm32") || self.present_wait >= VSYNC_WAIT
+    }
+
+    /// Saves the window's contents to a PNG file when the current frame is presented.
+    pub fn window_screenshot(&mut self, path: PathBuf) {
+        self.screenshot = Some(path);
+    }
+
+    /// Starts piping presented frames to a video encoder, or stops if the settings are None. Any
+    /// recording in progress is finished first.
+    pub fn window_record(&mut self, settings: Option<&VideoSettings>) {
+        if let Some(video) = self.video.take() {
+            video.finish();
+        }
+        if let Some(settings) = settings {
+            let viewport = self.state.viewport();
+            self.video = VideoRecorder::start(settings, viewport.x as u32, viewport.y as u32);
+        }
+    }
+
+    pub fn window_recording(&self) -> bool {
+        self.video.is_some()
+    }
+
+    /// Starts keeping recent presented frames, or stops and drops them if the settings are None.
+    /// Any frames already kept are dropped.
+    pub fn window_replay(&mut self, settings: Option<&ReplaySettings>) {
+        self.replay = settings.map(ReplayRecorder::new);
+    }
+
+    pub fn window_replay_save(&mut self, path: PathBuf, format: ReplayFormat) {
+        match &self.replay {
+            Some(replay) => replay.save(path, format),
+            None => warn!("Replays aren't being kept, not saving {}.", path.display()),
+        }
+    }
+
+    /// The update delta to use while recording a video with fixed timing.
+    pub fn window_record_step(&self) -> Option<f32> {
+        self.video.as_ref().and_then(|video| video.frame_step())
+    }
+
+    /// Queues a read of an area of the window, in physical pixels from the top left, for when the
+    /// current frame finishes drawing.
+    pub fn window_readback(&mut self, pos: Vector2<u32>, size: Vector2<u32>) -> PixelReadback {
+        self.state.readback_request(pos, size)
+    }
+
+    /// Reads the window's contents, with the top row first.
+    pub fn window_read_pixels(&mut self) -> Image {
+        self.state.target_unbind();
+        let viewport = self.state.viewport();
+        let (width, height) = (viewport.x as u32, viewport.y as u32);
+        let mut bytes = vec![0u8; (width * height * 4) as usize];
+        self.state.gl.read_pixels(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            PixelFormat::RGBA,
+            PixelType::UnsignedByte,
+            &mut bytes,
+        );
+        let row = (width * 4) as usize;
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for line in bytes.chunks_exact(row).rev() {
+            pixels.extend(line.chunks_exact(4).map(|p| RGBA8::new_raw(p[0], p[1], p[2], p[3])));
+        }
+        Image::from_vec(pixels, width, height)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn window_save_screenshot(&mut self, path: PathBuf) {
+        let image = self.window_read_pixels();
+        if let Some(directory) = path.parent() {
+            if let Err(error) = std::fs::create_dir_all(directory) {
+                warn!("Unable to create screenshot directory {}: {}", directory.display(), error);
+                return;
+            }
+        }
+        match std::fs::write(&path, formats::png::write(&image)) {
+            Ok(()) => info!("Saved screenshot to {}", path.display()),
+            Err(error) => warn!("Unable to save screenshot to {}: {}", path.display(), error),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn window_save_screenshot(&mut self, path: PathBuf) {
+        warn!("Screenshots can't be saved to {} on the web.", path.display());
+    }
+
+    pub fn window_camera(&mut self, camera: Option<Matrix4<f32>>) {
+        self.state.camera_set(camera);
     }
 
     pub fn window_logical_size(&self) -> Vector2<f32> {
@@ -136,13 +774,113 @@ impl Renderer {
         self.window.set_title(title);
     }
 
+    pub fn window_cursor(&mut self, cursor: &WindowCursor) {
+        let image = match cursor {
+            WindowCursor::Shape(shape) => {
+                self.window.set_cursor(Some(*shape));
+                self.cursor = None;
+                return;
+            }
+            WindowCursor::Hidden => {
+                self.window.set_cursor(None);
+                self.cursor = None;
+                return;
+            }
+            WindowCursor::Image(image) => image,
+        };
+        let texture = match self.cursor_textures.iter().find(|(cached, _)| cached == image) {
+            Some((_, texture)) => *texture,
+            None => {
+                let pixels =
+                    image.rgba.chunks_exact(4).map(|p| RGBA8::new_raw(p[0], p[1], p[2], p[3])).collect();
+                let texture = Texture(self.atlas.add(Image::from_vec(pixels, image.width, image.height)));
+                self.texture_sync();
+                self.cursor_textures.push((image.clone(), texture));
+                texture
+            }
+        };
+        self.window.set_cursor(None);
+        let layer = match self.cursor.take() {
+            Some(cursor) => cursor.into_layer(),
+            None => {
+                // Screen space, so the photo mode camera doesn't move it.
+                let mut layer = self.layer_create();
+                layer.set_screen_space(Some(ScreenOrigin::Center));
+                layer
+            }
+        };
+        let size = Vector2::new(image.width as f32, image.height as f32);
+        let hotspot = Vector2::new(image.hotspot.x as f32, image.hotspot.y as f32);
+        self.cursor = Some(SoftwareCursor::new(layer, texture, size, hotspot, self.cursor_pos));
+    }
+
+    /// Moves the custom cursor image, in virtual pixels. None hides it.
+    pub fn window_cursor_moved(&mut self, pos: Option<Vector2<f32>>) {
+        self.cursor_pos = pos;
+        if let Some(cursor) = &mut self.cursor {
+            cursor.set_pos(pos);
+        }
+    }
+
+    /// Returns false if the platform couldn't change the grab.
+    pub fn window_cursor_grab(&mut self, grab: bool) -> bool {
+        self.window.set_cursor_grab(grab)
+    }
+
+    /// Moves the IME candidate window to a position in the same units as cursor positions.
+    pub fn window_ime_position(&mut self, pos: Vector2<f32>) {
+        let viewport = self.state.viewport();
+        let window = self.window_from_virtual(pos);
+        let corner = winit::dpi::PhysicalPosition::new(
+            (window.x + viewport.x / 2.0) as f64,
+            (viewport.y / 2.0 - window.y) as f64,
+        );
+        self.window.set_ime_position(corner);
+    }
+
+    pub fn window_icon(&mut self, icon: Option<&WindowIcon>) {
+        self.window.set_icon(icon);
+    }
+
     pub fn window_display_mode(&mut self, display_mode: DisplayMode) {
         self.window.set_display_mode(display_mode);
     }
 
+    pub fn window_maximized(&mut self, maximized: bool) {
+        self.window.set_maximized(maximized);
+    }
+
+    pub fn window_placement(&self) -> Option<WindowPlacement> {
+        self.window.placement()
+    }
+
+    pub fn window_restore_placement(&mut self, placement: &WindowPlacement) {
+        self.window.set_placement(placement);
+    }
+
+    pub fn window_regions(&mut self, regions: &[WindowRegion]) {
+        self.window.set_regions(regions);
+    }
+
+    pub fn window_region_pressed(&mut self, pos: &Vector2<f32>) -> bool {
+        self.window.region_pressed(pos)
+    }
+
+    pub fn window_region_cursor_moved(&mut self, cursor: winit::dpi::PhysicalPosition<f64>) {
+        self.window.region_cursor_moved(cursor);
+    }
+
+    pub fn window_region_released(&mut self) {
+        self.window.region_released();
+    }
+
+    /// Takes the drag and drop steps the window reported outside of window events.
+    pub fn window_file_drops(&mut self) -> Vec<FileDrop> {
+        self.window.take_file_drops()
+    }
+
     pub fn clear_color(&mut self, clear_color: RGBA8) {
-        let color: Vector4<f32> = clear_color.into();
-        self.state.gl.clear_color(color.x, color.y, color.z, color.w);
+        self.state.clear_color(clear_color);
     }
 
     pub fn clear(&mut self, clear_mode: ClearMode) {
@@ -0,0 +1,229 @@
+use crate::render::raw::{resource, Capability, ClearMode, DrawMode, FramebufferBindingTarget, TextureUnit};
+use crate::render::shader;
+use crate::render::target::RenderTarget;
+use crate::render::OpenGLState;
+use crate::types::{GpuPass, ScalePolicy, UniformValue};
+use crate::utility::bad::UnsafeShared;
+use cgmath::*;
+use hashbrown::HashMap;
+
+struct PostPass {
+    program: resource::Program,
+    uniform_texture: Option<resource::UniformLocation>,
+    uniform_resolution: Option<resource::UniformLocation>,
+    uniforms: HashMap<String, (resource::UniformLocation, UniformValue)>,
+    enabled: bool,
+}
+
+impl PostPass {
+    fn new(state: &OpenGLState, fragment_shader: &str, enabled: bool) -> PostPass {
+        let gl = &state.gl;
+        let program = gl.shader_program(shader::post::VERTEX, fragment_shader);
+        PostPass {
+            program,
+            uniform_texture: gl.get_uniform_location(program, "tex"),
+            uniform_resolution: gl.get_uniform_location(program, "resolution"),
+            uniforms: HashMap::new(),
+            enabled,
+        }
+    }
+}
+
+/// Runs the frame through an ordered list of fullscreen passes before it's presented. While any
+/// pass is enabled, the frame is drawn into an offscreen target that stands in for the window.
+/// The built-in bloom pass runs ahead of the added passes. While a virtual resolution is set, the
+/// frame is always drawn offscreen at that resolution and scaled into the window by the last pass.
+pub struct PostProcessor {
+    state: UnsafeShared<OpenGLState>,
+    vao: resource::VertexArray,
+    bloom: PostPass,
+    copy: PostPass,
+    passes: Vec<PostPass>,
+    targets: Vec<RenderTarget>,
+    active: bool,
+}
+
+impl PostProcessor {
+    pub fn new(state: UnsafeShared<OpenGLState>) -> PostProcessor {
+        let vao = state.gl.create_vertex_array();
+        let bloom = PostPass::new(&state, shader::post::BLOOM, false);
+        let copy = PostPass::new(&state, shader::post::COPY, true);
+        PostProcessor {
+            state,
+            vao,
+            bloom,
+            copy,
+            passes: Vec::new(),
+            targets: Vec::new(),
+            active: false,
+        }
+    }
+
+    pub fn pass_add(&mut self, fragment_shader: &str) -> usize {
+        let pass = PostPass::new(&self.state, fragment_shader, true);
+        self.passes.push(pass);
+        self.passes.len() - 1
+    }
+
+    pub fn pass_uniform(&mut self, index: usize, name: &str, value: UniformValue) {
+        let pass = &mut self.passes[index];
+        if let Some((_, current)) = pass.uniforms.get_mut(name) {
+            *current = value;
+        } else if let Some(location) = self.state.gl.get_uniform_location(pass.program, name) {
+            pass.uniforms.insert(String::from(name), (location, value));
+        } else {
+            warn!("Post processing pass has no active uniform named {}.", name);
+        }
+    }
+
+    pub fn pass_enabled(&mut self, index: usize, enabled: bool) {
+        self.passes[index].enabled = enabled;
+    }
+
+    pub fn bloom_enabled(&mut self, enabled: bool) {
+        self.bloom.enabled = enabled;
+    }
+
+    /// The enabled passes, in the order they run.
+    fn enabled(&self) -> impl Iterator<Item = &PostPass> {
+        core::iter::once(&self.bloom).chain(self.passes.iter()).filter(|pass| pass.enabled)
+    }
+
+    /// Redirects the window's drawing into the offscreen targets if any pass is enabled or a
+    /// virtual resolution is set.
+    pub fn frame_begin(&mut self) {
+        let active = self.enabled().next().is_some() || self.state.resolution().is_some();
+        self.active = active;
+        if !self.active {
+            self.targets.clear();
+            return;
+        }
+        let size = self.state.screen_size();
+        if self.targets.first().map(|target| target.size()) != Some(size) {
+            self.targets.clear();
+            for _ in 0..2 {
+                self.targets.push(RenderTarget::new(self.state.clone(), size.x as u32, size.y as u32));
+            }
+        }
+        self.state.screen_set(Some(self.targets[0].framebuffer()));
+    }
+
+    /// Runs the enabled passes in order, with the last pass drawing to the window. If no pass is
+    /// enabled, the frame is copied to the window as is.
+    pub fn frame_end(&mut self) {
+        if !self.active {
+            return;
+        }
+        self.active = false;
+        self.state.screen_set(None);
+        self.state.target_unbind();
+
+        let gl = &self.state.gl;
+        let size = self.state.screen_size();
+        let window = self.state.viewport();
+        let (offset, area) = match self.state.resolution() {
+            Some(resolution) => resolution.viewport(window),
+            None => (Vector2::zero(), window),
+        };
+        // Pixel art stays sharp when scaled up, otherwise the frame is smoothed.
+        let sampler = match self.state.resolution() {
+            Some(resolution) if resolution.policy != ScalePolicy::Integer && !self.state.pixel_art() => {
+                Some(self.state.linear_sampler())
+            }
+            _ => None,
+        };
+        gl.disable(Capability::DepthTest);
+        gl.disable(Capability::Blend);
+        gl.bind_vertex_array(Some(self.vao));
+
+        let mut passes: Vec<&PostPass> = self.enabled().collect();
+        if passes.is_empty() {
+            passes.push(&self.copy);
+        }
+        // A second handle to the state, since the GL borrow is held across the passes.
+        let mut timers = self.state.clone();
+        for (index, pass) in passes.iter().enumerate() {
+            timers.gpu_timer_begin(GpuPass::Post(index));
+            let (source, destination) = pass_targets(index, passes.len());
+            if let Some(destination) = destination {
+                gl.bind_framebuffer(
+                    FramebufferBindingTarget::Framebuffer,
+                    Some(self.targets[destination].framebuffer()),
+                );
+                gl.viewport(0, 0, size.x as i32, size.y as i32);
+            } else {
+                gl.bind_framebuffer(FramebufferBindingTarget::Framebuffer, None);
+                if area != window {
+                    gl.viewport(0, 0, window.x as i32, window.y as i32);
+                    gl.clear(ClearMode::COLOR);
+                }
+                gl.viewport(offset.x as i32, offset.y as i32, area.x as i32, area.y as i32);
+                gl.bind_sampler(TextureUnit::Target, sampler);
+            }
+            gl.use_program(Some(pass.program));
+            self.targets[source].texture_handle().bind();
+            gl.uniform_1i(pass.uniform_texture.as_ref(), Self::texture_unit());
+            gl.uniform_2f(pass.uniform_resolution.as_ref(), size.x, size.y);
+            for (location, value) in pass.uniforms.values() {
+                match *value {
+                    UniformValue::Float(x) => gl.uniform_1f(Some(location), x),
+                    UniformValue::Vec2(v) => gl.uniform_2f(Some(location), v.x, v.y),
+                    UniformValue::Vec3(v) => gl.uniform_3f(Some(location), v.x, v.y, v.z),
+                    UniformValue::Vec4(v) => gl.uniform_4f(Some(location), v.x, v.y, v.z, v.w),
+                    UniformValue::Int(x) => gl.uniform_1i(Some(location), x),
+                }
+            }
+            gl.draw_arrays(DrawMode::Triangles, 0, 3);
+            timers.gpu_timer_end();
+        }
+
+        gl.bind_sampler(TextureUnit::Target, None);
+        gl.viewport(0, 0, window.x as i32, window.y as i32);
+        gl.enable(Capability::DepthTest);
+        gl.enable(Capability::Blend);
+        self.state.shader_bind();
+    }
+
+    fn texture_unit() -> i32 {
+        (TextureUnit::Target as u32 - TextureUnit::Atlas as u32) as i32
+    }
+}
+
+impl Drop for PostProcessor {
+    fn drop(&mut self) {
+        self.state.gl.delete_program(self.bloom.program);
+        self.state.gl.delete_program(self.copy.program);
+        for pass in &self.passes {
+            self.state.gl.delete_program(pass.program);
+        }
+        self.state.gl.delete_vertex_array(self.vao);
+    }
+}
+
+/// The target a pass reads from, and the target it draws to. Passes alternate between the two
+/// targets, starting from the one the frame was drawn to, and the last pass draws to the window,
+/// which is None.
+fn pass_targets(index: usize, count: usize) -> (usize, Option<usize>) {
+    let source = index % 2;
+    if index + 1 == count {
+        (source, None)
+    } else {
+        (source, Some(1 - source))
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets() {
+        assert_eq!(pass_targets(0, 1), (0, None));
+        let chain: Vec<_> = (0..3).map(|index| pass_targets(index, 3)).collect();
+        assert_eq!(chain, vec![(0, Some(1)), (1, Some(0)), (0, None)]);
+    }
+}
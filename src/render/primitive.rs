@@ -0,0 +1,387 @@
+use crate::render::buffer::Buffer;
+use crate::render::layer::SharedLayer;
+use crate::render::raw::{resource, BufferBindingTarget, Capability};
+use crate::render::shader;
+use crate::render::OpenGLState;
+use crate::types::{LayerTransform, LineJoin, Primitive, RGBA8};
+use crate::utility::bad::UnsafeShared;
+use cgmath::*;
+
+/// Miters longer than this many times the line's thickness are beveled instead.
+const MITER_LIMIT: f32 = 2.0;
+/// The most an arc in a round join turns between vertices, in radians.
+const ROUND_STEP: f32 = core::f32::consts::PI / 8.0;
+/// How far quads for rounded boxes reach past the box, leaving room for the smoothed edge.
+const EDGE_PADDING: f32 = 1.0;
+
+/// A corner of a triangle drawn by a primitive layer.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct PrimitiveVertex {
+    pub pos: Vector2<f32>,
+    pub color: RGBA8,
+    /// Position relative to the center of a rounded box.
+    pub local: Vector2<f32>,
+    /// Half size, corner radius, and outline thickness of a rounded box. Flat triangles leave this
+    /// zeroed.
+    pub shape: Vector4<f32>,
+}
+
+/// Primitive layers draw flat colored lines, polylines, convex polygons, circles, rings, and
+/// rounded rectangles, without any textures. Primitives are split into triangles when they're set,
+/// so they're cheap to draw every frame after that. Circles, rings, and rounded rectangles are each
+/// a quad shaded from a distance function, so their edges are smooth at any radius. Primitives share a coordinate space with layers, so a primitive layer with the same
+/// transform as a layer lines up with its sprites.
+pub struct PrimitiveLayer {
+    state: UnsafeShared<OpenGLState>,
+    shared: UnsafeShared<SharedLayer>,
+    program: resource::Program,
+    uniform_ortho: Option<resource::UniformLocation>,
+    vertices: Buffer<PrimitiveVertex>,
+    is_visible: bool,
+}
+
+impl PrimitiveLayer {
+    pub(crate) fn new(
+        state: UnsafeShared<OpenGLState>,
+        ortho: &Matrix4<f32>,
+        safe_ortho: &Matrix4<f32>,
+    ) -> (UnsafeShared<SharedLayer>, PrimitiveLayer) {
+        let shared = UnsafeShared::new(SharedLayer::new(ortho, safe_ortho));
+        let program = state.gl.shader_program(shader::primitive::VERTEX, shader::primitive::FRAGMENT);
+        let uniform_ortho = state.gl.get_uniform_location(program, "ortho");
+        let primitive_layer = PrimitiveLayer {
+            state: state.clone(),
+            shared: shared.clone(),
+            program,
+            uniform_ortho,
+            vertices: Buffer::new(state, BufferBindingTarget::ArrayBuffer),
+            is_visible: true,
+        };
+        (shared, primitive_layer)
+    }
+
+    /// Draws the primitives over everything drawn so far, in the order they were set.
+    pub fn draw(&mut self) {
+        if !self.is_visible || self.vertices.len() == 0 {
+            return;
+        }
+        let ortho_transform = self
+            .state
+            .pixel_snap(self.shared.ortho_transform(self.state.target_ortho(), self.state.camera()));
+        let gl = &self.state.gl;
+        // Triangles come out in either winding, so none can be culled.
+        gl.disable(Capability::DepthTest);
+        gl.disable(Capability::CullFace);
+        gl.use_program(Some(self.program));
+        gl.uniform_matrix_4fv(self.uniform_ortho.as_ref(), false, ortho_transform.as_ref());
+        self.vertices.draw_triangles();
+
+        gl.enable(Capability::CullFace);
+        gl.enable(Capability::DepthTest);
+        self.state.shader_bind();
+    }
+
+    /// Sets the primitives, drawn in order with later primitives over earlier ones. Translucent
+    /// lines are blended twice where their segments overlap at corners.
+    pub fn set_primitives(&mut self, primitives: &[Primitive]) {
+        let mut vertices = Vec::new();
+        for primitive in primitives {
+            tessellate(primitive, &mut vertices);
+        }
+        self.vertices.set(&vertices);
+    }
+
+    /// Clears all the primitives, drawing nothing.
+    pub fn clear_primitives(&mut self) {
+        self.vertices.clear();
+    }
+
+    /// Sets the transformation matrix used when drawing this.
+    pub fn set_transform(&mut self, transform: &LayerTransform) {
+        self.shared.set_transform_matrix(&transform.to_matrix());
+    }
+
+    /// Sets the transformation matrix used when drawing this.
+    pub fn set_transform_matrix(&mut self, transform: &Matrix4<f32>) {
+        self.shared.set_transform_matrix(transform);
+    }
+
+    /// If the primitive layer should be laid out within the window's safe area, like
+    /// `Layer::set_safe_area`. The default is false.
+    pub fn set_safe_area(&mut self, safe_area: bool) {
+        self.shared.set_safe_area(safe_area);
+    }
+
+    /// If the renderer should render this primitive layer or not when draw is called.
+    pub fn set_visible(&mut self, is_visible: bool) {
+        self.is_visible = is_visible;
+    }
+}
+
+impl Drop for PrimitiveLayer {
+    fn drop(&mut self) {
+        self.state.layer_drop(self.shared.index());
+        self.state.gl.delete_program(self.program);
+    }
+}
+
+/// Splits the primitive into triangles, appending three vertices per triangle.
+fn tessellate(primitive: &Primitive, output: &mut Vec<PrimitiveVertex>) {
+    match primitive {
+        Primitive::Line {
+            start,
+            end,
+            thickness,
+            color,
+        } => {
+            if let Some(normal) = normal(*start, *end, *thickness) {
+                quad(output, *start, *end, normal, *color);
+            }
+        }
+        Primitive::Polyline {
+            points,
+            thickness,
+            join,
+            closed,
+            color,
+        } => polyline(output, points, *thickness, *join, *closed, *color),
+        Primitive::Polygon {
+            points,
+            color,
+        } => {
+            // Convex polygons are a fan around their first point.
+            for pair in points.windows(2).skip(1) {
+                triangle(output, [points[0], pair[0], pair[1]], *color);
+            }
+        }
+        Primitive::Circle {
+            center,
+            radius,
+            color,
+        } => rounded_box(output, *center, Vector2::new(*radius, *radius), *radius, 0.0, *color),
+        Primitive::Ring {
+            center,
+            radius,
+            thickness,
+            color,
+        } => {
+            if *thickness > 0.0 {
+                rounded_box(output, *center, Vector2::new(*radius, *radius), *radius, *thickness, *color);
+            }
+        }
+        Primitive::RoundedRect {
+            pos,
+            size,
+            corner_radius,
+            thickness,
+            color,
+        } => {
+            let half = size / 2.0;
+            rounded_box(output, pos + half, half, *corner_radius, thickness.max(0.0), *color);
+        }
+    }
+}
+
+/// Appends a quad covering a rounded box, shaded by the fragment shader.
+fn rounded_box(
+    output: &mut Vec<PrimitiveVertex>,
+    center: Vector2<f32>,
+    half: Vector2<f32>,
+    radius: f32,
+    thickness: f32,
+    color: RGBA8,
+) {
+    if half.x.is_nan() || half.y.is_nan() || half.x <= 0.0 || half.y <= 0.0 {
+        return;
+    }
+    let radius = radius.clamp(0.0, half.x.min(half.y));
+    let shape = Vector4::new(half.x, half.y, radius, thickness);
+    let reach = half + Vector2::new(EDGE_PADDING, EDGE_PADDING);
+    let corners = [
+        Vector2::new(-reach.x, -reach.y),
+        Vector2::new(reach.x, -reach.y),
+        Vector2::new(reach.x, reach.y),
+        Vector2::new(-reach.x, -reach.y),
+        Vector2::new(reach.x, reach.y),
+        Vector2::new(-reach.x, reach.y),
+    ];
+    output.extend(corners.iter().map(|&local| PrimitiveVertex {
+        pos: center + local,
+        color,
+        local,
+        shape,
+    }));
+}
+
+fn polyline(
+    output: &mut Vec<PrimitiveVertex>,
+    points: &[Vector2<f32>],
+    thickness: f32,
+    join: LineJoin,
+    closed: bool,
+    color: RGBA8,
+) {
+    let mut points = points.to_vec();
+    points.dedup();
+    if closed && points.len() > 2 && points.first() == points.last() {
+        points.pop();
+    }
+    let mut segments: Vec<(Vector2<f32>, Vector2<f32>)> =
+        points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    if closed && points.len() > 2 {
+        segments.push((points[points.len() - 1], points[0]));
+    }
+    let normals: Vec<Vector2<f32>> =
+        segments.iter().filter_map(|&(start, end)| normal(start, end, thickness)).collect();
+    if normals.len() != segments.len() {
+        return;
+    }
+    for (&(start, end), &normal) in segments.iter().zip(&normals) {
+        quad(output, start, end, normal, color);
+    }
+    // Joins sit where one segment ends and the next starts.
+    let joins = if closed {
+        segments.len()
+    } else {
+        segments.len().saturating_sub(1)
+    };
+    for index in 0..joins {
+        let next = (index + 1) % segments.len();
+        let corner = segments[index].1;
+        let (before, after) = (normals[index], normals[next]);
+        let turn = (segments[index].1 - segments[index].0).perp_dot(segments[next].1 - segments[next].0);
+        if turn == 0.0 {
+            continue;
+        }
+        // The outside of the corner is on the right of a left turn, and the left of a right turn.
+        let (before, after) = if turn > 0.0 {
+            (-before, -after)
+        } else {
+            (before, after)
+        };
+        let (a, b) = (corner + before, corner + after);
+        match join {
+            LineJoin::Bevel => triangle(output, [corner, a, b], color),
+            LineJoin::Miter => {
+                let direction = (before + after).normalize();
+                let length = (thickness / 2.0) / direction.dot(before.normalize());
+                if length > thickness * MITER_LIMIT {
+                    triangle(output, [corner, a, b], color);
+                } else {
+                    let tip = corner + direction * length;
+                    triangle(output, [corner, a, tip], color);
+                    triangle(output, [corner, tip, b], color);
+                }
+            }
+            LineJoin::Round => {
+                let angle = before.angle(after).0;
+                let steps = (angle.abs() / ROUND_STEP).ceil().max(1.0) as usize;
+                let mut previous = a;
+                for step in 1..=steps {
+                    let rotation = Basis2::from_angle(Rad(angle * step as f32 / steps as f32));
+                    let next = corner + rotation.rotate_vector(before);
+                    triangle(output, [corner, previous, next], color);
+                    previous = next;
+                }
+            }
+        }
+    }
+}
+
+/// Half the thickness, pointing left of the direction from start to end. None if the points are
+/// the same or the thickness is empty.
+fn normal(start: Vector2<f32>, end: Vector2<f32>, thickness: f32) -> Option<Vector2<f32>> {
+    let direction = end - start;
+    if direction.magnitude2() == 0.0 || thickness.is_nan() || thickness <= 0.0 {
+        return None;
+    }
+    let direction = direction.normalize();
+    Some(Vector2::new(-direction.y, direction.x) * (thickness / 2.0))
+}
+
+fn quad(
+    output: &mut Vec<PrimitiveVertex>,
+    start: Vector2<f32>,
+    end: Vector2<f32>,
+    normal: Vector2<f32>,
+    color: RGBA8,
+) {
+    triangle(output, [start - normal, end - normal, end + normal], color);
+    triangle(output, [start - normal, end + normal, start + normal], color);
+}
+
+fn triangle(output: &mut Vec<PrimitiveVertex>, points: [Vector2<f32>; 3], color: RGBA8) {
+    output.extend(points.iter().map(|&pos| PrimitiveVertex {
+        pos,
+        color,
+        local: Vector2::zero(),
+        shape: Vector4::zero(),
+    }));
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::WHITE;
+
+    fn tessellated(primitive: Primitive) -> Vec<Vector2<f32>> {
+        let mut output = Vec::new();
+        tessellate(&primitive, &mut output);
+        output.iter().map(|vertex| vertex.pos).collect()
+    }
+
+    #[test]
+    fn line() {
+        let points =
+            tessellated(Primitive::line(Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0), 2.0, WHITE));
+        assert_eq!(points.len(), 6);
+        assert!(points.iter().all(|point| point.y.abs() == 1.0));
+        assert!(tessellated(Primitive::line(Vector2::new(1.0, 1.0), Vector2::new(1.0, 1.0), 2.0, WHITE))
+            .is_empty());
+    }
+
+    #[test]
+    fn polyline() {
+        let corner = [Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0), Vector2::new(10.0, 10.0)];
+        // Two segments and a mitered corner that reaches out to (11, -1).
+        let points = tessellated(Primitive::polyline(&corner, 2.0, WHITE));
+        assert_eq!(points.len(), 18);
+        assert!(points.iter().any(|point| (point - Vector2::new(11.0, -1.0)).magnitude() < 1e-4));
+        // Closing adds a third segment and joins at every point.
+        let closed = Primitive::Polyline {
+            points: corner.to_vec(),
+            thickness: 2.0,
+            join: LineJoin::Bevel,
+            closed: true,
+            color: WHITE,
+        };
+        assert_eq!(tessellated(closed).len(), 3 * 6 + 3 * 3);
+    }
+
+    #[test]
+    fn polygon() {
+        let square =
+            [Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0), Vector2::new(1.0, 1.0), Vector2::new(0.0, 1.0)];
+        assert_eq!(tessellated(Primitive::polygon(&square, WHITE)).len(), 6);
+        assert!(tessellated(Primitive::polygon(&square[..2], WHITE)).is_empty());
+    }
+
+    #[test]
+    fn rounded() {
+        let mut output = Vec::new();
+        tessellate(
+            &Primitive::rounded_rect(Vector2::new(10.0, 20.0), Vector2::new(4.0, 2.0), 5.0, WHITE),
+            &mut output,
+        );
+        assert_eq!(output.len(), 6);
+        // The radius is limited to half the shorter side, and the quad reaches past the edges.
+        assert_eq!(output[0].shape, Vector4::new(2.0, 1.0, 1.0, 0.0));
+        assert_eq!(output[0].pos, Vector2::new(9.0, 19.0));
+        assert!(tessellated(Primitive::circle(Vector2::new(0.0, 0.0), 0.0, WHITE)).is_empty());
+    }
+}
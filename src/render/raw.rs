@@ -1,5 +1,8 @@
+use core::cell::Cell;
 use glow::HasContext;
 
+// Named after the OpenGL targets, which all end in BUFFER.
+#[allow(clippy::enum_variant_names)]
 #[repr(u32)]
 #[derive(Copy, Clone)]
 pub enum BufferBindingTarget {
@@ -19,6 +22,29 @@ pub enum BufferBindingTarget {
     UniformBuffer = glow::UNIFORM_BUFFER,
 }
 
+#[repr(u32)]
+#[derive(Copy, Clone)]
+pub enum FramebufferBindingTarget {
+    Framebuffer = glow::FRAMEBUFFER,
+    DrawFramebuffer = glow::DRAW_FRAMEBUFFER,
+    ReadFramebuffer = glow::READ_FRAMEBUFFER,
+}
+
+#[repr(u32)]
+#[derive(Copy, Clone)]
+pub enum FramebufferAttachment {
+    Color0 = glow::COLOR_ATTACHMENT0,
+    Depth = glow::DEPTH_ATTACHMENT,
+    Stencil = glow::STENCIL_ATTACHMENT,
+    DepthStencil = glow::DEPTH_STENCIL_ATTACHMENT,
+}
+
+#[repr(u32)]
+#[derive(Copy, Clone)]
+pub enum RenderbufferBindingTarget {
+    Renderbuffer = glow::RENDERBUFFER,
+}
+
 #[repr(u32)]
 #[derive(Copy, Clone)]
 pub enum BufferUsage {
@@ -80,6 +106,7 @@ pub enum DepthTest {
 
 /// Describes how the screen will be cleared. Can be composed with other clear modes with the binary
 /// or operator. I.e. ClearMode::COLOR | ClearMode::DEPTH.
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct ClearMode(u32);
 
 impl ClearMode {
@@ -138,9 +165,10 @@ pub enum BlendFactor {
 }
 
 #[repr(u32)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub enum TextureUnit {
     Atlas = glow::TEXTURE0,
+    Target = glow::TEXTURE1,
 }
 
 #[repr(u32)]
@@ -192,6 +220,8 @@ pub enum TextureParameterTarget {
     TextureRectangle = glow::TEXTURE_RECTANGLE,
 }
 
+// Named after the OpenGL formats.
+#[allow(clippy::upper_case_acronyms)]
 #[repr(u32)]
 #[derive(Copy, Clone)]
 pub enum PixelFormat {
@@ -212,11 +242,16 @@ pub enum PixelFormat {
     DepthStencil = glow::DEPTH_STENCIL,
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[repr(u32)]
 #[derive(Copy, Clone)]
 pub enum PixelInternalFormat {
     DepthComponent = glow::DEPTH_COMPONENT,
+    DepthComponent16 = glow::DEPTH_COMPONENT16,
+    DepthComponent24 = glow::DEPTH_COMPONENT24,
+    DepthComponent32f = glow::DEPTH_COMPONENT32F,
     DepthStencil = glow::DEPTH_STENCIL,
+    Depth24Stencil8 = glow::DEPTH24_STENCIL8,
     Red = glow::RED,
     RG = glow::RG,
     RGB = glow::RGB,
@@ -376,6 +411,7 @@ pub enum AttributeType {
     UnsignedInt10f_11f_11f_Rev = glow::UNSIGNED_INT_10F_11F_11F_REV,
 }
 
+#[allow(clippy::enum_variant_names)]
 #[repr(u32)]
 #[derive(Copy, Clone)]
 pub enum IndiceType {
@@ -437,15 +473,25 @@ pub mod resource {
 
 pub struct OpenGL {
     gl: glow::Context,
+    /// Draw calls and instances drawn since the counts were last taken.
+    draws: Cell<u32>,
+    instances: Cell<u32>,
 }
 
 impl OpenGL {
     pub fn new(gl: glow::Context) -> OpenGL {
         OpenGL {
             gl,
+            draws: Cell::new(0),
+            instances: Cell::new(0),
         }
     }
 
+    /// Takes the number of draw calls and instances drawn since the last call.
+    pub fn take_draw_counts(&self) -> (u32, u32) {
+        (self.draws.take(), self.instances.take())
+    }
+
     pub fn get_string(&self, parameter: StringTarget) -> String {
         unsafe { self.gl.get_parameter_string(parameter as u32) }
     }
@@ -533,6 +579,26 @@ impl OpenGL {
         unsafe { self.gl.uniform_1_i32(location, x) };
     }
 
+    pub fn uniform_1f(&self, location: Option<&resource::UniformLocation>, x: f32) {
+        unsafe { self.gl.uniform_1_f32(location, x) };
+    }
+
+    pub fn uniform_2f(&self, location: Option<&resource::UniformLocation>, x: f32, y: f32) {
+        unsafe { self.gl.uniform_2_f32(location, x, y) };
+    }
+
+    pub fn uniform_3f(&self, location: Option<&resource::UniformLocation>, x: f32, y: f32, z: f32) {
+        unsafe { self.gl.uniform_3_f32(location, x, y, z) };
+    }
+
+    pub fn uniform_4f(&self, location: Option<&resource::UniformLocation>, x: f32, y: f32, z: f32, w: f32) {
+        unsafe { self.gl.uniform_4_f32(location, x, y, z, w) };
+    }
+
+    pub fn uniform_4fv(&self, location: Option<&resource::UniformLocation>, values: &[f32]) {
+        unsafe { self.gl.uniform_4_f32_slice(location, values) };
+    }
+
     pub fn create_vertex_array(&self) -> resource::VertexArray {
         unsafe { self.gl.create_vertex_array().unwrap() }
     }
@@ -553,6 +619,11 @@ impl OpenGL {
         unsafe { self.gl.vertex_attrib_divisor(index, divisor) };
     }
 
+    /// Sets the value used for an attribute while its array is disabled.
+    pub fn vertex_attrib_4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+        unsafe { self.gl.vertex_attrib_4_f32(index, x, y, z, w) };
+    }
+
     pub fn vertex_attrib_pointer_f32(
         &self,
         index: u32,
@@ -585,23 +656,36 @@ impl OpenGL {
 
     pub fn buffer_data<T: Sized>(&self, target: BufferBindingTarget, data: &[T], usage: BufferUsage) {
         unsafe {
-            let len = core::mem::size_of::<T>() * data.len();
+            let len = core::mem::size_of_val(data);
             let ptr = data.as_ptr() as *const u8;
             let slice = core::slice::from_raw_parts(ptr, len);
             self.gl.buffer_data_u8_slice(target as u32, slice, usage as u32);
         };
     }
 
-    pub fn buffer_sub_data<T: Sized>(&self, target: BufferBindingTarget, data: &[T]) {
+    /// Overwrites part of the bound buffer, starting at the offset in bytes.
+    pub fn buffer_sub_data<T: Sized>(&self, target: BufferBindingTarget, offset: usize, data: &[T]) {
         unsafe {
-            let len = core::mem::size_of::<T>() * data.len();
+            let len = core::mem::size_of_val(data);
             let ptr = data.as_ptr() as *const u8;
             let slice = core::slice::from_raw_parts(ptr, len);
-            self.gl.buffer_sub_data_u8_slice(target as u32, 0, slice);
+            self.gl.buffer_sub_data_u8_slice(target as u32, offset as i32, slice);
         };
     }
 
+    pub fn draw_arrays(&self, mode: DrawMode, first: i32, count: i32) {
+        self.draws.set(self.draws.get() + 1);
+        unsafe { self.gl.draw_arrays(mode as u32, first, count) };
+    }
+
+    pub fn draw_elements(&self, mode: DrawMode, count: i32, element_type: IndiceType, offset: i32) {
+        self.draws.set(self.draws.get() + 1);
+        unsafe { self.gl.draw_elements(mode as u32, count, element_type as u32, offset) };
+    }
+
     pub fn draw_arrays_instanced(&self, mode: DrawMode, first: i32, count: i32, instance_count: i32) {
+        self.draws.set(self.draws.get() + 1);
+        self.instances.set(self.instances.get() + instance_count as u32);
         unsafe { self.gl.draw_arrays_instanced(mode as u32, first, count, instance_count) };
     }
 
@@ -617,10 +701,95 @@ impl OpenGL {
         unsafe { self.gl.delete_texture(texture) };
     }
 
+    pub fn create_sampler(&self) -> resource::Sampler {
+        unsafe { self.gl.create_sampler().unwrap() }
+    }
+
+    pub fn delete_sampler(&self, sampler: resource::Sampler) {
+        unsafe { self.gl.delete_sampler(sampler) };
+    }
+
+    /// Binds the sampler to the texture unit, overriding the sampling settings of the texture bound
+    /// there. None restores the texture's own settings.
+    pub fn bind_sampler(&self, unit: TextureUnit, sampler: Option<resource::Sampler>) {
+        unsafe { self.gl.bind_sampler(unit as u32 - TextureUnit::Atlas as u32, sampler) };
+    }
+
+    pub fn sampler_parameter(&self, sampler: resource::Sampler, name: TextureParameterName, value: u32) {
+        unsafe { self.gl.sampler_parameter_i32(sampler, name as u32, value as i32) };
+    }
+
+    /// Sets the sampler's maximum anisotropy. This needs anisotropic filtering support, see
+    /// max_anisotropy.
+    pub fn sampler_anisotropy(&self, sampler: resource::Sampler, value: f32) {
+        unsafe { self.gl.sampler_parameter_f32(sampler, glow::TEXTURE_MAX_ANISOTROPY_EXT, value) };
+    }
+
+    /// The largest anisotropy supported for texture filtering. This is 1 when anisotropic
+    /// filtering isn't supported.
+    pub fn max_anisotropy(&self) -> f32 {
+        unsafe {
+            let value = self.gl.get_parameter_f32(glow::MAX_TEXTURE_MAX_ANISOTROPY_EXT);
+            if self.gl.get_error() != glow::NO_ERROR || value.is_nan() || value < 1.0 {
+                1.0
+            } else {
+                value
+            }
+        }
+    }
+
+    /// If timer queries are supported. WebGL needs the disjoint timer query extension for them.
+    pub fn timer_query_supported(&self) -> bool {
+        unsafe {
+            let query = match self.gl.create_query() {
+                Ok(query) => query,
+                Err(_) => return false,
+            };
+            self.gl.begin_query(glow::TIME_ELAPSED, query);
+            self.gl.end_query(glow::TIME_ELAPSED);
+            let supported = self.gl.get_error() == glow::NO_ERROR;
+            self.gl.delete_query(query);
+            supported
+        }
+    }
+
+    pub fn create_query(&self) -> resource::Query {
+        unsafe { self.gl.create_query().unwrap() }
+    }
+
+    pub fn delete_query(&self, query: resource::Query) {
+        unsafe { self.gl.delete_query(query) };
+    }
+
+    /// Starts measuring how long the GPU takes for the commands that follow. Only one timer query
+    /// can be active at a time.
+    pub fn begin_timer_query(&self, query: resource::Query) {
+        unsafe { self.gl.begin_query(glow::TIME_ELAPSED, query) };
+    }
+
+    pub fn end_timer_query(&self) {
+        unsafe { self.gl.end_query(glow::TIME_ELAPSED) };
+    }
+
+    /// If the query's result is ready to read without waiting on the GPU.
+    pub fn query_available(&self, query: resource::Query) -> bool {
+        unsafe { self.gl.get_query_parameter_u32(query, glow::QUERY_RESULT_AVAILABLE) != 0 }
+    }
+
+    /// The query's result, waiting on the GPU if it isn't ready. Timer queries are in nanoseconds.
+    pub fn query_result(&self, query: resource::Query) -> u32 {
+        unsafe { self.gl.get_query_parameter_u32(query, glow::QUERY_RESULT) }
+    }
+
+    pub fn generate_mipmap(&self, target: TextureBindingTarget) {
+        unsafe { self.gl.generate_mipmap(target as u32) };
+    }
+
     pub fn bind_texture(&self, target: TextureBindingTarget, texture: Option<resource::Texture>) {
         unsafe { self.gl.bind_texture(target as u32, texture) };
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn tex_image_2d<T: Sized>(
         &self,
         target: TextureLoadTarget,
@@ -634,7 +803,7 @@ impl OpenGL {
         pixels: &[T],
     ) {
         unsafe {
-            let len = core::mem::size_of::<T>() * pixels.len();
+            let len = core::mem::size_of_val(pixels);
             let ptr = pixels.as_ptr() as *const u8;
             let slice = core::slice::from_raw_parts(ptr, len);
             self.gl.tex_image_2d(
@@ -651,6 +820,64 @@ impl OpenGL {
         };
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn tex_sub_image_2d<T: Sized>(
+        &self,
+        target: TextureLoadTarget,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: PixelFormat,
+        ty: PixelType,
+        pixels: &[T],
+    ) {
+        unsafe {
+            let len = core::mem::size_of_val(pixels);
+            let ptr = pixels.as_ptr() as *const u8;
+            let slice = core::slice::from_raw_parts(ptr, len);
+            self.gl.tex_sub_image_2d(
+                target as u32,
+                level,
+                x_offset,
+                y_offset,
+                width,
+                height,
+                format as u32,
+                ty as u32,
+                glow::PixelUnpackData::Slice(slice),
+            )
+        };
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn tex_image_2d_empty(
+        &self,
+        target: TextureLoadTarget,
+        level: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        internal_format: PixelInternalFormat,
+        format: PixelFormat,
+        ty: PixelType,
+    ) {
+        unsafe {
+            self.gl.tex_image_2d(
+                target as u32,
+                level,
+                internal_format as u32 as i32,
+                width,
+                height,
+                border,
+                format as u32,
+                ty as u32,
+                None,
+            )
+        };
+    }
+
     pub fn tex_parameter_wrap_s(&self, target: TextureParameterTarget, value: TextureWrapValue) {
         unsafe {
             self.gl.tex_parameter_i32(target as u32, TextureParameterName::TextureWrapS as u32, value as i32)
@@ -689,10 +916,164 @@ impl OpenGL {
         };
     }
 
+    pub fn create_framebuffer(&self) -> resource::Framebuffer {
+        unsafe { self.gl.create_framebuffer().unwrap() }
+    }
+
+    pub fn bind_framebuffer(
+        &self,
+        target: FramebufferBindingTarget,
+        framebuffer: Option<resource::Framebuffer>,
+    ) {
+        unsafe { self.gl.bind_framebuffer(target as u32, framebuffer) };
+    }
+
+    pub fn delete_framebuffer(&self, framebuffer: resource::Framebuffer) {
+        unsafe { self.gl.delete_framebuffer(framebuffer) };
+    }
+
+    pub fn framebuffer_texture_2d(
+        &self,
+        target: FramebufferBindingTarget,
+        attachment: FramebufferAttachment,
+        texture_target: TextureLoadTarget,
+        texture: Option<resource::Texture>,
+        level: i32,
+    ) {
+        unsafe {
+            self.gl.framebuffer_texture_2d(
+                target as u32,
+                attachment as u32,
+                texture_target as u32,
+                texture,
+                level,
+            )
+        };
+    }
+
+    pub fn framebuffer_renderbuffer(
+        &self,
+        target: FramebufferBindingTarget,
+        attachment: FramebufferAttachment,
+        renderbuffer_target: RenderbufferBindingTarget,
+        renderbuffer: Option<resource::Renderbuffer>,
+    ) {
+        unsafe {
+            self.gl.framebuffer_renderbuffer(
+                target as u32,
+                attachment as u32,
+                renderbuffer_target as u32,
+                renderbuffer,
+            )
+        };
+    }
+
+    pub fn check_framebuffer_status(&self, target: FramebufferBindingTarget) -> bool {
+        unsafe { self.gl.check_framebuffer_status(target as u32) == glow::FRAMEBUFFER_COMPLETE }
+    }
+
+    pub fn create_renderbuffer(&self) -> resource::Renderbuffer {
+        unsafe { self.gl.create_renderbuffer().unwrap() }
+    }
+
+    pub fn bind_renderbuffer(
+        &self,
+        target: RenderbufferBindingTarget,
+        renderbuffer: Option<resource::Renderbuffer>,
+    ) {
+        unsafe { self.gl.bind_renderbuffer(target as u32, renderbuffer) };
+    }
+
+    pub fn delete_renderbuffer(&self, renderbuffer: resource::Renderbuffer) {
+        unsafe { self.gl.delete_renderbuffer(renderbuffer) };
+    }
+
+    pub fn renderbuffer_storage(
+        &self,
+        target: RenderbufferBindingTarget,
+        internal_format: PixelInternalFormat,
+        width: i32,
+        height: i32,
+    ) {
+        unsafe { self.gl.renderbuffer_storage(target as u32, internal_format as u32, width, height) };
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn read_pixels(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: PixelFormat,
+        ty: PixelType,
+        pixels: &mut [u8],
+    ) {
+        unsafe {
+            self.gl.read_pixels(
+                x,
+                y,
+                width,
+                height,
+                format as u32,
+                ty as u32,
+                glow::PixelPackData::Slice(pixels),
+            )
+        };
+    }
+
+    /// Reads pixels into the bound pixel pack buffer, starting at the offset in bytes. Doesn't wait
+    /// on the GPU.
+    #[allow(clippy::too_many_arguments)]
+    pub fn read_pixels_to_buffer(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: PixelFormat,
+        ty: PixelType,
+        offset: u32,
+    ) {
+        unsafe {
+            self.gl.read_pixels(
+                x,
+                y,
+                width,
+                height,
+                format as u32,
+                ty as u32,
+                glow::PixelPackData::BufferOffset(offset),
+            )
+        };
+    }
+
+    /// Copies part of the bound buffer into the slice, starting at the offset in bytes.
+    pub fn get_buffer_sub_data(&self, target: BufferBindingTarget, offset: usize, data: &mut [u8]) {
+        unsafe { self.gl.get_buffer_sub_data(target as u32, offset as i32, data) };
+    }
+
+    /// Creates a fence that's signaled once the GPU finishes every command issued before it.
+    pub fn fence_sync(&self) -> resource::Fence {
+        unsafe { self.gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0).unwrap() }
+    }
+
+    pub fn fence_signaled(&self, fence: resource::Fence) -> bool {
+        unsafe { self.gl.get_sync_status(fence) == glow::SIGNALED }
+    }
+
+    pub fn delete_sync(&self, fence: resource::Fence) {
+        unsafe { self.gl.delete_sync(fence) };
+    }
+
     pub fn enable(&self, capability: Capability) {
         unsafe { self.gl.enable(capability as u32) };
     }
 
+    pub fn disable(&self, capability: Capability) {
+        unsafe { self.gl.disable(capability as u32) };
+    }
+
     pub fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
         unsafe { self.gl.clear_color(red, green, blue, alpha) };
     }
@@ -701,10 +1082,26 @@ impl OpenGL {
         unsafe { self.gl.depth_func(test as u32) };
     }
 
+    pub fn depth_mask(&self, write: bool) {
+        unsafe { self.gl.depth_mask(write) };
+    }
+
     pub fn blend_func(&self, src: BlendFactor, dst: BlendFactor) {
         unsafe { self.gl.blend_func(src as u32, dst as u32) };
     }
 
+    pub fn blend_func_separate(
+        &self,
+        src_rgb: BlendFactor,
+        dst_rgb: BlendFactor,
+        src_alpha: BlendFactor,
+        dst_alpha: BlendFactor,
+    ) {
+        unsafe {
+            self.gl.blend_func_separate(src_rgb as u32, dst_rgb as u32, src_alpha as u32, dst_alpha as u32)
+        };
+    }
+
     pub fn cull_face(&self, face: CullFace) {
         unsafe { self.gl.cull_face(face as u32) };
     }
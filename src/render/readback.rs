@@ -0,0 +1,207 @@
+use crate::render::raw::{resource, BufferBindingTarget, BufferUsage, OpenGL, PixelFormat, PixelType};
+use crate::types::RGBA8;
+use crate::utility::bad::UnsafeShared;
+use cgmath::*;
+
+struct SharedReadback {
+    size: Vector2<u32>,
+    frame: u64,
+    ready: bool,
+    pixels: Option<Vec<RGBA8>>,
+}
+
+/// Pixels being read back from the window. The read is issued when the frame it was requested in
+/// finishes drawing, and the pixels are available a frame or two later, once the GPU has them.
+/// Poll the readback each frame until it returns the pixels. Dropping the readback cancels it.
+pub struct PixelReadback {
+    shared: UnsafeShared<SharedReadback>,
+}
+
+impl PixelReadback {
+    /// The pixels, with the top row first, once the GPU has them. Returns Some only once.
+    pub fn poll(&mut self) -> Option<Vec<RGBA8>> {
+        self.shared.pixels.take()
+    }
+
+    /// If the pixels are available or have already been taken.
+    pub fn is_ready(&self) -> bool {
+        self.shared.ready
+    }
+
+    /// The size of the area read, in physical pixels. The area is clipped to the window, so it
+    /// can be smaller than requested.
+    pub fn size(&self) -> Vector2<u32> {
+        self.shared.size
+    }
+
+    /// The number of the frame the pixels were read from. See `Engine::frame_number`.
+    pub fn frame(&self) -> u64 {
+        self.shared.frame
+    }
+}
+
+struct Pending {
+    buffer: resource::Buffer,
+    fence: resource::Fence,
+    shared: UnsafeShared<SharedReadback>,
+}
+
+/// Reads pixels into pixel pack buffers, and copies them out once a fence says the GPU has
+/// written them, so reading never stalls the frame.
+pub(crate) struct Readbacks {
+    free: Vec<resource::Buffer>,
+    /// Readbacks requested this frame, with the top left of their area.
+    queued: Vec<(Vector2<u32>, UnsafeShared<SharedReadback>)>,
+    pending: Vec<Pending>,
+}
+
+impl Readbacks {
+    pub fn new() -> Readbacks {
+        Readbacks {
+            free: Vec::new(),
+            queued: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Queues a read of an area of the window, in physical pixels from the top left.
+    pub fn request(&mut self, pos: Vector2<u32>, size: Vector2<u32>) -> PixelReadback {
+        let shared = UnsafeShared::new(SharedReadback {
+            size,
+            frame: 0,
+            ready: false,
+            pixels: None,
+        });
+        self.queued.push((pos, shared.clone()));
+        PixelReadback {
+            shared,
+        }
+    }
+
+    /// Copies out the pixels the GPU is done with, then issues this frame's reads. The window's
+    /// framebuffer must be bound.
+    pub fn frame_end(&mut self, gl: &OpenGL, viewport: Vector2<u32>, frame: u64) {
+        let mut index = 0;
+        while index < self.pending.len() {
+            if !gl.fence_signaled(self.pending[index].fence) {
+                index += 1;
+                continue;
+            }
+            let mut pending = self.pending.swap_remove(index);
+            gl.delete_sync(pending.fence);
+            // Skip the copy if the readback was dropped.
+            if pending.shared.count() > 1 {
+                let size = pending.shared.size;
+                let mut bytes = vec![0u8; (size.x * size.y * 4) as usize];
+                gl.bind_buffer(BufferBindingTarget::PixelPackBuffer, Some(pending.buffer));
+                gl.get_buffer_sub_data(BufferBindingTarget::PixelPackBuffer, 0, &mut bytes);
+                pending.shared.pixels = Some(top_down(&bytes, size.x));
+                pending.shared.ready = true;
+            }
+            self.free.push(pending.buffer);
+        }
+
+        for (pos, mut shared) in self.queued.drain(..) {
+            if shared.count() == 1 {
+                continue;
+            }
+            let (min, max) = clip(pos, shared.size, viewport);
+            shared.size = max - min;
+            shared.frame = frame;
+            if shared.size.x == 0 || shared.size.y == 0 {
+                shared.pixels = Some(Vec::new());
+                shared.ready = true;
+                continue;
+            }
+            let buffer = self.free.pop().unwrap_or_else(|| gl.create_buffer());
+            gl.bind_buffer(BufferBindingTarget::PixelPackBuffer, Some(buffer));
+            gl.buffer_data_empty(
+                BufferBindingTarget::PixelPackBuffer,
+                (shared.size.x * shared.size.y * 4) as i32,
+                BufferUsage::StreamRead,
+            );
+            // OpenGL measures from the bottom left.
+            gl.read_pixels_to_buffer(
+                min.x as i32,
+                (viewport.y - max.y) as i32,
+                shared.size.x as i32,
+                shared.size.y as i32,
+                PixelFormat::RGBA,
+                PixelType::UnsignedByte,
+                0,
+            );
+            self.pending.push(Pending {
+                buffer,
+                fence: gl.fence_sync(),
+                shared,
+            });
+        }
+        gl.bind_buffer(BufferBindingTarget::PixelPackBuffer, None);
+    }
+
+    pub fn delete(&mut self, gl: &OpenGL) {
+        self.queued.clear();
+        for pending in self.pending.drain(..) {
+            gl.delete_sync(pending.fence);
+            gl.delete_buffer(pending.buffer);
+        }
+        for buffer in self.free.drain(..) {
+            gl.delete_buffer(buffer);
+        }
+    }
+}
+
+/// Clips an area, from its top left and size, to the viewport. Returns the clipped area's top
+/// left and bottom right.
+fn clip(pos: Vector2<u32>, size: Vector2<u32>, viewport: Vector2<u32>) -> (Vector2<u32>, Vector2<u32>) {
+    let min = Vector2::new(pos.x.min(viewport.x), pos.y.min(viewport.y));
+    let max = Vector2::new(
+        pos.x.saturating_add(size.x).min(viewport.x),
+        pos.y.saturating_add(size.y).min(viewport.y),
+    );
+    (min, max)
+}
+
+/// Converts rows of RGBA bytes read from OpenGL, which start at the bottom, into pixels with the
+/// top row first.
+fn top_down(bytes: &[u8], width: u32) -> Vec<RGBA8> {
+    let mut pixels = Vec::with_capacity(bytes.len() / 4);
+    for line in bytes.chunks_exact((width * 4) as usize).rev() {
+        pixels.extend(line.chunks_exact(4).map(|p| RGBA8::new_raw(p[0], p[1], p[2], p[3])));
+    }
+    pixels
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clipping() {
+        let viewport = Vector2::new(100, 50);
+        let area = |x, y, w, h| clip(Vector2::new(x, y), Vector2::new(w, h), viewport);
+        assert_eq!(area(10, 10, 20, 5), (Vector2::new(10, 10), Vector2::new(30, 15)));
+        assert_eq!(area(90, 40, 20, 20), (Vector2::new(90, 40), Vector2::new(100, 50)));
+        // Areas outside the window are empty, even if they'd overflow.
+        let (min, max) = area(200, 0, u32::MAX, 10);
+        assert_eq!(max - min, Vector2::new(0, 10));
+    }
+
+    #[test]
+    fn rows() {
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+        assert_eq!(
+            top_down(&bytes, 2),
+            vec![
+                RGBA8::new_raw(9, 10, 11, 12),
+                RGBA8::new_raw(13, 14, 15, 16),
+                RGBA8::new_raw(1, 2, 3, 4),
+                RGBA8::new_raw(5, 6, 7, 8),
+            ]
+        );
+    }
+}
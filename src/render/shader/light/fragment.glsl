@@ -0,0 +1,59 @@
+#version 300 es
+precision highp float;
+
+const int MAX_LIGHTS = 32;
+const int MAX_OCCLUDERS = 128;
+
+in vec2 v_uv;
+out vec4 a_color;
+
+// Maps clip space back into the light map's coordinate space.
+uniform mat4 inverse;
+uniform vec4 ambient;
+uniform int light_count;
+uniform int occluder_count;
+uniform vec4 light_shape[MAX_LIGHTS]; // x y radius
+uniform vec4 light_color[MAX_LIGHTS];
+uniform vec4 light_cone[MAX_LIGHTS]; // direction.x direction.y cos(spread / 2)
+uniform vec4 occluders[MAX_OCCLUDERS]; // start.x start.y end.x end.y
+
+// True if the segment from p to q crosses the segment from a to b.
+bool crosses(vec2 p, vec2 q, vec2 a, vec2 b) {
+    vec2 r = q - p;
+    vec2 s = b - a;
+    float denom = r.x * s.y - r.y * s.x;
+    if (abs(denom) < 0.0001) {
+        return false;
+    }
+    vec2 d = a - p;
+    float t = (d.x * s.y - d.y * s.x) / denom;
+    float u = (d.x * r.y - d.y * r.x) / denom;
+    return t > 0.0 && t < 1.0 && u >= 0.0 && u <= 1.0;
+}
+
+void main() {
+    vec2 pos = (inverse * vec4(v_uv * 2.0 - 1.0, 0.0, 1.0)).xy;
+    vec3 total = ambient.rgb;
+    for (int i = 0; i < light_count; i++) {
+        vec2 to = pos - light_shape[i].xy;
+        float dist = length(to);
+        if (dist >= light_shape[i].z) {
+            continue;
+        }
+        if (dist > 0.0 && dot(to / dist, light_cone[i].xy) < light_cone[i].z) {
+            continue;
+        }
+        bool shadowed = false;
+        for (int j = 0; j < occluder_count; j++) {
+            if (crosses(light_shape[i].xy, pos, occluders[j].xy, occluders[j].zw)) {
+                shadowed = true;
+                break;
+            }
+        }
+        if (!shadowed) {
+            float falloff = 1.0 - dist / light_shape[i].z;
+            total += light_color[i].rgb * light_color[i].a * falloff * falloff;
+        }
+    }
+    a_color = vec4(min(total, vec3(1.0)), 1.0);
+}
@@ -0,0 +1 @@
+pub const FRAGMENT: &str = include_str!("fragment.glsl");
@@ -0,0 +1,20 @@
+#version 300 es
+precision highp float;
+
+layout(location = 0) in vec3 a_pos;
+layout(location = 1) in vec2 a_uv;
+layout(location = 2) in vec4 a_color;
+
+out vec2 v_uv;
+out vec4 v_color;
+
+uniform mat4 ortho;
+// Atlas coordinates are measured against the largest atlas, and this scales them to its current
+// size.
+uniform float atlas_scale;
+
+void main() {
+    v_uv = a_uv * atlas_scale;
+    v_color = a_color;
+    gl_Position = ortho * vec4(a_pos, 1.0);
+}
\ No newline at end of file
@@ -1 +1,6 @@
+pub mod light;
+pub mod mesh;
+pub mod post;
+pub mod primitive;
+pub mod shape;
 pub mod texture;
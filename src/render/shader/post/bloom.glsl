@@ -0,0 +1,41 @@
+#version 300 es
+precision highp float;
+
+const float PI = 3.14159265;
+// Brightness where pixels start to glow.
+const float THRESHOLD = 0.7;
+// Distance between the rings of samples, in pixels.
+const float SPACING = 4.0;
+const float STRENGTH = 0.6;
+
+in vec2 v_uv;
+out vec4 a_color;
+
+uniform sampler2D tex;
+uniform vec2 resolution;
+
+// The part of the color above the threshold.
+vec3 bright(vec2 uv) {
+    vec3 color = texture(tex, uv).rgb;
+    float peak = max(color.r, max(color.g, color.b));
+    return color * smoothstep(THRESHOLD, 1.0, peak);
+}
+
+// Blurs the bright parts with three rings of samples, weighted toward the center, and adds them
+// back over the frame.
+void main() {
+    vec4 base = texture(tex, v_uv);
+    vec2 texel = 1.0 / resolution;
+    vec3 glow = vec3(0.0);
+    float total = 0.0;
+    for (int ring = 1; ring <= 3; ring++) {
+        float weight = 1.0 / float(ring);
+        for (int i = 0; i < 8; i++) {
+            float angle = float(i) * PI * 0.25 + float(ring) * 0.4;
+            vec2 offset = vec2(cos(angle), sin(angle)) * float(ring) * SPACING * texel;
+            glow += bright(v_uv + offset) * weight;
+            total += weight;
+        }
+    }
+    a_color = vec4(base.rgb + glow / total * STRENGTH, base.a);
+}
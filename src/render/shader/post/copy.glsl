@@ -0,0 +1,11 @@
+#version 300 es
+precision highp float;
+
+in vec2 v_uv;
+out vec4 a_color;
+
+uniform sampler2D tex;
+
+void main() {
+    a_color = texture(tex, v_uv);
+}
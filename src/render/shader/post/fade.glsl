@@ -0,0 +1,13 @@
+#version 300 es
+precision highp float;
+
+in vec2 v_uv;
+out vec4 a_color;
+
+uniform sampler2D tex;
+uniform float decay;
+
+// The trail is stored premultiplied, so scaling every channel fades it toward transparent.
+void main() {
+    a_color = texture(tex, v_uv) * decay;
+}
@@ -0,0 +1,4 @@
+pub const VERTEX: &str = include_str!("vertex.glsl");
+pub const BLOOM: &str = include_str!("bloom.glsl");
+pub const COPY: &str = include_str!("copy.glsl");
+pub const FADE: &str = include_str!("fade.glsl");
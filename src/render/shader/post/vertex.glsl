@@ -0,0 +1,11 @@
+#version 300 es
+precision highp float;
+
+out vec2 v_uv;
+
+// A single triangle that covers the whole screen. The UVs are 0 to 1 across the visible part.
+void main() {
+    vec2 pos = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2));
+    v_uv = pos;
+    gl_Position = vec4(pos * 2.0 - 1.0, 0.0, 1.0);
+}
\ No newline at end of file
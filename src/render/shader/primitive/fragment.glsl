@@ -0,0 +1,28 @@
+#version 300 es
+precision highp float;
+
+in vec4 v_color;
+// Position relative to the center of a rounded box.
+in vec2 v_local;
+// Half size, corner radius, and outline thickness of a rounded box. Flat triangles have no size.
+flat in vec4 v_shape;
+out vec4 a_color;
+
+void main() {
+    float coverage = 1.0;
+    if (v_shape.x > 0.0) {
+        float radius = v_shape.z;
+        vec2 d = abs(v_local) - (v_shape.xy - radius);
+        float dist = length(max(d, 0.0)) + min(max(d.x, d.y), 0.0) - radius;
+        if (v_shape.w > 0.0) {
+            dist = abs(dist + v_shape.w * 0.5) - v_shape.w * 0.5;
+        }
+        // Half a screen pixel in the layer's coordinates, so edges are smooth at any scale.
+        float aa = max(length(fwidth(v_local)) * 0.5, 0.0001);
+        coverage = 1.0 - smoothstep(-aa, aa, dist);
+        if (coverage <= 0.0) {
+            discard;
+        }
+    }
+    a_color = vec4(v_color.rgb, v_color.a * coverage);
+}
\ No newline at end of file
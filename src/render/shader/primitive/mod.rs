@@ -0,0 +1,2 @@
+pub const VERTEX: &str = include_str!("vertex.glsl");
+pub const FRAGMENT: &str = include_str!("fragment.glsl");
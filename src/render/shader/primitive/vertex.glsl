@@ -0,0 +1,20 @@
+#version 300 es
+precision highp float;
+
+layout(location = 0) in vec2 a_pos;
+layout(location = 1) in vec4 a_color;
+layout(location = 2) in vec2 a_local;
+layout(location = 3) in vec4 a_shape;
+
+out vec4 v_color;
+out vec2 v_local;
+flat out vec4 v_shape;
+
+uniform mat4 ortho;
+
+void main() {
+    v_color = a_color;
+    v_local = a_local;
+    v_shape = a_shape;
+    gl_Position = ortho * vec4(a_pos, 0.0, 1.0);
+}
\ No newline at end of file
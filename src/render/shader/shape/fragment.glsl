@@ -0,0 +1,81 @@
+#version 300 es
+precision highp float;
+
+const int MAX_SHAPES = 16;
+const int MAX_PRIMITIVES = 64;
+const float CIRCLE = 0.0;
+const float BOX = 1.0;
+const float CAPSULE = 2.0;
+const float UNION = 0.0;
+const float SUBTRACT = 1.0;
+
+in vec2 v_uv;
+out vec4 a_color;
+
+// Maps clip space back into the shape layer's coordinate space.
+uniform mat4 inverse;
+uniform int shape_count;
+uniform vec4 shape_fill[MAX_SHAPES];
+uniform vec4 shape_border[MAX_SHAPES];
+uniform vec4 shape_range[MAX_SHAPES]; // first primitive, primitive count, border width
+uniform vec4 primitive_points[MAX_PRIMITIVES]; // center or start, size or end
+uniform vec4 primitive_params[MAX_PRIMITIVES]; // radius, rotation in radians, kind, operation
+
+float distance_to(int i, vec2 pos) {
+    vec4 points = primitive_points[i];
+    vec4 params = primitive_params[i];
+    if (params.z == CIRCLE) {
+        return length(pos - points.xy) - params.x;
+    } else if (params.z == BOX) {
+        vec2 local = pos - points.xy;
+        float c = cos(-params.y);
+        float s = sin(-params.y);
+        local = vec2(c * local.x - s * local.y, s * local.x + c * local.y);
+        float radius = min(params.x, min(points.z, points.w) * 0.5);
+        vec2 d = abs(local) - (points.zw * 0.5 - radius);
+        return length(max(d, 0.0)) + min(max(d.x, d.y), 0.0) - radius;
+    }
+    vec2 pa = pos - points.xy;
+    vec2 ba = points.zw - points.xy;
+    float h = clamp(dot(pa, ba) / max(dot(ba, ba), 0.0001), 0.0, 1.0);
+    return length(pa - ba * h) - params.x;
+}
+
+void main() {
+    vec2 pos = (inverse * vec4(v_uv * 2.0 - 1.0, 0.0, 1.0)).xy;
+    // Half a screen pixel in the layer's coordinates, so edges are smooth at any scale.
+    float aa = max(length(fwidth(pos)) * 0.5, 0.0001);
+    vec4 total = vec4(0.0);
+    for (int shape = 0; shape < shape_count; shape++) {
+        vec4 range = shape_range[shape];
+        int first = int(range.x);
+        int count = int(range.y);
+        float d = 1e10;
+        for (int i = first; i < first + count; i++) {
+            float e = distance_to(i, pos);
+            float op = primitive_params[i].w;
+            if (i == first || op == UNION) {
+                d = i == first ? e : min(d, e);
+            } else if (op == SUBTRACT) {
+                d = max(d, -e);
+            } else {
+                d = max(d, e);
+            }
+        }
+        float coverage = 1.0 - smoothstep(-aa, aa, d);
+        if (coverage <= 0.0) {
+            continue;
+        }
+        vec4 color = shape_fill[shape];
+        if (range.z > 0.0) {
+            color = mix(color, shape_border[shape], smoothstep(-range.z - aa, -range.z + aa, d));
+        }
+        // Later shapes are composited over earlier ones.
+        float alpha = color.a * coverage;
+        total = vec4(color.rgb * alpha, alpha) + total * (1.0 - alpha);
+    }
+    if (total.a <= 0.0) {
+        discard;
+    }
+    a_color = vec4(total.rgb / total.a, total.a);
+}
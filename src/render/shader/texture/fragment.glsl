@@ -3,13 +3,102 @@ precision mediump float;
 
 in vec2 v_uv;
 in vec4 v_color;
+in highp vec2 v_tile;
+flat in vec4 v_rect;
+in highp vec2 v_local;
+flat in highp vec4 v_effect;
 out vec4 a_color;
 
 uniform sampler2D tex[1];
+// 0 is clamp, 1 is repeat, and 2 is mirror.
+uniform highp int wrap;
+uniform vec4 grade_multiply;
+uniform float grade_saturation;
+uniform vec4 grade_shadows;
+uniform vec4 grade_highlights;
+uniform float grade_strength;
+
+const float EFFECT_DISSOLVE = 1.0;
+const float EFFECT_RADIAL_WIPE = 2.0;
+const float EFFECT_FLASH = 3.0;
+const float EFFECT_DISTANCE_FIELD = 4.0;
+const float EFFECT_DISTANCE_FIELD_SHADOW = 5.0;
+const float TURN = 6.283185307179586;
+
+float hash(highp vec2 p) {
+    return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+// Smooth value noise in [0, 1], with features about 8 pixels wide.
+float noise(highp vec2 p) {
+    highp vec2 cell = floor(p / 8.0);
+    vec2 t = smoothstep(0.0, 1.0, fract(p / 8.0));
+    float a = mix(hash(cell), hash(cell + vec2(1.0, 0.0)), t.x);
+    float b = mix(hash(cell + vec2(0.0, 1.0)), hash(cell + vec2(1.0, 1.0)), t.x);
+    return mix(a, b, t.y) * 0.8 + hash(floor(p)) * 0.2;
+}
+
+// Bilinear sample of the alpha channel, kept within the sprite's texture so neighbors in the
+// atlas don't bleed in. Distance fields need filtering even when the layer samples nearest.
+float field(highp vec2 uv) {
+    highp vec2 size = vec2(textureSize(tex[0], 0));
+    highp vec2 p = uv * size - 0.5;
+    highp vec2 lo = floor(p);
+    vec2 t = p - lo;
+    highp vec2 rect_min = vec2(min(v_rect.x, v_rect.y), min(v_rect.z, v_rect.w)) * size;
+    highp vec2 rect_max = vec2(max(v_rect.x, v_rect.y), max(v_rect.z, v_rect.w)) * size - 1.0;
+    ivec2 a = ivec2(clamp(lo, rect_min, rect_max));
+    ivec2 b = ivec2(clamp(lo + 1.0, rect_min, rect_max));
+    float s00 = texelFetch(tex[0], a, 0).a;
+    float s10 = texelFetch(tex[0], ivec2(b.x, a.y), 0).a;
+    float s01 = texelFetch(tex[0], ivec2(a.x, b.y), 0).a;
+    float s11 = texelFetch(tex[0], b, 0).a;
+    return mix(mix(s00, s10, t.x), mix(s01, s11, t.x), t.y);
+}
 
 void main() {
-    a_color = texture(tex[0], v_uv) * v_color;
+    vec2 uv = v_uv;
+    if (wrap != 0) {
+        // Position within the current repetition of the texture, from 0 to 1.
+        vec2 t = wrap == 1 ? fract(v_tile) : 1.0 - abs(mod(v_tile, 2.0) - 1.0);
+        uv = vec2(mix(v_rect.x, v_rect.y, t.x), mix(v_rect.z, v_rect.w, t.y));
+    }
+    if (v_effect.z == EFFECT_DISTANCE_FIELD) {
+        float distance = field(uv);
+        float width = max(fwidth(distance) * 0.75, 0.001);
+        a_color = vec4(v_color.rgb, v_color.a * smoothstep(0.5 - width, 0.5 + width, distance));
+    } else if (v_effect.z == EFFECT_DISTANCE_FIELD_SHADOW) {
+        // The field reaches 0 at the edge of its padding.
+        float distance = field(uv);
+        float width = max(fwidth(distance) * 0.75, 0.001);
+        float spread = v_effect.w * 0.5;
+        a_color = vec4(v_color.rgb, v_color.a * smoothstep(0.5 - spread - width, 0.5 + width, distance));
+    } else {
+        a_color = texture(tex[0], uv) * v_color;
+    }
     if (a_color.a <= 0.0) {
         discard;
     }
-}
\ No newline at end of file
+    float effect = v_effect.z;
+    float progress = v_effect.w;
+    if (effect == EFFECT_DISSOLVE) {
+        float edge = noise(v_local) - progress * 1.1 + 0.05;
+        if (edge < 0.0) {
+            discard;
+        }
+        a_color.rgb = mix(a_color.rgb, vec3(1.0), 1.0 - smoothstep(0.0, 0.05, edge));
+    } else if (effect == EFFECT_RADIAL_WIPE) {
+        // Angle clockwise from the top, in turns.
+        highp vec2 offset = v_local - v_effect.xy * 0.5;
+        float angle = fract(atan(offset.x, offset.y) / TURN + 1.0);
+        if (angle < progress) {
+            discard;
+        }
+    } else if (effect == EFFECT_FLASH) {
+        a_color.rgb = mix(a_color.rgb, vec3(1.0), progress);
+    }
+    float luma = dot(a_color.rgb, vec3(0.299, 0.587, 0.114));
+    vec3 rgb = mix(vec3(luma), a_color.rgb, grade_saturation);
+    rgb = mix(rgb, mix(grade_shadows.rgb, grade_highlights.rgb, clamp(luma, 0.0, 1.0)), grade_strength);
+    a_color = vec4(clamp(rgb, 0.0, 1.0), a_color.a) * grade_multiply;
+}
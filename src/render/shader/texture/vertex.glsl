@@ -9,11 +9,33 @@ layout(location = 1) in vec2 a_size;
 layout(location = 2) in vec4 a_uv;
 layout(location = 3) in vec4 a_color;
 layout(location = 4) in float a_rotation;
+layout(location = 5) in float a_visible;
+layout(location = 6) in float a_effect;
+layout(location = 7) in float a_effect_progress;
+layout(location = 8) in float a_depth_bias;
+layout(location = 9) in vec2 a_scale;
+layout(location = 10) in vec2 a_flip;
+// Only gradient sprites store corner colors. Other sprites read white, set by the layer.
+layout(location = 11) in vec4 a_corner_bottom_left;
+layout(location = 12) in vec4 a_corner_bottom_right;
+layout(location = 13) in vec4 a_corner_top_left;
+layout(location = 14) in vec4 a_corner_top_right;
 
 out vec2 v_uv;
 out vec4 v_color;
+out vec2 v_tile;
+flat out vec4 v_rect;
+// Position within the sprite in pixels, from the bottom left corner.
+out highp vec2 v_local;
+// Sprite size in pixels, effect, and effect progress.
+flat out highp vec4 v_effect;
 
 uniform mat4 ortho;
+uniform sampler2D tex[1];
+uniform highp int wrap;
+// Atlas coordinates are measured against the largest atlas, and this scales them to its current
+// size. Render targets are sampled unscaled.
+uniform float atlas_scale;
 
 // UV Layout: xmin xmax ymin ymax
 // ymin and ymax are swapped below because OpenGL reads images from bottom row to top row, but
@@ -43,11 +65,42 @@ vec4 rotateZ(vec3 pos) {
 }
 
 void main() {
-    vec4 temp = a_uv * uv_lut[gl_VertexID];
+    // Hidden and fully transparent sprites collapse to a degenerate quad and produce no fragments.
+    if (a_visible == 0.0 || a_color.a == 0.0) {
+        gl_Position = vec4(0.0);
+        return;
+    }
+
+    // Flipping swaps the edges of the texture's region.
+    vec4 rect = a_uv * atlas_scale;
+    if (a_flip.x != 0.0) {
+        rect.xy = rect.yx;
+    }
+    if (a_flip.y != 0.0) {
+        rect.zw = rect.wz;
+    }
+    vec4 temp = rect * uv_lut[gl_VertexID];
     v_uv = vec2(temp.x + temp.y, temp.z + temp.w);
-    v_color = a_color;
+    // In the same order as the corners of pos_lut.
+    vec4 corners[4] = vec4[4](a_corner_top_left, a_corner_bottom_left, a_corner_top_right, a_corner_bottom_right);
+    v_color = a_color * corners[gl_VertexID];
+    v_tile = vec2(0.0);
+    v_rect = rect;
+    vec2 pixels = a_size * 65536.0;
+    v_local = (pos_lut[gl_VertexID] / 65536.0) * pixels;
+    v_effect = vec4(pixels, a_effect, a_effect_progress);
+    if (wrap != 0) {
+        // The number of times the texture repeats across the sprite at its size in pixels.
+        vec2 texels = abs(vec2(rect.y - rect.x, rect.w - rect.z)) * vec2(textureSize(tex[0], 0));
+        vec2 corner = vec2(uv_lut[gl_VertexID].y, uv_lut[gl_VertexID].w);
+        v_tile = corner * (a_size * 65536.0) / max(texels, vec2(1.0));
+    }
 
-    vec3 size = vec3(a_size * pos_lut[gl_VertexID], 0.0);
-    vec3 pos = a_pos + size;
+    // Scaled around the center, which is also what the sprite rotates around.
+    vec2 half_size = a_size * 32768.0;
+    vec2 corner = (a_size * pos_lut[gl_VertexID] - half_size) * a_scale + half_size;
+    vec3 pos = a_pos + vec3(corner, 0.0);
     gl_Position = ortho * rotateZ(pos);
+    // Each step of bias moves NDC depth by 1/32768, which is 1/65536th of its range.
+    gl_Position.z -= a_depth_bias / 32768.0 * gl_Position.w;
 }
\ No newline at end of file
@@ -0,0 +1,251 @@
+use crate::render::layer::SharedLayer;
+use crate::render::raw::{resource, Capability, DrawMode};
+use crate::render::shader;
+use crate::render::OpenGLState;
+use crate::types::{LayerTransform, Shape, ShapeOperation, ShapePrimitive};
+use crate::utility::bad::UnsafeShared;
+use cgmath::*;
+
+const MAX_SHAPES: usize = 16;
+const MAX_PRIMITIVES: usize = 64;
+
+struct ShapeUniforms {
+    inverse: Option<resource::UniformLocation>,
+    shape_count: Option<resource::UniformLocation>,
+    shape_fill: Option<resource::UniformLocation>,
+    shape_border: Option<resource::UniformLocation>,
+    shape_range: Option<resource::UniformLocation>,
+    primitive_points: Option<resource::UniformLocation>,
+    primitive_params: Option<resource::UniformLocation>,
+}
+
+/// Shape layers draw shapes built from circles, boxes, and capsules, without any textures. Shapes
+/// are evaluated per pixel from distance functions, so they stay smooth at any scale. Shapes share
+/// a coordinate space with layers, so a shape layer with the same transform as a layer lines up
+/// with its sprites.
+pub struct ShapeLayer {
+    state: UnsafeShared<OpenGLState>,
+    shared: UnsafeShared<SharedLayer>,
+    program: resource::Program,
+    vao: resource::VertexArray,
+    uniforms: ShapeUniforms,
+    is_visible: bool,
+    shape_fill: Vec<f32>,
+    shape_border: Vec<f32>,
+    shape_range: Vec<f32>,
+    primitive_points: Vec<f32>,
+    primitive_params: Vec<f32>,
+}
+
+impl ShapeLayer {
+    pub(crate) fn new(
+        state: UnsafeShared<OpenGLState>,
+        ortho: &Matrix4<f32>,
+        safe_ortho: &Matrix4<f32>,
+    ) -> (UnsafeShared<SharedLayer>, ShapeLayer) {
+        let shared = UnsafeShared::new(SharedLayer::new(ortho, safe_ortho));
+        let gl = &state.gl;
+        let program = gl.shader_program(shader::post::VERTEX, shader::shape::FRAGMENT);
+        let uniforms = ShapeUniforms {
+            inverse: gl.get_uniform_location(program, "inverse"),
+            shape_count: gl.get_uniform_location(program, "shape_count"),
+            shape_fill: gl.get_uniform_location(program, "shape_fill"),
+            shape_border: gl.get_uniform_location(program, "shape_border"),
+            shape_range: gl.get_uniform_location(program, "shape_range"),
+            primitive_points: gl.get_uniform_location(program, "primitive_points"),
+            primitive_params: gl.get_uniform_location(program, "primitive_params"),
+        };
+        let vao = gl.create_vertex_array();
+        let shape_layer = ShapeLayer {
+            state,
+            shared: shared.clone(),
+            program,
+            vao,
+            uniforms,
+            is_visible: true,
+            shape_fill: Vec::new(),
+            shape_border: Vec::new(),
+            shape_range: Vec::new(),
+            primitive_points: Vec::new(),
+            primitive_params: Vec::new(),
+        };
+        (shared, shape_layer)
+    }
+
+    /// Draws the shapes over everything drawn so far.
+    pub fn draw(&mut self) {
+        if !self.is_visible || self.shape_range.is_empty() {
+            return;
+        }
+        let ortho_transform = self
+            .state
+            .pixel_snap(self.shared.ortho_transform(self.state.target_ortho(), self.state.camera()));
+        let inverse = match ortho_transform.invert() {
+            Some(inverse) => inverse,
+            None => return,
+        };
+
+        let gl = &self.state.gl;
+        let uniforms = &self.uniforms;
+        gl.disable(Capability::DepthTest);
+        gl.bind_vertex_array(Some(self.vao));
+        gl.use_program(Some(self.program));
+        gl.uniform_matrix_4fv(uniforms.inverse.as_ref(), false, inverse.as_ref());
+        gl.uniform_1i(uniforms.shape_count.as_ref(), (self.shape_range.len() / 4) as i32);
+        gl.uniform_4fv(uniforms.shape_fill.as_ref(), &self.shape_fill);
+        gl.uniform_4fv(uniforms.shape_border.as_ref(), &self.shape_border);
+        gl.uniform_4fv(uniforms.shape_range.as_ref(), &self.shape_range);
+        gl.uniform_4fv(uniforms.primitive_points.as_ref(), &self.primitive_points);
+        gl.uniform_4fv(uniforms.primitive_params.as_ref(), &self.primitive_params);
+        gl.draw_arrays(DrawMode::Triangles, 0, 3);
+
+        gl.enable(Capability::DepthTest);
+        self.state.shader_bind();
+    }
+
+    /// Sets the shapes, drawn in order with later shapes over earlier ones. Up to 16 shapes made
+    /// of up to 64 primitives in total are supported, and any more are ignored.
+    pub fn set_shapes(&mut self, shapes: &[Shape]) {
+        self.shape_fill.clear();
+        self.shape_border.clear();
+        self.shape_range.clear();
+        self.primitive_points.clear();
+        self.primitive_params.clear();
+        let mut ignored = 0;
+        for (index, shape) in shapes.iter().enumerate() {
+            let first = self.primitive_points.len() / 4;
+            if !fits(index, first, shape.primitives.len()) {
+                ignored += 1;
+                continue;
+            }
+            for (operation, primitive) in &shape.primitives {
+                let (points, params) = pack(operation, primitive);
+                self.primitive_points.extend_from_slice(&points);
+                self.primitive_params.extend_from_slice(&params);
+            }
+            let fill: Vector4<f32> = shape.fill.into();
+            let border: Vector4<f32> = shape.border_color.into();
+            self.shape_fill.extend_from_slice(&[fill.x, fill.y, fill.z, fill.w]);
+            self.shape_border.extend_from_slice(&[border.x, border.y, border.z, border.w]);
+            self.shape_range.extend_from_slice(&[
+                first as f32,
+                shape.primitives.len() as f32,
+                shape.border_width.max(0.0),
+                0.0,
+            ]);
+        }
+        if ignored > 0 {
+            warn!(
+                "Shape layers support up to {} shapes and {} primitives, ignoring {} shapes.",
+                MAX_SHAPES, MAX_PRIMITIVES, ignored
+            );
+        }
+    }
+
+    /// Sets the transformation matrix used when drawing this.
+    pub fn set_transform(&mut self, transform: &LayerTransform) {
+        self.shared.set_transform_matrix(&transform.to_matrix());
+    }
+
+    /// Sets the transformation matrix used when drawing this.
+    pub fn set_transform_matrix(&mut self, transform: &Matrix4<f32>) {
+        self.shared.set_transform_matrix(transform);
+    }
+
+    /// If the shape layer should be laid out within the window's safe area, like
+    /// `Layer::set_safe_area`. The default is false.
+    pub fn set_safe_area(&mut self, safe_area: bool) {
+        self.shared.set_safe_area(safe_area);
+    }
+
+    /// If the renderer should render this shape layer or not when draw is called.
+    pub fn set_visible(&mut self, is_visible: bool) {
+        self.is_visible = is_visible;
+    }
+}
+
+impl Drop for ShapeLayer {
+    fn drop(&mut self) {
+        self.state.layer_drop(self.shared.index());
+        self.state.gl.delete_program(self.program);
+        self.state.gl.delete_vertex_array(self.vao);
+    }
+}
+
+/// If a shape fits in the uniforms, given its index and the primitives taken by earlier shapes.
+fn fits(index: usize, first: usize, primitives: usize) -> bool {
+    index < MAX_SHAPES && first + primitives <= MAX_PRIMITIVES
+}
+
+/// Packs a primitive into its points and parameters uniforms. The parameters are the radius, the
+/// rotation in radians, the kind of primitive, and the operation.
+fn pack(operation: &ShapeOperation, primitive: &ShapePrimitive) -> ([f32; 4], [f32; 4]) {
+    let operation = match operation {
+        ShapeOperation::Union => 0.0,
+        ShapeOperation::Subtract => 1.0,
+        ShapeOperation::Intersect => 2.0,
+    };
+    match *primitive {
+        ShapePrimitive::Circle {
+            center,
+            radius,
+        } => ([center.x, center.y, 0.0, 0.0], [radius, 0.0, 0.0, operation]),
+        ShapePrimitive::Box {
+            center,
+            size,
+            corner_radius,
+            rotation,
+        } => {
+            let rotation = core::f32::consts::PI * 2.0 * rotation;
+            ([center.x, center.y, size.x, size.y], [corner_radius.max(0.0), rotation, 1.0, operation])
+        }
+        ShapePrimitive::Capsule {
+            start,
+            end,
+            radius,
+        } => ([start.x, start.y, end.x, end.y], [radius, 0.0, 2.0, operation]),
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packing() {
+        let circle = ShapePrimitive::Circle {
+            center: Vector2::new(1.0, 2.0),
+            radius: 3.0,
+        };
+        assert_eq!(pack(&ShapeOperation::Union, &circle), ([1.0, 2.0, 0.0, 0.0], [3.0, 0.0, 0.0, 0.0]));
+
+        let rounded = ShapePrimitive::Box {
+            center: Vector2::new(1.0, 2.0),
+            size: Vector2::new(4.0, 5.0),
+            corner_radius: -1.0,
+            rotation: 0.25,
+        };
+        let (points, params) = pack(&ShapeOperation::Subtract, &rounded);
+        assert_eq!(points, [1.0, 2.0, 4.0, 5.0]);
+        assert_eq!(params, [0.0, core::f32::consts::FRAC_PI_2, 1.0, 1.0]);
+
+        let capsule = ShapePrimitive::Capsule {
+            start: Vector2::new(1.0, 2.0),
+            end: Vector2::new(3.0, 4.0),
+            radius: 5.0,
+        };
+        assert_eq!(pack(&ShapeOperation::Intersect, &capsule), ([1.0, 2.0, 3.0, 4.0], [5.0, 0.0, 2.0, 2.0]));
+    }
+
+    #[test]
+    fn limits() {
+        assert!(fits(0, 0, MAX_PRIMITIVES));
+        assert!(!fits(0, 1, MAX_PRIMITIVES));
+        assert!(fits(MAX_SHAPES - 1, 10, 4));
+        assert!(!fits(MAX_SHAPES, 10, 4));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::types::{Sprite, SpriteToken};
+use crate::utility::pool::{Pool, PoolHandle};
+use core::ops::Range;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Gives each set of slots its own id, so tokens from another layer can be caught.
+static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+/// Sprites that are created, updated, and removed one at a time through tokens. Sprites are kept
+/// packed for drawing in a pool, and the range that changed since it was last taken is tracked so
+/// only that part needs to be uploaded.
+pub(crate) struct SpriteSlots {
+    id: u32,
+    pool: Pool<Sprite>,
+    dirty: Option<Range<usize>>,
+}
+
+impl SpriteSlots {
+    pub fn new() -> SpriteSlots {
+        SpriteSlots {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            pool: Pool::new(),
+            dirty: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn sprites(&self) -> &[Sprite] {
+        self.pool.as_slice()
+    }
+
+    pub fn create(&mut self, sprite: &Sprite) -> SpriteToken {
+        let handle = self.pool.acquire(*sprite);
+        self.mark(self.pool.len() - 1);
+        SpriteToken::new(self.id, handle)
+    }
+
+    pub fn get(&self, token: &SpriteToken) -> &Sprite {
+        &self.pool.as_slice()[self.index(token)]
+    }
+
+    pub fn update(&mut self, token: &SpriteToken, sprite: &Sprite) {
+        let index = self.index(token);
+        self.pool.as_mut_slice()[index] = *sprite;
+        self.mark(index);
+    }
+
+    /// Gets the token's handle, for use with `sprite_mut`. Panics if the token doesn't belong here.
+    pub fn handle(&self, token: &SpriteToken) -> PoolHandle {
+        self.index(token);
+        token.handle()
+    }
+
+    /// Gets the sprite for the handle to modify it in place, marking it as changed. None if the
+    /// sprite has been removed.
+    pub fn sprite_mut(&mut self, handle: PoolHandle) -> Option<&mut Sprite> {
+        let index = self.pool.index_of(handle)?;
+        self.mark(index);
+        Some(&mut self.pool.as_mut_slice()[index])
+    }
+
+    /// Removes the sprite by moving the last sprite into its place.
+    pub fn remove(&mut self, token: SpriteToken) {
+        let index = self.index(&token);
+        self.pool.release(token.handle());
+        // Marked even when nothing moved, so the shorter length is picked up.
+        self.mark(index);
+    }
+
+    /// Takes the range of sprites that changed since the last call. The range may extend past the
+    /// end of the sprites if sprites were removed.
+    pub fn take_dirty(&mut self) -> Option<Range<usize>> {
+        self.dirty.take()
+    }
+
+    fn index(&self, token: &SpriteToken) -> usize {
+        if token.layer() != self.id {
+            panic!("Sprite token {:?} doesn't belong to this layer.", token.handle());
+        }
+        match self.pool.index_of(token.handle()) {
+            Some(index) => index,
+            None => panic!("Sprite token {:?} was already removed.", token.handle()),
+        }
+    }
+
+    fn mark(&mut self, index: usize) {
+        self.dirty = Some(match self.dirty.take() {
+            Some(range) => range.start.min(index)..range.end.max(index + 1),
+            None => index..index + 1,
+        });
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::*;
+
+    fn at(x: f32) -> Sprite {
+        Sprite {
+            pos: Vector3::new(x, 0.0, 0.0),
+            ..Sprite::default()
+        }
+    }
+
+    #[test]
+    fn dirty_ranges() {
+        let mut slots = SpriteSlots::new();
+        let a = slots.create(&at(0.0));
+        let b = slots.create(&at(1.0));
+        let c = slots.create(&at(2.0));
+        assert_eq!(slots.take_dirty(), Some(0..3));
+        assert_eq!(slots.take_dirty(), None);
+
+        slots.update(&b, &at(5.0));
+        assert_eq!(slots.take_dirty(), Some(1..2));
+
+        // The last sprite moves into the removed sprite's place.
+        let removed = a.handle();
+        slots.remove(a);
+        assert_eq!(slots.take_dirty(), Some(0..1));
+        assert_eq!(slots.get(&c).pos.x, 2.0);
+        assert_eq!(slots.get(&b).pos.x, 5.0);
+        assert_eq!(slots.len(), 2);
+
+        slots.sprite_mut(b.handle()).unwrap().pos.x = 6.0;
+        assert_eq!(slots.take_dirty(), Some(1..2));
+        assert!(slots.sprite_mut(removed).is_none());
+
+        // The removed sprite's slot is reused, but its handle stays removed.
+        let d = slots.create(&at(3.0));
+        assert!(slots.sprite_mut(removed).is_none());
+        assert!(slots.sprite_mut(d.handle()).is_some());
+        assert_eq!(slots.sprites()[2].pos.x, 3.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't belong to this layer")]
+    fn foreign_token() {
+        let mut first = SpriteSlots::new();
+        let mut second = SpriteSlots::new();
+        let token = first.create(&at(0.0));
+        // The same slot exists on the second layer, but the token still isn't accepted there.
+        second.create(&at(1.0));
+        second.update(&token, &at(2.0));
+    }
+}
@@ -1,15 +1,44 @@
+use super::gpu_timer::GpuTimers;
 use super::layer::SharedLayer;
-use super::raw::{resource, BlendFactor, Capability, CullFace, DepthTest, OpenGL, TextureUnit};
+use super::raw::{
+    resource, BlendFactor, Capability, CullFace, DepthTest, FramebufferBindingTarget, OpenGL,
+    TextureMagFilterValue, TextureMinFilterValue, TextureParameterName, TextureUnit, TextureWrapValue,
+};
+use super::readback::{PixelReadback, Readbacks};
 use super::shader;
+use crate::types::{
+    colors, ColorGrade, GpuPass, GpuTiming, TextureFilter, TextureSampling, TextureWrap, VirtualResolution,
+    RGBA8,
+};
 use crate::utility::bad::UnsafeShared;
 use cgmath::*;
 
 pub struct OpenGLState {
     pub gl: OpenGL,
     layers: Vec<UnsafeShared<SharedLayer>>,
+    viewport: Vector2<f32>,
+    resolution: Option<VirtualResolution>,
+    clear_color: RGBA8,
+    screen: Option<resource::Framebuffer>,
+    target: Option<(resource::Framebuffer, Vector2<f32>, Matrix4<f32>)>,
+    camera: Option<Matrix4<f32>>,
     program: resource::Program,
     uniform_ortho: resource::UniformLocation,
     uniform_texture: resource::UniformLocation,
+    uniform_wrap: resource::UniformLocation,
+    uniform_atlas_scale: resource::UniformLocation,
+    uniform_grade: [resource::UniformLocation; 5],
+    linear: resource::Sampler,
+    trilinear: resource::Sampler,
+    anisotropic: Option<resource::Sampler>,
+    mipmaps: bool,
+    filter_limit: TextureFilter,
+    light_resolution: f32,
+    pixel_art: bool,
+    frame: u64,
+    atlas_scale: f32,
+    timers: Option<GpuTimers>,
+    readbacks: Readbacks,
 }
 
 impl OpenGLState {
@@ -18,7 +47,6 @@ impl OpenGLState {
         gl.enable(Capability::CullFace);
         gl.enable(Capability::Blend);
         gl.enable(Capability::DepthTest);
-        gl.clear_color(0.0, 0.0, 0.0, 1.0);
         gl.depth_func(DepthTest::Less);
         gl.blend_func(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha);
         gl.cull_face(CullFace::Back);
@@ -27,18 +55,61 @@ impl OpenGLState {
         let program = gl.shader_program(shader::texture::VERTEX, shader::texture::FRAGMENT);
         let uniform_ortho = gl.get_uniform_location(program, "ortho").unwrap();
         let uniform_texture = gl.get_uniform_location(program, "tex[0]").unwrap();
+        let uniform_wrap = gl.get_uniform_location(program, "wrap").unwrap();
+        let uniform_atlas_scale = gl.get_uniform_location(program, "atlas_scale").unwrap();
+        let uniform_grade = [
+            gl.get_uniform_location(program, "grade_multiply").unwrap(),
+            gl.get_uniform_location(program, "grade_saturation").unwrap(),
+            gl.get_uniform_location(program, "grade_shadows").unwrap(),
+            gl.get_uniform_location(program, "grade_highlights").unwrap(),
+            gl.get_uniform_location(program, "grade_strength").unwrap(),
+        ];
+
+        // Textures sample with nearest filtering. These override that while bound.
+        let linear = Self::sampler(&gl, TextureMinFilterValue::Linear);
+        let trilinear = Self::sampler(&gl, TextureMinFilterValue::LinearMipmapLinear);
+        let max_anisotropy = gl.max_anisotropy();
+        let anisotropic = if max_anisotropy > 1.0 {
+            let sampler = Self::sampler(&gl, TextureMinFilterValue::LinearMipmapLinear);
+            gl.sampler_anisotropy(sampler, max_anisotropy);
+            Some(sampler)
+        } else {
+            None
+        };
 
         let mut state = OpenGLState {
             gl,
             layers: Vec::new(),
+            viewport: Vector2::new(0.0, 0.0),
+            resolution: None,
+            clear_color: colors::BLACK,
+            screen: None,
+            target: None,
+            camera: None,
             program,
             uniform_ortho,
             uniform_texture,
+            uniform_wrap,
+            uniform_atlas_scale,
+            uniform_grade,
+            linear,
+            trilinear,
+            anisotropic,
+            mipmaps: false,
+            filter_limit: TextureFilter::Anisotropic,
+            light_resolution: 1.0,
+            pixel_art: false,
+            frame: 0,
+            atlas_scale: 1.0,
+            timers: None,
+            readbacks: Readbacks::new(),
         };
 
         // Bind and configure the shader.
+        state.apply_clear_color(colors::BLACK);
         state.shader_bind();
         state.shader_texture(TextureUnit::Atlas);
+        state.shader_grade(&ColorGrade::default());
 
         state
     }
@@ -57,13 +128,122 @@ impl OpenGLState {
         }
     }
 
-    pub fn resize(&mut self, physical: &Vector2<f32>, ortho: &Matrix4<f32>) {
-        self.gl.viewport(0, 0, physical.x as i32, physical.y as i32);
+    pub fn resize(&mut self, physical: &Vector2<f32>, ortho: &Matrix4<f32>, safe_ortho: &Matrix4<f32>) {
+        self.viewport = *physical;
+        if self.target.is_none() {
+            self.screen_viewport();
+        }
         for layer in &mut self.layers {
-            layer.set_ortho(ortho);
+            layer.set_ortho(ortho, safe_ortho);
+        }
+    }
+
+    /// The physical size of the window.
+    pub fn viewport(&self) -> Vector2<f32> {
+        self.viewport
+    }
+
+    /// Sets the virtual resolution the screen is drawn at. None draws at the window's size.
+    pub fn resolution_set(&mut self, resolution: Option<VirtualResolution>) {
+        self.resolution = resolution;
+        if self.target.is_none() {
+            self.screen_viewport();
+        }
+    }
+
+    pub fn resolution(&self) -> Option<&VirtualResolution> {
+        self.resolution.as_ref()
+    }
+
+    /// The size of the screen layers draw to, which is the virtual resolution if one is set, or
+    /// the physical size of the window.
+    pub fn screen_size(&self) -> Vector2<f32> {
+        match &self.resolution {
+            Some(resolution) => resolution.size(),
+            None => self.viewport,
         }
     }
 
+    fn screen_viewport(&self) {
+        let size = self.screen_size();
+        self.gl.viewport(0, 0, size.x as i32, size.y as i32);
+    }
+
+    /// Sets the clear color used for the window. Render targets may override this while bound.
+    pub fn clear_color(&mut self, color: RGBA8) {
+        self.clear_color = color;
+        if self.target.is_none() {
+            self.apply_clear_color(color);
+        }
+    }
+
+    /// Sets the clear color in OpenGL without changing the window's clear color.
+    pub fn apply_clear_color(&self, color: RGBA8) {
+        let color: Vector4<f32> = color.into();
+        self.gl.clear_color(color.x, color.y, color.z, color.w);
+    }
+
+    /// Sets the framebuffer that stands in for the window, like when post processing is active.
+    /// None is the window itself. Drawing goes to the screen framebuffer when no render target is
+    /// bound.
+    pub fn screen_set(&mut self, framebuffer: Option<resource::Framebuffer>) {
+        self.screen = framebuffer;
+        if self.target.is_none() {
+            self.gl.bind_framebuffer(FramebufferBindingTarget::Framebuffer, self.screen);
+            self.screen_viewport();
+        }
+    }
+
+    /// Redirects drawing into the given framebuffer. Layers drawn while a target is bound use the
+    /// target's ortho matrix instead of the window's.
+    pub fn target_bind(
+        &mut self,
+        framebuffer: resource::Framebuffer,
+        size: &Vector2<f32>,
+        ortho: &Matrix4<f32>,
+    ) {
+        self.gl.bind_framebuffer(FramebufferBindingTarget::Framebuffer, Some(framebuffer));
+        self.gl.viewport(0, 0, size.x as i32, size.y as i32);
+        self.target = Some((framebuffer, *size, *ortho));
+    }
+
+    /// Restores drawing to the screen framebuffer.
+    pub fn target_unbind(&mut self) {
+        self.gl.bind_framebuffer(FramebufferBindingTarget::Framebuffer, self.screen);
+        self.screen_viewport();
+        self.apply_clear_color(self.clear_color);
+        self.target = None;
+    }
+
+    /// The ortho matrix of the bound render target, if any.
+    pub fn target_ortho(&self) -> Option<&Matrix4<f32>> {
+        self.target.as_ref().map(|(_, _, ortho)| ortho)
+    }
+
+    /// If the given framebuffer is the bound render target.
+    pub fn target_is_bound(&self, framebuffer: resource::Framebuffer) -> bool {
+        matches!(self.target, Some((bound, _, _)) if bound == framebuffer)
+    }
+
+    /// The framebuffer being drawn to and its size, which is the bound render target or the
+    /// screen.
+    pub fn framebuffer(&self) -> (Option<resource::Framebuffer>, Vector2<f32>) {
+        match self.target {
+            Some((framebuffer, size, _)) => (Some(framebuffer), size),
+            None => (self.screen, self.screen_size()),
+        }
+    }
+
+    /// Sets an extra transform applied to every layer drawn to the window, under the layer's own
+    /// transform. This is how photo mode moves the view.
+    pub fn camera_set(&mut self, camera: Option<Matrix4<f32>>) {
+        self.camera = camera;
+    }
+
+    pub fn camera(&self) -> Option<&Matrix4<f32>> {
+        self.camera.as_ref()
+    }
+
     /// Binds the shader.
     pub fn shader_bind(&mut self) {
         self.gl.use_program(Some(self.program));
@@ -76,13 +256,240 @@ impl OpenGLState {
 
     /// Updates the texture uniform in the shader.
     pub fn shader_texture(&mut self, unit: TextureUnit) {
+        let scale = if unit == TextureUnit::Atlas {
+            self.atlas_scale
+        } else {
+            1.0
+        };
+        self.gl.uniform_1f(Some(&self.uniform_atlas_scale), scale);
         let unit = (unit as u32 - TextureUnit::Atlas as u32) as i32;
         self.gl.uniform_1i(Some(&self.uniform_texture), unit);
     }
+
+    /// Sets the size of the atlas, relative to the largest size it can be, and applies it to the
+    /// shader.
+    pub fn atlas_scale_set(&mut self, scale: f32) {
+        self.atlas_scale = scale;
+        self.shader_bind();
+        self.shader_texture(TextureUnit::Atlas);
+    }
+
+    /// How atlas coordinates are scaled to the atlas's current size.
+    pub fn atlas_scale(&self) -> f32 {
+        self.atlas_scale
+    }
+
+    /// Applies the color grade to the shader. Draws that don't set their own grade should reset
+    /// this to the default afterward.
+    pub fn shader_grade(&mut self, grade: &ColorGrade) {
+        let [multiply, saturation, shadows, highlights, strength] = &self.uniform_grade;
+        let color: Vector4<f32> = grade.multiply.into();
+        self.gl.uniform_4f(Some(multiply), color.x, color.y, color.z, color.w);
+        self.gl.uniform_1f(Some(saturation), grade.saturation);
+        let curve = grade.curve.unwrap_or_default();
+        let color: Vector4<f32> = curve.shadows.into();
+        self.gl.uniform_4f(Some(shadows), color.x, color.y, color.z, color.w);
+        let color: Vector4<f32> = curve.highlights.into();
+        self.gl.uniform_4f(Some(highlights), color.x, color.y, color.z, color.w);
+        let strength_value = match grade.curve {
+            Some(curve) => curve.strength.clamp(0.0, 1.0),
+            None => 0.0,
+        };
+        self.gl.uniform_1f(Some(strength), strength_value);
+    }
+
+    fn sampler(gl: &OpenGL, min: TextureMinFilterValue) -> resource::Sampler {
+        let sampler = gl.create_sampler();
+        let wrap = TextureWrapValue::ClampToEdge as u32;
+        gl.sampler_parameter(sampler, TextureParameterName::TextureMinFilter, min as u32);
+        gl.sampler_parameter(
+            sampler,
+            TextureParameterName::TextureMagFilter,
+            TextureMagFilterValue::Linear as u32,
+        );
+        gl.sampler_parameter(sampler, TextureParameterName::TextureWrapS, wrap);
+        gl.sampler_parameter(sampler, TextureParameterName::TextureWrapT, wrap);
+        sampler
+    }
+
+    /// Sets if the atlas has mipmaps, which the trilinear and anisotropic filters need.
+    pub fn mipmaps_set(&mut self, mipmaps: bool) {
+        self.mipmaps = mipmaps;
+    }
+
+    /// If the atlas should have mipmaps. Pixel art mode turns them off.
+    pub fn mipmaps(&self) -> bool {
+        self.mipmaps && !self.pixel_art
+    }
+
+    /// Counts a frame as started.
+    pub fn frame_advance(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Enables or disables GPU timing. Returns false if timer queries aren't supported, in which
+    /// case timing stays disabled.
+    pub fn gpu_timing_set(&mut self, enabled: bool) -> bool {
+        match (enabled, &mut self.timers) {
+            (true, None) => {
+                if !self.gl.timer_query_supported() {
+                    return false;
+                }
+                self.timers = Some(GpuTimers::new());
+            }
+            (false, Some(timers)) => {
+                timers.delete(&self.gl);
+                self.timers = None;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Starts timing a pass on the GPU, if GPU timing is enabled.
+    pub fn gpu_timer_begin(&mut self, pass: GpuPass) {
+        if let Some(timers) = &mut self.timers {
+            timers.begin(&self.gl, pass);
+        }
+    }
+
+    /// Pauses GPU timing, like for drawing that isn't part of the game's frame.
+    pub fn gpu_timer_pause(&mut self, paused: bool) {
+        if let Some(timers) = &mut self.timers {
+            timers.set_paused(paused);
+        }
+    }
+
+    pub fn gpu_timer_end(&mut self) {
+        if let Some(timers) = &mut self.timers {
+            timers.end(&self.gl);
+        }
+    }
+
+    pub fn gpu_timer_frame_end(&mut self) {
+        if let Some(timers) = &mut self.timers {
+            timers.frame_end(&self.gl);
+        }
+    }
+
+    /// The GPU timings of the most recent frame the GPU finished.
+    pub fn gpu_timings(&self) -> &[GpuTiming] {
+        match &self.timers {
+            Some(timers) => timers.latest(),
+            None => &[],
+        }
+    }
+
+    /// Queues a read of an area of the window, in physical pixels from the top left. See
+    /// `PixelReadback`.
+    pub fn readback_request(&mut self, pos: Vector2<u32>, size: Vector2<u32>) -> PixelReadback {
+        self.readbacks.request(pos, size)
+    }
+
+    /// Issues the frame's queued readbacks and finishes the ones the GPU is done with.
+    pub fn readback_frame_end(&mut self) {
+        self.target_unbind();
+        let viewport = Vector2::new(self.viewport.x as u32, self.viewport.y as u32);
+        self.readbacks.frame_end(&self.gl, viewport, self.frame);
+    }
+
+    /// The number of frames started so far.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Sets if layers draw as pixel art, with nearest filtering and snapped transforms.
+    pub fn pixel_art_set(&mut self, pixel_art: bool) {
+        self.pixel_art = pixel_art;
+    }
+
+    pub fn pixel_art(&self) -> bool {
+        self.pixel_art
+    }
+
+    /// Snaps a layer's matrix in pixel art mode, so each unit of the layer covers a whole number
+    /// of pixels of the framebuffer being drawn to, and the layer's origin lands on a pixel corner.
+    /// Outside of pixel art mode the matrix is returned unchanged.
+    pub fn pixel_snap(&self, matrix: Matrix4<f32>) -> Matrix4<f32> {
+        if !self.pixel_art {
+            return matrix;
+        }
+        let (_, size) = self.framebuffer();
+        let half = size / 2.0;
+        if half.x <= 0.0 || half.y <= 0.0 {
+            return matrix;
+        }
+        // Move from clip space into pixels, measured from the center of the framebuffer.
+        let mut pixels = Matrix4::from_nonuniform_scale(half.x, half.y, 1.0) * matrix;
+        for axis in [&mut pixels.x, &mut pixels.y] {
+            let length = axis.truncate().truncate().magnitude();
+            if length > 0.0 {
+                let factor = length.round().max(1.0) / length;
+                axis.x *= factor;
+                axis.y *= factor;
+            }
+        }
+        pixels.w.x = (pixels.w.x + half.x).round() - half.x;
+        pixels.w.y = (pixels.w.y + half.y).round() - half.y;
+        Matrix4::from_nonuniform_scale(1.0 / half.x, 1.0 / half.y, 1.0) * pixels
+    }
+
+    /// Sets the most expensive filter layers may sample with.
+    pub fn filter_limit_set(&mut self, filter: TextureFilter) {
+        self.filter_limit = filter;
+    }
+
+    /// Sets the fraction of the resolution light maps are computed at.
+    pub fn light_resolution_set(&mut self, resolution: f32) {
+        self.light_resolution = resolution.clamp(0.1, 1.0);
+    }
+
+    pub fn light_resolution(&self) -> f32 {
+        self.light_resolution
+    }
+
+    /// A sampler with linear filtering and no mipmaps.
+    pub fn linear_sampler(&self) -> resource::Sampler {
+        self.linear
+    }
+
+    /// Applies the sampling settings to the texture unit and the shader. Draws that don't set their
+    /// own sampling should reset this to the default afterward.
+    pub fn shader_sampling(&mut self, unit: TextureUnit, sampling: &TextureSampling) {
+        let mipmaps = self.mipmaps && unit == TextureUnit::Atlas;
+        let filter = if self.pixel_art {
+            TextureFilter::Nearest
+        } else {
+            sampling.filter.min(self.filter_limit)
+        };
+        let sampler = match filter {
+            TextureFilter::Nearest => None,
+            TextureFilter::Linear => Some(self.linear),
+            TextureFilter::Trilinear | TextureFilter::Anisotropic if !mipmaps => Some(self.linear),
+            TextureFilter::Trilinear => Some(self.trilinear),
+            TextureFilter::Anisotropic => Some(self.anisotropic.unwrap_or(self.trilinear)),
+        };
+        self.gl.bind_sampler(unit, sampler);
+        let wrap = match sampling.wrap {
+            TextureWrap::Clamp => 0,
+            TextureWrap::Repeat => 1,
+            TextureWrap::Mirror => 2,
+        };
+        self.gl.uniform_1i(Some(&self.uniform_wrap), wrap);
+    }
 }
 
 impl Drop for OpenGLState {
     fn drop(&mut self) {
         self.gl.delete_program(self.program);
+        self.gl.delete_sampler(self.linear);
+        self.gl.delete_sampler(self.trilinear);
+        if let Some(anisotropic) = self.anisotropic {
+            self.gl.delete_sampler(anisotropic);
+        }
+        if let Some(timers) = &mut self.timers {
+            timers.delete(&self.gl);
+        }
+        self.readbacks.delete(&self.gl);
     }
 }
@@ -0,0 +1,147 @@
+use crate::types::Sprite;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+
+enum Submission {
+    Set(usize, Vec<Sprite>),
+    Remove(usize),
+}
+
+/// Submits sprites to a layer from any thread, like from gameplay systems running in parallel.
+/// Each sender, including each clone, holds its own batch of sprites, which every submit replaces.
+/// The layer picks up the latest batches when it's drawn and draws them in the order their senders
+/// were created. Dropping a sender removes its batch.
+pub struct SpriteSender {
+    id: usize,
+    next_id: Arc<AtomicUsize>,
+    sender: mpsc::Sender<Submission>,
+}
+
+impl SpriteSender {
+    /// Replaces this sender's batch. If the layer was dropped, the sprites are discarded.
+    pub fn submit(&self, sprites: Vec<Sprite>) {
+        let _ = self.sender.send(Submission::Set(self.id, sprites));
+    }
+
+    /// Clears this sender's batch.
+    pub fn clear(&self) {
+        self.submit(Vec::new());
+    }
+}
+
+impl Clone for SpriteSender {
+    /// Creates a sender with its own empty batch.
+    fn clone(&self) -> SpriteSender {
+        SpriteSender {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            next_id: self.next_id.clone(),
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl Drop for SpriteSender {
+    fn drop(&mut self) {
+        let _ = self.sender.send(Submission::Remove(self.id));
+    }
+}
+
+/// The layer's end of its senders, holding the latest batch from each.
+pub(crate) struct SpriteReceiver {
+    next_id: Arc<AtomicUsize>,
+    sender: mpsc::Sender<Submission>,
+    receiver: mpsc::Receiver<Submission>,
+    /// Batches sorted by sender id.
+    batches: Vec<(usize, Vec<Sprite>)>,
+    len: usize,
+}
+
+impl SpriteReceiver {
+    pub fn new() -> SpriteReceiver {
+        let (sender, receiver) = mpsc::channel();
+        SpriteReceiver {
+            next_id: Arc::new(AtomicUsize::new(0)),
+            sender,
+            receiver,
+            batches: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn sender(&self) -> SpriteSender {
+        SpriteSender {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            next_id: self.next_id.clone(),
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// The number of sprites across every batch, as of the last receive.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Takes everything submitted since the last call. If any batch changed, returns every batch
+    /// joined in order.
+    pub fn receive(&mut self) -> Option<Vec<Sprite>> {
+        let mut changed = false;
+        for submission in self.receiver.try_iter() {
+            changed = true;
+            match submission {
+                Submission::Set(id, sprites) => match self.batches.binary_search_by_key(&id, |(id, _)| *id) {
+                    Ok(index) => self.batches[index].1 = sprites,
+                    Err(index) => self.batches.insert(index, (id, sprites)),
+                },
+                Submission::Remove(id) => {
+                    if let Ok(index) = self.batches.binary_search_by_key(&id, |(id, _)| *id) {
+                        self.batches.remove(index);
+                    }
+                }
+            }
+        }
+        if !changed {
+            return None;
+        }
+        let sprites: Vec<Sprite> =
+            self.batches.iter().flat_map(|(_, sprites)| sprites.iter().copied()).collect();
+        self.len = sprites.len();
+        Some(sprites)
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::*;
+
+    fn at(x: f32) -> Sprite {
+        Sprite {
+            pos: Vector3::new(x, 0.0, 0.0),
+            ..Sprite::default()
+        }
+    }
+
+    #[test]
+    fn batches() {
+        let mut receiver = SpriteReceiver::new();
+        let a = receiver.sender();
+        let b = a.clone();
+        assert_eq!(receiver.receive(), None);
+
+        std::thread::spawn(move || b.submit(vec![at(1.0), at(2.0)])).join().unwrap();
+        a.submit(vec![at(0.0)]);
+        let xs = |sprites: Vec<Sprite>| sprites.iter().map(|sprite| sprite.pos.x).collect::<Vec<_>>();
+        // The batch from the dropped clone goes away.
+        assert_eq!(receiver.receive().map(xs), Some(vec![0.0]));
+
+        let c = a.clone();
+        c.submit(vec![at(3.0)]);
+        a.submit(vec![at(4.0)]);
+        assert_eq!(receiver.receive().map(xs), Some(vec![4.0, 3.0]));
+        assert_eq!(receiver.len(), 2);
+    }
+}
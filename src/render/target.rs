@@ -0,0 +1,129 @@
+use crate::render::matrix_from_bounds;
+use crate::render::raw::{
+    resource, ClearMode, FramebufferAttachment, FramebufferBindingTarget, PixelInternalFormat,
+    RenderbufferBindingTarget, TextureLoadTarget, TextureUnit,
+};
+use crate::render::texture_handle::TextureHandle;
+use crate::render::OpenGLState;
+use crate::types::{Texture, RGBA8};
+use crate::utility::bad::UnsafeShared;
+use cgmath::*;
+
+/// Render targets are offscreen images that layers can be drawn into. Once drawn, the contents of
+/// the target can be used as the texture source of a layer.
+pub struct RenderTarget {
+    state: UnsafeShared<OpenGLState>,
+    framebuffer: resource::Framebuffer,
+    depth: resource::Renderbuffer,
+    texture: UnsafeShared<TextureHandle>,
+    size: Vector2<f32>,
+    ortho: Matrix4<f32>,
+    clear_color: Option<RGBA8>,
+    clear_mode: Option<ClearMode>,
+}
+
+impl RenderTarget {
+    pub(crate) fn new(state: UnsafeShared<OpenGLState>, width: u32, height: u32) -> RenderTarget {
+        if width == 0 || height == 0 {
+            panic!("Neither width or height can be 0.");
+        }
+        let gl = &state.gl;
+
+        let texture = TextureHandle::new(state.clone(), TextureUnit::Target);
+        texture.set_empty(width as i32, height as i32);
+
+        let depth = gl.create_renderbuffer();
+        gl.bind_renderbuffer(RenderbufferBindingTarget::Renderbuffer, Some(depth));
+        gl.renderbuffer_storage(
+            RenderbufferBindingTarget::Renderbuffer,
+            PixelInternalFormat::DepthComponent24,
+            width as i32,
+            height as i32,
+        );
+
+        let framebuffer = gl.create_framebuffer();
+        gl.bind_framebuffer(FramebufferBindingTarget::Framebuffer, Some(framebuffer));
+        gl.framebuffer_texture_2d(
+            FramebufferBindingTarget::Framebuffer,
+            FramebufferAttachment::Color0,
+            TextureLoadTarget::Texture2D,
+            Some(texture.id()),
+            0,
+        );
+        gl.framebuffer_renderbuffer(
+            FramebufferBindingTarget::Framebuffer,
+            FramebufferAttachment::Depth,
+            RenderbufferBindingTarget::Renderbuffer,
+            Some(depth),
+        );
+        if !gl.check_framebuffer_status(FramebufferBindingTarget::Framebuffer) {
+            panic!("Unable to create a complete render target framebuffer.");
+        }
+        gl.bind_framebuffer(FramebufferBindingTarget::Framebuffer, None);
+
+        let size = Vector2::new(width as f32, height as f32);
+        RenderTarget {
+            state,
+            framebuffer,
+            depth,
+            texture: UnsafeShared::new(texture),
+            size,
+            ortho: matrix_from_bounds(&size),
+            clear_color: None,
+            clear_mode: None,
+        }
+    }
+
+    pub(crate) fn bind(&mut self) {
+        self.state.target_bind(self.framebuffer, &self.size, &self.ortho);
+        if let Some(color) = self.clear_color {
+            self.state.apply_clear_color(color);
+        }
+        if let Some(mode) = self.clear_mode {
+            self.state.gl.clear(mode);
+        }
+    }
+
+    pub(crate) fn framebuffer(&self) -> resource::Framebuffer {
+        self.framebuffer
+    }
+
+    pub(crate) fn texture_handle(&self) -> UnsafeShared<TextureHandle> {
+        self.texture.clone()
+    }
+
+    /// The size of the render target in pixels.
+    pub fn size(&self) -> Vector2<f32> {
+        self.size
+    }
+
+    /// Sets the clear color used while this target is bound. When set to None, the window's clear
+    /// color is used. The default is None.
+    pub fn set_clear_color(&mut self, clear_color: Option<RGBA8>) {
+        self.clear_color = clear_color;
+    }
+
+    /// Sets which buffers are cleared automatically each time this target is bound. When set to
+    /// None, the target is only cleared by explicit calls to clear. The default is None.
+    pub fn set_clear_mode(&mut self, clear_mode: Option<ClearMode>) {
+        self.clear_mode = clear_mode;
+    }
+
+    /// A texture covering the whole render target. This only has meaning for sprites in a layer
+    /// whose texture source is this render target.
+    pub fn texture(&self) -> Texture {
+        // Framebuffer rows are stored bottom to top, the opposite of uploaded images, so the
+        // texture is flipped to match.
+        Texture(Vector4::new(0, u16::MAX, u16::MAX, 0))
+    }
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        if self.state.target_is_bound(self.framebuffer) {
+            self.state.target_unbind();
+        }
+        self.state.gl.delete_framebuffer(self.framebuffer);
+        self.state.gl.delete_renderbuffer(self.depth);
+    }
+}
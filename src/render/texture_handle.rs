@@ -27,6 +27,33 @@ impl TextureHandle {
         texture
     }
 
+    pub fn id(&self) -> resource::Texture {
+        self.id
+    }
+
+    /// Binds the texture to its texture unit.
+    pub fn bind(&self) {
+        let gl = &self.state.gl;
+        gl.active_texture(self.unit);
+        gl.bind_texture(TextureBindingTarget::Texture2D, Some(self.id));
+    }
+
+    /// Resizes the texture, leaving its contents undefined.
+    pub fn set_empty(&self, width: i32, height: i32) {
+        self.bind();
+        self.state.gl.tex_image_2d_empty(
+            TextureLoadTarget::Texture2D,
+            0,
+            width,
+            height,
+            0,
+            PixelInternalFormat::RGBA,
+            PixelFormat::RGBA,
+            PixelType::UnsignedByte,
+        );
+        self.set_parameters();
+    }
+
     pub fn set_texture(&self, texture: &Image) {
         let width = texture.width() as i32;
         let height = texture.height() as i32;
@@ -34,11 +61,31 @@ impl TextureHandle {
         self.set_raw(width, height, slice);
     }
 
+    /// Replaces a region of the texture's contents without resizing it.
+    pub fn set_region(&self, x: u32, y: u32, texture: &Image) {
+        self.bind();
+        self.state.gl.tex_sub_image_2d(
+            TextureLoadTarget::Texture2D,
+            0,
+            x as i32,
+            y as i32,
+            texture.width() as i32,
+            texture.height() as i32,
+            PixelFormat::RGBA,
+            PixelType::UnsignedByte,
+            texture.as_slice(),
+        );
+    }
+
+    /// Generates mipmaps from the texture's current contents.
+    pub fn generate_mipmaps(&self) {
+        self.bind();
+        self.state.gl.generate_mipmap(TextureBindingTarget::Texture2D);
+    }
+
     fn set_raw<T: Sized>(&self, width: i32, height: i32, buffer: &[T]) {
-        let gl = &self.state.gl;
-        gl.active_texture(self.unit);
-        gl.bind_texture(TextureBindingTarget::Texture2D, Some(self.id));
-        gl.tex_image_2d(
+        self.bind();
+        self.state.gl.tex_image_2d(
             TextureLoadTarget::Texture2D,
             0,
             width,
@@ -49,6 +96,11 @@ impl TextureHandle {
             PixelType::UnsignedByte,
             buffer,
         );
+        self.set_parameters();
+    }
+
+    fn set_parameters(&self) {
+        let gl = &self.state.gl;
         gl.tex_parameter_wrap_s(TextureParameterTarget::Texture2D, TextureWrapValue::ClampToEdge);
         gl.tex_parameter_wrap_t(TextureParameterTarget::Texture2D, TextureWrapValue::ClampToEdge);
         gl.tex_parameter_min_filter(TextureParameterTarget::Texture2D, TextureMinFilterValue::Nearest);
@@ -0,0 +1,125 @@
+use crate::render::raw::{
+    resource, BlendFactor, Capability, ClearMode, DrawMode, FramebufferBindingTarget, TextureUnit,
+};
+use crate::render::shader;
+use crate::render::target::RenderTarget;
+use crate::render::OpenGLState;
+use crate::utility::bad::UnsafeShared;
+
+/// Accumulates what a layer draws across frames. Each time the layer is drawn, the previous
+/// frame's trail is faded into a fresh target, the layer's sprites are drawn over it, and the
+/// result is drawn over the framebuffer. The trail is stored premultiplied so it fades to
+/// transparent instead of to black.
+pub(crate) struct Trail {
+    state: UnsafeShared<OpenGLState>,
+    fade: resource::Program,
+    fade_texture: Option<resource::UniformLocation>,
+    fade_decay: Option<resource::UniformLocation>,
+    copy: resource::Program,
+    copy_texture: Option<resource::UniformLocation>,
+    vao: resource::VertexArray,
+    targets: Vec<RenderTarget>,
+    current: usize,
+    decay: f32,
+    framebuffer: Option<resource::Framebuffer>,
+}
+
+impl Trail {
+    pub fn new(state: UnsafeShared<OpenGLState>, decay: f32) -> Trail {
+        let gl = &state.gl;
+        let fade = gl.shader_program(shader::post::VERTEX, shader::post::FADE);
+        let fade_texture = gl.get_uniform_location(fade, "tex");
+        let fade_decay = gl.get_uniform_location(fade, "decay");
+        let copy = gl.shader_program(shader::post::VERTEX, shader::post::COPY);
+        let copy_texture = gl.get_uniform_location(copy, "tex");
+        let vao = gl.create_vertex_array();
+        Trail {
+            state,
+            fade,
+            fade_texture,
+            fade_decay,
+            copy,
+            copy_texture,
+            vao,
+            targets: Vec::new(),
+            current: 0,
+            decay: decay.clamp(0.0, 1.0),
+            framebuffer: None,
+        }
+    }
+
+    pub fn set_decay(&mut self, decay: f32) {
+        self.decay = decay.clamp(0.0, 1.0);
+    }
+
+    /// Redirects drawing into the next trail target, which starts as the faded previous trail.
+    pub fn begin(&mut self) {
+        let (framebuffer, size) = self.state.framebuffer();
+        self.framebuffer = framebuffer;
+        // A new trail starts empty, which fading by 0 takes care of.
+        let mut decay = self.decay;
+        if self.targets.first().map(|target| target.size()) != Some(size) {
+            self.targets.clear();
+            for _ in 0..2 {
+                self.targets.push(RenderTarget::new(self.state.clone(), size.x as u32, size.y as u32));
+            }
+            decay = 0.0;
+        }
+
+        let gl = &self.state.gl;
+        let previous = &self.targets[self.current];
+        self.current = 1 - self.current;
+        gl.bind_framebuffer(
+            FramebufferBindingTarget::Framebuffer,
+            Some(self.targets[self.current].framebuffer()),
+        );
+        gl.clear(ClearMode::DEPTH);
+        gl.disable(Capability::DepthTest);
+        gl.disable(Capability::Blend);
+        gl.bind_vertex_array(Some(self.vao));
+        gl.use_program(Some(self.fade));
+        previous.texture_handle().bind();
+        gl.uniform_1i(self.fade_texture.as_ref(), texture_unit());
+        gl.uniform_1f(self.fade_decay.as_ref(), decay);
+        gl.draw_arrays(DrawMode::Triangles, 0, 3);
+
+        gl.enable(Capability::DepthTest);
+        gl.enable(Capability::Blend);
+        gl.blend_func_separate(
+            BlendFactor::SrcAlpha,
+            BlendFactor::OneMinusSrcAlpha,
+            BlendFactor::One,
+            BlendFactor::OneMinusSrcAlpha,
+        );
+        self.state.shader_bind();
+    }
+
+    /// Restores the framebuffer and draws the trail over it.
+    pub fn end(&mut self) {
+        let gl = &self.state.gl;
+        gl.bind_framebuffer(FramebufferBindingTarget::Framebuffer, self.framebuffer);
+        gl.disable(Capability::DepthTest);
+        gl.blend_func(BlendFactor::One, BlendFactor::OneMinusSrcAlpha);
+        gl.bind_vertex_array(Some(self.vao));
+        gl.use_program(Some(self.copy));
+        self.targets[self.current].texture_handle().bind();
+        gl.uniform_1i(self.copy_texture.as_ref(), texture_unit());
+        gl.draw_arrays(DrawMode::Triangles, 0, 3);
+
+        gl.enable(Capability::DepthTest);
+        gl.blend_func(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha);
+        self.state.shader_bind();
+    }
+}
+
+impl Drop for Trail {
+    fn drop(&mut self) {
+        self.state.gl.delete_program(self.fade);
+        self.state.gl.delete_program(self.copy);
+        self.state.gl.delete_vertex_array(self.vao);
+    }
+}
+
+fn texture_unit() -> i32 {
+    (TextureUnit::Target as u32 - TextureUnit::Atlas as u32) as i32
+}
@@ -1,3 +1,4 @@
+use crate::render::primitive::PrimitiveVertex;
 use crate::render::raw::{AttributeType, OpenGL};
 use crate::types::*;
 use core::mem;
@@ -12,71 +13,199 @@ impl VertexDescription for Sprite {
     const VERTEX_SIZE: usize = mem::size_of::<Self>();
 
     fn configure_vertex_attribute(gl: &OpenGL) {
-        let mut index = 0;
+        sprite_attributes(gl, Self::VERTEX_SIZE as i32);
+    }
+}
+
+/// Gradient sprites are a sprite followed by its corner colors, so they share the sprite's
+/// attributes and add one per corner. Other sprites leave the corner attributes disabled, so they
+/// read the white set by the layer before drawing.
+impl VertexDescription for GradientSprite {
+    const VERTEX_SIZE: usize = mem::size_of::<Self>();
+
+    fn configure_vertex_attribute(gl: &OpenGL) {
+        let first = sprite_attributes(gl, Self::VERTEX_SIZE as i32);
+        let offset = mem::size_of::<Sprite>() as i32;
+
+        // Corner colors
+        for corner in 0..4 {
+            let index = first + corner;
+            gl.enable_vertex_attrib_array(index);
+            gl.vertex_attrib_divisor(index, 1);
+            gl.vertex_attrib_pointer_f32(
+                index,
+                4,
+                AttributeType::UnsignedByte,
+                true,
+                Self::VERTEX_SIZE as i32,
+                offset + corner as i32 * 4,
+            );
+        }
+    }
+}
+
+/// Configures the sprite's attributes with the given stride, returning the next free attribute.
+fn sprite_attributes(gl: &OpenGL, stride: i32) -> u32 {
+    let mut index = 0;
+    let mut size = 0;
+
+    // Position
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 3, AttributeType::Float, false, stride, size);
+    index += 1;
+    size += 3 * 4;
+
+    // Size
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 2, AttributeType::UnsignedShort, true, stride, size);
+    index += 1;
+    size += 2 * 2;
+
+    // UV
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 4, AttributeType::UnsignedShort, true, stride, size);
+    index += 1;
+    size += 4 * 2;
+
+    // RGBA8
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 4, AttributeType::UnsignedByte, true, stride, size);
+    index += 1;
+    size += 4;
+
+    // Rotation
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 1, AttributeType::UnsignedShort, true, stride, size);
+    index += 1;
+    size += 2;
+
+    // Visible
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 1, AttributeType::UnsignedByte, false, stride, size);
+    index += 1;
+    size += 1;
+
+    // Effect
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 1, AttributeType::UnsignedByte, false, stride, size);
+    index += 1;
+    size += 1;
+
+    // Effect progress
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 1, AttributeType::UnsignedShort, true, stride, size);
+    index += 1;
+    size += 2;
+
+    // Depth bias
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 1, AttributeType::Short, false, stride, size);
+    index += 1;
+    size += 2;
+
+    // Scale
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 2, AttributeType::Float, false, stride, size);
+    index += 1;
+    size += 2 * 4;
+
+    // Flip
+    gl.enable_vertex_attrib_array(index);
+    gl.vertex_attrib_divisor(index, 1);
+    gl.vertex_attrib_pointer_f32(index, 2, AttributeType::UnsignedByte, false, stride, size);
+    index + 1
+}
+
+/// Instances share the sprite shader. Only position, color, and rotation come from the buffer, and
+/// the remaining attributes are left disabled so they read the template's values, which are set
+/// before drawing.
+impl VertexDescription for SpriteInstance {
+    const VERTEX_SIZE: usize = mem::size_of::<Self>();
+
+    fn configure_vertex_attribute(gl: &OpenGL) {
         let mut size = 0;
 
         // Position
-        gl.enable_vertex_attrib_array(index);
-        gl.vertex_attrib_divisor(index, 1);
-        gl.vertex_attrib_pointer_f32(index, 3, AttributeType::Float, false, Self::VERTEX_SIZE as i32, size);
-        index += 1;
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_divisor(0, 1);
+        gl.vertex_attrib_pointer_f32(0, 3, AttributeType::Float, false, Self::VERTEX_SIZE as i32, size);
         size += 3 * 4;
 
-        // Size
-        gl.enable_vertex_attrib_array(index);
-        gl.vertex_attrib_divisor(index, 1);
-        gl.vertex_attrib_pointer_f32(
-            index,
-            2,
-            AttributeType::UnsignedShort,
-            true,
-            Self::VERTEX_SIZE as i32,
-            size,
-        );
-        index += 1;
-        size += 2 * 2;
+        // RGBA8
+        gl.enable_vertex_attrib_array(3);
+        gl.vertex_attrib_divisor(3, 1);
+        gl.vertex_attrib_pointer_f32(3, 4, AttributeType::UnsignedByte, true, Self::VERTEX_SIZE as i32, size);
+        size += 4;
 
-        // UV
-        gl.enable_vertex_attrib_array(index);
-        gl.vertex_attrib_divisor(index, 1);
+        // Rotation
+        gl.enable_vertex_attrib_array(4);
+        gl.vertex_attrib_divisor(4, 1);
         gl.vertex_attrib_pointer_f32(
-            index,
             4,
+            1,
             AttributeType::UnsignedShort,
             true,
             Self::VERTEX_SIZE as i32,
             size,
         );
-        index += 1;
-        size += 4 * 2;
+    }
+}
+
+impl VertexDescription for PrimitiveVertex {
+    const VERTEX_SIZE: usize = mem::size_of::<Self>();
+
+    fn configure_vertex_attribute(gl: &OpenGL) {
+        let mut size = 0;
+
+        // Position
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_f32(0, 2, AttributeType::Float, false, Self::VERTEX_SIZE as i32, size);
+        size += 2 * 4;
 
         // RGBA8
-        gl.enable_vertex_attrib_array(index);
-        gl.vertex_attrib_divisor(index, 1);
-        gl.vertex_attrib_pointer_f32(
-            index,
-            4,
-            AttributeType::UnsignedByte,
-            true,
-            Self::VERTEX_SIZE as i32,
-            size,
-        );
-        index += 1;
-        size += 4 * 1;
+        gl.enable_vertex_attrib_array(1);
+        gl.vertex_attrib_pointer_f32(1, 4, AttributeType::UnsignedByte, true, Self::VERTEX_SIZE as i32, size);
+        size += 4;
 
-        // Rotation
-        gl.enable_vertex_attrib_array(index);
-        gl.vertex_attrib_divisor(index, 1);
-        gl.vertex_attrib_pointer_f32(
-            index,
-            1,
-            AttributeType::UnsignedShort,
-            true,
-            Self::VERTEX_SIZE as i32,
-            size,
-        );
-        //index += 1;
-        // size += 1 * 2;
-        // warn!("{}, {}", size, core::mem::size_of::<Sprite>()); // DEBUG
+        // Local position
+        gl.enable_vertex_attrib_array(2);
+        gl.vertex_attrib_pointer_f32(2, 2, AttributeType::Float, false, Self::VERTEX_SIZE as i32, size);
+        size += 2 * 4;
+
+        // Shape
+        gl.enable_vertex_attrib_array(3);
+        gl.vertex_attrib_pointer_f32(3, 4, AttributeType::Float, false, Self::VERTEX_SIZE as i32, size);
+    }
+}
+
+impl VertexDescription for MeshVertex {
+    const VERTEX_SIZE: usize = mem::size_of::<Self>();
+
+    fn configure_vertex_attribute(gl: &OpenGL) {
+        let mut size = 0;
+
+        // Position
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_f32(0, 3, AttributeType::Float, false, Self::VERTEX_SIZE as i32, size);
+        size += 3 * 4;
+
+        // UV
+        gl.enable_vertex_attrib_array(1);
+        gl.vertex_attrib_pointer_f32(1, 2, AttributeType::Float, false, Self::VERTEX_SIZE as i32, size);
+        size += 2 * 4;
+
+        // RGBA8
+        gl.enable_vertex_attrib_array(2);
+        gl.vertex_attrib_pointer_f32(2, 4, AttributeType::UnsignedByte, true, Self::VERTEX_SIZE as i32, size);
     }
 }
@@ -7,3 +7,26 @@ pub use self::native::OpenGLWindow;
 mod wasm;
 #[cfg(target_arch = "wasm32")]
 pub use self::wasm::OpenGLWindow;
+
+use crate::types::CursorShape;
+use winit::window::CursorIcon;
+
+fn cursor_icon(shape: CursorShape) -> CursorIcon {
+    match shape {
+        CursorShape::Default => CursorIcon::Default,
+        CursorShape::Pointer => CursorIcon::Hand,
+        CursorShape::Text => CursorIcon::Text,
+        CursorShape::Crosshair => CursorIcon::Crosshair,
+        CursorShape::Move => CursorIcon::Move,
+        CursorShape::Grab => CursorIcon::Grab,
+        CursorShape::Grabbing => CursorIcon::Grabbing,
+        CursorShape::Wait => CursorIcon::Wait,
+        CursorShape::Progress => CursorIcon::Progress,
+        CursorShape::Help => CursorIcon::Help,
+        CursorShape::NotAllowed => CursorIcon::NotAllowed,
+        CursorShape::ResizeHorizontal => CursorIcon::EwResize,
+        CursorShape::ResizeVertical => CursorIcon::NsResize,
+        CursorShape::ResizeDiagonal => CursorIcon::NwseResize,
+        CursorShape::ResizeAntiDiagonal => CursorIcon::NeswResize,
+    }
+}
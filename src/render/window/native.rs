@@ -1,17 +1,37 @@
+use crate::input::FileDrop;
 use crate::types::*;
 use cgmath::*;
 use glutin::ContextBuilder;
-use winit::dpi::LogicalSize;
+use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
 use winit::event_loop::EventLoop;
-use winit::window::{Fullscreen, Window, WindowBuilder};
+use winit::window::{Fullscreen, Icon, Window, WindowBuilder};
+
+/// The smallest size a window region can resize the window to, in physical pixels.
+const MIN_RESIZE: i32 = 64;
+
+/// A move or resize in progress. Winit can't hand these off to the platform, so they follow the
+/// cursor instead.
+struct RegionState {
+    kind: WindowRegionKind,
+    cursor: PhysicalPosition<f64>,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+}
 
 pub struct OpenGLWindow {
     inner: glutin::ContextWrapper<glutin::PossiblyCurrent, Window>,
+    regions: Vec<WindowRegion>,
+    cursor: PhysicalPosition<f64>,
+    active: Option<RegionState>,
+    /// Winit can't tell if the window is maximized, so this tracks maximizing done through the
+    /// engine, along with the bounds to restore to.
+    maximized: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
 }
 
 impl OpenGLWindow {
     pub fn new(desc: &WindowSettings, event_loop: &EventLoop<()>) -> (OpenGLWindow, glow::Context) {
-        let mut window_builder = WindowBuilder::new().with_title(&desc.title);
+        let mut window_builder =
+            WindowBuilder::new().with_title(&desc.title).with_decorations(desc.decorations);
         match desc.display_mode {
             DisplayMode::Windowed {
                 width,
@@ -26,6 +46,14 @@ impl OpenGLWindow {
                 window_builder = window_builder.with_fullscreen(Some(fullscreen));
             }
         }
+        if let Some(icon) = desc.icon.as_ref().and_then(winit_icon) {
+            #[cfg(target_os = "windows")]
+            {
+                use winit::platform::windows::WindowBuilderExtWindows;
+                window_builder = window_builder.with_taskbar_icon(Some(icon.clone()));
+            }
+            window_builder = window_builder.with_window_icon(Some(icon));
+        }
         let mut context_builder = ContextBuilder::new();
         match desc.vsync {
             Vsync::Disabled => {
@@ -35,7 +63,10 @@ impl OpenGLWindow {
                 context_builder = context_builder.with_vsync(true);
             }
         }
-        let window_context = context_builder.build_windowed(window_builder, &event_loop).unwrap();
+        if desc.multisampling > 0 {
+            context_builder = context_builder.with_multisampling(desc.multisampling);
+        }
+        let window_context = context_builder.build_windowed(window_builder, event_loop).unwrap();
         let window_context = unsafe { window_context.make_current() }.unwrap();
         let gl = unsafe {
             glow::Context::from_loader_function(|s| window_context.get_proc_address(s) as *const _)
@@ -44,6 +75,10 @@ impl OpenGLWindow {
         (
             OpenGLWindow {
                 inner: window_context,
+                regions: Vec::new(),
+                cursor: PhysicalPosition::new(0.0, 0.0),
+                active: None,
+                maximized: None,
             },
             gl,
         )
@@ -72,10 +107,65 @@ impl OpenGLWindow {
         self.inner.swap_buffers().unwrap();
     }
 
+    /// The refresh rate of the monitor the window is on, in Hz. Winit only lists the modes a monitor
+    /// supports, so this is the highest rate at the monitor's current resolution.
+    pub fn refresh_rate(&self) -> Option<f32> {
+        let monitor = self.inner.window().current_monitor()?;
+        let size = monitor.size();
+        let rate =
+            monitor.video_modes().filter(|mode| mode.size() == size).map(|mode| mode.refresh_rate()).max()?;
+        if rate > 0 {
+            Some(rate as f32)
+        } else {
+            None
+        }
+    }
+
+    /// Native windows don't report insets yet.
+    pub fn safe_area(&self) -> SafeArea {
+        SafeArea::default()
+    }
+
+    pub fn set_cursor_grab(&self, grab: bool) -> bool {
+        match self.inner.window().set_cursor_grab(grab) {
+            Ok(()) => true,
+            Err(error) => {
+                warn!("Unable to change the cursor grab: {}", error);
+                false
+            }
+        }
+    }
+
+    /// Moves the IME candidate window, in physical pixels from the top left of the window.
+    pub fn set_ime_position(&self, pos: PhysicalPosition<f64>) {
+        self.inner.window().set_ime_position(pos);
+    }
+
+    /// Shows a platform cursor, or hides the cursor with None.
+    pub fn set_cursor(&self, shape: Option<CursorShape>) {
+        match shape {
+            Some(shape) => {
+                self.inner.window().set_cursor_icon(super::cursor_icon(shape));
+                self.inner.window().set_cursor_visible(true);
+            }
+            None => self.inner.window().set_cursor_visible(false),
+        }
+    }
+
     pub fn set_title(&self, title: &str) {
         self.inner.window().set_title(title);
     }
 
+    pub fn set_icon(&self, icon: Option<&WindowIcon>) {
+        let icon = icon.and_then(winit_icon);
+        #[cfg(target_os = "windows")]
+        {
+            use winit::platform::windows::WindowExtWindows;
+            self.inner.window().set_taskbar_icon(icon.clone());
+        }
+        self.inner.window().set_window_icon(icon);
+    }
+
     pub fn set_display_mode(&self, display_mode: DisplayMode) {
         match display_mode {
             DisplayMode::Windowed {
@@ -93,4 +183,160 @@ impl OpenGLWindow {
             }
         }
     }
+
+    pub fn set_maximized(&mut self, maximized: bool) {
+        let window = self.inner.window();
+        if maximized && self.maximized.is_none() {
+            let position = window.outer_position().unwrap_or_else(|_| PhysicalPosition::new(0, 0));
+            self.maximized = Some((position, window.inner_size()));
+        } else if !maximized {
+            self.maximized = None;
+        }
+        window.set_maximized(maximized);
+    }
+
+    pub fn placement(&self) -> Option<WindowPlacement> {
+        let window = self.inner.window();
+        let (position, size) = match self.maximized {
+            Some(bounds) => bounds,
+            None => (window.outer_position().ok()?, window.inner_size()),
+        };
+        let monitor = window.current_monitor();
+        let origin =
+            monitor.as_ref().map(|monitor| monitor.position()).unwrap_or(PhysicalPosition::new(0, 0));
+        Some(WindowPlacement {
+            monitor: monitor.and_then(|monitor| monitor.name()),
+            position: Vector2::new(position.x - origin.x, position.y - origin.y),
+            size: Vector2::new(size.width, size.height),
+            maximized: self.maximized.is_some(),
+        })
+    }
+
+    /// Restores a placement on the monitor with the same name, or the primary monitor if it's
+    /// gone. The window is kept inside the monitor in case the monitor's resolution changed.
+    pub fn set_placement(&mut self, placement: &WindowPlacement) {
+        let window = self.inner.window();
+        let monitor = window
+            .available_monitors()
+            .find(|monitor| placement.monitor.is_some() && monitor.name() == placement.monitor)
+            .or_else(|| window.primary_monitor());
+        let mut size = PhysicalSize::new(placement.size.x.max(1), placement.size.y.max(1));
+        let mut position = PhysicalPosition::new(placement.position.x, placement.position.y);
+        if let Some(monitor) = monitor {
+            let origin = monitor.position();
+            let bounds = monitor.size();
+            size.width = size.width.min(bounds.width);
+            size.height = size.height.min(bounds.height);
+            position.x = origin.x + position.x.clamp(0, (bounds.width - size.width) as i32);
+            position.y = origin.y + position.y.clamp(0, (bounds.height - size.height) as i32);
+        }
+        window.set_maximized(false);
+        window.set_inner_size(size);
+        window.set_outer_position(position);
+        self.maximized = None;
+        if placement.maximized {
+            self.set_maximized(true);
+        }
+    }
+
+    pub fn set_regions(&mut self, regions: &[WindowRegion]) {
+        self.regions.clear();
+        self.regions.extend_from_slice(regions);
+    }
+
+    /// Starts moving or resizing the window if the position is in a region. Returns true if a
+    /// region was pressed.
+    pub fn region_pressed(&mut self, pos: &Vector2<f32>) -> bool {
+        let window = self.inner.window();
+        let region = match self.regions.iter().rev().find(|region| region.bounds.contains_point(pos)) {
+            Some(region) => *region,
+            None => return false,
+        };
+        let position = match window.outer_position() {
+            Ok(position) => position,
+            Err(_) => return false,
+        };
+        self.active = Some(RegionState {
+            kind: region.kind,
+            cursor: Self::screen_cursor(position, self.cursor),
+            position,
+            size: window.inner_size(),
+        });
+        true
+    }
+
+    /// Tracks the cursor and applies any move or resize in progress. The position is relative to
+    /// the window in physical pixels.
+    pub fn region_cursor_moved(&mut self, cursor: PhysicalPosition<f64>) {
+        self.cursor = cursor;
+        let active = match &self.active {
+            Some(active) => active,
+            None => return,
+        };
+        let window = self.inner.window();
+        let position = match window.outer_position() {
+            Ok(position) => position,
+            Err(_) => return,
+        };
+        // Measured on the screen, since moving the window moves the cursor relative to it.
+        let screen = Self::screen_cursor(position, cursor);
+        let delta = ((screen.x - active.cursor.x) as i32, (screen.y - active.cursor.y) as i32);
+        match active.kind {
+            WindowRegionKind::Drag => {
+                let target = PhysicalPosition::new(active.position.x + delta.0, active.position.y + delta.1);
+                if target != position {
+                    window.set_outer_position(target);
+                }
+            }
+            WindowRegionKind::Resize(edge) => {
+                let (horizontal, vertical) = edge.sides();
+                let (x, width) =
+                    Self::resize_axis(horizontal, delta.0, active.position.x, active.size.width as i32);
+                let (y, height) =
+                    Self::resize_axis(vertical, delta.1, active.position.y, active.size.height as i32);
+                if x != position.x || y != position.y {
+                    window.set_outer_position(PhysicalPosition::new(x, y));
+                }
+                window.set_inner_size(PhysicalSize::new(width as u32, height as u32));
+            }
+        }
+    }
+
+    pub fn region_released(&mut self) {
+        self.active = None;
+    }
+
+    /// Drag and drop steps are sent as window events instead.
+    pub fn take_file_drops(&mut self) -> Vec<FileDrop> {
+        Vec::new()
+    }
+
+    fn screen_cursor(
+        position: PhysicalPosition<i32>,
+        cursor: PhysicalPosition<f64>,
+    ) -> PhysicalPosition<f64> {
+        PhysicalPosition::new(position.x as f64 + cursor.x, position.y as f64 + cursor.y)
+    }
+
+    /// Moves one side of the window along an axis, returning the new start and length.
+    fn resize_axis(side: i32, delta: i32, start: i32, length: i32) -> (i32, i32) {
+        match side {
+            -1 => {
+                let length_new = (length - delta).max(MIN_RESIZE);
+                (start + length - length_new, length_new)
+            }
+            1 => (start, (length + delta).max(MIN_RESIZE)),
+            _ => (start, length),
+        }
+    }
+}
+
+fn winit_icon(icon: &WindowIcon) -> Option<Icon> {
+    match Icon::from_rgba(icon.rgba.clone(), icon.width, icon.height) {
+        Ok(icon) => Some(icon),
+        Err(error) => {
+            warn!("Unable to use the window icon: {}", error);
+            None
+        }
+    }
 }
@@ -1,5 +1,9 @@
+use crate::input::{DroppedFile, FileDrop};
 use crate::types::*;
 use cgmath::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use winit::dpi::LogicalSize;
 use winit::event_loop::EventLoop;
@@ -8,6 +12,8 @@ use winit::window::{Fullscreen, Window, WindowBuilder};
 
 pub struct OpenGLWindow {
     inner: Window,
+    /// Filled by the canvas's drag and drop listeners.
+    file_drops: Rc<RefCell<Vec<FileDrop>>>,
 }
 
 impl OpenGLWindow {
@@ -26,8 +32,11 @@ impl OpenGLWindow {
         let winit_window = builder.build(event_loop).expect("Window build");
 
         let canvas = winit_window.canvas();
+        let options = js_sys::Object::new();
+        js_sys::Reflect::set(&options, &"antialias".into(), &(desc.multisampling > 0).into())
+            .expect("Set webgl2 context options");
         let webgl2_context = canvas
-            .get_context("webgl2") // Result<Option<Object>, JsValue>
+            .get_context_with_context_options("webgl2", &options) // Result<Option<Object>, JsValue>
             .expect("Get webgl2 context A") // Option<Object>
             .expect("Get webgl2 context B") // Object
             .dyn_into::<web_sys::WebGl2RenderingContext>() // Result<WebGl2RenderingContext, Object>
@@ -39,8 +48,12 @@ impl OpenGLWindow {
         let body = document.body().unwrap();
         body.append_child(&canvas).expect("Append canvas to HTML body");
 
+        let file_drops = Rc::new(RefCell::new(Vec::new()));
+        listen_file_drops(&canvas, &file_drops);
+
         let window = OpenGLWindow {
             inner: winit_window,
+            file_drops,
         };
         window.set_title(&desc.title);
         window.set_display_mode(desc.display_mode);
@@ -65,6 +78,71 @@ impl OpenGLWindow {
         // This is implicit on web.
     }
 
+    /// Browsers don't expose the display's refresh rate.
+    pub fn refresh_rate(&self) -> Option<f32> {
+        None
+    }
+
+    /// Reads the CSS safe area insets through a hidden probe element.
+    pub fn safe_area(&self) -> SafeArea {
+        let window = web_sys::window().unwrap();
+        let document = window.document().unwrap();
+        let body = document.body().unwrap();
+        let probe = document.create_element("div").expect("Create safe area probe");
+        probe
+            .set_attribute(
+                "style",
+                "position: fixed; visibility: hidden; pointer-events: none; \
+                 padding: env(safe-area-inset-top) env(safe-area-inset-right) \
+                 env(safe-area-inset-bottom) env(safe-area-inset-left);",
+            )
+            .expect("Style safe area probe");
+        body.append_child(&probe).expect("Append safe area probe");
+        let style = window.get_computed_style(&probe).ok().flatten();
+        let inset = |property: &str| -> f32 {
+            style
+                .as_ref()
+                .and_then(|style| style.get_property_value(property).ok())
+                .and_then(|value| value.trim().trim_end_matches("px").parse::<f32>().ok())
+                .unwrap_or(0.0)
+        };
+        let safe_area = SafeArea::new(
+            inset("padding-left"),
+            inset("padding-right"),
+            inset("padding-top"),
+            inset("padding-bottom"),
+        );
+        let _ = body.remove_child(&probe);
+        safe_area
+    }
+
+    /// Browsers take the icon from the page.
+    pub fn set_icon(&self, _icon: Option<&WindowIcon>) {}
+
+    pub fn set_cursor_grab(&self, grab: bool) -> bool {
+        match self.inner.set_cursor_grab(grab) {
+            Ok(()) => true,
+            Err(error) => {
+                warn!("Unable to change the cursor grab: {}", error);
+                false
+            }
+        }
+    }
+
+    /// Browsers place the IME themselves.
+    pub fn set_ime_position(&self, _pos: winit::dpi::PhysicalPosition<f64>) {}
+
+    /// Shows a platform cursor, or hides the cursor with None.
+    pub fn set_cursor(&self, shape: Option<CursorShape>) {
+        match shape {
+            Some(shape) => {
+                self.inner.set_cursor_icon(super::cursor_icon(shape));
+                self.inner.set_cursor_visible(true);
+            }
+            None => self.inner.set_cursor_visible(false),
+        }
+    }
+
     pub fn set_title(&self, title: &str) {
         web_sys::window() // Option<Window>
             .unwrap() // Window
@@ -90,4 +168,94 @@ impl OpenGLWindow {
             }
         }
     }
+
+    /// Browsers manage the page, so the window can't be maximized or placed.
+    pub fn set_maximized(&mut self, _maximized: bool) {}
+
+    pub fn placement(&self) -> Option<WindowPlacement> {
+        None
+    }
+
+    pub fn set_placement(&mut self, _placement: &WindowPlacement) {}
+
+    /// Browsers manage the page, so window regions do nothing.
+    pub fn set_regions(&mut self, _regions: &[WindowRegion]) {}
+
+    pub fn region_pressed(&mut self, _pos: &Vector2<f32>) -> bool {
+        false
+    }
+
+    pub fn region_cursor_moved(&mut self, _cursor: winit::dpi::PhysicalPosition<f64>) {}
+
+    pub fn region_released(&mut self) {}
+
+    /// Takes the drag and drop steps reported since the last call.
+    pub fn take_file_drops(&mut self) -> Vec<FileDrop> {
+        core::mem::take(&mut *self.file_drops.borrow_mut())
+    }
+}
+
+/// Listens for files dragged onto the canvas. Dropped files are read in the background, and are
+/// queued once their contents are loaded.
+fn listen_file_drops(canvas: &web_sys::HtmlCanvasElement, drops: &Rc<RefCell<Vec<FileDrop>>>) {
+    let hovered = drops.clone();
+    let on_drag_over = Closure::wrap(Box::new(move |event: web_sys::DragEvent| {
+        // The browser only allows a drop if the drag over event is cancelled.
+        event.prevent_default();
+        hovered.borrow_mut().push(FileDrop::Hovered);
+    }) as Box<dyn FnMut(web_sys::DragEvent)>);
+    let cancelled = drops.clone();
+    let on_drag_leave = Closure::wrap(Box::new(move |_event: web_sys::DragEvent| {
+        cancelled.borrow_mut().push(FileDrop::Cancelled);
+    }) as Box<dyn FnMut(web_sys::DragEvent)>);
+    let dropped = drops.clone();
+    let on_drop = Closure::wrap(Box::new(move |event: web_sys::DragEvent| {
+        // Keeps the browser from opening the file.
+        event.prevent_default();
+        let files = match event.data_transfer().and_then(|transfer| transfer.files()) {
+            Some(files) if files.length() > 0 => files,
+            _ => {
+                dropped.borrow_mut().push(FileDrop::Cancelled);
+                return;
+            }
+        };
+        for index in 0..files.length() {
+            let file = match files.get(index) {
+                Some(file) => file,
+                None => continue,
+            };
+            let reader = match web_sys::FileReader::new() {
+                Ok(reader) => reader,
+                Err(_) => continue,
+            };
+            let name = file.name();
+            let loaded = dropped.clone();
+            let source = reader.clone();
+            let on_load_end = Closure::once_into_js(move || match source.result() {
+                Ok(result) if result.is_instance_of::<js_sys::ArrayBuffer>() => {
+                    let bytes = js_sys::Uint8Array::new(&result).to_vec();
+                    loaded.borrow_mut().push(FileDrop::Dropped(DroppedFile::Bytes {
+                        name,
+                        bytes,
+                    }));
+                }
+                _ => warn!("Unable to read dropped file {}.", name),
+            });
+            reader.set_onloadend(Some(on_load_end.unchecked_ref()));
+            if reader.read_as_array_buffer(&file).is_err() {
+                warn!("Unable to read dropped file {}.", file.name());
+            }
+        }
+    }) as Box<dyn FnMut(web_sys::DragEvent)>);
+    for (name, listener) in
+        [("dragover", &on_drag_over), ("dragleave", &on_drag_leave), ("drop", &on_drop)].iter()
+    {
+        canvas
+            .add_event_listener_with_callback(name, listener.as_ref().unchecked_ref())
+            .expect("Add drag and drop listener");
+    }
+    // The listeners live as long as the page.
+    on_drag_over.forget();
+    on_drag_leave.forget();
+    on_drop.forget();
 }
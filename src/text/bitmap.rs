@@ -0,0 +1,294 @@
+use crate::texture::*;
+use crate::*;
+use cgmath::*;
+use hashbrown::HashMap;
+
+/// A glyph cut from a bitmap font's page. Offsets and sizes are in the font's pixels.
+#[derive(Debug, Copy, Clone)]
+struct BitmapGlyph {
+    uv: Vector4<u16>,
+    size: Vector2<f32>,
+    /// Offset from the top of the line to the top of the glyph, and from the pen to its left.
+    offset: Vector2<f32>,
+    advance: f32,
+}
+
+/// A character entry of an AngelCode BMFont file.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+struct FntChar {
+    id: u32,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    xoffset: i32,
+    yoffset: i32,
+    xadvance: i32,
+    page: usize,
+}
+
+/// The parts of an AngelCode BMFont file used for layout.
+#[derive(Debug, Default, PartialEq)]
+struct FntFile {
+    size: u32,
+    line_height: u32,
+    base: u32,
+    chars: Vec<FntChar>,
+    kernings: Vec<(u32, u32, i32)>,
+}
+
+/// A font of pre-drawn glyphs, loaded from an AngelCode BMFont file and its page images. Glyphs
+/// are drawn exactly as they appear in the pages when the text's scale matches the font's size.
+pub struct BitmapFont {
+    size: f32,
+    line_height: f32,
+    base: f32,
+    glyphs: HashMap<char, BitmapGlyph>,
+    kernings: HashMap<(char, char), f32>,
+}
+
+impl BitmapFont {
+    /// Parses the text form of a BMFont file and adds each glyph to the atlas.
+    pub fn new(atlas: &mut TextureAtlas, fnt: &[u8], pages: &[Image]) -> BitmapFont {
+        let text = core::str::from_utf8(fnt).expect("BMFont file isn't valid UTF-8.");
+        let file = parse(text);
+        let mut glyphs = HashMap::with_capacity(file.chars.len());
+        for entry in &file.chars {
+            let c = match char::from_u32(entry.id) {
+                Some(c) => c,
+                None => continue,
+            };
+            let uv = if entry.width > 0 && entry.height > 0 {
+                let page = pages.get(entry.page).expect("BMFont glyph references a missing page.");
+                if entry.x + entry.width > page.width() || entry.y + entry.height > page.height() {
+                    panic!("BMFont glyph {} is outside of its page.", entry.id);
+                }
+                let mut pixels = Vec::with_capacity((entry.width * entry.height) as usize);
+                for y in entry.y..entry.y + entry.height {
+                    for x in entry.x..entry.x + entry.width {
+                        pixels.push(page.get(x, y));
+                    }
+                }
+                atlas.add(Image::from_vec(pixels, entry.width, entry.height))
+            } else {
+                Vector4::new(0, 0, 0, 0)
+            };
+            glyphs.insert(
+                c,
+                BitmapGlyph {
+                    uv,
+                    size: Vector2::new(entry.width as f32, entry.height as f32),
+                    offset: Vector2::new(entry.xoffset as f32, entry.yoffset as f32),
+                    advance: entry.xadvance as f32,
+                },
+            );
+        }
+        let kernings = file
+            .kernings
+            .iter()
+            .filter_map(|&(first, second, amount)| {
+                Some(((char::from_u32(first)?, char::from_u32(second)?), amount as f32))
+            })
+            .collect();
+        info!("Loaded bitmap font with {} glyphs.", glyphs.len());
+        BitmapFont {
+            size: if file.size > 0 {
+                file.size
+            } else {
+                file.line_height
+            } as f32,
+            line_height: file.line_height as f32,
+            base: file.base as f32,
+            glyphs,
+            kernings,
+        }
+    }
+
+    /// How much the font's pixels are scaled by to draw at the text's scale.
+    fn factor(&self, desc: &Text) -> f32 {
+        desc.scale as f32 / self.size.max(1.0)
+    }
+
+    /// Lays out the text, left aligned, breaking lines at newlines and wrapping at spaces past the
+    /// max width. The bottom of the last line sits on the text's position.
+    pub fn glyphs(&self, desc: &Text) -> Vec<TextGlyph> {
+        let factor = self.factor(desc);
+        let line_height = self.line_height * factor;
+        let mut output: Vec<TextGlyph> = Vec::with_capacity(desc.string.len());
+        let mut line = 0.0;
+        let mut pen = 0.0;
+        let mut line_start = 0;
+        let mut previous: Option<char> = None;
+        for (index, (byte, c)) in desc.string.char_indices().enumerate() {
+            let advance = match self.glyphs.get(&c) {
+                Some(glyph) if c != '\n' => glyph.advance * factor,
+                _ => 0.0,
+            };
+            if let Some(kerning) = previous.and_then(|previous| self.kernings.get(&(previous, c))) {
+                pen += kerning * factor;
+            }
+            // Wrap after the last space on the line, or before this character if there isn't one.
+            if let (Some(max_width), false) = (desc.max_width, c == ' ' || c == '\n') {
+                if pen + advance > max_width && index > line_start {
+                    let space = output[line_start..]
+                        .iter()
+                        .rposition(|glyph: &TextGlyph| desc.string[glyph.bytes.clone()].starts_with(' '));
+                    let start = match space {
+                        Some(space) => line_start + space + 1,
+                        None => index,
+                    };
+                    line += 1.0;
+                    let shift = output.get(start).map(|glyph| glyph.pos.x).unwrap_or(pen);
+                    for glyph in &mut output[start..] {
+                        glyph.pos.x -= shift;
+                        glyph.pos.y = -line * line_height;
+                    }
+                    pen -= shift;
+                    line_start = start;
+                }
+            }
+            output.push(TextGlyph {
+                index,
+                bytes: byte..byte + c.len_utf8(),
+                pos: Vector2::new(pen, -line * line_height),
+                size: Vector2::new(advance, line_height),
+                baseline: 0.0,
+                rtl: false,
+            });
+            pen += advance;
+            previous = Some(c);
+            if c == '\n' {
+                line += 1.0;
+                pen = 0.0;
+                line_start = index + 1;
+                previous = None;
+            }
+        }
+        // Lines were placed downward from 0, so move them up onto the position.
+        let lines = output.last().map(|glyph| -glyph.pos.y / line_height).unwrap_or(0.0);
+        let descent = (self.line_height - self.base) * factor;
+        for glyph in &mut output {
+            glyph.pos.x += desc.pos.x;
+            glyph.pos.y += desc.pos.y + lines * line_height;
+            glyph.baseline = glyph.pos.y + descent;
+        }
+        output
+    }
+
+    /// Appends a sprite for each shown glyph, returning the bounds of the glyphs drawn or hidden.
+    pub fn rasterize(&self, desc: &Text, sprites: &mut Vec<Sprite>) -> (Vector2<f32>, Vector2<f32>) {
+        let factor = self.factor(desc);
+        let reveal = desc.reveal.unwrap_or(usize::MAX);
+        let mut min = Vector2::new(f32::MAX, f32::MAX);
+        let mut max = Vector2::new(f32::MIN, f32::MIN);
+        for layout in self.glyphs(desc) {
+            let c = match desc.string[layout.bytes.clone()].chars().next() {
+                Some(c) => c,
+                None => continue,
+            };
+            let glyph = match self.glyphs.get(&c) {
+                Some(glyph) if glyph.size.x > 0.0 && glyph.size.y > 0.0 => glyph,
+                _ => continue,
+            };
+            let top = layout.pos.y + layout.size.y;
+            let size = glyph.size * factor;
+            let pos = desc.hinting.snap(Vector2::new(
+                layout.pos.x + glyph.offset.x * factor,
+                top - glyph.offset.y * factor - size.y,
+            ));
+            if layout.index < reveal {
+                let color = desc.style_at(layout.index).0;
+                sprites.push(Sprite::new(pos.extend(desc.pos.z), size, Texture(glyph.uv), color, 0.0));
+            }
+            min.x = min.x.min(pos.x);
+            min.y = min.y.min(pos.y);
+            max.x = max.x.max(pos.x + size.x);
+            max.y = max.y.max(pos.y + size.y);
+        }
+        (min, max)
+    }
+}
+
+/// Parses the text form of a BMFont file. Unknown lines and keys are ignored.
+fn parse(text: &str) -> FntFile {
+    let mut file = FntFile::default();
+    for line in text.lines() {
+        let mut tokens = line.split_whitespace();
+        let tag = match tokens.next() {
+            Some(tag) => tag,
+            None => continue,
+        };
+        let pairs: Vec<(&str, &str)> = tokens.filter_map(|token| token.split_once('=')).collect();
+        let value = |key: &str| -> i32 {
+            pairs.iter().find(|(name, _)| *name == key).and_then(|(_, value)| value.parse().ok()).unwrap_or(0)
+        };
+        match tag {
+            "info" => file.size = value("size").unsigned_abs(),
+            "common" => {
+                file.line_height = value("lineHeight").max(0) as u32;
+                file.base = value("base").max(0) as u32;
+            }
+            "char" => file.chars.push(FntChar {
+                id: value("id").max(0) as u32,
+                x: value("x").max(0) as u32,
+                y: value("y").max(0) as u32,
+                width: value("width").max(0) as u32,
+                height: value("height").max(0) as u32,
+                xoffset: value("xoffset"),
+                yoffset: value("yoffset"),
+                xadvance: value("xadvance"),
+                page: value("page").max(0) as usize,
+            }),
+            "kerning" => file.kernings.push((
+                value("first").max(0) as u32,
+                value("second").max(0) as u32,
+                value("amount"),
+            )),
+            _ => {}
+        }
+    }
+    if file.line_height == 0 {
+        panic!("BMFont file is missing its common line.");
+    }
+    file
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FNT: &str = "info face=\"Pixel\" size=-8 bold=0\n\
+        common lineHeight=10 base=8 scaleW=16 scaleH=16 pages=1\n\
+        page id=0 file=\"pixel_0.png\"\n\
+        chars count=2\n\
+        char id=65 x=0 y=0 width=5 height=7 xoffset=0 yoffset=1 xadvance=6 page=0 chnl=15\n\
+        char id=32 x=0 y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=3 page=0 chnl=15\n\
+        kernings count=1\n\
+        kerning first=65 second=65 amount=-1\n";
+
+    #[test]
+    fn layout() {
+        let file = parse(FNT);
+        assert_eq!((file.size, file.line_height, file.base), (8, 10, 8));
+        assert_eq!(file.chars[0].yoffset, 1);
+        assert_eq!(file.kernings, vec![(65, 65, -1)]);
+
+        let mut atlas = TextureAtlas::new(64, 64);
+        let font = BitmapFont::new(&mut atlas, FNT.as_bytes(), &[Image::from_color(colors::WHITE, 16, 16)]);
+        let desc = Text {
+            string: String::from("AA A"),
+            scale: 8,
+            max_width: Some(12.0),
+            ..Text::default()
+        };
+        let glyphs = font.glyphs(&desc);
+        // "AA " stays on the first line with kerning, and the last "A" wraps.
+        assert_eq!(glyphs[1].pos, Vector2::new(5.0, 10.0));
+        assert_eq!(glyphs[3].pos, Vector2::new(0.0, 0.0));
+        assert_eq!(glyphs[3].baseline, 2.0);
+    }
+}
@@ -1,22 +1,52 @@
+use crate::text::bitmap::BitmapFont;
+use crate::text::shaping::{self, Shaped};
 use crate::texture::*;
 use crate::*;
 use cgmath::*;
+use core::ops::Range;
 use fontdue::layout::{
-    CoordinateSystem, GlyphRasterConfig, HorizontalAlign, Layout, LayoutSettings, TextStyle, VerticalAlign,
+    CoordinateSystem, GlyphPosition, GlyphRasterConfig, HorizontalAlign, Layout, LayoutSettings, TextStyle,
+    VerticalAlign,
 };
 use fontdue::{Font, FontSettings};
 use hashbrown::HashMap;
 
+/// How far behind the text its background is drawn.
+const BACKGROUND_DEPTH: f32 = 0.001;
+/// How far behind the text its shadow is drawn.
+const SHADOW_DEPTH: f32 = 0.0005;
+/// Size distance field glyphs are rasterized at, in pixels.
+const DISTANCE_FIELD_PX: f32 = 48.0;
+/// How far distance fields extend past the edge of a glyph, in pixels at the rasterized size.
+const DISTANCE_FIELD_SPREAD: i32 = 6;
+
 #[derive(Debug, Copy, Clone)]
 struct CharCacheValue {
     uv: Vector4<u16>,
     size: Vector2<f32>,
 }
 
+/// A distance field glyph. The offset is from the pen position on the baseline to the bottom left
+/// corner of the field, at the rasterized size.
+#[derive(Debug, Copy, Clone)]
+struct FieldCacheValue {
+    uv: Vector4<u16>,
+    offset: Vector2<f32>,
+    size: Vector2<f32>,
+}
+
 pub struct TextCache {
     cache: HashMap<GlyphRasterConfig, CharCacheValue>,
-    layout: Layout,
+    fields: HashMap<(usize, u16), FieldCacheValue>,
+    /// Glyphs carry the color of their span.
+    layout: Layout<RGBA8>,
+    /// The last text laid out, after shaping.
+    shaped: Shaped,
+    /// The glyphs of the last text laid out, in visual order within each line.
+    positions: Vec<GlyphPosition<RGBA8>>,
     fonts: Vec<Font>,
+    bitmaps: Vec<BitmapFont>,
+    corners: HashMap<u32, Vector4<u16>>,
     dirty: bool,
 }
 
@@ -24,8 +54,13 @@ impl TextCache {
     pub fn new() -> TextCache {
         let mut manager = TextCache {
             cache: HashMap::new(),
+            fields: HashMap::new(),
             layout: Layout::new(CoordinateSystem::PositiveYUp),
+            shaped: shaping::shape(""),
+            positions: Vec::new(),
             fonts: Vec::new(),
+            bitmaps: Vec::new(),
+            corners: HashMap::new(),
             dirty: true,
         };
         manager.add_font_bytes(include_bytes!("fonts/Roboto-Regular.ttf") as &[u8]);
@@ -43,17 +78,21 @@ impl TextCache {
         index
     }
 
-    // pub fn add_font_path(&mut self, path: &str) -> usize {
-    //     let file = File::open(Path::new(path)).expect("Unable to read path.");
-    //     let mut file = BufReader::new(file);
-    //     let mut bytes = Vec::new();
-    //     file.read_to_end(&mut bytes).expect("Unable to read bytes.");
-    //     self.add_font_bytes(&bytes)
-    // }
+    pub fn add_bitmap_font(&mut self, atlas: &mut TextureAtlas, fnt: &[u8], pages: &[Image]) -> usize {
+        self.bitmaps.push(BitmapFont::new(atlas, fnt, pages));
+        self.bitmaps.len() - 1
+    }
 
-    pub fn rasterize(&mut self, atlas: &mut TextureAtlas, desc: &Text, sprites: &mut Vec<Sprite>) {
-        let font_index = desc.font.key();
-        let font = &self.fonts[font_index];
+    /// The bitmap font the text is drawn with, if it uses one.
+    fn bitmap(&self, desc: &Text) -> Option<&BitmapFont> {
+        if desc.font.is_bitmap() {
+            self.bitmaps.get(desc.font.key())
+        } else {
+            None
+        }
+    }
+
+    fn layout(&mut self, desc: &Text) {
         self.layout.reset(&LayoutSettings {
             x: desc.pos.x,
             y: desc.pos.y,
@@ -63,13 +102,415 @@ impl TextCache {
             vertical_align: VerticalAlign::Middle,
             ..LayoutSettings::default()
         });
-        let style = TextStyle::new(&desc.string, desc.scale as f32, font_index);
-        self.layout.append(self.fonts.as_slice(), &style);
+        self.shaped = shaping::shape(&desc.string);
+        let shaped = &self.shaped;
+        // Characters with the same style are appended together as a run.
+        let mut run: Option<(usize, (RGBA8, u32, FontToken))> = None;
+        let ends =
+            shaped.string.char_indices().map(|(byte, _)| byte).chain(core::iter::once(shaped.string.len()));
+        for (index, byte) in ends.enumerate() {
+            // Bitmap fonts can't be mixed into outline text, so their spans keep the text's font.
+            let style = if byte < shaped.string.len() {
+                let (color, scale, font) = desc.style_at(shaped.sources[index].start);
+                Some((
+                    color,
+                    scale,
+                    if font.is_bitmap() {
+                        desc.font
+                    } else {
+                        font
+                    },
+                ))
+            } else {
+                None
+            };
+            match run {
+                Some((_, current)) if Some(current) == style => continue,
+                Some((start, (color, scale, font))) => {
+                    let text = &shaped.string[start..byte];
+                    let style = TextStyle::with_user_data(text, scale as f32, font.key(), color);
+                    self.layout.append(self.fonts.as_slice(), &style);
+                }
+                None => {}
+            }
+            run = style.map(|style| (byte, style));
+        }
+        self.positions.clear();
+        self.positions.extend_from_slice(self.layout.glyphs());
+        if desc.subpixel {
+            self.space(desc.max_width.is_some());
+        }
+        if !self.shaped.simple {
+            self.arrange();
+        }
+        for position in &mut self.positions {
+            let pos = desc.hinting.snap(Vector2::new(position.x, position.y));
+            position.x = pos.x;
+            position.y = pos.y;
+        }
+    }
+
+    /// Respaces the glyphs of each line by their exact advances, instead of the layout's advances
+    /// rounded up to whole pixels. Centered lines are shifted to stay centered.
+    fn space(&mut self, centered: bool) {
+        let count = self.positions.len();
+        let mut start = 0;
+        while start < count {
+            let mut line: Option<f32> = None;
+            let mut cursor = 0.0;
+            let mut rounded = 0.0;
+            let mut end = start;
+            while end < count {
+                let position = &mut self.positions[end];
+                let font = &self.fonts[position.key.font_index];
+                let metrics = font.metrics_indexed(position.key.glyph_index as usize, position.key.px);
+                let baseline = position.y - metrics.bounds.ymin;
+                let pen = position.x - metrics.bounds.xmin;
+                match line {
+                    Some(line) if (baseline - line).abs() >= 0.5 => break,
+                    Some(_) => {}
+                    None => {
+                        line = Some(baseline);
+                        cursor = pen;
+                    }
+                }
+                let advance = if position.char_data.is_control() {
+                    0.0
+                } else {
+                    metrics.advance_width
+                };
+                rounded = pen + advance.ceil();
+                position.x = cursor + metrics.bounds.xmin;
+                cursor += advance;
+                end += 1;
+            }
+            if centered {
+                let shift = (rounded - cursor) / 2.0;
+                for position in &mut self.positions[start..end] {
+                    position.x += shift;
+                }
+            }
+            start = end;
+        }
+    }
 
-        for &position in self.layout.glyphs() {
+    /// Moves the glyphs of each line from the layout's left to right order into visual order, and
+    /// centers marks over the character before them.
+    fn arrange(&mut self) {
+        let chars: Vec<char> = self.shaped.string.chars().collect();
+        let count = self.positions.len().min(chars.len());
+        let mut pens = Vec::with_capacity(count);
+        let mut offsets = Vec::with_capacity(count);
+        let mut baselines = Vec::with_capacity(count);
+        let mut advances = Vec::with_capacity(count);
+        for position in &self.positions[..count] {
+            let font = &self.fonts[position.key.font_index];
+            let metrics = font.metrics_indexed(position.key.glyph_index as usize, position.key.px);
+            pens.push(position.x - metrics.bounds.xmin);
+            offsets.push(metrics.bounds.xmin);
+            baselines.push(position.y - metrics.bounds.ymin);
+            advances.push(if position.char_data.is_control() {
+                0.0
+            } else {
+                metrics.advance_width
+            });
+        }
+
+        let mut start = 0;
+        while start < count {
+            let mut end = start + 1;
+            while end < count && (baselines[end] - baselines[start]).abs() < 0.5 {
+                end += 1;
+            }
+            // The layout's spacing includes kerning, except after the last character of a line.
+            for index in start..end - 1 {
+                advances[index] = pens[index + 1] - pens[index];
+            }
+            // Whitespace at the end of a line stays at the paragraph's level.
+            let mut levels = self.shaped.levels[start..end].to_vec();
+            for index in (start..end).rev() {
+                if !chars[index].is_whitespace() {
+                    break;
+                }
+                levels[index - start] = self.shaped.bases[index];
+            }
+
+            let mut cursor = pens[start];
+            let mut arranged = pens[start..end].to_vec();
+            for offset in shaping::reorder(&levels) {
+                let index = start + offset;
+                if index > start && shaping::is_mark(chars[index]) {
+                    continue;
+                }
+                arranged[offset] = cursor;
+                cursor += advances[index];
+            }
+            let mut base = start;
+            for index in start..end {
+                let position = &mut self.positions[index];
+                if index > start && shaping::is_mark(chars[index]) {
+                    let center = arranged[base - start] + advances[base] / 2.0;
+                    position.x = (center - position.width as f32 / 2.0).round();
+                } else {
+                    position.x = arranged[index - start] + offsets[index];
+                    base = index;
+                }
+            }
+            start = end;
+        }
+    }
+
+    /// The ascent and descent of a line in the font at the size.
+    fn line_metrics(font: &Font, px: f32) -> (f32, f32) {
+        match font.horizontal_line_metrics(px) {
+            Some(line) => (line.ascent, line.descent),
+            None => (px, 0.0),
+        }
+    }
+
+    /// Lays out the text and finds the box of each character.
+    pub fn glyphs(&mut self, desc: &Text) -> Vec<TextGlyph> {
+        if let Some(bitmap) = self.bitmap(desc) {
+            return bitmap.glyphs(desc);
+        }
+        self.layout(desc);
+        let bytes: Vec<Range<usize>> =
+            desc.string.char_indices().map(|(byte, c)| byte..byte + c.len_utf8()).collect();
+        let mut output = Vec::with_capacity(bytes.len());
+        let shaped = &self.shaped;
+        for ((position, source), level) in self.positions.iter().zip(&shaped.sources).zip(&shaped.levels) {
+            let font = &self.fonts[position.key.font_index];
+            let px = position.key.px;
+            let (ascent, descent) = Self::line_metrics(font, px);
+            let metrics = font.metrics_indexed(position.key.glyph_index as usize, px);
+            let advance = if position.char_data.is_control() {
+                0.0
+            } else {
+                metrics.advance_width.ceil()
+            };
+            let baseline = position.y - metrics.bounds.ymin;
+            let pen = position.x - metrics.bounds.xmin;
+            let rtl = level % 2 == 1;
+            // Ligatures belong to the first of their characters. The rest sit with no width on the
+            // ligature's trailing edge.
+            output.push(TextGlyph {
+                index: source.start,
+                bytes: bytes[source.start].start..bytes[source.end - 1].end,
+                pos: Vector2::new(pen, baseline + descent),
+                size: Vector2::new(advance, ascent - descent),
+                baseline,
+                rtl,
+            });
+            let trailing = if rtl {
+                pen
+            } else {
+                pen + advance
+            };
+            for (index, bytes) in bytes.iter().enumerate().take(source.end).skip(source.start + 1) {
+                output.push(TextGlyph {
+                    index,
+                    bytes: bytes.clone(),
+                    pos: Vector2::new(trailing, baseline + descent),
+                    size: Vector2::new(0.0, ascent - descent),
+                    baseline,
+                    rtl,
+                });
+            }
+        }
+        output
+    }
+
+    /// Finds the link under the position. Each character covers its advance horizontally and its
+    /// line's height vertically, so gaps between letters and words still hit.
+    pub fn hit_test(&mut self, desc: &Text, pos: Vector2<f32>) -> Option<u32> {
+        if desc.links.is_empty() {
+            return None;
+        }
+        for glyph in self.glyphs(desc) {
+            let link =
+                match desc.links.iter().find(|link| link.start <= glyph.index && glyph.index < link.end) {
+                    Some(link) => link,
+                    None => continue,
+                };
+            if pos.x >= glyph.pos.x
+                && pos.x < glyph.pos.x + glyph.size.x
+                && pos.y >= glyph.pos.y
+                && pos.y < glyph.pos.y + glyph.size.y
+            {
+                return Some(link.id);
+            }
+        }
+        None
+    }
+
+    /// Finds the byte index of the caret position closest to the position. The line is the one
+    /// containing the position vertically, or the closest one. Within the line, the caret goes
+    /// on the side of the closest character the position is on, which is before the character on
+    /// its left half unless the character is right to left.
+    pub fn index_at(&mut self, desc: &Text, pos: Vector2<f32>) -> usize {
+        let glyphs = self.glyphs(desc);
+        let distance = |glyph: &TextGlyph| {
+            if pos.y < glyph.pos.y {
+                glyph.pos.y - pos.y
+            } else if pos.y > glyph.pos.y + glyph.size.y {
+                pos.y - glyph.pos.y - glyph.size.y
+            } else {
+                0.0
+            }
+        };
+        let line = match glyphs.iter().min_by(|a, b| distance(a).total_cmp(&distance(b))) {
+            Some(closest) => closest.baseline,
+            None => return desc.string.len(),
+        };
+        let on_line = glyphs.iter().filter(|glyph| (glyph.baseline - line).abs() < 0.5);
+        let empty = on_line.clone().next().map(|glyph| glyph.bytes.start).unwrap_or(desc.string.len());
+        let center = |glyph: &TextGlyph| glyph.pos.x + glyph.size.x / 2.0;
+        let closest = on_line
+            .filter(|glyph| glyph.size.x > 0.0)
+            .min_by(|a, b| (center(a) - pos.x).abs().total_cmp(&(center(b) - pos.x).abs()));
+        match closest {
+            // Right to left characters start on their right edge.
+            Some(glyph) if (pos.x < center(glyph)) != glyph.rtl => glyph.bytes.start,
+            Some(glyph) => glyph.bytes.end,
+            None => empty,
+        }
+    }
+
+    /// Lays out the text and measures each line. Lines are found by where the baseline changes.
+    pub fn measure(&mut self, desc: &Text) -> TextMetrics {
+        let mut lines: Vec<TextLineMetrics> = Vec::new();
+        // The left and right of each line's characters.
+        let mut edges: Vec<(f32, f32)> = Vec::new();
+        for glyph in self.glyphs(desc) {
+            match lines.last_mut() {
+                Some(line) if (line.baseline - glyph.baseline).abs() < 0.5 => {
+                    line.range.end = glyph.index + 1;
+                    let top = (line.pos.y + line.size.y).max(glyph.pos.y + glyph.size.y);
+                    line.pos.y = line.pos.y.min(glyph.pos.y);
+                    line.size.y = top - line.pos.y;
+                }
+                _ => {
+                    lines.push(TextLineMetrics {
+                        range: glyph.index..glyph.index + 1,
+                        pos: glyph.pos,
+                        size: Vector2::new(0.0, glyph.size.y),
+                        baseline: glyph.baseline,
+                    });
+                    edges.push((f32::MAX, f32::MIN));
+                }
+            }
+            if glyph.size.x > 0.0 {
+                let edge = edges.last_mut().unwrap();
+                edge.0 = edge.0.min(glyph.pos.x);
+                edge.1 = edge.1.max(glyph.pos.x + glyph.size.x);
+            }
+        }
+
+        let mut min = Vector2::new(f32::MAX, f32::MAX);
+        let mut max = Vector2::new(f32::MIN, f32::MIN);
+        for (line, (left, right)) in lines.iter_mut().zip(edges) {
+            if left <= right {
+                line.pos.x = left;
+                line.size.x = right - left;
+            } else {
+                line.pos.x = desc.pos.x;
+            }
+            min.x = min.x.min(line.pos.x);
+            min.y = min.y.min(line.pos.y);
+            max.x = max.x.max(line.pos.x + line.size.x);
+            max.y = max.y.max(line.pos.y + line.size.y);
+        }
+        if lines.is_empty() {
+            return TextMetrics {
+                pos: desc.pos.truncate(),
+                size: Vector2::zero(),
+                lines,
+            };
+        }
+        TextMetrics {
+            pos: min,
+            size: max - min,
+            lines,
+        }
+    }
+
+    pub fn rasterize(&mut self, atlas: &mut TextureAtlas, desc: &Text, sprites: &mut Vec<Sprite>) {
+        let start = sprites.len();
+        let (min, max) = match self.bitmap(desc) {
+            Some(bitmap) => bitmap.rasterize(desc, sprites),
+            None => self.rasterize_outline(atlas, desc, sprites),
+        };
+
+        if let Some(shadow) = &desc.shadow {
+            let shadows: Vec<Sprite> = sprites[start..]
+                .iter()
+                .filter(|sprite| sprite.effect == SpriteEffect::DistanceField)
+                .map(|glyph| {
+                    let mut sprite = *glyph;
+                    sprite.pos += shadow.offset.extend(-SHADOW_DEPTH);
+                    sprite.color = shadow.color;
+                    sprite.set_effect(SpriteEffect::DistanceFieldShadow, shadow.spread);
+                    sprite
+                })
+                .collect();
+            // Inserted ahead of the glyphs so the glyphs blend over their shadows.
+            sprites.splice(start..start, shadows);
+        }
+        if let Some(background) = &desc.background {
+            if min.x <= max.x {
+                let padding = Vector2::new(background.padding, background.padding);
+                let pos = (min - padding).extend(desc.pos.z - BACKGROUND_DEPTH);
+                let size = max - min + padding * 2.0;
+                let boxes = self.background(atlas, background, pos, size);
+                // Inserted ahead of the glyphs so the glyph edges blend over the box.
+                sprites.splice(start..start, boxes);
+            }
+        }
+    }
+
+    /// Rasterizes text drawn with outline fonts. Returns the bounds of the glyphs.
+    fn rasterize_outline(
+        &mut self,
+        atlas: &mut TextureAtlas,
+        desc: &Text,
+        sprites: &mut Vec<Sprite>,
+    ) -> (Vector2<f32>, Vector2<f32>) {
+        self.layout(desc);
+
+        let mut min = Vector2::new(f32::MAX, f32::MAX);
+        let mut max = Vector2::new(f32::MIN, f32::MIN);
+        // Reveal counts the characters of the string before shaping.
+        let reveal = desc.reveal.unwrap_or(usize::MAX);
+        for (&position, source) in self.positions.iter().zip(&self.shaped.sources) {
+            let index = source.start;
             if position.width == 0 {
                 continue;
             }
+            let font = &self.fonts[position.key.font_index];
+            let px = position.key.px;
+            let color = position.user_data;
+            if desc.distance_field {
+                let key = (position.key.font_index, position.key.glyph_index);
+                let value = *self.fields.entry(key).or_insert_with(|| Self::field(atlas, font, key.1));
+                let metrics = font.metrics_indexed(position.key.glyph_index as usize, px);
+                let pen = Vector2::new(position.x - metrics.bounds.xmin, position.y - metrics.bounds.ymin);
+                let factor = px / DISTANCE_FIELD_PX;
+                let pos = pen + value.offset * factor;
+                let size = value.size * factor;
+                if index < reveal {
+                    let mut sprite = Sprite::new(pos.extend(desc.pos.z), size, Texture(value.uv), color, 0.0);
+                    sprite.set_effect(SpriteEffect::DistanceField, 0.0);
+                    sprites.push(sprite);
+                }
+                // The spread is padding, so it doesn't count toward the bounds.
+                let spread =
+                    Vector2::new(DISTANCE_FIELD_SPREAD as f32, DISTANCE_FIELD_SPREAD as f32) * factor;
+                min.x = min.x.min(pos.x + spread.x);
+                min.y = min.y.min(pos.y + spread.y);
+                max.x = max.x.max(pos.x + size.x - spread.x);
+                max.y = max.y.max(pos.y + size.y - spread.y);
+                continue;
+            }
             let value = match self.cache.get(&position.key).copied() {
                 Some(value) => value,
                 None => {
@@ -84,17 +525,121 @@ impl TextCache {
                     value
                 }
             };
-            sprites.push(Sprite::new(
-                Vector3::new(position.x, position.y, desc.pos.z),
-                value.size,
-                Texture(value.uv),
-                desc.color,
-                0.0,
-            ));
+            // Hidden glyphs still count toward the bounds so the background doesn't grow as the
+            // text is revealed.
+            if index < reveal {
+                sprites.push(Sprite::new(
+                    Vector3::new(position.x, position.y, desc.pos.z),
+                    value.size,
+                    Texture(value.uv),
+                    color,
+                    0.0,
+                ));
+            }
+            min.x = min.x.min(position.x);
+            min.y = min.y.min(position.y);
+            max.x = max.x.max(position.x + value.size.x);
+            max.y = max.y.max(position.y + value.size.y);
         }
+        (min, max)
+    }
+
+    /// Rasterizes a glyph at the distance field size and converts it into a signed distance field,
+    /// stored in the alpha channel.
+    fn field(atlas: &mut TextureAtlas, font: &Font, glyph: u16) -> FieldCacheValue {
+        let (metrics, bitmap) = font.rasterize_indexed(glyph as usize, DISTANCE_FIELD_PX);
+        let spread = DISTANCE_FIELD_SPREAD;
+        let (width, height) = (metrics.width as i32, metrics.height as i32);
+        let (field_width, field_height) = (width + spread * 2, height + spread * 2);
+        // Coordinates are in the padded field.
+        let inside = |x: i32, y: i32| {
+            let (x, y) = (x - spread, y - spread);
+            x >= 0 && y >= 0 && x < width && y < height && bitmap[(y * width + x) as usize] >= 128
+        };
+        let mut pixels = Vec::with_capacity((field_width * field_height) as usize);
+        for y in 0..field_height {
+            for x in 0..field_width {
+                let state = inside(x, y);
+                let mut nearest = (spread * spread) as f32;
+                for dy in -spread..=spread {
+                    for dx in -spread..=spread {
+                        let distance = (dx * dx + dy * dy) as f32;
+                        if distance < nearest && inside(x + dx, y + dy) != state {
+                            nearest = distance;
+                        }
+                    }
+                }
+                // The edge sits halfway between a pixel and its nearest opposite.
+                let distance = nearest.sqrt() - 0.5;
+                let signed = if state {
+                    distance
+                } else {
+                    -distance
+                };
+                let value = (0.5 + signed / (spread as f32 * 2.0)).clamp(0.0, 1.0);
+                pixels.push(RGBA8::new_raw(255, 255, 255, (value * 255.0).round() as u8));
+            }
+        }
+        let image = Image::from_vec(pixels, field_width as u32, field_height as u32);
+        FieldCacheValue {
+            uv: atlas.add(image),
+            offset: Vector2::new((metrics.xmin - spread) as f32, (metrics.ymin - spread) as f32),
+            size: Vector2::new(field_width as f32, field_height as f32),
+        }
+    }
+
+    /// Builds a box out of sprites. Rounded boxes use a center cross with a quarter circle sprite in
+    /// each corner.
+    fn background(
+        &mut self,
+        atlas: &mut TextureAtlas,
+        background: &TextBackground,
+        pos: Vector3<f32>,
+        size: Vector2<f32>,
+    ) -> Vec<Sprite> {
+        let color = background.color;
+        let radius = background.corner_radius.min(size.x / 2.0).min(size.y / 2.0).max(0.0).floor();
+        if radius < 1.0 {
+            return vec![Sprite::new(pos, size, Texture::default(), color, 0.0)];
+        }
+        let uv = self.corner(atlas, radius as u32);
+        let mid =
+            Vector2::new(((uv.x as u32 + uv.y as u32) / 2) as u16, ((uv.z as u32 + uv.w as u32) / 2) as u16);
+        let r = radius;
+        let quad = |x: f32, y: f32, w: f32, h: f32, texture: Texture| {
+            Sprite::new(Vector3::new(pos.x + x, pos.y + y, pos.z), Vector2::new(w, h), texture, color, 0.0)
+        };
+        let solid = Texture::default();
+        vec![
+            quad(r, 0.0, size.x - r * 2.0, size.y, solid),
+            quad(0.0, r, r, size.y - r * 2.0, solid),
+            quad(size.x - r, r, r, size.y - r * 2.0, solid),
+            // Atlas textures store the top row first, so the top of the circle is the lower UVs.
+            quad(0.0, size.y - r, r, r, Texture(Vector4::new(uv.x, mid.x, uv.z, mid.y))),
+            quad(size.x - r, size.y - r, r, r, Texture(Vector4::new(mid.x, uv.y, uv.z, mid.y))),
+            quad(0.0, 0.0, r, r, Texture(Vector4::new(uv.x, mid.x, mid.y, uv.w))),
+            quad(size.x - r, 0.0, r, r, Texture(Vector4::new(mid.x, uv.y, mid.y, uv.w))),
+        ]
+    }
+
+    /// Gets an antialiased white circle with the given radius from the atlas, adding it if needed.
+    fn corner(&mut self, atlas: &mut TextureAtlas, radius: u32) -> Vector4<u16> {
+        *self.corners.entry(radius).or_insert_with(|| {
+            let diameter = radius * 2;
+            let center = radius as f32;
+            let mut pixels = Vec::with_capacity((diameter * diameter) as usize);
+            for y in 0..diameter {
+                for x in 0..diameter {
+                    let offset = Vector2::new(x as f32 + 0.5 - center, y as f32 + 0.5 - center);
+                    let coverage = (center - offset.magnitude() + 0.5).clamp(0.0, 1.0);
+                    pixels.push(RGBA8::new_raw(255, 255, 255, (coverage * 255.0) as u8));
+                }
+            }
+            atlas.add(Image::from_vec(pixels, diameter, diameter))
+        })
     }
 
-    fn alpha_to_rgba(bitmap: &Vec<u8>) -> Vec<RGBA8> {
+    fn alpha_to_rgba(bitmap: &[u8]) -> Vec<RGBA8> {
         let mut output = Vec::with_capacity(bitmap.len());
         for v in bitmap {
             let v = *v;
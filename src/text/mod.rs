@@ -1,3 +1,7 @@
+mod bitmap;
 mod cache;
+mod shaping;
+mod system;
 
 pub(crate) use self::cache::TextCache;
+pub(crate) use self::system::{system_fonts, SystemFont};
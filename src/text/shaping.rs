@@ -0,0 +1,399 @@
+use core::ops::Range;
+use unicode_normalization::char::compose;
+
+const LAM: char = '\u{0644}';
+
+/// Text prepared for layout. Marks are composed into their base character where possible, Arabic
+/// letters take their joined forms, and lam followed by alef becomes a ligature, so the shaped
+/// string may have fewer characters than the source.
+pub struct Shaped {
+    /// The characters to lay out.
+    pub string: String,
+    /// For each character of the string, the characters of the source text it came from.
+    pub sources: Vec<Range<usize>>,
+    /// For each character of the string, its bidirectional embedding level. Odd levels are right
+    /// to left.
+    pub levels: Vec<u8>,
+    /// For each character of the string, the embedding level of its paragraph.
+    pub bases: Vec<u8>,
+    /// If nothing needs to be reordered or placed over another character after layout.
+    pub simple: bool,
+}
+
+/// Bidirectional character classes, reduced to what this needs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Class {
+    Left,
+    Right,
+    /// Arabic letters, which are right to left and turn numbers after them into Arabic numbers.
+    Arabic,
+    /// European numbers.
+    Number,
+    /// Arabic-Indic numbers.
+    ArabicNumber,
+    /// Combining marks, which take the class of the character before them.
+    Mark,
+    Neutral,
+}
+
+fn class(c: char) -> Class {
+    let code = c as u32;
+    if is_mark(c) {
+        Class::Mark
+    } else if c.is_ascii_digit() || (0x06F0..=0x06F9).contains(&code) {
+        Class::Number
+    } else if (0x0660..=0x0669).contains(&code) || code == 0x066B || code == 0x066C {
+        Class::ArabicNumber
+    } else if (0x0590..=0x05FF).contains(&code)
+        || (0x07C0..=0x085F).contains(&code)
+        || (0xFB1D..=0xFB4F).contains(&code)
+        || (0x10800..=0x10FFF).contains(&code)
+        || (0x1E800..=0x1EFFF).contains(&code)
+    {
+        Class::Right
+    } else if (0x0600..=0x07BF).contains(&code)
+        || (0x0860..=0x08FF).contains(&code)
+        || (0xFB50..=0xFDFF).contains(&code)
+        || (0xFE70..=0xFEFF).contains(&code)
+    {
+        if c.is_alphabetic() {
+            Class::Arabic
+        } else {
+            Class::Neutral
+        }
+    } else if c.is_alphabetic() {
+        Class::Left
+    } else {
+        Class::Neutral
+    }
+}
+
+/// If the character is a combining mark that's drawn over the character before it.
+pub fn is_mark(c: char) -> bool {
+    matches!(c as u32,
+        0x0300..=0x036F
+        | 0x0483..=0x0489
+        | 0x0591..=0x05BD
+        | 0x05BF
+        | 0x05C1..=0x05C2
+        | 0x05C4..=0x05C5
+        | 0x05C7
+        | 0x0610..=0x061A
+        | 0x064B..=0x065F
+        | 0x0670
+        | 0x06D6..=0x06DC
+        | 0x06DF..=0x06E4
+        | 0x06E7..=0x06E8
+        | 0x06EA..=0x06ED
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x20D0..=0x20FF
+        | 0xFE20..=0xFE2F)
+}
+
+/// The isolated presentation form of an Arabic letter and how many forms it has. Letters with 4
+/// forms join on both sides, and letters with 2 only join to the letter before them. The forms are
+/// stored in the order isolated, final, initial, medial.
+fn forms(c: char) -> Option<(u32, u32)> {
+    let form = match c as u32 {
+        0x0621 => (0xFE80, 1),
+        0x0622 => (0xFE81, 2),
+        0x0623 => (0xFE83, 2),
+        0x0624 => (0xFE85, 2),
+        0x0625 => (0xFE87, 2),
+        0x0626 => (0xFE89, 4),
+        0x0627 => (0xFE8D, 2),
+        0x0628 => (0xFE8F, 4),
+        0x0629 => (0xFE93, 2),
+        code @ 0x062A..=0x062E => (0xFE95 + (code - 0x062A) * 4, 4),
+        code @ 0x062F..=0x0632 => (0xFEA9 + (code - 0x062F) * 2, 2),
+        code @ 0x0633..=0x063A => (0xFEB1 + (code - 0x0633) * 4, 4),
+        code @ 0x0641..=0x0647 => (0xFED1 + (code - 0x0641) * 4, 4),
+        0x0648 => (0xFEED, 2),
+        0x0649 => (0xFEEF, 2),
+        0x064A => (0xFEF1, 4),
+        0x067E => (0xFB56, 4),
+        0x0686 => (0xFB7A, 4),
+        0x0698 => (0xFB8A, 2),
+        0x06A9 => (0xFB8E, 4),
+        0x06AF => (0xFB92, 4),
+        0x06CC => (0xFBFC, 4),
+        _ => return None,
+    };
+    Some(form)
+}
+
+/// The isolated form of the ligature of lam with the alef.
+fn lam_alef(alef: char) -> Option<u32> {
+    match alef {
+        '\u{0622}' => Some(0xFEF5),
+        '\u{0623}' => Some(0xFEF7),
+        '\u{0625}' => Some(0xFEF9),
+        '\u{0627}' => Some(0xFEFB),
+        _ => None,
+    }
+}
+
+/// If the character joins to the characters on both sides, like tatweel and the zero width joiner.
+fn joins_both(c: char) -> bool {
+    c == '\u{0640}' || c == '\u{200D}' || matches!(forms(c), Some((_, 4)))
+}
+
+/// If the character can join to the character before it.
+fn joins_before(c: char) -> bool {
+    joins_both(c) || matches!(forms(c), Some((_, 2)))
+}
+
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        '‹' => '›',
+        '›' => '‹',
+        _ => c,
+    }
+}
+
+pub fn shape(text: &str) -> Shaped {
+    // Compose marks into the character before them.
+    let mut chars: Vec<char> = Vec::with_capacity(text.len());
+    let mut sources: Vec<Range<usize>> = Vec::with_capacity(text.len());
+    for (index, c) in text.chars().enumerate() {
+        if let (true, Some(last)) = (is_mark(c), chars.last_mut()) {
+            if let Some(composed) = compose(*last, c) {
+                *last = composed;
+                sources.last_mut().unwrap().end = index + 1;
+                continue;
+            }
+        }
+        chars.push(c);
+        sources.push(index..index + 1);
+    }
+
+    // Join Arabic letters. Marks are skipped when looking for neighbors. How each character joins
+    // is found up front, since letters are replaced with their forms along the way.
+    let mut joins: Vec<(bool, bool)> = chars.iter().map(|&c| (joins_both(c), joins_before(c))).collect();
+    let neighbor = |chars: &[char], mut index: usize, forward: bool| loop {
+        if forward {
+            index += 1;
+        } else if index == 0 {
+            return None;
+        } else {
+            index -= 1;
+        }
+        match chars.get(index) {
+            Some(&c) if is_mark(c) => continue,
+            Some(_) => return Some(index),
+            None => return None,
+        }
+    };
+    let mut index = 0;
+    while index < chars.len() {
+        let c = chars[index];
+        let joins_prev = joins[index].1 && neighbor(&chars, index, false).is_some_and(|prev| joins[prev].0);
+        if c == LAM {
+            if let Some(ligature) = chars.get(index + 1).and_then(|&alef| lam_alef(alef)) {
+                chars[index] = char::from_u32(ligature + joins_prev as u32).unwrap();
+                chars.remove(index + 1);
+                let end = sources.remove(index + 1).end;
+                sources[index].end = end;
+                // The ligature ends with the alef, which only joins to the letter before it.
+                joins.remove(index + 1);
+                joins[index] = (false, true);
+                index += 1;
+                continue;
+            }
+        }
+        if let Some((isolated, count)) = forms(c) {
+            let joins_next = count == 4 && neighbor(&chars, index, true).is_some_and(|next| joins[next].1);
+            let form = match (joins_prev, joins_next) {
+                (false, false) => 0,
+                (true, false) => 1,
+                (false, true) => 2,
+                (true, true) => 3,
+            };
+            chars[index] = char::from_u32(isolated + form).unwrap();
+        }
+        index += 1;
+    }
+
+    // Resolve embedding levels one paragraph at a time.
+    let mut levels = vec![0u8; chars.len()];
+    let mut bases = vec![0u8; chars.len()];
+    let mut start = 0;
+    while start < chars.len() {
+        let end =
+            chars[start..].iter().position(|&c| c == '\n').map_or(chars.len(), |offset| start + offset + 1);
+        let base = resolve(&chars[start..end], &mut levels[start..end]);
+        bases[start..end].iter_mut().for_each(|value| *value = base);
+        start = end;
+    }
+    for (c, level) in chars.iter_mut().zip(levels.iter()) {
+        if level % 2 == 1 {
+            *c = mirror(*c);
+        }
+    }
+
+    let simple = levels.iter().all(|&level| level == 0) && !chars.iter().any(|&c| is_mark(c));
+    Shaped {
+        string: chars.into_iter().collect(),
+        sources,
+        levels,
+        bases,
+        simple,
+    }
+}
+
+/// Resolves the embedding levels of a paragraph, following a reduced form of the Unicode
+/// bidirectional algorithm without explicit embeddings. Returns the paragraph's level.
+fn resolve(chars: &[char], levels: &mut [u8]) -> u8 {
+    let mut classes: Vec<Class> = chars.iter().map(|&c| class(c)).collect();
+    let base = match classes.iter().find(|class| matches!(class, Class::Left | Class::Right | Class::Arabic))
+    {
+        Some(Class::Left) | None => 0,
+        Some(_) => 1,
+    };
+    let start = if base == 0 {
+        Class::Left
+    } else {
+        Class::Right
+    };
+
+    // Marks take the class of the character before them, and numbers after Arabic letters are
+    // Arabic numbers. Numbers after left to right letters are left to right.
+    let mut strong = start;
+    for index in 0..classes.len() {
+        if classes[index] == Class::Mark {
+            classes[index] = if index == 0 {
+                Class::Neutral
+            } else {
+                classes[index - 1]
+            };
+        }
+        match classes[index] {
+            Class::Left | Class::Right | Class::Arabic => strong = classes[index],
+            Class::Number if strong == Class::Arabic => classes[index] = Class::ArabicNumber,
+            Class::Number if strong == Class::Left => classes[index] = Class::Left,
+            _ => {}
+        }
+        if classes[index] == Class::Arabic {
+            classes[index] = Class::Right;
+        }
+    }
+
+    // Neutrals between characters of the same direction take that direction, and otherwise the
+    // paragraph's. Numbers count as right to left here.
+    let direction = |class: Class| match class {
+        Class::Left => Some(Class::Left),
+        Class::Right | Class::Number | Class::ArabicNumber => Some(Class::Right),
+        _ => None,
+    };
+    let mut index = 0;
+    while index < classes.len() {
+        if direction(classes[index]).is_some() {
+            index += 1;
+            continue;
+        }
+        let end =
+            (index..classes.len()).find(|&end| direction(classes[end]).is_some()).unwrap_or(classes.len());
+        let before = if index == 0 {
+            start
+        } else {
+            direction(classes[index - 1]).unwrap()
+        };
+        let after = classes.get(end).and_then(|&class| direction(class)).unwrap_or(start);
+        let resolved = if before == after {
+            before
+        } else {
+            start
+        };
+        classes[index..end].iter_mut().for_each(|class| *class = resolved);
+        index = end;
+    }
+
+    for (level, class) in levels.iter_mut().zip(classes) {
+        *level = match (base, class) {
+            (0, Class::Left) => 0,
+            (0, Class::Right) => 1,
+            (0, _) => 2,
+            (_, Class::Right) => 1,
+            (_, _) => 2,
+        };
+    }
+    // Line and paragraph ends stay at the paragraph's level.
+    for (level, c) in levels.iter_mut().zip(chars).rev() {
+        if !c.is_whitespace() {
+            break;
+        }
+        *level = base;
+    }
+    base
+}
+
+/// The visual order of a line from its embedding levels, as indices into the line. Each run at a
+/// level or higher is reversed, from the highest level down to the lowest odd level.
+pub fn reorder(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let highest = levels.iter().copied().max().unwrap_or(0);
+    let lowest_odd = levels.iter().copied().filter(|level| level % 2 == 1).min().unwrap_or(highest + 1);
+    let mut level = highest;
+    while level >= lowest_odd && level > 0 {
+        let mut index = 0;
+        while index < order.len() {
+            if levels[order[index]] < level {
+                index += 1;
+                continue;
+            }
+            let end = (index..order.len()).find(|&end| levels[order[end]] < level).unwrap_or(order.len());
+            order[index..end].reverse();
+            index = end;
+        }
+        level -= 1;
+    }
+    order
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arabic() {
+        // Seen, lam, alef, meem.
+        let shaped = shape("\u{0633}\u{0644}\u{0627}\u{0645}");
+        assert_eq!(shaped.string, "\u{FEB3}\u{FEFC}\u{FEE1}");
+        assert_eq!(shaped.sources, vec![0..1, 1..3, 3..4]);
+        assert_eq!(shaped.levels, vec![1, 1, 1]);
+        assert_eq!(reorder(&shaped.levels), vec![2, 1, 0]);
+
+        // Beh, yeh with a fatha, teh.
+        let shaped = shape("\u{0628}\u{064A}\u{064E}\u{062A}");
+        assert_eq!(shaped.string, "\u{FE91}\u{FEF4}\u{064E}\u{FE96}");
+        assert!(!shaped.simple);
+    }
+
+    #[test]
+    fn mixed() {
+        let shaped = shape("e\u{0301} (\u{05D0}\u{05D1} 12)");
+        assert_eq!(shaped.string, "\u{00E9} (\u{05D0}\u{05D1} 12)");
+        assert_eq!(shaped.sources[0], 0..2);
+        assert_eq!(shaped.levels, vec![0, 0, 0, 1, 1, 1, 2, 2, 0]);
+        assert_eq!(reorder(&shaped.levels), vec![0, 1, 2, 6, 7, 5, 4, 3, 8]);
+
+        let shaped = shape("\u{05D0} (a)");
+        assert_eq!(shaped.string, "\u{05D0} )a(");
+        assert_eq!(shaped.levels, vec![1, 1, 1, 2, 1]);
+    }
+}
@@ -0,0 +1,160 @@
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+/// A font file installed on the system.
+pub struct SystemFont {
+    pub family: String,
+    pub style: String,
+    pub path: PathBuf,
+}
+
+/// Finds every parsable font in the platform's font directories.
+pub fn system_fonts() -> Vec<SystemFont> {
+    let mut fonts = Vec::new();
+    let mut pending = font_directories();
+    while let Some(directory) = pending.pop() {
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let extension = path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_ascii_lowercase());
+            if !matches!(extension.as_deref(), Some("ttf") | Some("otf") | Some("ttc")) {
+                continue;
+            }
+            if let Some((family, style)) = File::open(&path).ok().and_then(|mut file| read_names(&mut file)) {
+                fonts.push(SystemFont {
+                    family,
+                    style,
+                    path,
+                });
+            }
+        }
+    }
+    fonts
+}
+
+#[cfg(target_arch = "wasm32")]
+fn font_directories() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn font_directories() -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    if cfg!(target_os = "windows") {
+        let windows =
+            std::env::var_os("WINDIR").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("C:\\Windows"));
+        directories.push(windows.join("Fonts"));
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            directories.push(PathBuf::from(local).join("Microsoft").join("Windows").join("Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        directories.push(PathBuf::from("/System/Library/Fonts"));
+        directories.push(PathBuf::from("/Library/Fonts"));
+        if let Some(home) = home {
+            directories.push(home.join("Library").join("Fonts"));
+        }
+    } else {
+        directories.push(PathBuf::from("/usr/share/fonts"));
+        directories.push(PathBuf::from("/usr/local/share/fonts"));
+        if let Some(home) = home {
+            directories.push(home.join(".local").join("share").join("fonts"));
+            directories.push(home.join(".fonts"));
+        }
+    }
+    directories
+}
+
+const NAME_FAMILY: u16 = 1;
+const NAME_STYLE: u16 = 2;
+const NAME_TYPOGRAPHIC_FAMILY: u16 = 16;
+const NAME_TYPOGRAPHIC_STYLE: u16 = 17;
+
+/// Reads the family and style names from the name table of the first font in the file. Only the
+/// headers and the name table are read.
+fn read_names<R: Read + Seek>(reader: &mut R) -> Option<(String, String)> {
+    let mut offset = 0;
+    let header = read_at(reader, 0, 12)?;
+    if &header[0..4] == b"ttcf" {
+        offset = be_u32(&read_at(reader, 12, 4)?, 0);
+    }
+    let header = read_at(reader, offset, 12)?;
+    let table_count = be_u16(&header, 4) as u32;
+    let tables = read_at(reader, offset + 12, table_count * 16)?;
+    let record = tables.chunks(16).find(|record| &record[0..4] == b"name")?;
+    let table = read_at(reader, be_u32(record, 8), be_u32(record, 12))?;
+
+    let count = be_u16(&table, 2) as usize;
+    let strings = be_u16(&table, 4) as usize;
+    let mut names: [Option<String>; 4] = [None, None, None, None];
+    for index in 0..count {
+        let record = table.get(6 + index * 12..18 + index * 12)?;
+        let platform = be_u16(record, 0);
+        let slot = match be_u16(record, 6) {
+            NAME_FAMILY => 0,
+            NAME_STYLE => 1,
+            NAME_TYPOGRAPHIC_FAMILY => 2,
+            NAME_TYPOGRAPHIC_STYLE => 3,
+            _ => continue,
+        };
+        let start = strings + be_u16(record, 10) as usize;
+        let bytes = match table.get(start..start + be_u16(record, 8) as usize) {
+            Some(bytes) => bytes,
+            None => continue,
+        };
+        // Windows and Unicode names are UTF-16BE. Macintosh names are treated as ASCII.
+        let name = match platform {
+            0 | 3 => String::from_utf16_lossy(&bytes.chunks(2).map(|c| be_u16(c, 0)).collect::<Vec<u16>>()),
+            1 => bytes.iter().map(|&b| b as char).collect(),
+            _ => continue,
+        };
+        // Prefer Windows names, which are the most consistently present.
+        if names[slot].is_none() || platform == 3 {
+            names[slot] = Some(name);
+        }
+    }
+    let [family, style, typographic_family, typographic_style] = names;
+    let family = typographic_family.or(family)?;
+    let style = typographic_style.or(style).unwrap_or_else(|| String::from("Regular"));
+    Some((family, style))
+}
+
+fn read_at<R: Read + Seek>(reader: &mut R, offset: u32, len: u32) -> Option<Vec<u8>> {
+    let mut buffer = vec![0; len as usize];
+    reader.seek(SeekFrom::Start(offset as u64)).ok()?;
+    reader.read_exact(&mut buffer).ok()?;
+    Some(buffer)
+}
+
+fn be_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn be_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn bundled_font_names() {
+        let bytes = include_bytes!("fonts/Roboto-Regular.ttf") as &[u8];
+        let names = read_names(&mut Cursor::new(bytes));
+        assert_eq!(names, Some((String::from("Roboto"), String::from("Regular"))));
+    }
+}
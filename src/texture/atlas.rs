@@ -4,42 +4,123 @@ use crate::texture::*;
 use cgmath::*;
 
 const MAX: u32 = 65536;
-const SIZE: u32 = 4096;
-pub const PIXEL_SIZE: u32 = MAX / SIZE;
+/// The largest the atlas can grow to. Texture coordinates are measured against this size, so they
+/// stay the same as the atlas grows.
+pub const MAX_SIZE: u32 = 4096;
+pub const PIXEL_SIZE: u32 = MAX / MAX_SIZE;
 const NUDGE: u16 = 4;
 
 pub struct TextureAtlas {
     packer: Packer,
     atlas: Image,
+    max_size: u32,
     dirty: bool,
 }
 
 impl TextureAtlas {
-    pub fn new() -> TextureAtlas {
+    /// Creates an atlas with the given size that doubles when full, up to the max size. If either
+    /// size isn't a power of two up to 4096, or the size is larger than the max size, this function
+    /// will panic.
+    pub fn new(size: u32, max_size: u32) -> TextureAtlas {
+        let valid = |size: u32| size.is_power_of_two() && size <= MAX_SIZE;
+        if !valid(size) || !valid(max_size) || size > max_size {
+            panic!("Atlas sizes must be powers of two up to {}, got {} and {}.", MAX_SIZE, size, max_size);
+        }
         let mut atlas = TextureAtlas {
-            packer: Packer::new(SIZE, SIZE),
-            atlas: Image::from_color(WHITE, SIZE, SIZE),
+            packer: Packer::new(size, size),
+            atlas: Image::from_color(WHITE, size, size),
+            max_size,
             dirty: false,
         };
         atlas.add(Image::from_color(WHITE, 1, 1));
         atlas
     }
 
+    /// The current width and height of the atlas in pixels.
+    pub fn size(&self) -> u32 {
+        self.atlas.width()
+    }
+
+    /// The fraction of the atlas's current area that textures take up.
+    pub fn occupancy(&self) -> f32 {
+        self.packer.used() as f32 / (self.size() as f32 * self.size() as f32)
+    }
+
+    /// If the atlas changed since it was last synced, and needs to be uploaded whole.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Adds a texture. If the atlas is full and can't grow, this function will panic.
     pub fn add(&mut self, texture: Image) -> Vector4<u16> {
-        if let Some(rect) = self.packer.pack(texture.width(), texture.height()) {
-            self.atlas.set_texture(rect.x, rect.y, &texture);
-            self.dirty = true;
-            Vector4::new(
-                (rect.x * PIXEL_SIZE) as u16 + NUDGE,            // Left
-                ((rect.x + rect.w) * PIXEL_SIZE) as u16 - NUDGE, // Right
-                (rect.y * PIXEL_SIZE) as u16 + NUDGE,            // Top
-                ((rect.y + rect.h) * PIXEL_SIZE) as u16 - NUDGE, // Bottom
-            )
-        } else {
-            panic!("Unable to fit texture into atlas.");
+        match self.try_add(texture) {
+            Ok(uv) => uv,
+            Err(error) => panic!("{}", error),
         }
     }
 
+    /// Adds a texture, returning an error if the atlas is full and can't grow.
+    pub fn try_add(&mut self, texture: Image) -> Result<Vector4<u16>, &'static str> {
+        let (uv, _, _) = self.add_uploaded(&texture)?;
+        self.dirty = true;
+        Ok(uv)
+    }
+
+    /// Adds a texture without marking the atlas as changed, for when the caller uploads the
+    /// texture's region itself. If the atlas had to grow, it's marked as changed anyway, since the
+    /// whole atlas needs to be uploaded again. Returns the texture's UVs and the offset in the atlas
+    /// it was written to.
+    pub fn add_uploaded(&mut self, texture: &Image) -> Result<(Vector4<u16>, u32, u32), &'static str> {
+        let rect = loop {
+            if let Some(rect) = self.packer.pack(texture.width(), texture.height()) {
+                break rect;
+            }
+            if !self.grow() {
+                return Err("Unable to fit texture into atlas.");
+            }
+        };
+        self.atlas.set_texture(rect.x, rect.y, texture);
+        let uv = Vector4::new(
+            (rect.x * PIXEL_SIZE) as u16 + NUDGE,            // Left
+            ((rect.x + rect.w) * PIXEL_SIZE) as u16 - NUDGE, // Right
+            (rect.y * PIXEL_SIZE) as u16 + NUDGE,            // Top
+            ((rect.y + rect.h) * PIXEL_SIZE) as u16 - NUDGE, // Bottom
+        );
+        Ok((uv, rect.x, rect.y))
+    }
+
+    /// Doubles the size of the atlas, keeping every texture where it is. Returns false if the atlas
+    /// is already at its max size.
+    fn grow(&mut self) -> bool {
+        let size = self.size();
+        if size >= self.max_size {
+            return false;
+        }
+        let grown = size * 2;
+        let mut atlas = Image::from_color(WHITE, grown, grown);
+        atlas.set_texture(0, 0, &self.atlas);
+        self.atlas = atlas;
+        self.packer.grow(grown, grown);
+        self.dirty = true;
+        true
+    }
+
+    /// Overwrites part of a texture already in the atlas. The offset is in pixels from the top left
+    /// of the texture. Returns the offset in the atlas that was written to.
+    pub fn update(&mut self, uv: Vector4<u16>, x: u32, y: u32, texture: &Image) -> (u32, u32) {
+        // Edges are nudged inward by less than half a pixel, so rounding recovers them.
+        let edge = |value: u16| (value as u32 + PIXEL_SIZE / 2) / PIXEL_SIZE;
+        let left = edge(uv.x.min(uv.y));
+        let right = edge(uv.x.max(uv.y));
+        let top = edge(uv.z.min(uv.w));
+        let bottom = edge(uv.z.max(uv.w));
+        if left + x + texture.width() > right || top + y + texture.height() > bottom {
+            panic!("Texture update falls outside of the texture.");
+        }
+        self.atlas.set_texture(left + x, top + y, texture);
+        (left + x, top + y)
+    }
+
     pub fn sync(&mut self) -> Option<&Image> {
         if self.dirty {
             self.dirty = false;
@@ -49,3 +130,28 @@ impl TextureAtlas {
         }
     }
 }
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grow() {
+        let mut atlas = TextureAtlas::new(64, 128);
+        let red = atlas.add(Image::from_color(RED, 60, 60));
+        assert_eq!(atlas.size(), 64);
+        let blue = atlas.add(Image::from_color(BLUE, 60, 60));
+        assert_eq!(atlas.size(), 128);
+        assert!(atlas.occupancy() >= 7200.0 / 16384.0 && atlas.occupancy() < 0.45);
+        assert!(atlas.try_add(Image::from_color(GREEN, 128, 128)).is_err());
+
+        // Textures keep their coordinates as the atlas grows.
+        let pixel = |uv: Vector4<u16>| atlas.atlas.get(uv.x as u32 / PIXEL_SIZE, uv.z as u32 / PIXEL_SIZE);
+        assert_eq!(pixel(red), RED);
+        assert_eq!(pixel(blue), BLUE);
+    }
+}
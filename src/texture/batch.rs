@@ -0,0 +1,97 @@
+use crate::texture::Image;
+use crate::TextureFormat;
+
+/// Decodes every file, spreading the work over a thread per core. The callback gets each image in
+/// the order the files were given, as soon as it and every file before it are decoded, so the
+/// caller can work on earlier images while later ones are still decoding. The web has no threads,
+/// so files are decoded one at a time there. If a file fails to decode, the panic is carried over
+/// to the calling thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn decode_batch<F: FnMut(usize, Image)>(files: &[(&[u8], TextureFormat)], mut each: F) {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::thread;
+
+    let workers = thread::available_parallelism().map(|count| count.get()).unwrap_or(1).min(files.len());
+    if workers <= 1 {
+        for (index, &(bytes, format)) in files.iter().enumerate() {
+            each(index, Image::from_raw(bytes, format));
+        }
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let (bytes, format) = match files.get(index) {
+                    Some(&file) => file,
+                    None => break,
+                };
+                let image = panic::catch_unwind(AssertUnwindSafe(|| Image::from_raw(bytes, format)));
+                if sender.send((index, image)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Images are handed out in order, holding any that finish early.
+        let mut pending: Vec<Option<Image>> = vec![None; files.len()];
+        let mut ready = 0;
+        for (index, image) in receiver {
+            match image {
+                Ok(image) => pending[index] = Some(image),
+                Err(payload) => panic::resume_unwind(payload),
+            }
+            while let Some(image) = pending.get_mut(ready).and_then(Option::take) {
+                each(ready, image);
+                ready += 1;
+            }
+        }
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn decode_batch<F: FnMut(usize, Image)>(files: &[(&[u8], TextureFormat)], mut each: F) {
+    for (index, &(bytes, format)) in files.iter().enumerate() {
+        each(index, Image::from_raw(bytes, format));
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RGBA8;
+
+    /// A QOI file for a 1x1 image of the given color.
+    fn qoi(color: RGBA8) -> Vec<u8> {
+        let mut bytes = Vec::from(&b"qoif"[..]);
+        bytes.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 1, 4, 0]);
+        bytes.extend_from_slice(&[0xFF, color.r, color.g, color.b, color.a]);
+        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+        bytes
+    }
+
+    #[test]
+    fn in_order() {
+        let files: Vec<Vec<u8>> = (0..32).map(|index| qoi(RGBA8::new_raw(index, 0, 0, 255))).collect();
+        let files: Vec<(&[u8], TextureFormat)> =
+            files.iter().map(|bytes| (bytes.as_slice(), TextureFormat::QOI)).collect();
+        let mut seen = Vec::new();
+        decode_batch(&files, |index, image| {
+            assert_eq!(image.get(0, 0).r as usize, index);
+            seen.push(index);
+        });
+        assert_eq!(seen, (0..32).collect::<Vec<_>>());
+    }
+}
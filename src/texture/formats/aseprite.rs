@@ -0,0 +1,412 @@
+use crate::texture::image::Image;
+use crate::RGBA8;
+
+const HEADER_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+const CHUNK_OLD_PALETTE: u16 = 0x0004;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+const LAYER_VISIBLE: u16 = 1;
+const LAYER_TYPE_NORMAL: u16 = 0;
+
+pub struct AsepriteFrame {
+    pub image: Image,
+    /// Duration in milliseconds.
+    pub duration: u16,
+}
+
+pub struct AsepriteTag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    /// 0 is forward, 1 is reverse, 2 is ping-pong, and 3 is reverse ping-pong.
+    pub direction: u8,
+}
+
+pub struct Aseprite {
+    pub frames: Vec<AsepriteFrame>,
+    pub tags: Vec<AsepriteTag>,
+}
+
+struct Layer {
+    visible: bool,
+    opacity: u8,
+}
+
+#[derive(Clone)]
+struct Cel {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    opacity: u8,
+    pixels: Vec<RGBA8>,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        let slice = self.bytes.get(self.position..self.position + len).expect("Aseprite file is truncated.");
+        self.position += len;
+        slice
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.take(1)[0]
+    }
+
+    fn u16(&mut self) -> u16 {
+        let b = self.take(2);
+        u16::from_le_bytes([b[0], b[1]])
+    }
+
+    fn i16(&mut self) -> i16 {
+        self.u16() as i16
+    }
+
+    fn u32(&mut self) -> u32 {
+        let b = self.take(4);
+        u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+    }
+
+    fn string(&mut self) -> String {
+        let len = self.u16() as usize;
+        String::from_utf8_lossy(self.take(len)).into_owned()
+    }
+}
+
+/// Reads an Aseprite file, flattening the visible layers of each frame into an image.
+pub fn read(bytes: &[u8]) -> Aseprite {
+    let mut reader = Reader {
+        bytes,
+        position: 0,
+    };
+    let header = reader.take(128);
+    let mut header = Reader {
+        bytes: header,
+        position: 4,
+    };
+    if header.u16() != HEADER_MAGIC {
+        panic!("Aseprite file has an invalid header.");
+    }
+    let frame_count = header.u16() as usize;
+    let width = header.u16() as u32;
+    let height = header.u16() as u32;
+    let depth = header.u16();
+    header.position = 28;
+    let transparent_index = header.u8();
+    if width == 0 || height == 0 {
+        panic!("Aseprite sprite has no size.");
+    }
+    if depth != 32 && depth != 16 && depth != 8 {
+        panic!("Aseprite color depth {} is unsupported.", depth);
+    }
+
+    let mut layers: Vec<Layer> = Vec::new();
+    let mut parents: Vec<bool> = Vec::new();
+    let mut palette = vec![RGBA8::new_raw(0, 0, 0, 0); 256];
+    let mut tags = Vec::new();
+    // Cels are decoded per frame, indexed by layer, so linked cels can refer back to them.
+    let mut cels: Vec<Vec<Option<Cel>>> = Vec::with_capacity(frame_count);
+    let mut durations = Vec::with_capacity(frame_count);
+
+    for _ in 0..frame_count {
+        let frame_start = reader.position;
+        let frame_size = reader.u32() as usize;
+        if reader.u16() != FRAME_MAGIC {
+            panic!("Aseprite frame has an invalid header.");
+        }
+        let old_chunks = reader.u16() as u32;
+        durations.push(reader.u16());
+        reader.take(2);
+        let chunks = match reader.u32() {
+            0 => old_chunks,
+            chunks => chunks,
+        };
+        let mut frame_cels: Vec<Option<Cel>> = Vec::new();
+
+        for _ in 0..chunks {
+            let chunk_start = reader.position;
+            let chunk_size = reader.u32() as usize;
+            let chunk_type = reader.u16();
+            let mut chunk = Reader {
+                bytes: reader.take(chunk_size.checked_sub(6).expect("Aseprite chunk is truncated.")),
+                position: 0,
+            };
+            match chunk_type {
+                CHUNK_LAYER => {
+                    let flags = chunk.u16();
+                    let layer_type = chunk.u16();
+                    let level = chunk.u16() as usize;
+                    chunk.take(6);
+                    let opacity = chunk.u8();
+                    // Layers inside a hidden group are hidden too.
+                    parents.truncate(level);
+                    let visible = flags & LAYER_VISIBLE != 0 && parents.iter().all(|&parent| parent);
+                    parents.push(visible);
+                    layers.push(Layer {
+                        visible: visible && layer_type == LAYER_TYPE_NORMAL,
+                        opacity,
+                    });
+                }
+                CHUNK_CEL => {
+                    let layer = chunk.u16() as usize;
+                    let x = chunk.i16() as i32;
+                    let y = chunk.i16() as i32;
+                    let opacity = chunk.u8();
+                    let cel_type = chunk.u16();
+                    chunk.take(7);
+                    let cel = match cel_type {
+                        0 | 2 => {
+                            let cel_width = chunk.u16() as u32;
+                            let cel_height = chunk.u16() as u32;
+                            let data = &chunk.bytes[chunk.position..];
+                            let data = if cel_type == 2 {
+                                inflate::inflate_bytes_zlib(data).expect("Unable to decompress Aseprite cel.")
+                            } else {
+                                data.to_vec()
+                            };
+                            let pixels =
+                                decode(&data, depth, &palette, transparent_index, cel_width * cel_height);
+                            Some(Cel {
+                                x,
+                                y,
+                                width: cel_width,
+                                height: cel_height,
+                                opacity,
+                                pixels,
+                            })
+                        }
+                        1 => {
+                            let linked = chunk.u16() as usize;
+                            cels.get(linked).and_then(|frame| frame.get(layer)).cloned().flatten()
+                        }
+                        _ => None,
+                    };
+                    if frame_cels.len() <= layer {
+                        frame_cels.resize(layer + 1, None);
+                    }
+                    frame_cels[layer] = cel;
+                }
+                CHUNK_PALETTE => {
+                    let size = chunk.u32() as usize;
+                    let first = chunk.u32() as usize;
+                    let last = chunk.u32() as usize;
+                    chunk.take(8);
+                    if palette.len() < size {
+                        palette.resize(size, RGBA8::new_raw(0, 0, 0, 0));
+                    }
+                    for index in first..=last {
+                        let flags = chunk.u16();
+                        let color = chunk.take(4);
+                        if index < palette.len() {
+                            palette[index] = RGBA8::new_raw(color[0], color[1], color[2], color[3]);
+                        }
+                        if flags & 1 != 0 {
+                            chunk.string();
+                        }
+                    }
+                }
+                CHUNK_OLD_PALETTE => {
+                    let packets = chunk.u16();
+                    let mut index = 0;
+                    for _ in 0..packets {
+                        index += chunk.u8() as usize;
+                        let count = match chunk.u8() {
+                            0 => 256,
+                            count => count as usize,
+                        };
+                        for _ in 0..count {
+                            let color = chunk.take(3);
+                            if index < palette.len() {
+                                palette[index] = RGBA8::new_raw(color[0], color[1], color[2], 255);
+                            }
+                            index += 1;
+                        }
+                    }
+                }
+                CHUNK_TAGS => {
+                    let count = chunk.u16();
+                    chunk.take(8);
+                    for _ in 0..count {
+                        let from = chunk.u16() as usize;
+                        let to = chunk.u16() as usize;
+                        let direction = chunk.u8();
+                        chunk.take(12);
+                        tags.push(AsepriteTag {
+                            name: chunk.string(),
+                            from,
+                            to,
+                            direction,
+                        });
+                    }
+                }
+                _ => {}
+            }
+            reader.position = chunk_start + chunk_size;
+        }
+        cels.push(frame_cels);
+        reader.position = frame_start + frame_size;
+    }
+
+    let frames = cels
+        .iter()
+        .zip(durations)
+        .map(|(frame_cels, duration)| {
+            let mut image = Image::from_color(RGBA8::new_raw(0, 0, 0, 0), width, height);
+            for (layer, cel) in layers.iter().zip(frame_cels.iter()) {
+                if let (true, Some(cel)) = (layer.visible, cel) {
+                    composite(&mut image, cel, ((cel.opacity as u32 * layer.opacity as u32) / 255) as u8);
+                }
+            }
+            AsepriteFrame {
+                image,
+                duration,
+            }
+        })
+        .collect();
+
+    Aseprite {
+        frames,
+        tags,
+    }
+}
+
+fn decode(data: &[u8], depth: u16, palette: &[RGBA8], transparent_index: u8, count: u32) -> Vec<RGBA8> {
+    let count = count as usize;
+    let pixels: Vec<RGBA8> = match depth {
+        32 => data.chunks_exact(4).map(|p| RGBA8::new_raw(p[0], p[1], p[2], p[3])).collect(),
+        16 => data.chunks_exact(2).map(|p| RGBA8::new_raw(p[0], p[0], p[0], p[1])).collect(),
+        _ => data
+            .iter()
+            .map(|&index| {
+                if index == transparent_index {
+                    RGBA8::new_raw(0, 0, 0, 0)
+                } else {
+                    palette.get(index as usize).copied().unwrap_or(RGBA8::new_raw(0, 0, 0, 0))
+                }
+            })
+            .collect(),
+    };
+    if pixels.len() < count {
+        panic!("Aseprite cel is truncated.");
+    }
+    pixels
+}
+
+/// Draws the cel over the image with normal blending.
+fn composite(image: &mut Image, cel: &Cel, opacity: u8) {
+    for cy in 0..cel.height {
+        for cx in 0..cel.width {
+            let x = cel.x + cx as i32;
+            let y = cel.y + cy as i32;
+            if x < 0 || y < 0 || x as u32 >= image.width() || y as u32 >= image.height() {
+                continue;
+            }
+            let src = cel.pixels[(cy * cel.width + cx) as usize];
+            let src_alpha = src.a as f32 / 255.0 * opacity as f32 / 255.0;
+            if src_alpha <= 0.0 {
+                continue;
+            }
+            let dst = image.get(x as u32, y as u32);
+            let dst_alpha = dst.a as f32 / 255.0;
+            let out_alpha = src_alpha + dst_alpha * (1.0 - src_alpha);
+            let blend = |s: u8, d: u8| {
+                ((s as f32 * src_alpha + d as f32 * dst_alpha * (1.0 - src_alpha)) / out_alpha).round() as u8
+            };
+            image.set(
+                x as u32,
+                y as u32,
+                RGBA8::new_raw(
+                    blend(src.r, dst.r),
+                    blend(src.g, dst.g),
+                    blend(src.b, dst.b),
+                    (out_alpha * 255.0).round() as u8,
+                ),
+            );
+        }
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_type: u16, data: &[u8]) -> Vec<u8> {
+        let mut out = ((data.len() + 6) as u32).to_le_bytes().to_vec();
+        out.extend_from_slice(&chunk_type.to_le_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn frame(duration: u16, chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = chunks.concat();
+        let mut out = ((body.len() + 16) as u32).to_le_bytes().to_vec();
+        out.extend_from_slice(&FRAME_MAGIC.to_le_bytes());
+        out.extend_from_slice(&(chunks.len() as u16).to_le_bytes());
+        out.extend_from_slice(&duration.to_le_bytes());
+        out.extend_from_slice(&[0; 2]);
+        out.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+        out.extend_from_slice(&body);
+        out
+    }
+
+    fn cel(x: i16, color: [u8; 4]) -> Vec<u8> {
+        let mut data = vec![0, 0];
+        data.extend_from_slice(&x.to_le_bytes());
+        data.extend_from_slice(&0i16.to_le_bytes());
+        data.push(255);
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&[0; 7]);
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&color);
+        chunk(CHUNK_CEL, &data)
+    }
+
+    #[test]
+    fn frames_and_tags() {
+        let mut layer = vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 0, 0, 0];
+        layer.extend_from_slice(&5u16.to_le_bytes());
+        layer.extend_from_slice(b"Layer");
+        let mut tags = vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        tags.extend_from_slice(&[0, 0, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        tags.extend_from_slice(&4u16.to_le_bytes());
+        tags.extend_from_slice(b"walk");
+
+        let frames = [
+            frame(100, &[chunk(CHUNK_LAYER, &layer), cel(0, [255, 0, 0, 255]), chunk(CHUNK_TAGS, &tags)]),
+            frame(50, &[cel(1, [0, 255, 0, 255])]),
+        ]
+        .concat();
+        let mut header = vec![0; 128];
+        header[0..4].copy_from_slice(&((128 + frames.len()) as u32).to_le_bytes());
+        header[4..6].copy_from_slice(&HEADER_MAGIC.to_le_bytes());
+        header[6..8].copy_from_slice(&2u16.to_le_bytes());
+        header[8..10].copy_from_slice(&2u16.to_le_bytes());
+        header[10..12].copy_from_slice(&1u16.to_le_bytes());
+        header[12..14].copy_from_slice(&32u16.to_le_bytes());
+        let file = [header, frames].concat();
+
+        let aseprite = read(&file);
+        assert_eq!(aseprite.frames.len(), 2);
+        assert_eq!(aseprite.frames[0].duration, 100);
+        assert_eq!(aseprite.frames[0].image.get(0, 0), RGBA8::new_raw(255, 0, 0, 255));
+        assert_eq!(aseprite.frames[0].image.get(1, 0), RGBA8::new_raw(0, 0, 0, 0));
+        assert_eq!(aseprite.frames[1].duration, 50);
+        assert_eq!(aseprite.frames[1].image.get(1, 0), RGBA8::new_raw(0, 255, 0, 255));
+        assert_eq!(aseprite.tags.len(), 1);
+        assert_eq!(aseprite.tags[0].name, "walk");
+        assert_eq!((aseprite.tags[0].from, aseprite.tags[0].to, aseprite.tags[0].direction), (0, 1, 2));
+    }
+}
@@ -0,0 +1,220 @@
+use crate::texture::image::Image;
+use crate::RGBA8;
+
+const COMPRESSION_RGB: u32 = 0;
+const COMPRESSION_BITFIELDS: u32 = 3;
+const COMPRESSION_ALPHA_BITFIELDS: u32 = 6;
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    let b = bytes.get(offset..offset + 2).expect("BMP file is truncated.");
+    u16::from_le_bytes([b[0], b[1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    let b = bytes.get(offset..offset + 4).expect("BMP file is truncated.");
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+/// Extracts a channel with the mask and scales it to 8 bits.
+fn channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let max = mask >> shift;
+    (((value & mask) >> shift) * 255 / max) as u8
+}
+
+pub fn read(bytes: &[u8]) -> Image {
+    if bytes.len() < 26 || &bytes[0..2] != b"BM" {
+        panic!("BMP file has an invalid header.");
+    }
+    let data_offset = u32_at(bytes, 10) as usize;
+    let header_size = u32_at(bytes, 14) as usize;
+    let core = header_size == 12;
+    let (width, height, bpp, compression) = if core {
+        (u16_at(bytes, 18) as i32, u16_at(bytes, 20) as i16 as i32, u16_at(bytes, 24), COMPRESSION_RGB)
+    } else {
+        (u32_at(bytes, 18) as i32, u32_at(bytes, 22) as i32, u16_at(bytes, 28), u32_at(bytes, 30))
+    };
+    if width <= 0 || height == 0 {
+        panic!("BMP image has an invalid size.");
+    }
+    let top_down = height < 0;
+    let (width, height) = (width as u32, height.unsigned_abs());
+
+    let (masks, mut use_alpha) = match compression {
+        COMPRESSION_RGB => match bpp {
+            16 => ([0x7C00, 0x03E0, 0x001F, 0], false),
+            _ => ([0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000], bpp == 32),
+        },
+        COMPRESSION_BITFIELDS | COMPRESSION_ALPHA_BITFIELDS => {
+            // The masks follow a 40 byte header, or are part of a larger one.
+            let offset = 14 + 40;
+            let alpha = if compression == COMPRESSION_ALPHA_BITFIELDS || header_size >= 56 {
+                u32_at(bytes, offset + 12)
+            } else {
+                0
+            };
+            ([u32_at(bytes, offset), u32_at(bytes, offset + 4), u32_at(bytes, offset + 8), alpha], alpha != 0)
+        }
+        _ => panic!("BMP compression type {} is unsupported.", compression),
+    };
+
+    let palette: Vec<RGBA8> = if bpp <= 8 {
+        let entry = if core {
+            3
+        } else {
+            4
+        };
+        let count = if core {
+            0
+        } else {
+            u32_at(bytes, 46) as usize
+        };
+        let count = if count == 0 {
+            1 << bpp
+        } else {
+            count
+        };
+        let start = 14 + header_size;
+        (0..count)
+            .map(|index| {
+                let b = bytes
+                    .get(start + index * entry..start + index * entry + 3)
+                    .expect("BMP file is truncated.");
+                RGBA8::new_raw(b[2], b[1], b[0], 255)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let stride = (width as usize * bpp as usize).div_ceil(32) * 4;
+    let mut output = vec![RGBA8::new_raw(0, 0, 0, 255); (width * height) as usize];
+    for row in 0..height as usize {
+        let start = data_offset + row * stride;
+        let line = bytes.get(start..start + stride).expect("BMP file is truncated.");
+        let y = if top_down {
+            row
+        } else {
+            height as usize - 1 - row
+        };
+        for x in 0..width as usize {
+            let pixel = match bpp {
+                1 | 4 | 8 => {
+                    let bit = x * bpp as usize;
+                    let index = (line[bit / 8] >> (8 - bpp as usize - bit % 8)) & ((1 << bpp) - 1) as u8;
+                    *palette.get(index as usize).expect("BMP palette index is out of range.")
+                }
+                16 | 32 => {
+                    let value = if bpp == 16 {
+                        u16::from_le_bytes([line[x * 2], line[x * 2 + 1]]) as u32
+                    } else {
+                        u32::from_le_bytes([line[x * 4], line[x * 4 + 1], line[x * 4 + 2], line[x * 4 + 3]])
+                    };
+                    RGBA8::new_raw(
+                        channel(value, masks[0]),
+                        channel(value, masks[1]),
+                        channel(value, masks[2]),
+                        if use_alpha {
+                            channel(value, masks[3])
+                        } else {
+                            255
+                        },
+                    )
+                }
+                24 => RGBA8::new_raw(line[x * 3 + 2], line[x * 3 + 1], line[x * 3], 255),
+                _ => panic!("BMP bit depth {} is unsupported.", bpp),
+            };
+            output[y * width as usize + x] = pixel;
+        }
+    }
+
+    // Many writers leave the alpha channel of 32 bit images zeroed, meaning opaque.
+    if use_alpha && output.iter().all(|pixel| pixel.a == 0) {
+        use_alpha = false;
+    }
+    if !use_alpha {
+        for pixel in &mut output {
+            pixel.a = 255;
+        }
+    }
+    Image::from_vec(output, width, height)
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file header and 40 byte info header, followed by the extra bytes, like masks or a
+    /// palette. The pixel data goes right after.
+    fn header(width: i32, height: i32, bpp: u16, compression: u32, extra: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::from(&b"BM"[..]);
+        bytes.extend_from_slice(&[0; 8]);
+        bytes.extend_from_slice(&(14 + 40 + extra.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&40u32.to_le_bytes());
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&bpp.to_le_bytes());
+        bytes.extend_from_slice(&compression.to_le_bytes());
+        bytes.extend_from_slice(&[0; 20]);
+        bytes.extend_from_slice(extra);
+        bytes
+    }
+
+    #[test]
+    fn true_color() {
+        // Rows are stored bottom to top, padded to 4 bytes.
+        let mut bytes = header(2, 2, 24, COMPRESSION_RGB, &[]);
+        bytes.extend_from_slice(&[0, 0, 255, 0, 255, 0, 0, 0]);
+        bytes.extend_from_slice(&[255, 0, 0, 255, 255, 255, 0, 0]);
+        let image = read(&bytes);
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(
+            image.as_slice(),
+            &[
+                RGBA8::new_raw(0, 0, 255, 255),
+                RGBA8::new_raw(255, 255, 255, 255),
+                RGBA8::new_raw(255, 0, 0, 255),
+                RGBA8::new_raw(0, 255, 0, 255),
+            ][..]
+        );
+
+        // 32 bit images with a zeroed alpha channel are opaque.
+        let mut bytes = header(1, 1, 32, COMPRESSION_RGB, &[]);
+        bytes.extend_from_slice(&0x0011_2233u32.to_le_bytes());
+        assert_eq!(read(&bytes).as_slice(), &[RGBA8::new_raw(0x11, 0x22, 0x33, 255)][..]);
+
+        // 16 bit images default to 5 bits per channel.
+        let mut bytes = header(1, 1, 16, COMPRESSION_RGB, &[]);
+        bytes.extend_from_slice(&[0x00, 0x7C, 0, 0]);
+        assert_eq!(read(&bytes).as_slice(), &[RGBA8::new_raw(255, 0, 0, 255)][..]);
+    }
+
+    #[test]
+    fn bitfields() {
+        let mut masks = Vec::new();
+        for mask in [0x00FF_0000u32, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000] {
+            masks.extend_from_slice(&mask.to_le_bytes());
+        }
+        let mut bytes = header(1, 1, 32, COMPRESSION_ALPHA_BITFIELDS, &masks);
+        bytes.extend_from_slice(&0x8011_2233u32.to_le_bytes());
+        assert_eq!(read(&bytes).as_slice(), &[RGBA8::new_raw(0x11, 0x22, 0x33, 0x80)][..]);
+    }
+
+    #[test]
+    fn palette() {
+        // A negative height stores rows top to bottom.
+        let palette = [0, 0, 0, 0, 255, 255, 255, 0];
+        let mut bytes = header(3, -1, 1, COMPRESSION_RGB, &palette);
+        bytes.extend_from_slice(&[0b1010_0000, 0, 0, 0]);
+        let (black, white) = (RGBA8::new_raw(0, 0, 0, 255), RGBA8::new_raw(255, 255, 255, 255));
+        assert_eq!(read(&bytes).as_slice(), &[white, black, white][..]);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::texture::image::Image;
+use crate::RGBA8;
+use std::collections::HashMap;
+
+/// Levels of red, green, and blue in the palette. 6 * 7 * 6 = 252 colors, leaving the rest of the
+/// 256 color table unused.
+const LEVELS: [u32; 3] = [6, 7, 6];
+/// Codes are 9 to 12 bits, with 8 bit pixel indices.
+const MIN_CODE_SIZE: u8 = 8;
+const CLEAR_CODE: u16 = 1 << MIN_CODE_SIZE;
+const END_CODE: u16 = CLEAR_CODE + 1;
+const MAX_CODE: u16 = 4095;
+/// 4x4 ordered dithering thresholds, hiding the banding from the fixed palette.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Encodes frames as a looping animated GIF. Every frame is shown for the delay, in hundredths of
+/// a second, and is drawn at the size of the first frame. Colors are dithered to a fixed palette,
+/// and alpha is ignored.
+pub fn write(frames: &[Image], delay: u16) -> Vec<u8> {
+    let (width, height) = frames.first().map(|frame| (frame.width(), frame.height())).unwrap_or((0, 0));
+    let mut output = Vec::new();
+    output.extend_from_slice(b"GIF89a");
+    output.extend_from_slice(&(width as u16).to_le_bytes());
+    output.extend_from_slice(&(height as u16).to_le_bytes());
+    // A global color table of 256 entries, with 8 bits per channel.
+    output.extend_from_slice(&[0xf7, 0, 0]);
+    for index in 0..256 {
+        let color = palette(index);
+        output.extend_from_slice(&[color.r, color.g, color.b]);
+    }
+    // Loops forever.
+    output.extend_from_slice(&[0x21, 0xff, 11]);
+    output.extend_from_slice(b"NETSCAPE2.0");
+    output.extend_from_slice(&[3, 1, 0, 0, 0]);
+
+    for frame in frames {
+        output.extend_from_slice(&[0x21, 0xf9, 4, 0x04]);
+        output.extend_from_slice(&delay.to_le_bytes());
+        output.extend_from_slice(&[0, 0]);
+        output.push(0x2c);
+        output.extend_from_slice(&[0, 0, 0, 0]);
+        output.extend_from_slice(&(frame.width() as u16).to_le_bytes());
+        output.extend_from_slice(&(frame.height() as u16).to_le_bytes());
+        output.push(0);
+        output.push(MIN_CODE_SIZE);
+        let data = compress(&indices(frame));
+        for block in data.chunks(255) {
+            output.push(block.len() as u8);
+            output.extend_from_slice(block);
+        }
+        output.push(0);
+    }
+    output.push(0x3b);
+    output
+}
+
+/// The color of a palette entry. Entries past the last color are black.
+fn palette(index: u32) -> RGBA8 {
+    let [r, g, b] = LEVELS;
+    if index >= r * g * b {
+        return RGBA8::new_raw(0, 0, 0, 255);
+    }
+    let level = |value: u32, levels: u32| (value * 255 / (levels - 1)) as u8;
+    RGBA8::new_raw(level(index / (g * b), r), level(index / b % g, g), level(index % b, b), 255)
+}
+
+/// Dithers the image to palette indices.
+fn indices(image: &Image) -> Vec<u8> {
+    let [r, g, b] = LEVELS;
+    let mut output = Vec::with_capacity(image.as_slice().len());
+    for (i, pixel) in image.as_slice().iter().enumerate() {
+        let (x, y) = (i % image.width() as usize, i / image.width() as usize);
+        let threshold = (BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+        let level = |value: u8, levels: u32| {
+            let step = 255.0 / (levels - 1) as f32;
+            (value as f32 / step + threshold).round().clamp(0.0, (levels - 1) as f32) as u32
+        };
+        output.push(((level(pixel.r, r) * g + level(pixel.g, g)) * b + level(pixel.b, b)) as u8);
+    }
+    output
+}
+
+/// Compresses palette indices with variable length LZW codes.
+fn compress(indices: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut code_size = MIN_CODE_SIZE as u32 + 1;
+    let mut last_code = END_CODE;
+    writer.write(CLEAR_CODE, code_size);
+
+    let mut current = match indices.first() {
+        Some(&index) => index as u16,
+        None => {
+            writer.write(END_CODE, code_size);
+            return writer.finish();
+        }
+    };
+    for &index in &indices[1..] {
+        if let Some(&code) = table.get(&(current, index)) {
+            current = code;
+            continue;
+        }
+        writer.write(current, code_size);
+        last_code += 1;
+        table.insert((current, index), last_code);
+        if last_code >= 1 << code_size {
+            code_size += 1;
+        }
+        if last_code == MAX_CODE {
+            writer.write(CLEAR_CODE, code_size);
+            table.clear();
+            code_size = MIN_CODE_SIZE as u32 + 1;
+            last_code = END_CODE;
+        }
+        current = index as u16;
+    }
+    writer.write(current, code_size);
+    // The decoder adds an entry for the last code unless it's the first since a clear, which can
+    // widen the end code.
+    if last_code > END_CODE && last_code + 1 >= 1 << code_size {
+        code_size += 1;
+    }
+    writer.write(END_CODE, code_size);
+    writer.finish()
+}
+
+/// Packs codes least significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.bits |= (code as u32) << self.count;
+        self.count += size;
+        while self.count >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+        self.bytes
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes the frames of a GIF written by `write` to palette indices.
+    fn decode(bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut frames = Vec::new();
+        // Header, screen descriptor, color table, and loop extension.
+        let mut at = 6 + 7 + 768 + 19;
+        while bytes[at] != 0x3b {
+            // Graphic control extension, then the image descriptor.
+            at += 8 + 10;
+            assert_eq!(bytes[at], MIN_CODE_SIZE);
+            at += 1;
+            let mut data = Vec::new();
+            while bytes[at] != 0 {
+                let length = bytes[at] as usize;
+                data.extend_from_slice(&bytes[at + 1..at + 1 + length]);
+                at += 1 + length;
+            }
+            at += 1;
+            frames.push(decompress(&data));
+        }
+        frames
+    }
+
+    fn decompress(data: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut code_size = 9;
+        let mut previous: Option<Vec<u8>> = None;
+        let (mut bits, mut count, mut bytes) = (0u32, 0, data.iter());
+        loop {
+            while count < code_size {
+                bits |= (*bytes.next().expect("Missing end code.") as u32) << count;
+                count += 8;
+            }
+            let code = (bits & ((1 << code_size) - 1)) as usize;
+            bits >>= code_size;
+            count -= code_size;
+
+            if code == CLEAR_CODE as usize {
+                table = (0..=END_CODE).map(|index| vec![index as u8]).collect();
+                code_size = 9;
+                previous = None;
+                continue;
+            }
+            if code == END_CODE as usize {
+                return output;
+            }
+            let entry = match &previous {
+                None => table[code].clone(),
+                Some(previous) => {
+                    let entry = if code < table.len() {
+                        table[code].clone()
+                    } else {
+                        assert_eq!(code, table.len());
+                        let mut entry = previous.clone();
+                        entry.push(previous[0]);
+                        entry
+                    };
+                    let mut added = previous.clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                    if table.len() == 1 << code_size && code_size < 12 {
+                        code_size += 1;
+                    }
+                    entry
+                }
+            };
+            output.extend_from_slice(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        // Noise fills the code table, so the encoder has to clear it.
+        let mut seed = 1u32;
+        let mut noise = Vec::new();
+        for _ in 0..64 * 64 {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            noise.push(RGBA8::new_raw((seed >> 8) as u8, (seed >> 16) as u8, (seed >> 24) as u8, 255));
+        }
+        let noise = Image::from_vec(noise, 64, 64);
+        let solid = Image::from_color(RGBA8::new_raw(255, 255, 255, 255), 64, 64);
+        let frames = [noise, solid];
+        let decoded = decode(&write(&frames, 7));
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0], indices(&frames[0]));
+        assert_eq!(decoded[1], indices(&frames[1]));
+
+        // Colors in the palette are kept exactly.
+        assert_eq!(palette(decoded[1][0] as u32), RGBA8::new_raw(255, 255, 255, 255));
+        // Ends on either side of the codes widening.
+        for length in 1..600 {
+            let pixels = (0..length).map(|i| i as u8).collect::<Vec<_>>();
+            assert_eq!(decompress(&compress(&pixels)), pixels);
+        }
+    }
+}
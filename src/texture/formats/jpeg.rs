@@ -0,0 +1,507 @@
+use crate::texture::image::Image;
+use crate::RGBA8;
+
+const MARKER_SOF0: u8 = 0xC0;
+const MARKER_SOF1: u8 = 0xC1;
+const MARKER_SOF2: u8 = 0xC2;
+const MARKER_DHT: u8 = 0xC4;
+const MARKER_SOI: u8 = 0xD8;
+const MARKER_EOI: u8 = 0xD9;
+const MARKER_SOS: u8 = 0xDA;
+const MARKER_DQT: u8 = 0xDB;
+const MARKER_DRI: u8 = 0xDD;
+
+/// Maps the order coefficients are stored in to their position in the 8x8 block.
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20, 13, 6, 7,
+    14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59, 52, 45, 38, 31, 39,
+    46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+#[derive(Clone, Default)]
+struct Huffman {
+    /// The largest code of each length, or -1 when there are none.
+    max_code: [i32; 17],
+    /// The offset into the symbols of the first code of each length, minus that code.
+    offset: [i32; 17],
+    symbols: Vec<u8>,
+}
+
+impl Huffman {
+    fn new(counts: &[u8], symbols: &[u8]) -> Huffman {
+        let mut table = Huffman {
+            max_code: [-1; 17],
+            offset: [0; 17],
+            symbols: symbols.to_vec(),
+        };
+        let mut code = 0i32;
+        let mut index = 0i32;
+        for length in 1..=16 {
+            let count = counts[length - 1] as i32;
+            if count > 0 {
+                table.offset[length] = index - code;
+                code += count;
+                index += count;
+                table.max_code[length] = code - 1;
+            }
+            code <<= 1;
+        }
+        table
+    }
+}
+
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant: usize,
+    dc_table: usize,
+    ac_table: usize,
+    prediction: i32,
+    /// Samples for the whole image, padded out to a whole number of MCUs.
+    stride: usize,
+    samples: Vec<u8>,
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    buffer: u32,
+    bits: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn fill(&mut self) {
+        while self.bits <= 24 {
+            let mut byte = 0;
+            // A marker ends the entropy coded data. Past it, zeros are supplied.
+            if let Some(&next) = self.bytes.get(self.position) {
+                if next != 0xFF {
+                    byte = next;
+                    self.position += 1;
+                } else if self.bytes.get(self.position + 1) == Some(&0x00) {
+                    byte = 0xFF;
+                    self.position += 2;
+                }
+            }
+            self.buffer |= (byte as u32) << (24 - self.bits);
+            self.bits += 8;
+        }
+    }
+
+    fn bit(&mut self) -> u32 {
+        self.fill();
+        let bit = self.buffer >> 31;
+        self.buffer <<= 1;
+        self.bits -= 1;
+        bit
+    }
+
+    fn receive(&mut self, count: u32) -> i32 {
+        if count == 0 {
+            return 0;
+        }
+        self.fill();
+        let value = self.buffer >> (32 - count);
+        self.buffer <<= count;
+        self.bits -= count;
+        value as i32
+    }
+
+    fn receive_extend(&mut self, count: u32) -> i32 {
+        let value = self.receive(count);
+        if count > 0 && value < (1 << (count - 1)) {
+            value - (1 << count) + 1
+        } else {
+            value
+        }
+    }
+
+    fn decode(&mut self, table: &Huffman) -> u8 {
+        let mut code = 0i32;
+        for length in 1..=16 {
+            code = (code << 1) | self.bit() as i32;
+            if code <= table.max_code[length] {
+                let index = (code + table.offset[length]) as usize;
+                return *table.symbols.get(index).expect("JPEG has an invalid Huffman code.");
+            }
+        }
+        panic!("JPEG has an invalid Huffman code.");
+    }
+
+    /// Drops any partial byte and skips the restart marker that follows.
+    fn restart(&mut self) {
+        self.buffer = 0;
+        self.bits = 0;
+        while self.position + 1 < self.bytes.len() {
+            if self.bytes[self.position] == 0xFF && (0xD0..=0xD7).contains(&self.bytes[self.position + 1]) {
+                self.position += 2;
+                return;
+            }
+            self.position += 1;
+        }
+    }
+}
+
+struct Idct {
+    /// cos((2x + 1)uπ / 16) scaled by C(u) / 2, indexed by [x][u].
+    table: [[f32; 8]; 8],
+}
+
+impl Idct {
+    fn new() -> Idct {
+        let mut table = [[0.0; 8]; 8];
+        for (x, row) in table.iter_mut().enumerate() {
+            for (u, value) in row.iter_mut().enumerate() {
+                let scale = if u == 0 {
+                    core::f32::consts::FRAC_1_SQRT_2
+                } else {
+                    1.0
+                };
+                *value = scale * 0.5 * (((2 * x + 1) * u) as f32 * core::f32::consts::PI / 16.0).cos();
+            }
+        }
+        Idct {
+            table,
+        }
+    }
+
+    fn transform(&self, coefficients: &[i32; 64], output: &mut [u8], stride: usize) {
+        let mut rows = [0.0f32; 64];
+        for v in 0..8 {
+            for x in 0..8 {
+                let mut sum = 0.0;
+                for u in 0..8 {
+                    sum += self.table[x][u] * coefficients[v * 8 + u] as f32;
+                }
+                rows[v * 8 + x] = sum;
+            }
+        }
+        for y in 0..8 {
+            for x in 0..8 {
+                let mut sum = 0.0;
+                for v in 0..8 {
+                    sum += self.table[y][v] * rows[v * 8 + x];
+                }
+                output[y * stride + x] = (sum + 128.0).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> usize {
+    let b = bytes.get(offset..offset + 2).expect("JPEG file is truncated.");
+    u16::from_be_bytes([b[0], b[1]]) as usize
+}
+
+pub fn read(bytes: &[u8]) -> Image {
+    if bytes.len() < 4 || bytes[0] != 0xFF || bytes[1] != MARKER_SOI {
+        panic!("JPEG file has an invalid header.");
+    }
+    let idct = Idct::new();
+    let mut quant = [[0i32; 64]; 4];
+    let mut dc_tables = vec![Huffman::default(); 4];
+    let mut ac_tables = vec![Huffman::default(); 4];
+    let mut components: Vec<Component> = Vec::new();
+    let mut width = 0;
+    let mut height = 0;
+    let mut restart_interval = 0;
+    let mut position = 2;
+
+    loop {
+        // Markers may be padded with any number of 0xFF bytes.
+        while bytes.get(position) == Some(&0xFF) && bytes.get(position + 1) == Some(&0xFF) {
+            position += 1;
+        }
+        if bytes.get(position) != Some(&0xFF) {
+            panic!("JPEG file has an invalid marker.");
+        }
+        let marker = *bytes.get(position + 1).expect("JPEG file is truncated.");
+        position += 2;
+        if marker == MARKER_EOI {
+            break;
+        }
+        let len = u16_at(bytes, position);
+        let segment = bytes.get(position + 2..position + len).expect("JPEG file is truncated.");
+        position += len;
+        match marker {
+            MARKER_DQT => {
+                let mut data = segment;
+                while !data.is_empty() {
+                    let precision = data[0] >> 4;
+                    let table = &mut quant[(data[0] & 3) as usize];
+                    for (index, value) in table.iter_mut().enumerate() {
+                        *value = if precision == 0 {
+                            data[1 + index] as i32
+                        } else {
+                            u16_at(data, 1 + index * 2) as i32
+                        };
+                    }
+                    data = &data[if precision == 0 {
+                        65
+                    } else {
+                        129
+                    }..];
+                }
+            }
+            MARKER_DHT => {
+                let mut data = segment;
+                while !data.is_empty() {
+                    let counts = &data[1..17];
+                    let total: usize = counts.iter().map(|&count| count as usize).sum();
+                    let table = Huffman::new(counts, &data[17..17 + total]);
+                    let index = (data[0] & 3) as usize;
+                    if data[0] >> 4 == 0 {
+                        dc_tables[index] = table;
+                    } else {
+                        ac_tables[index] = table;
+                    }
+                    data = &data[17 + total..];
+                }
+            }
+            MARKER_DRI => restart_interval = u16_at(segment, 0),
+            MARKER_SOF0 | MARKER_SOF1 => {
+                if segment[0] != 8 {
+                    panic!("JPEG sample precision {} is unsupported.", segment[0]);
+                }
+                height = u16_at(segment, 1);
+                width = u16_at(segment, 3);
+                let count = segment[5] as usize;
+                if count != 1 && count != 3 {
+                    panic!("JPEG with {} components is unsupported.", count);
+                }
+                for index in 0..count {
+                    let spec = &segment[6 + index * 3..9 + index * 3];
+                    components.push(Component {
+                        id: spec[0],
+                        h: (spec[1] >> 4).max(1) as usize,
+                        v: (spec[1] & 15).max(1) as usize,
+                        quant: (spec[2] & 3) as usize,
+                        dc_table: 0,
+                        ac_table: 0,
+                        prediction: 0,
+                        stride: 0,
+                        samples: Vec::new(),
+                    });
+                }
+            }
+            MARKER_SOF2 => panic!("Progressive JPEG is unsupported."),
+            0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => {
+                panic!("JPEG compression type {:#X} is unsupported.", marker)
+            }
+            MARKER_SOS => {
+                if components.is_empty() || width == 0 || height == 0 {
+                    panic!("JPEG scan appears before its frame.");
+                }
+                let count = segment[0] as usize;
+                let mut scan = Vec::with_capacity(count);
+                for index in 0..count {
+                    let id = segment[1 + index * 2];
+                    let tables = segment[2 + index * 2];
+                    let component = components
+                        .iter()
+                        .position(|component| component.id == id)
+                        .expect("JPEG scan references a missing component.");
+                    components[component].dc_table = (tables >> 4 & 3) as usize;
+                    components[component].ac_table = (tables & 3) as usize;
+                    scan.push(component);
+                }
+                let mut reader = BitReader {
+                    bytes,
+                    position,
+                    buffer: 0,
+                    bits: 0,
+                };
+                decode_scan(
+                    &mut reader,
+                    &mut components,
+                    &scan,
+                    (width, height),
+                    restart_interval,
+                    (&quant, &dc_tables, &ac_tables),
+                    &idct,
+                );
+                // Skip to the marker that ends the scan.
+                position = reader.position;
+                while position + 1 < bytes.len() {
+                    let next = bytes[position + 1];
+                    if bytes[position] == 0xFF && next != 0 && !(0xD0..=0xD7).contains(&next) {
+                        break;
+                    }
+                    position += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if components.is_empty() || components[0].samples.is_empty() {
+        panic!("JPEG file has no image data.");
+    }
+    let h_max = components.iter().map(|component| component.h).max().unwrap();
+    let v_max = components.iter().map(|component| component.v).max().unwrap();
+    let sample = |component: &Component, x: usize, y: usize| -> f32 {
+        let x = x * component.h / h_max;
+        let y = y * component.v / v_max;
+        component.samples[y * component.stride + x] as f32
+    };
+    let mut output = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            if components.len() == 1 {
+                let luma = sample(&components[0], x, y) as u8;
+                output.push(RGBA8::new_raw(luma, luma, luma, 255));
+            } else {
+                let luma = sample(&components[0], x, y);
+                let cb = sample(&components[1], x, y) - 128.0;
+                let cr = sample(&components[2], x, y) - 128.0;
+                let r = luma + 1.402 * cr;
+                let g = luma - 0.344_136 * cb - 0.714_136 * cr;
+                let b = luma + 1.772 * cb;
+                let convert = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+                output.push(RGBA8::new_raw(convert(r), convert(g), convert(b), 255));
+            }
+        }
+    }
+    Image::from_vec(output, width as u32, height as u32)
+}
+
+fn decode_scan(
+    reader: &mut BitReader,
+    components: &mut [Component],
+    scan: &[usize],
+    (width, height): (usize, usize),
+    restart_interval: usize,
+    (quant, dc_tables, ac_tables): (&[[i32; 64]; 4], &[Huffman], &[Huffman]),
+    idct: &Idct,
+) {
+    let h_max = components.iter().map(|component| component.h).max().unwrap();
+    let v_max = components.iter().map(|component| component.v).max().unwrap();
+    let mcus_x = width.div_ceil(8 * h_max);
+    let mcus_y = height.div_ceil(8 * v_max);
+    for component in components.iter_mut() {
+        if component.samples.is_empty() {
+            component.stride = mcus_x * component.h * 8;
+            component.samples = vec![0; component.stride * mcus_y * component.v * 8];
+        }
+        component.prediction = 0;
+    }
+
+    // A scan of a single component isn't interleaved, so each block is its own MCU and blocks
+    // outside the image's edge are left out.
+    let (units_x, units_y) = if scan.len() == 1 {
+        let component = &components[scan[0]];
+        (
+            (width * component.h).div_ceil(h_max).div_ceil(8),
+            (height * component.v).div_ceil(v_max).div_ceil(8),
+        )
+    } else {
+        (mcus_x, mcus_y)
+    };
+
+    let mut coefficients = [0i32; 64];
+    for unit in 0..units_x * units_y {
+        if restart_interval > 0 && unit > 0 && unit % restart_interval == 0 {
+            reader.restart();
+            for component in components.iter_mut() {
+                component.prediction = 0;
+            }
+        }
+        let (unit_x, unit_y) = (unit % units_x, unit / units_x);
+        for &index in scan {
+            let component = &mut components[index];
+            let (blocks_x, blocks_y) = if scan.len() == 1 {
+                (1, 1)
+            } else {
+                (component.h, component.v)
+            };
+            for block_y in 0..blocks_y {
+                for block_x in 0..blocks_x {
+                    coefficients.iter_mut().for_each(|value| *value = 0);
+                    let table = &quant[component.quant];
+                    let size = reader.decode(&dc_tables[component.dc_table]) as u32;
+                    component.prediction += reader.receive_extend(size);
+                    coefficients[0] = component.prediction * table[0];
+                    let mut k = 1;
+                    while k < 64 {
+                        let symbol = reader.decode(&ac_tables[component.ac_table]);
+                        let (run, size) = ((symbol >> 4) as usize, (symbol & 15) as u32);
+                        if size == 0 {
+                            if run != 15 {
+                                break;
+                            }
+                            k += 16;
+                            continue;
+                        }
+                        k += run;
+                        if k > 63 {
+                            break;
+                        }
+                        coefficients[ZIGZAG[k]] = reader.receive_extend(size) * table[k];
+                        k += 1;
+                    }
+                    let x = (unit_x * blocks_x + block_x) * 8;
+                    let y = (unit_y * blocks_y + block_y) * 8;
+                    let stride = component.stride;
+                    idct.transform(&coefficients, &mut component.samples[y * stride + x..], stride);
+                }
+            }
+        }
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(bytes: &mut Vec<u8>, marker: u8, data: &[u8]) {
+        bytes.extend_from_slice(&[0xFF, marker]);
+        bytes.extend_from_slice(&(data.len() as u16 + 2).to_be_bytes());
+        bytes.extend_from_slice(data);
+    }
+
+    #[test]
+    fn baseline() {
+        // A 16x8 grayscale image of two flat blocks, with only DC coefficients.
+        let mut bytes = vec![0xFF, MARKER_SOI];
+        let mut table = vec![0];
+        table.extend_from_slice(&[1; 64]);
+        segment(&mut bytes, MARKER_DQT, &table);
+        segment(&mut bytes, MARKER_SOF0, &[8, 0, 8, 0, 16, 1, 1, 0x11, 0]);
+        // DC codes 0 and 1 are sizes 0 and 9, and AC code 0 ends the block.
+        let mut dc = vec![0x00, 2];
+        dc.extend_from_slice(&[0; 15]);
+        dc.extend_from_slice(&[0, 9]);
+        segment(&mut bytes, MARKER_DHT, &dc);
+        let mut ac = vec![0x10, 1];
+        ac.extend_from_slice(&[0; 15]);
+        ac.push(0);
+        segment(&mut bytes, MARKER_DHT, &ac);
+        segment(&mut bytes, MARKER_SOS, &[1, 1, 0x00, 0, 63, 0]);
+        // The first block's DC is 0. The second adds 256, which is 8 levels brighter per pixel.
+        // Bits: 0 0 | 1 100000000 0, padded with ones.
+        bytes.extend_from_slice(&[0b0011_0000, 0b0000_0111]);
+        bytes.extend_from_slice(&[0xFF, MARKER_EOI]);
+
+        let image = read(&bytes);
+        assert_eq!((image.width(), image.height()), (16, 8));
+        for y in 0..8 {
+            assert_eq!(image.get(0, y), RGBA8::new_raw(128, 128, 128, 255));
+            assert_eq!(image.get(7, y), RGBA8::new_raw(128, 128, 128, 255));
+            assert_eq!(image.get(8, y), RGBA8::new_raw(160, 160, 160, 255));
+            assert_eq!(image.get(15, y), RGBA8::new_raw(160, 160, 160, 255));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Progressive JPEG is unsupported.")]
+    fn progressive() {
+        let mut bytes = vec![0xFF, MARKER_SOI];
+        segment(&mut bytes, MARKER_SOF2, &[8, 0, 8, 0, 8, 1, 1, 0x11, 0]);
+        read(&bytes);
+    }
+}
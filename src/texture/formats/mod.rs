@@ -1 +1,8 @@
+pub mod aseprite;
+pub mod bmp;
+pub mod gif;
+pub mod jpeg;
 pub mod png;
+pub mod qoi;
+pub mod svg;
+pub mod tga;
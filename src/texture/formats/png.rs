@@ -1,6 +1,6 @@
 use crate::texture::image::Image;
 use crate::RGBA8;
-use png::{ColorType, Decoder};
+use png::{BitDepth, ColorType, Decoder, Encoder, HasParameters};
 
 pub fn read(bytes: &[u8]) -> Image {
     let decoder = Decoder::new(bytes);
@@ -40,3 +40,18 @@ pub fn read(bytes: &[u8]) -> Image {
         ColorType::Indexed => panic!("PNG Indexed color type is unsupported."),
     }
 }
+
+pub fn write(image: &Image) -> Vec<u8> {
+    let mut output = Vec::new();
+    {
+        let mut encoder = Encoder::new(&mut output, image.width(), image.height());
+        encoder.set(ColorType::RGBA).set(BitDepth::Eight);
+        let mut writer = encoder.write_header().expect("Unable to write PNG header.");
+        let mut data = Vec::with_capacity(image.as_slice().len() * 4);
+        for pixel in image.as_slice() {
+            data.extend_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+        }
+        writer.write_image_data(&data).expect("Unable to write PNG payload.");
+    }
+    output
+}
@@ -0,0 +1,95 @@
+use crate::texture::image::Image;
+use crate::RGBA8;
+
+const MAGIC: &[u8; 4] = b"qoif";
+const OP_RGB: u8 = 0xFE;
+const OP_RGBA: u8 = 0xFF;
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xC0;
+const OP_MASK: u8 = 0xC0;
+
+pub fn read(bytes: &[u8]) -> Image {
+    if bytes.len() < 14 || &bytes[0..4] != MAGIC {
+        panic!("QOI file has an invalid header.");
+    }
+    let width = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    let height = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+    let len = (width as usize).checked_mul(height as usize).expect("QOI image is too large.");
+
+    let mut output = Vec::with_capacity(len);
+    let mut seen = [RGBA8::new_raw(0, 0, 0, 0); 64];
+    let mut pixel = RGBA8::new_raw(0, 0, 0, 255);
+    let mut position = 14;
+    let mut next = || {
+        let byte = *bytes.get(position).expect("QOI file is truncated.");
+        position += 1;
+        byte
+    };
+    while output.len() < len {
+        let op = next();
+        if op == OP_RGB {
+            pixel = RGBA8::new_raw(next(), next(), next(), pixel.a);
+        } else if op == OP_RGBA {
+            pixel = RGBA8::new_raw(next(), next(), next(), next());
+        } else {
+            match op & OP_MASK {
+                OP_INDEX => pixel = seen[op as usize],
+                OP_DIFF => {
+                    pixel.r = pixel.r.wrapping_add((op >> 4) & 0x03).wrapping_sub(2);
+                    pixel.g = pixel.g.wrapping_add((op >> 2) & 0x03).wrapping_sub(2);
+                    pixel.b = pixel.b.wrapping_add(op & 0x03).wrapping_sub(2);
+                }
+                OP_LUMA => {
+                    let second = next();
+                    let dg = (op & 0x3F).wrapping_sub(32);
+                    pixel.r = pixel.r.wrapping_add(dg.wrapping_sub(8).wrapping_add(second >> 4));
+                    pixel.g = pixel.g.wrapping_add(dg);
+                    pixel.b = pixel.b.wrapping_add(dg.wrapping_sub(8).wrapping_add(second & 0x0F));
+                }
+                _ => {
+                    let run = (op & 0x3F) as usize + 1;
+                    for _ in 1..run.min(len - output.len()) {
+                        output.push(pixel);
+                    }
+                }
+            }
+        }
+        let hash =
+            (pixel.r as usize * 3 + pixel.g as usize * 5 + pixel.b as usize * 7 + pixel.a as usize * 11) % 64;
+        seen[hash] = pixel;
+        output.push(pixel);
+    }
+    Image::from_vec(output, width, height)
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ops() {
+        let mut bytes = Vec::from(&MAGIC[..]);
+        bytes.extend_from_slice(&[0, 0, 0, 3, 0, 0, 0, 2, 4, 0]);
+        bytes.extend_from_slice(&[OP_RGBA, 10, 20, 30, 40]); // (10, 20, 30, 40)
+        bytes.push(OP_RUN | 1); // Two more of the same.
+        bytes.push(OP_DIFF | 3 << 4 | 2 << 2 | 1); // (11, 20, 29, 40)
+        bytes.extend_from_slice(&[OP_LUMA | 40, 7 << 4 | 9]); // (18, 28, 38, 40)
+        bytes.push(OP_INDEX | ((10 * 3 + 20 * 5 + 30 * 7 + 40 * 11) % 64) as u8); // (10, 20, 30, 40)
+        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+
+        let image = read(&bytes);
+        let pixels = image.as_slice();
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(pixels[0], RGBA8::new_raw(10, 20, 30, 40));
+        assert_eq!(pixels[2], RGBA8::new_raw(10, 20, 30, 40));
+        assert_eq!(pixels[3], RGBA8::new_raw(11, 20, 29, 40));
+        assert_eq!(pixels[4], RGBA8::new_raw(18, 28, 38, 40));
+        assert_eq!(pixels[5], RGBA8::new_raw(10, 20, 30, 40));
+    }
+}
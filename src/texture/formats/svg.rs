@@ -0,0 +1,906 @@
+use crate::texture::image::Image;
+use crate::RGBA8;
+use cgmath::*;
+use core::f32::consts::PI;
+use xml::attribute::OwnedAttribute;
+use xml::reader::{EventReader, XmlEvent};
+
+/// Sub-scanlines sampled per row of pixels for anti-aliasing.
+const SAMPLES: usize = 16;
+/// Length of the line segments curves are split into, in pixels.
+const FLATTEN_LENGTH: f32 = 2.0;
+/// Elements whose content isn't drawn directly.
+const HIDDEN: [&str; 14] = [
+    "defs",
+    "clipPath",
+    "mask",
+    "symbol",
+    "marker",
+    "pattern",
+    "linearGradient",
+    "radialGradient",
+    "title",
+    "desc",
+    "metadata",
+    "style",
+    "text",
+    "script",
+];
+
+/// A 2D affine transform stored as [a, b, c, d, e, f], mapping (x, y) to
+/// (a * x + c * y + e, b * x + d * y + f).
+type Affine = [f32; 6];
+
+const IDENTITY: Affine = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// Returns the transform that applies `second`, then `first`.
+fn multiply(first: &Affine, second: &Affine) -> Affine {
+    let [a, b, c, d, e, f] = *first;
+    let [g, h, i, j, k, l] = *second;
+    [a * g + c * h, b * g + d * h, a * i + c * j, b * i + d * j, a * k + c * l + e, b * k + d * l + f]
+}
+
+fn apply(transform: &Affine, point: Vector2<f32>) -> Vector2<f32> {
+    let [a, b, c, d, e, f] = *transform;
+    Vector2::new(a * point.x + c * point.y + e, b * point.x + d * point.y + f)
+}
+
+/// How much the transform scales lengths by, on average.
+fn scale_of(transform: &Affine) -> f32 {
+    (transform[0] * transform[3] - transform[1] * transform[2]).abs().sqrt()
+}
+
+/// The presentation attributes in effect for an element.
+#[derive(Clone)]
+struct Style {
+    transform: Affine,
+    fill: Option<RGBA8>,
+    stroke: Option<RGBA8>,
+    stroke_width: f32,
+    opacity: f32,
+    fill_opacity: f32,
+    stroke_opacity: f32,
+    even_odd: bool,
+}
+
+impl Style {
+    fn new(transform: Affine) -> Style {
+        Style {
+            transform,
+            fill: Some(RGBA8::new_raw(0, 0, 0, 255)),
+            stroke: None,
+            stroke_width: 1.0,
+            opacity: 1.0,
+            fill_opacity: 1.0,
+            stroke_opacity: 1.0,
+            even_odd: false,
+        }
+    }
+
+    /// Applies an element's attributes, including its style attribute, on top of the inherited
+    /// style.
+    fn apply(&mut self, attributes: &[OwnedAttribute]) {
+        for attribute in attributes {
+            self.set(&attribute.name.local_name, &attribute.value);
+        }
+        if let Some(style) = attr(attributes, "style") {
+            for declaration in style.split(';') {
+                if let Some((key, value)) = declaration.split_once(':') {
+                    self.set(key.trim(), value);
+                }
+            }
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        let number = || value.parse::<f32>().ok();
+        match key {
+            "transform" => self.transform = multiply(&self.transform, &parse_transform(value)),
+            "fill" => self.fill = parse_paint(value),
+            "stroke" => self.stroke = parse_paint(value),
+            "stroke-width" => self.stroke_width = parse_length(value).unwrap_or(self.stroke_width),
+            "opacity" => self.opacity *= number().unwrap_or(1.0).clamp(0.0, 1.0),
+            "fill-opacity" => self.fill_opacity = number().unwrap_or(1.0).clamp(0.0, 1.0),
+            "stroke-opacity" => self.stroke_opacity = number().unwrap_or(1.0).clamp(0.0, 1.0),
+            "fill-rule" => self.even_odd = value == "evenodd",
+            _ => {}
+        }
+    }
+}
+
+/// A flattened outline, in pixels.
+struct Contour {
+    points: Vec<Vector2<f32>>,
+    closed: bool,
+}
+
+/// Builds flattened contours from path commands given in user units.
+struct PathBuilder {
+    transform: Affine,
+    scale: f32,
+    contours: Vec<Contour>,
+    current: Vector2<f32>,
+    start: Vector2<f32>,
+}
+
+impl PathBuilder {
+    fn new(transform: Affine) -> PathBuilder {
+        PathBuilder {
+            transform,
+            scale: scale_of(&transform),
+            contours: Vec::new(),
+            current: Vector2::zero(),
+            start: Vector2::zero(),
+        }
+    }
+
+    fn move_to(&mut self, point: Vector2<f32>) {
+        self.contours.push(Contour {
+            points: vec![apply(&self.transform, point)],
+            closed: false,
+        });
+        self.current = point;
+        self.start = point;
+    }
+
+    fn line_to(&mut self, point: Vector2<f32>) {
+        if self.contours.last().is_none_or(|contour| contour.closed) {
+            let current = self.current;
+            self.move_to(current);
+        }
+        let point_px = apply(&self.transform, point);
+        self.contours.last_mut().unwrap().points.push(point_px);
+        self.current = point;
+    }
+
+    /// The number of segments to split a curve into, from the length of its control polygon.
+    fn segments(&self, points: &[Vector2<f32>]) -> usize {
+        let length: f32 = points.windows(2).map(|pair| (pair[1] - pair[0]).magnitude()).sum();
+        ((length * self.scale / FLATTEN_LENGTH).ceil() as usize).clamp(1, 256)
+    }
+
+    fn quad_to(&mut self, control: Vector2<f32>, point: Vector2<f32>) {
+        let from = self.current;
+        let segments = self.segments(&[from, control, point]);
+        for step in 1..=segments {
+            let t = step as f32 / segments as f32;
+            let u = 1.0 - t;
+            self.line_to(from * (u * u) + control * (2.0 * u * t) + point * (t * t));
+        }
+    }
+
+    fn cubic_to(&mut self, first: Vector2<f32>, second: Vector2<f32>, point: Vector2<f32>) {
+        let from = self.current;
+        let segments = self.segments(&[from, first, second, point]);
+        for step in 1..=segments {
+            let t = step as f32 / segments as f32;
+            let u = 1.0 - t;
+            self.line_to(
+                from * (u * u * u)
+                    + first * (3.0 * u * u * t)
+                    + second * (3.0 * u * t * t)
+                    + point * (t * t * t),
+            );
+        }
+    }
+
+    /// Adds an elliptical arc, following the endpoint parameterization from the SVG spec.
+    fn arc_to(&mut self, radius: Vector2<f32>, angle: f32, large: bool, sweep: bool, point: Vector2<f32>) {
+        let from = self.current;
+        let (mut rx, mut ry) = (radius.x.abs(), radius.y.abs());
+        if rx == 0.0 || ry == 0.0 || from == point {
+            self.line_to(point);
+            return;
+        }
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let half = (from - point) / 2.0;
+        let x1 = cos * half.x + sin * half.y;
+        let y1 = -sin * half.x + cos * half.y;
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+        if large == sweep {
+            coefficient = -coefficient;
+        }
+        let cx1 = coefficient * rx * y1 / ry;
+        let cy1 = -coefficient * ry * x1 / rx;
+        let middle = (from + point) / 2.0;
+        let center = Vector2::new(cos * cx1 - sin * cy1 + middle.x, sin * cx1 + cos * cy1 + middle.y);
+        let start = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+        let mut delta = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx) - start;
+        if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        } else if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        }
+
+        let segments =
+            ((delta.abs() * rx.max(ry) * self.scale / FLATTEN_LENGTH).ceil() as usize).clamp(1, 256);
+        for step in 1..segments {
+            let (t_sin, t_cos) = (start + delta * step as f32 / segments as f32).sin_cos();
+            self.line_to(Vector2::new(
+                center.x + rx * t_cos * cos - ry * t_sin * sin,
+                center.y + rx * t_cos * sin + ry * t_sin * cos,
+            ));
+        }
+        // Ends exactly on the point to avoid drift.
+        self.line_to(point);
+    }
+
+    fn close(&mut self) {
+        if let Some(contour) = self.contours.last_mut() {
+            contour.closed = true;
+        }
+        self.current = self.start;
+    }
+}
+
+/// Reads numbers and commands from path data and point lists.
+struct Tokens<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(value: &'a str) -> Tokens<'a> {
+        Tokens {
+            bytes: value.as_bytes(),
+            position: 0,
+        }
+    }
+
+    fn skip(&mut self) {
+        while let Some(byte) = self.bytes.get(self.position) {
+            if !(byte.is_ascii_whitespace() || *byte == b',') {
+                break;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn is_done(&mut self) -> bool {
+        self.skip();
+        self.position >= self.bytes.len()
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        self.skip();
+        let byte = *self.bytes.get(self.position)?;
+        if byte.is_ascii_alphabetic() && byte != b'e' && byte != b'E' {
+            self.position += 1;
+            return Some(byte);
+        }
+        None
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip();
+        let start = self.position;
+        let peek = |position: usize| self.bytes.get(position).copied().unwrap_or(0);
+        let mut end = start;
+        if peek(end) == b'+' || peek(end) == b'-' {
+            end += 1;
+        }
+        let mut dot = false;
+        while peek(end).is_ascii_digit() || (peek(end) == b'.' && !dot) {
+            dot |= peek(end) == b'.';
+            end += 1;
+        }
+        if (peek(end) == b'e' || peek(end) == b'E')
+            && (peek(end + 1).is_ascii_digit()
+                || ((peek(end + 1) == b'-' || peek(end + 1) == b'+') && peek(end + 2).is_ascii_digit()))
+        {
+            end += 2;
+            while peek(end).is_ascii_digit() {
+                end += 1;
+            }
+        }
+        let value = core::str::from_utf8(&self.bytes[start..end]).ok()?.parse().ok()?;
+        self.position = end;
+        Some(value)
+    }
+
+    fn point(&mut self) -> Option<Vector2<f32>> {
+        Some(Vector2::new(self.number()?, self.number()?))
+    }
+
+    /// Arc flags may be written without separators, like "a1 1 0 01 1 1".
+    fn flag(&mut self) -> Option<bool> {
+        self.skip();
+        let value = match self.bytes.get(self.position)? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.position += 1;
+        Some(value)
+    }
+}
+
+/// Parses path data. Drawing stops at the first error, as the spec asks.
+fn parse_path(builder: &mut PathBuilder, data: &str) {
+    let mut tokens = Tokens::new(data);
+    let mut command = 0;
+    // The second control point of the last curve, for the smooth curve commands.
+    let mut last_control: Option<(u8, Vector2<f32>)> = None;
+    while !tokens.is_done() {
+        match tokens.command() {
+            Some(next) => command = next,
+            None if command == b'M' => command = b'L',
+            None if command == b'm' => command = b'l',
+            None if command == 0 || command == b'Z' || command == b'z' => return,
+            None => {}
+        }
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative {
+            builder.current
+        } else {
+            Vector2::zero()
+        };
+        // The first control point of a smooth curve reflects the last curve's second one.
+        let reflect = |kind: u8| match last_control {
+            Some((last, control)) if last == kind => builder.current * 2.0 - control,
+            _ => builder.current,
+        };
+        let (smooth_cubic, smooth_quad) = (reflect(b'C'), reflect(b'Q'));
+        let result = (|| {
+            let control = match command.to_ascii_uppercase() {
+                b'M' => {
+                    builder.move_to(origin + tokens.point()?);
+                    None
+                }
+                b'L' => {
+                    builder.line_to(origin + tokens.point()?);
+                    None
+                }
+                b'H' => {
+                    builder.line_to(Vector2::new(origin.x + tokens.number()?, builder.current.y));
+                    None
+                }
+                b'V' => {
+                    builder.line_to(Vector2::new(builder.current.x, origin.y + tokens.number()?));
+                    None
+                }
+                b'C' => {
+                    let (first, second) = (origin + tokens.point()?, origin + tokens.point()?);
+                    builder.cubic_to(first, second, origin + tokens.point()?);
+                    Some((b'C', second))
+                }
+                b'S' => {
+                    let first = smooth_cubic;
+                    let second = origin + tokens.point()?;
+                    builder.cubic_to(first, second, origin + tokens.point()?);
+                    Some((b'C', second))
+                }
+                b'Q' => {
+                    let control = origin + tokens.point()?;
+                    builder.quad_to(control, origin + tokens.point()?);
+                    Some((b'Q', control))
+                }
+                b'T' => {
+                    let control = smooth_quad;
+                    builder.quad_to(control, origin + tokens.point()?);
+                    Some((b'Q', control))
+                }
+                b'A' => {
+                    let radius = tokens.point()?;
+                    let angle = tokens.number()?;
+                    let (large, sweep) = (tokens.flag()?, tokens.flag()?);
+                    builder.arc_to(radius, angle, large, sweep, origin + tokens.point()?);
+                    None
+                }
+                b'Z' => {
+                    builder.close();
+                    None
+                }
+                _ => return None,
+            };
+            Some(control)
+        })();
+        match result {
+            Some(control) => last_control = control,
+            None => return,
+        }
+    }
+}
+
+fn parse_transform(value: &str) -> Affine {
+    let mut transform = IDENTITY;
+    for part in value.split(')') {
+        let (name, arguments) = match part.split_once('(') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let mut tokens = Tokens::new(arguments);
+        let mut values = Vec::new();
+        while let Some(value) = tokens.number() {
+            values.push(value);
+        }
+        let value = |index: usize, default: f32| values.get(index).copied().unwrap_or(default);
+        let next = match name.trim().trim_start_matches(',').trim() {
+            "matrix" if values.len() == 6 => {
+                [values[0], values[1], values[2], values[3], values[4], values[5]]
+            }
+            "translate" => [1.0, 0.0, 0.0, 1.0, value(0, 0.0), value(1, 0.0)],
+            "scale" => [value(0, 1.0), 0.0, 0.0, value(1, value(0, 1.0)), 0.0, 0.0],
+            "rotate" => {
+                let (sin, cos) = value(0, 0.0).to_radians().sin_cos();
+                let (x, y) = (value(1, 0.0), value(2, 0.0));
+                // Rotates around (x, y).
+                [cos, sin, -sin, cos, x - cos * x + sin * y, y - sin * x - cos * y]
+            }
+            "skewX" => [1.0, 0.0, value(0, 0.0).to_radians().tan(), 1.0, 0.0, 0.0],
+            "skewY" => [1.0, value(0, 0.0).to_radians().tan(), 0.0, 1.0, 0.0, 0.0],
+            _ => continue,
+        };
+        transform = multiply(&transform, &next);
+    }
+    transform
+}
+
+/// Parses a length in user units. Percentages aren't supported.
+fn parse_length(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let split = value.find(|c: char| c.is_ascii_alphabetic() || c == '%').unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let factor = match unit {
+        "" | "px" => 1.0,
+        "pt" => 4.0 / 3.0,
+        "pc" => 16.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "em" => 16.0,
+        _ => return None,
+    };
+    number.trim().parse::<f32>().ok().map(|number| number * factor)
+}
+
+/// Parses a paint, where "none" and unsupported paints like gradients are None. Gradient
+/// references use their fallback color if they have one.
+fn parse_paint(value: &str) -> Option<RGBA8> {
+    let value = value.trim();
+    if value.starts_with("url(") {
+        return parse_paint(value.split_once(')')?.1);
+    }
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits: Vec<u8> =
+            hex.chars().map(|c| c.to_digit(16).map(|digit| digit as u8)).collect::<Option<_>>()?;
+        return match digits.len() {
+            3 => Some(RGBA8::new_raw(digits[0] * 17, digits[1] * 17, digits[2] * 17, 255)),
+            6 => Some(RGBA8::new_raw(
+                digits[0] * 16 + digits[1],
+                digits[2] * 16 + digits[3],
+                digits[4] * 16 + digits[5],
+                255,
+            )),
+            _ => None,
+        };
+    }
+    if let Some(arguments) = value.strip_prefix("rgb(").and_then(|rest| rest.strip_suffix(')')) {
+        let mut channels = arguments.split(',').map(|channel| {
+            let channel = channel.trim();
+            match channel.strip_suffix('%') {
+                Some(percent) => percent.trim().parse::<f32>().ok().map(|percent| percent * 2.55),
+                None => channel.parse::<f32>().ok(),
+            }
+            .map(|channel| channel.round().clamp(0.0, 255.0) as u8)
+        });
+        return Some(RGBA8::new_raw(channels.next()??, channels.next()??, channels.next()??, 255));
+    }
+    let (r, g, b) = match value.to_ascii_lowercase().as_str() {
+        "black" | "currentcolor" => (0, 0, 0),
+        "white" => (255, 255, 255),
+        "red" => (255, 0, 0),
+        "lime" => (0, 255, 0),
+        "green" => (0, 128, 0),
+        "blue" => (0, 0, 255),
+        "yellow" => (255, 255, 0),
+        "cyan" | "aqua" => (0, 255, 255),
+        "magenta" | "fuchsia" => (255, 0, 255),
+        "gray" | "grey" => (128, 128, 128),
+        "silver" => (192, 192, 192),
+        "maroon" => (128, 0, 0),
+        "olive" => (128, 128, 0),
+        "navy" => (0, 0, 128),
+        "purple" => (128, 0, 128),
+        "teal" => (0, 128, 128),
+        "orange" => (255, 165, 0),
+        _ => return None,
+    };
+    Some(RGBA8::new_raw(r, g, b, 255))
+}
+
+/// Premultiplied color that shapes are composited into.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vector4<f32>>,
+    coverage: Vec<f32>,
+    crossings: Vec<(f32, i32)>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![Vector4::zero(); width * height],
+            coverage: vec![0.0; width],
+            crossings: Vec::new(),
+        }
+    }
+
+    /// Fills the polygons, treating every contour as closed.
+    fn fill(&mut self, polygons: &[Vec<Vector2<f32>>], color: RGBA8, opacity: f32, even_odd: bool) {
+        let (top, bottom) = polygons
+            .iter()
+            .flatten()
+            .fold((f32::MAX, f32::MIN), |(top, bottom), point| (top.min(point.y), bottom.max(point.y)));
+        if top > bottom {
+            return;
+        }
+        let alpha = color.a as f32 / 255.0 * opacity;
+        let source =
+            Vector4::new(color.r as f32 / 255.0, color.g as f32 / 255.0, color.b as f32 / 255.0, 1.0);
+        let first = top.floor().max(0.0) as usize;
+        let last = (bottom.ceil().max(0.0) as usize).min(self.height);
+        for row in first..last {
+            self.coverage.iter_mut().for_each(|value| *value = 0.0);
+            for sample in 0..SAMPLES {
+                let y = row as f32 + (sample as f32 + 0.5) / SAMPLES as f32;
+                self.crossings.clear();
+                for polygon in polygons {
+                    for index in 0..polygon.len() {
+                        let (from, to) = (polygon[index], polygon[(index + 1) % polygon.len()]);
+                        if (from.y <= y) != (to.y <= y) {
+                            let x = from.x + (y - from.y) * (to.x - from.x) / (to.y - from.y);
+                            self.crossings.push((
+                                x,
+                                if to.y > from.y {
+                                    1
+                                } else {
+                                    -1
+                                },
+                            ));
+                        }
+                    }
+                }
+                self.crossings
+                    .sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(core::cmp::Ordering::Equal));
+                let mut winding = 0;
+                for index in 0..self.crossings.len().saturating_sub(1) {
+                    winding += self.crossings[index].1;
+                    let inside = if even_odd {
+                        winding % 2 != 0
+                    } else {
+                        winding != 0
+                    };
+                    if inside {
+                        let (left, right) = (self.crossings[index].0, self.crossings[index + 1].0);
+                        Self::span(&mut self.coverage, left, right);
+                    }
+                }
+            }
+            for (x, coverage) in self.coverage.iter().enumerate() {
+                let coverage = (coverage / SAMPLES as f32).min(1.0) * alpha;
+                if coverage > 0.0 {
+                    let pixel = &mut self.pixels[row * self.width + x];
+                    *pixel = source * coverage + *pixel * (1.0 - coverage);
+                }
+            }
+        }
+    }
+
+    /// Adds the horizontal coverage of a span to the row.
+    fn span(coverage: &mut [f32], left: f32, right: f32) {
+        let left = left.max(0.0);
+        let right = right.min(coverage.len() as f32);
+        if right <= left {
+            return;
+        }
+        let (first, last) = (left.floor() as usize, right.ceil() as usize);
+        for (x, value) in coverage.iter_mut().enumerate().take(last).skip(first) {
+            *value += right.min(x as f32 + 1.0) - left.max(x as f32);
+        }
+    }
+
+    fn into_image(self) -> Image {
+        let pixels = self
+            .pixels
+            .iter()
+            .map(|pixel| {
+                if pixel.w <= 0.0 {
+                    return RGBA8::new_raw(0, 0, 0, 0);
+                }
+                let channel = |value: f32| (value / pixel.w * 255.0).round().clamp(0.0, 255.0) as u8;
+                RGBA8::new_raw(
+                    channel(pixel.x),
+                    channel(pixel.y),
+                    channel(pixel.z),
+                    (pixel.w * 255.0).round() as u8,
+                )
+            })
+            .collect();
+        Image::from_vec(pixels, self.width as u32, self.height as u32)
+    }
+}
+
+/// Reverses the polygon if needed so it winds the same way as every other stroke piece, which
+/// lets overlapping pieces merge under the nonzero rule.
+fn oriented(mut polygon: Vec<Vector2<f32>>) -> Vec<Vector2<f32>> {
+    let mut area = 0.0;
+    for index in 0..polygon.len() {
+        let (a, b) = (polygon[index], polygon[(index + 1) % polygon.len()]);
+        area += a.x * b.y - b.x * a.y;
+    }
+    if area < 0.0 {
+        polygon.reverse();
+    }
+    polygon
+}
+
+/// Converts contours into polygons covering their stroke, with butt caps and round joins.
+fn stroke(contours: &[Contour], width: f32) -> Vec<Vec<Vector2<f32>>> {
+    let half = width / 2.0;
+    let joint_segments = ((PI * width / FLATTEN_LENGTH).ceil() as usize).clamp(8, 64);
+    let mut polygons = Vec::new();
+    for contour in contours {
+        let points = &contour.points;
+        let count = if contour.closed {
+            points.len()
+        } else {
+            points.len().saturating_sub(1)
+        };
+        for index in 0..count {
+            let (from, to) = (points[index], points[(index + 1) % points.len()]);
+            let direction = to - from;
+            if direction.magnitude2() == 0.0 {
+                continue;
+            }
+            let normal = Vector2::new(-direction.y, direction.x).normalize() * half;
+            polygons.push(oriented(vec![from + normal, to + normal, to - normal, from - normal]));
+        }
+        let joints = if contour.closed {
+            0..points.len()
+        } else {
+            1..points.len().saturating_sub(1)
+        };
+        for index in joints {
+            let center = points[index];
+            polygons.push(
+                (0..joint_segments)
+                    .map(|step| {
+                        let (sin, cos) = (step as f32 / joint_segments as f32 * 2.0 * PI).sin_cos();
+                        center + Vector2::new(cos, sin) * half
+                    })
+                    .collect(),
+            );
+        }
+    }
+    polygons
+}
+
+fn attr(attributes: &[OwnedAttribute], key: &str) -> Option<String> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name.local_name == key)
+        .map(|attribute| attribute.value.clone())
+}
+
+fn attr_length(attributes: &[OwnedAttribute], key: &str) -> Option<f32> {
+    attr(attributes, key).and_then(|value| parse_length(&value))
+}
+
+/// Builds the outline of a basic shape or path element.
+fn outline(name: &str, attributes: &[OwnedAttribute], transform: Affine) -> Option<Vec<Contour>> {
+    let length = |key: &str| attr_length(attributes, key).unwrap_or(0.0);
+    let mut builder = PathBuilder::new(transform);
+    match name {
+        "path" => parse_path(&mut builder, &attr(attributes, "d")?),
+        "rect" => {
+            let (x, y, width, height) = (length("x"), length("y"), length("width"), length("height"));
+            if width <= 0.0 || height <= 0.0 {
+                return None;
+            }
+            let rx = attr_length(attributes, "rx");
+            let ry = attr_length(attributes, "ry");
+            let radius = Vector2::new(
+                rx.or(ry).unwrap_or(0.0).clamp(0.0, width / 2.0),
+                ry.or(rx).unwrap_or(0.0).clamp(0.0, height / 2.0),
+            );
+            builder.move_to(Vector2::new(x + radius.x, y));
+            builder.line_to(Vector2::new(x + width - radius.x, y));
+            builder.arc_to(radius, 0.0, false, true, Vector2::new(x + width, y + radius.y));
+            builder.line_to(Vector2::new(x + width, y + height - radius.y));
+            builder.arc_to(radius, 0.0, false, true, Vector2::new(x + width - radius.x, y + height));
+            builder.line_to(Vector2::new(x + radius.x, y + height));
+            builder.arc_to(radius, 0.0, false, true, Vector2::new(x, y + height - radius.y));
+            builder.line_to(Vector2::new(x, y + radius.y));
+            builder.arc_to(radius, 0.0, false, true, Vector2::new(x + radius.x, y));
+            builder.close();
+        }
+        "circle" | "ellipse" => {
+            let center = Vector2::new(length("cx"), length("cy"));
+            let radius = if name == "circle" {
+                Vector2::new(length("r"), length("r"))
+            } else {
+                Vector2::new(length("rx"), length("ry"))
+            };
+            if radius.x <= 0.0 || radius.y <= 0.0 {
+                return None;
+            }
+            builder.move_to(center + Vector2::new(radius.x, 0.0));
+            builder.arc_to(radius, 0.0, false, true, center - Vector2::new(radius.x, 0.0));
+            builder.arc_to(radius, 0.0, false, true, center + Vector2::new(radius.x, 0.0));
+            builder.close();
+        }
+        "line" => {
+            builder.move_to(Vector2::new(length("x1"), length("y1")));
+            builder.line_to(Vector2::new(length("x2"), length("y2")));
+        }
+        "polyline" | "polygon" => {
+            let points = attr(attributes, "points")?;
+            let mut tokens = Tokens::new(&points);
+            let mut first = true;
+            while let Some(point) = tokens.point() {
+                if first {
+                    builder.move_to(point);
+                    first = false;
+                } else {
+                    builder.line_to(point);
+                }
+            }
+            if name == "polygon" {
+                builder.close();
+            }
+        }
+        _ => return None,
+    }
+    Some(builder.contours)
+}
+
+/// Sets up the canvas from the root element. The view box is fit inside the size while keeping
+/// its aspect ratio, and centered.
+fn root(attributes: &[OwnedAttribute], scale: f32) -> (Canvas, Affine) {
+    let view_box: Option<[f32; 4]> = attr(attributes, "viewBox").and_then(|value| {
+        let mut tokens = Tokens::new(&value);
+        let values = [tokens.number()?, tokens.number()?, tokens.number()?, tokens.number()?];
+        Some(values).filter(|values| values[2] > 0.0 && values[3] > 0.0)
+    });
+    let width = attr_length(attributes, "width").or(view_box.map(|values| values[2]));
+    let height = attr_length(attributes, "height").or(view_box.map(|values| values[3]));
+    let (width, height) = match (width, height) {
+        (Some(width), Some(height)) if width > 0.0 && height > 0.0 => (width, height),
+        _ => panic!("SVG file has no size."),
+    };
+    let mut transform = [scale, 0.0, 0.0, scale, 0.0, 0.0];
+    if let Some([x, y, view_width, view_height]) = view_box {
+        let fit = (width / view_width).min(height / view_height);
+        let offset = Vector2::new(width - view_width * fit, height - view_height * fit) / 2.0;
+        transform = multiply(&transform, &[fit, 0.0, 0.0, fit, offset.x - x * fit, offset.y - y * fit]);
+    }
+    let pixels = |size: f32| ((size * scale).ceil() as usize).max(1);
+    (Canvas::new(pixels(width), pixels(height)), transform)
+}
+
+/// Rasterizes an SVG file, scaling its size by the scale. Paths, basic shapes, groups,
+/// transforms, solid fills and strokes, and opacity are supported. Gradients, patterns, clipping,
+/// masks, text, and references with `use` are skipped. If there is an issue parsing the file, this
+/// function will panic.
+pub fn read(bytes: &[u8], scale: f32) -> Image {
+    if scale.is_nan() || scale <= 0.0 {
+        panic!("SVG scale must be greater than 0.");
+    }
+    let mut canvas: Option<Canvas> = None;
+    let mut styles: Vec<Style> = Vec::new();
+    // Depth inside an element whose content isn't drawn.
+    let mut hidden = 0;
+    for event in EventReader::new(bytes) {
+        match event.expect("Unable to parse SVG.") {
+            XmlEvent::StartElement {
+                name,
+                attributes,
+                ..
+            } => {
+                let name = name.local_name.as_str();
+                let display_none = attr(&attributes, "display").is_some_and(|value| value.trim() == "none")
+                    || attr(&attributes, "style")
+                        .is_some_and(|value| value.replace(' ', "").contains("display:none"));
+                if hidden > 0 || HIDDEN.contains(&name) || display_none {
+                    hidden += 1;
+                    continue;
+                }
+                if canvas.is_none() {
+                    if name != "svg" {
+                        panic!("SVG file has no svg element.");
+                    }
+                    let (root_canvas, transform) = root(&attributes, scale);
+                    canvas = Some(root_canvas);
+                    styles.push(Style::new(transform));
+                }
+                let mut style = styles.last().unwrap().clone();
+                style.apply(&attributes);
+                if let Some(contours) = outline(name, &attributes, style.transform) {
+                    let canvas = canvas.as_mut().unwrap();
+                    if let Some(fill) = style.fill {
+                        let polygons: Vec<Vec<Vector2<f32>>> =
+                            contours.iter().map(|contour| contour.points.clone()).collect();
+                        canvas.fill(&polygons, fill, style.opacity * style.fill_opacity, style.even_odd);
+                    }
+                    if let (Some(stroke_color), true) = (style.stroke, style.stroke_width > 0.0) {
+                        let polygons = stroke(&contours, style.stroke_width * scale_of(&style.transform));
+                        canvas.fill(&polygons, stroke_color, style.opacity * style.stroke_opacity, false);
+                    }
+                }
+                styles.push(style);
+            }
+            XmlEvent::EndElement {
+                ..
+            } => {
+                if hidden > 0 {
+                    hidden -= 1;
+                } else {
+                    styles.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+    canvas.expect("SVG file has no svg element.").into_image()
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes() {
+        let svg = br##"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="4" viewBox="0 0 16 8">
+            <rect width="8" height="8" fill="#f00"/>
+            <g transform="translate(8 0)" opacity="0.5">
+                <path d="M0 0h8v8H0z" fill="blue"/>
+            </g>
+            <defs><rect width="16" height="8" fill="lime"/></defs>
+        </svg>"##;
+        let image = read(svg, 2.0);
+        let pixels = image.as_slice();
+        assert_eq!((image.width(), image.height()), (16, 8));
+        assert_eq!(pixels[0], RGBA8::new_raw(255, 0, 0, 255));
+        assert_eq!(pixels[7 * 16 + 7], RGBA8::new_raw(255, 0, 0, 255));
+        assert_eq!(pixels[8], RGBA8::new_raw(0, 0, 255, 128));
+    }
+
+    #[test]
+    fn path_data() {
+        let mut tokens = Tokens::new("M1.5.5-2e1,3a1 1 0 01 4 5");
+        assert_eq!(tokens.command(), Some(b'M'));
+        assert_eq!(tokens.number(), Some(1.5));
+        assert_eq!(tokens.number(), Some(0.5));
+        assert_eq!(tokens.number(), Some(-20.0));
+        assert_eq!(tokens.number(), Some(3.0));
+        assert_eq!(tokens.command(), Some(b'a'));
+        assert_eq!(tokens.point(), Some(Vector2::new(1.0, 1.0)));
+        assert_eq!(tokens.number(), Some(0.0));
+        assert_eq!((tokens.flag(), tokens.flag()), (Some(false), Some(true)));
+        assert_eq!(tokens.point(), Some(Vector2::new(4.0, 5.0)));
+        assert!(tokens.is_done());
+
+        // A half circle from the arc command ends exactly on its end point.
+        let mut builder = PathBuilder::new(IDENTITY);
+        parse_path(&mut builder, "M0 0 A5 5 0 0 1 10 0");
+        let points = &builder.contours[0].points;
+        assert_eq!(*points.last().unwrap(), Vector2::new(10.0, 0.0));
+        assert!(points.iter().all(|point| ((point - Vector2::new(5.0, 0.0)).magnitude() - 5.0).abs() < 0.01));
+    }
+}
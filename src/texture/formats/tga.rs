@@ -0,0 +1,181 @@
+use crate::texture::image::Image;
+use crate::RGBA8;
+
+const TYPE_COLOR_MAPPED: u8 = 1;
+const TYPE_TRUE_COLOR: u8 = 2;
+const TYPE_GRAYSCALE: u8 = 3;
+const TYPE_RLE: u8 = 8;
+const DESCRIPTOR_RIGHT_TO_LEFT: u8 = 0x10;
+const DESCRIPTOR_TOP_TO_BOTTOM: u8 = 0x20;
+
+/// Decodes a single pixel in the file's layout, which stores channels as BGR(A).
+fn pixel(bytes: &[u8], bpp: u8, grayscale: bool) -> RGBA8 {
+    match (bpp, grayscale) {
+        (8, true) => RGBA8::new_raw(bytes[0], bytes[0], bytes[0], 255),
+        (16, true) => RGBA8::new_raw(bytes[0], bytes[0], bytes[0], bytes[1]),
+        (15, false) | (16, false) => {
+            let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+            let scale = |bits: u16| ((bits & 0x1F) * 255 / 31) as u8;
+            RGBA8::new_raw(scale(value >> 10), scale(value >> 5), scale(value), 255)
+        }
+        (24, false) => RGBA8::new_raw(bytes[2], bytes[1], bytes[0], 255),
+        (32, false) => RGBA8::new_raw(bytes[2], bytes[1], bytes[0], bytes[3]),
+        _ => panic!("TGA bit depth {} is unsupported.", bpp),
+    }
+}
+
+pub fn read(bytes: &[u8]) -> Image {
+    if bytes.len() < 18 {
+        panic!("TGA file has an invalid header.");
+    }
+    let id_len = bytes[0] as usize;
+    let map_type = bytes[1];
+    let image_type = bytes[2];
+    let map_first = u16::from_le_bytes([bytes[3], bytes[4]]) as usize;
+    let map_len = u16::from_le_bytes([bytes[5], bytes[6]]) as usize;
+    let map_bpp = bytes[7];
+    let width = u16::from_le_bytes([bytes[12], bytes[13]]) as u32;
+    let height = u16::from_le_bytes([bytes[14], bytes[15]]) as u32;
+    let bpp = bytes[16];
+    let descriptor = bytes[17];
+    if width == 0 || height == 0 {
+        panic!("TGA image has an invalid size.");
+    }
+
+    let rle = image_type & TYPE_RLE != 0;
+    let kind = image_type & !TYPE_RLE;
+    let mut position = 18 + id_len;
+    let map_size = (map_bpp as usize).div_ceil(8);
+    let palette: Vec<RGBA8> = if map_type == 1 {
+        let data = bytes.get(position..position + map_len * map_size).expect("TGA file is truncated.");
+        position += map_len * map_size;
+        data.chunks_exact(map_size).map(|entry| pixel(entry, map_bpp, false)).collect()
+    } else {
+        Vec::new()
+    };
+    let lookup = |data: &[u8]| -> RGBA8 {
+        match kind {
+            TYPE_COLOR_MAPPED => {
+                let index = if bpp == 16 {
+                    u16::from_le_bytes([data[0], data[1]]) as usize
+                } else {
+                    data[0] as usize
+                };
+                *palette.get(index.wrapping_sub(map_first)).expect("TGA palette index is out of range.")
+            }
+            TYPE_TRUE_COLOR => pixel(data, bpp, false),
+            TYPE_GRAYSCALE => pixel(data, bpp, true),
+            _ => panic!("TGA image type {} is unsupported.", image_type),
+        }
+    };
+
+    let size = (bpp as usize).div_ceil(8);
+    let len = (width * height) as usize;
+    let mut pixels = Vec::with_capacity(len);
+    let mut take = |count: usize| {
+        let data = bytes.get(position..position + count).expect("TGA file is truncated.");
+        position += count;
+        data
+    };
+    while pixels.len() < len {
+        if rle {
+            let packet = take(1)[0];
+            let count = ((packet & 0x7F) as usize + 1).min(len - pixels.len());
+            if packet & 0x80 != 0 {
+                let value = lookup(take(size));
+                pixels.extend(core::iter::repeat_n(value, count));
+            } else {
+                for _ in 0..count {
+                    pixels.push(lookup(take(size)));
+                }
+            }
+        } else {
+            pixels.push(lookup(take(size)));
+        }
+    }
+
+    // Rows are stored bottom to top unless the descriptor says otherwise.
+    let (width, height) = (width as usize, height as usize);
+    let mut output = Vec::with_capacity(len);
+    for row in 0..height {
+        let source = if descriptor & DESCRIPTOR_TOP_TO_BOTTOM != 0 {
+            row
+        } else {
+            height - 1 - row
+        };
+        let line = &pixels[source * width..(source + 1) * width];
+        if descriptor & DESCRIPTOR_RIGHT_TO_LEFT != 0 {
+            output.extend(line.iter().rev());
+        } else {
+            output.extend_from_slice(line);
+        }
+    }
+    Image::from_vec(output, width as u32, height as u32)
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(image_type: u8, map: Option<(u16, u8)>, size: (u16, u16), bpp: u8, descriptor: u8) -> Vec<u8> {
+        let (map_len, map_bpp) = map.unwrap_or((0, 0));
+        let mut bytes = vec![0, map.is_some() as u8, image_type, 0, 0];
+        bytes.extend_from_slice(&map_len.to_le_bytes());
+        bytes.push(map_bpp);
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&size.0.to_le_bytes());
+        bytes.extend_from_slice(&size.1.to_le_bytes());
+        bytes.extend_from_slice(&[bpp, descriptor]);
+        bytes
+    }
+
+    #[test]
+    fn true_color() {
+        // Rows are stored bottom to top by default.
+        let mut bytes = header(TYPE_TRUE_COLOR, None, (2, 2), 24, 0);
+        bytes.extend_from_slice(&[0, 0, 255, 0, 255, 0]);
+        bytes.extend_from_slice(&[255, 0, 0, 255, 255, 255]);
+        let image = read(&bytes);
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(
+            image.as_slice(),
+            &[
+                RGBA8::new_raw(0, 0, 255, 255),
+                RGBA8::new_raw(255, 255, 255, 255),
+                RGBA8::new_raw(255, 0, 0, 255),
+                RGBA8::new_raw(0, 255, 0, 255),
+            ][..]
+        );
+    }
+
+    #[test]
+    fn run_length() {
+        let mut bytes = header(TYPE_TRUE_COLOR | TYPE_RLE, None, (3, 1), 32, DESCRIPTOR_TOP_TO_BOTTOM);
+        bytes.extend_from_slice(&[0x81, 30, 20, 10, 40]); // Two of the same.
+        bytes.extend_from_slice(&[0x00, 3, 2, 1, 4]); // One raw pixel.
+        assert_eq!(
+            read(&bytes).as_slice(),
+            &[RGBA8::new_raw(10, 20, 30, 40), RGBA8::new_raw(10, 20, 30, 40), RGBA8::new_raw(1, 2, 3, 4)][..]
+        );
+    }
+
+    #[test]
+    fn mapped_and_grayscale() {
+        let descriptor = DESCRIPTOR_TOP_TO_BOTTOM | DESCRIPTOR_RIGHT_TO_LEFT;
+        let mut bytes = header(TYPE_COLOR_MAPPED, Some((2, 24)), (2, 1), 8, descriptor);
+        bytes.extend_from_slice(&[0, 0, 255, 255, 0, 0]);
+        bytes.extend_from_slice(&[0, 1]);
+        assert_eq!(
+            read(&bytes).as_slice(),
+            &[RGBA8::new_raw(0, 0, 255, 255), RGBA8::new_raw(255, 0, 0, 255)][..]
+        );
+
+        let mut bytes = header(TYPE_GRAYSCALE, None, (1, 1), 8, 0);
+        bytes.push(100);
+        assert_eq!(read(&bytes).as_slice(), &[RGBA8::new_raw(100, 100, 100, 255)][..]);
+    }
+}
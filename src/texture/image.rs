@@ -12,6 +12,10 @@ impl Image {
     pub fn from_raw(bytes: &[u8], format: TextureFormat) -> Image {
         match format {
             TextureFormat::PNG => formats::png::read(bytes),
+            TextureFormat::JPEG => formats::jpeg::read(bytes),
+            TextureFormat::BMP => formats::bmp::read(bytes),
+            TextureFormat::TGA => formats::tga::read(bytes),
+            TextureFormat::QOI => formats::qoi::read(bytes),
         }
     }
 
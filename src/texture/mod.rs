@@ -1,8 +1,10 @@
 mod atlas;
-mod formats;
+mod batch;
+pub mod formats;
 mod image;
 mod packer;
 
 pub use self::atlas::TextureAtlas;
-pub use self::atlas::PIXEL_SIZE;
+pub use self::atlas::{MAX_SIZE, PIXEL_SIZE};
+pub use self::batch::decode_batch;
 pub use self::image::Image;
@@ -67,20 +67,22 @@ pub struct Packer {
     border: Rect,
     // The skylines are sorted by their `x` position.
     skylines: Vec<Skyline>,
+    /// Area of every rectangle packed so far.
+    used: u64,
 }
 
 impl Packer {
     pub fn new(w: u32, h: u32) -> Packer {
-        let mut skylines = Vec::new();
-        skylines.push(Skyline {
+        let skylines = vec![Skyline {
             x: 0,
             y: 0,
             w,
-        });
+        }];
 
         Packer {
             border: Rect::new(0, 0, w, h),
             skylines,
+            used: 0,
         }
     }
 
@@ -104,8 +106,8 @@ impl Packer {
     }
 
     fn find_skyline(&self, w: u32, h: u32) -> Option<(usize, Rect)> {
-        let mut bottom = core::u32::MAX;
-        let mut width = core::u32::MAX;
+        let mut bottom = u32::MAX;
+        let mut width = u32::MAX;
         let mut index = None;
         let mut rect = Rect::new(0, 0, 0, 0);
 
@@ -121,11 +123,7 @@ impl Packer {
             }
         }
 
-        if let Some(index) = index {
-            Some((index, rect))
-        } else {
-            None
-        }
+        index.map(|index| (index, rect))
     }
 
     fn split(&mut self, index: usize, rect: &Rect) {
@@ -171,10 +169,30 @@ impl Packer {
         }
     }
 
+    /// Enlarges the area being packed, keeping everything already packed in place.
+    pub fn grow(&mut self, w: u32, h: u32) {
+        let old = self.border;
+        if w > old.w {
+            self.skylines.push(Skyline {
+                x: old.w,
+                y: 0,
+                w: w - old.w,
+            });
+        }
+        self.border = Rect::new(0, 0, w.max(old.w), h.max(old.h));
+        self.merge();
+    }
+
+    /// The area of every rectangle packed so far.
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
     pub fn pack(&mut self, width: u32, height: u32) -> Option<Rect> {
         if let Some((i, rect)) = self.find_skyline(width, height) {
             self.split(i, &rect);
             self.merge();
+            self.used += rect.w as u64 * rect.h as u64;
             return Some(rect);
         }
         None
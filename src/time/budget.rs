@@ -0,0 +1,66 @@
+use core::time::Duration;
+
+/// Counts frames in a row that take longer than a budget, for FrameBudgetExceeded messages.
+pub struct FrameBudget {
+    budget: Duration,
+    frames: u32,
+    overruns: u32,
+}
+
+impl FrameBudget {
+    pub fn new(budget: Duration, frames: u32) -> FrameBudget {
+        FrameBudget {
+            budget,
+            frames: frames.max(1),
+            overruns: 0,
+        }
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Records a frame. Returns how many frames in a row have been over budget each time that
+    /// count reaches another multiple of the frames to wait for.
+    pub fn frame(&mut self, frame: Duration) -> Option<u32> {
+        if frame <= self.budget {
+            self.overruns = 0;
+            return None;
+        }
+        self.overruns = self.overruns.saturating_add(1);
+        if self.overruns % self.frames == 0 {
+            Some(self.overruns)
+        } else {
+            None
+        }
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overruns() {
+        let slow = Duration::from_millis(20);
+        let fast = Duration::from_millis(10);
+        let mut budget = FrameBudget::new(Duration::from_millis(16), 3);
+        let sent: Vec<u32> = (0..7).filter_map(|_| budget.frame(slow)).collect();
+        assert_eq!(sent, vec![3, 6]);
+
+        // A fast frame starts the count over.
+        assert_eq!(budget.frame(fast), None);
+        assert_eq!(budget.frame(slow), None);
+        assert_eq!(budget.frame(slow), None);
+        assert_eq!(budget.frame(slow), Some(3));
+
+        // Long slowdowns keep counting past the frame history's capacity.
+        let mut budget = FrameBudget::new(Duration::from_millis(16), 200);
+        let sent: Vec<u32> = (0..400).filter_map(|_| budget.frame(slow)).collect();
+        assert_eq!(sent, vec![200, 400]);
+    }
+}
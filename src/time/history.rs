@@ -0,0 +1,145 @@
+use alloc::collections::VecDeque;
+use core::time::Duration;
+
+/// The number of frames kept by default.
+const DEFAULT_CAPACITY: usize = 120;
+
+/// A rolling buffer of recent frame times, measured from one update to the next. Useful for
+/// deciding when to scale quality up or down.
+pub struct FrameHistory {
+    frames: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl FrameHistory {
+    pub(crate) fn new() -> FrameHistory {
+        FrameHistory {
+            frames: VecDeque::with_capacity(DEFAULT_CAPACITY),
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+
+    /// Records a frame, dropping the oldest one if the history is full.
+    pub(crate) fn push(&mut self, frame: Duration) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// Sets how many frames are kept. Older frames past the new capacity are dropped. The capacity
+    /// is at least 1.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.frames.len() > self.capacity {
+            self.frames.pop_front();
+        }
+    }
+
+    /// The maximum number of frames kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of frames recorded.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Iterates over the recorded frames, from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.frames.iter().copied()
+    }
+
+    /// The most recent frame.
+    pub fn latest(&self) -> Option<Duration> {
+        self.frames.back().copied()
+    }
+
+    /// The mean frame time, or zero if there's no history.
+    pub fn average(&self) -> Duration {
+        if self.frames.is_empty() {
+            return Duration::ZERO;
+        }
+        self.frames.iter().sum::<Duration>() / self.frames.len() as u32
+    }
+
+    /// The shortest frame time, or zero if there's no history.
+    pub fn min(&self) -> Duration {
+        self.frames.iter().min().copied().unwrap_or_default()
+    }
+
+    /// The longest frame time, or zero if there's no history.
+    pub fn max(&self) -> Duration {
+        self.frames.iter().max().copied().unwrap_or_default()
+    }
+
+    /// The standard deviation of frame times. Higher values mean less consistent pacing, even
+    /// when the average is fine.
+    pub fn jitter(&self) -> Duration {
+        if self.frames.len() < 2 {
+            return Duration::ZERO;
+        }
+        let average = self.average().as_secs_f64();
+        let variance = self
+            .frames
+            .iter()
+            .map(|frame| {
+                let difference = frame.as_secs_f64() - average;
+                difference * difference
+            })
+            .sum::<f64>()
+            / self.frames.len() as f64;
+        Duration::from_secs_f64(variance.sqrt())
+    }
+
+    /// The frame time that the given fraction of frames are at or under. For example, 0.99 gives
+    /// the 99th percentile. The fraction is clamped to [0, 1].
+    pub fn percentile(&self, fraction: f32) -> Duration {
+        if self.frames.is_empty() {
+            return Duration::ZERO;
+        }
+        let mut sorted: Vec<Duration> = self.frames.iter().copied().collect();
+        sorted.sort_unstable();
+        let index = ((sorted.len() - 1) as f32 * fraction.clamp(0.0, 1.0)).round() as usize;
+        sorted[index]
+    }
+
+    /// The number of most recent frames in a row that took longer than the budget.
+    pub fn over_budget(&self, budget: Duration) -> usize {
+        self.frames.iter().rev().take_while(|frame| **frame > budget).count()
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics() {
+        let mut history = FrameHistory::new();
+        history.set_capacity(4);
+        for millis in [100, 10, 20, 30, 40] {
+            history.push(Duration::from_millis(millis));
+        }
+
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.iter().next(), Some(Duration::from_millis(10)));
+        assert_eq!(history.average(), Duration::from_millis(25));
+        assert_eq!(history.min(), Duration::from_millis(10));
+        assert_eq!(history.max(), Duration::from_millis(40));
+        assert_eq!(history.percentile(1.0), Duration::from_millis(40));
+        assert_eq!(history.over_budget(Duration::from_millis(15)), 3);
+        assert_eq!(history.over_budget(Duration::from_millis(40)), 0);
+        let jitter = history.jitter().as_secs_f64();
+        assert!((jitter - 0.0111803).abs() < 0.000001);
+    }
+}
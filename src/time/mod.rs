@@ -1,6 +1,14 @@
+mod budget;
 mod convert;
+mod history;
+mod spans;
 mod timer;
+mod watchdog;
 
+pub(crate) use self::budget::FrameBudget;
 pub use self::convert::*;
+pub use self::history::FrameHistory;
+pub(crate) use self::spans::FrameSpans;
 pub use self::timer::*;
+pub(crate) use self::watchdog::Watchdog;
 pub use instant::Instant;
@@ -0,0 +1,120 @@
+use crate::time::Timer;
+use crate::types::{FramePhase, PhaseTiming, SpanEvent};
+use core::time::Duration;
+
+/// Times each phase of the frame loop. Each phase has its own timer, which also logs its average
+/// at trace level, and the hook is told when phases begin and end so they can be forwarded to an
+/// external profiler.
+pub struct FrameSpans {
+    timers: [Timer; 4],
+    /// Time spent in each phase so far this frame.
+    current: [Duration; 4],
+    /// Time spent in each phase last frame.
+    last: [Duration; 4],
+    hook: Option<fn(FramePhase, SpanEvent)>,
+}
+
+impl FrameSpans {
+    pub fn new() -> FrameSpans {
+        FrameSpans {
+            timers: [
+                Timer::new("Frame::Input"),
+                Timer::new("Frame::Simulation"),
+                Timer::new("Frame::Update"),
+                Timer::new("Frame::Draw"),
+            ],
+            current: [Duration::ZERO; 4],
+            last: [Duration::ZERO; 4],
+            hook: None,
+        }
+    }
+
+    pub fn set_hook(&mut self, hook: Option<fn(FramePhase, SpanEvent)>) {
+        self.hook = hook;
+    }
+
+    pub fn begin(&mut self, phase: FramePhase) {
+        if let Some(hook) = self.hook {
+            hook(phase, SpanEvent::Begin);
+        }
+        self.timers[phase as usize].start();
+    }
+
+    /// Ends the phase. A phase can run several times a frame, like input, and its times add up.
+    pub fn end(&mut self, phase: FramePhase) {
+        let elapsed = self.timers[phase as usize].stop();
+        self.current[phase as usize] += elapsed;
+        if let Some(hook) = self.hook {
+            hook(phase, SpanEvent::End(elapsed));
+        }
+    }
+
+    /// Finishes the frame, so its times are reported and the next frame starts from zero.
+    pub fn frame_end(&mut self) {
+        self.last = self.current;
+        self.current = [Duration::ZERO; 4];
+    }
+
+    /// The time spent in each phase last frame.
+    pub fn timings(&self) -> Vec<PhaseTiming> {
+        FramePhase::ALL
+            .iter()
+            .map(|&phase| PhaseTiming {
+                phase,
+                time: self.last[phase as usize],
+            })
+            .collect()
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static EVENTS: Mutex<Vec<(FramePhase, bool)>> = Mutex::new(Vec::new());
+
+    fn record(phase: FramePhase, event: SpanEvent) {
+        EVENTS.lock().unwrap().push((phase, event == SpanEvent::Begin));
+    }
+
+    #[test]
+    fn phases() {
+        let mut spans = FrameSpans::new();
+        spans.set_hook(Some(record));
+        spans.begin(FramePhase::Input);
+        spans.end(FramePhase::Input);
+        spans.begin(FramePhase::Input);
+        std::thread::sleep(Duration::from_millis(2));
+        spans.end(FramePhase::Input);
+        spans.begin(FramePhase::Update);
+        spans.end(FramePhase::Update);
+        assert!(spans.timings().iter().all(|timing| timing.time.is_zero()));
+
+        spans.frame_end();
+        let timings = spans.timings();
+        let phases: Vec<FramePhase> = timings.iter().map(|timing| timing.phase).collect();
+        assert_eq!(phases, FramePhase::ALL);
+        assert!(timings[0].time >= Duration::from_millis(2));
+        assert!(timings[1].time.is_zero());
+        assert_eq!(
+            *EVENTS.lock().unwrap(),
+            [
+                (FramePhase::Input, true),
+                (FramePhase::Input, false),
+                (FramePhase::Input, true),
+                (FramePhase::Input, false),
+                (FramePhase::Update, true),
+                (FramePhase::Update, false),
+            ]
+        );
+
+        // The next frame starts from zero.
+        spans.frame_end();
+        assert!(spans.timings().iter().all(|timing| timing.time.is_zero()));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::time::convert::*;
 use crate::time::Instant;
+use core::time::Duration;
 
 pub struct Timer {
     label: &'static str,
@@ -25,9 +26,11 @@ impl Timer {
         self.start = Instant::now();
     }
 
+    /// Stops timing, returning how long it's been since the timer was started.
     #[inline]
-    pub fn stop(&mut self) {
-        self.duration += as_nanoseconds(&self.start.elapsed());
+    pub fn stop(&mut self) -> Duration {
+        let elapsed = self.start.elapsed();
+        self.duration += as_nanoseconds(&elapsed);
         self.invocations += 1;
         if as_nanoseconds(&self.last_display.elapsed()) > NANOS_PER_SEC {
             self.last_display = Instant::now();
@@ -37,5 +40,6 @@ impl Timer {
             self.duration = 0;
             self.invocations = 0;
         }
+        elapsed
     }
 }
@@ -0,0 +1,67 @@
+use core::time::Duration;
+
+/// The number of over budget frames in a row before a warning is logged.
+const STRIKES: u32 = 3;
+
+/// Watches frame times and logs a warning when several frames in a row exceed the budget.
+pub struct Watchdog {
+    budget: Duration,
+    strikes: u32,
+}
+
+impl Watchdog {
+    pub fn new(budget: Duration) -> Watchdog {
+        Watchdog {
+            budget,
+            strikes: 0,
+        }
+    }
+
+    /// Records a frame, split into the time spent in the update event and the time spent
+    /// presenting the frame. Returns true if a warning was logged.
+    pub fn frame(&mut self, update: Duration, present: Duration) -> bool {
+        if update + present <= self.budget {
+            self.strikes = 0;
+            return false;
+        }
+        self.strikes += 1;
+        if self.strikes < STRIKES {
+            return false;
+        }
+        self.strikes = 0;
+        let phase = if update >= present {
+            "update"
+        } else {
+            "present"
+        };
+        warn!(
+            "Frame budget of {:?} exceeded {} frames in a row, slowest phase is {} (update {:?}, present {:?})",
+            self.budget, STRIKES, phase, update, present
+        );
+        true
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strikes() {
+        let mut watchdog = Watchdog::new(Duration::from_millis(10));
+        let slow = Duration::from_millis(8);
+        let fast = Duration::from_millis(1);
+
+        assert!(!watchdog.frame(slow, slow));
+        assert!(!watchdog.frame(slow, slow));
+        assert!(!watchdog.frame(fast, fast));
+        assert!(!watchdog.frame(slow, slow));
+        assert!(!watchdog.frame(slow, slow));
+        assert!(watchdog.frame(slow, slow));
+        assert!(!watchdog.frame(slow, slow));
+    }
+}
@@ -0,0 +1,191 @@
+use super::*;
+use cgmath::*;
+
+/// A single frame of an animation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AnimationFrame {
+    /// Texture shown during the frame.
+    pub texture: Texture,
+    /// How long the frame is shown in seconds.
+    pub duration: f32,
+}
+
+impl AnimationFrame {
+    pub fn new(texture: Texture, duration: f32) -> AnimationFrame {
+        AnimationFrame {
+            texture,
+            duration,
+        }
+    }
+}
+
+/// An ordered list of frames played by an animated sprite.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Animation {
+    pub frames: Vec<AnimationFrame>,
+}
+
+impl Animation {
+    pub fn new(frames: Vec<AnimationFrame>) -> Animation {
+        Animation {
+            frames,
+        }
+    }
+
+    /// Creates an animation where every frame is shown for the same duration in seconds.
+    pub fn from_textures(textures: &[Texture], duration: f32) -> Animation {
+        Animation {
+            frames: textures.iter().map(|&texture| AnimationFrame::new(texture, duration)).collect(),
+        }
+    }
+
+    /// The total length of one pass through the frames in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+}
+
+/// How an animated sprite proceeds after its last frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AnimationMode {
+    /// Stops on the last frame and reports the animation as completed.
+    Once,
+    /// Starts again from the first frame.
+    Loop,
+    /// Plays backwards to the first frame, then forwards again.
+    PingPong,
+}
+
+/// A named animation within a sprite sheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationClip {
+    pub name: String,
+    pub animation: Animation,
+    pub mode: AnimationMode,
+}
+
+/// Textures and animation clips loaded together from an animation file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpriteSheet {
+    /// Every frame in the file, in order.
+    pub animation: Animation,
+    /// The tagged ranges of frames in the file.
+    pub clips: Vec<AnimationClip>,
+}
+
+impl SpriteSheet {
+    /// Finds a clip by name.
+    pub fn clip(&self, name: &str) -> Option<&AnimationClip> {
+        self.clips.iter().find(|clip| clip.name == name)
+    }
+}
+
+/// Identifies an animated sprite in completion events.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AnimatedSpriteId(pub(crate) u64);
+
+/// A value a keyframe track passes through at a point in time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Keyframe<T> {
+    /// When the track reaches the value, in seconds from the start.
+    pub time: f32,
+    pub value: T,
+}
+
+impl<T> Keyframe<T> {
+    pub fn new(time: f32, value: T) -> Keyframe<T> {
+        Keyframe {
+            time,
+            value,
+        }
+    }
+}
+
+/// Tracks of keyframes that drive a sprite's properties over time, like a pulsing highlight or a
+/// short cutscene move. Each track's keyframes are sorted by time. Values are blended linearly
+/// between keyframes, and hold at the first and last keyframe before and after them. Properties
+/// with an empty track are left alone.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpriteKeyframes {
+    pub color: Vec<Keyframe<RGBA8>>,
+    pub position: Vec<Keyframe<Vector3<f32>>>,
+    /// Rotation in turns. Values aren't wrapped before blending, so a track from 0 to 2 spins the
+    /// sprite twice.
+    pub rotation: Vec<Keyframe<f32>>,
+    pub scale: Vec<Keyframe<Vector2<f32>>>,
+}
+
+impl SpriteKeyframes {
+    /// The time of the last keyframe across every track, in seconds.
+    pub fn duration(&self) -> f32 {
+        [
+            self.color.last().map(|frame| frame.time),
+            self.position.last().map(|frame| frame.time),
+            self.rotation.last().map(|frame| frame.time),
+            self.scale.last().map(|frame| frame.time),
+        ]
+        .iter()
+        .flatten()
+        .fold(0.0, |duration: f32, &time| duration.max(time))
+    }
+
+    /// Sets the sprite's tracked properties to their values at the time, in seconds.
+    pub fn apply(&self, time: f32, sprite: &mut Sprite) {
+        if let Some(color) = sample(&self.color, time, RGBA8::lerp) {
+            sprite.color = color;
+        }
+        if let Some(pos) = sample(&self.position, time, |a, b, t| a.lerp(b, t)) {
+            sprite.pos = pos;
+        }
+        if let Some(turns) = sample(&self.rotation, time, |a, b, t| a + (b - a) * t) {
+            sprite.rotation = (turns.rem_euclid(1.0) * 65536.0) as u16;
+        }
+        if let Some(scale) = sample(&self.scale, time, |a, b, t| a.lerp(b, t)) {
+            sprite.scale = scale;
+        }
+    }
+}
+
+/// Gets the track's value at the time. None if the track is empty.
+fn sample<T: Copy>(frames: &[Keyframe<T>], time: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let next = frames.iter().position(|frame| frame.time > time);
+    match next {
+        Some(0) => frames.first().map(|frame| frame.value),
+        Some(next) => {
+            let (a, b) = (&frames[next - 1], &frames[next]);
+            Some(lerp(a.value, b.value, (time - a.time) / (b.time - a.time)))
+        }
+        None => frames.last().map(|frame| frame.value),
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyframes() {
+        let keyframes = SpriteKeyframes {
+            color: vec![Keyframe::new(0.0, colors::BLACK), Keyframe::new(2.0, colors::WHITE)],
+            rotation: vec![Keyframe::new(1.0, 0.0), Keyframe::new(3.0, 1.5)],
+            ..SpriteKeyframes::default()
+        };
+        assert_eq!(keyframes.duration(), 3.0);
+
+        let mut sprite = Sprite::default();
+        keyframes.apply(1.0, &mut sprite);
+        assert_eq!(sprite.color, RGBA8::new_raw(128, 128, 128, 255));
+        assert_eq!(sprite.rotation, 0);
+        assert_eq!(sprite.pos, Sprite::default().pos);
+
+        keyframes.apply(2.0, &mut sprite);
+        assert_eq!(sprite.rotation, 49152);
+        keyframes.apply(5.0, &mut sprite);
+        assert_eq!(sprite.color, colors::WHITE);
+        assert_eq!(sprite.rotation, 32768);
+    }
+}
@@ -0,0 +1,130 @@
+use super::LayerTransform;
+use crate::math::AABB2D;
+use cgmath::*;
+
+/// A view into the world, which builds the transform for the layers that draw the world and
+/// converts between screen and world coordinates. Screen coordinates match the positions in
+/// cursor and touch events, with the origin at the center of the screen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// The point in the world shown at the center of the screen.
+    pub position: Vector2<f32>,
+    /// The zoom level on each axis. 2.0 shows everything twice as large. The default is 1.0 on
+    /// both axes.
+    pub zoom: Vector2<f32>,
+    /// Rotation of the view, measured in turns.
+    pub rotation: f32,
+    /// Size of the screen, which only matters for finding what's visible. Update this when the
+    /// window is resized.
+    pub viewport: Vector2<f32>,
+}
+
+impl Camera {
+    /// Creates a camera looking at the world's origin.
+    pub fn new(viewport: Vector2<f32>) -> Camera {
+        Camera {
+            position: Vector2::zero(),
+            zoom: Vector2::new(1.0, 1.0),
+            rotation: 0.0,
+            viewport,
+        }
+    }
+
+    /// The transform for layers drawing the world through this camera.
+    pub fn transform(&self) -> LayerTransform {
+        LayerTransform {
+            translation: -self.position,
+            scale: self.zoom,
+            rotation: self.rotation,
+            pivot: Vector2::zero(),
+        }
+    }
+
+    /// Converts a position on the screen, like the cursor's, into the world.
+    pub fn screen_to_world(&self, pos: Vector2<f32>) -> Vector2<f32> {
+        self.transform().screen_to_layer(pos)
+    }
+
+    /// Converts a position in the world onto the screen.
+    pub fn world_to_screen(&self, pos: Vector2<f32>) -> Vector2<f32> {
+        (self.transform().to_matrix() * pos.extend(0.0).extend(1.0)).truncate().truncate()
+    }
+
+    /// The size of the world visible on screen, ignoring rotation.
+    pub fn viewport_world_size(&self) -> Vector2<f32> {
+        self.viewport.div_element_wise(self.zoom)
+    }
+
+    /// The smallest box in the world containing everything visible on screen, like for culling.
+    pub fn visible_bounds(&self) -> AABB2D {
+        let half = self.viewport / 2.0;
+        let corners = [
+            self.screen_to_world(Vector2::new(-half.x, -half.y)),
+            self.screen_to_world(Vector2::new(half.x, -half.y)),
+            self.screen_to_world(Vector2::new(-half.x, half.y)),
+            self.screen_to_world(Vector2::new(half.x, half.y)),
+        ];
+        let mut min = corners[0];
+        let mut max = corners[0];
+        for corner in &corners[1..] {
+            min = Vector2::new(min.x.min(corner.x), min.y.min(corner.y));
+            max = Vector2::new(max.x.max(corner.x), max.y.max(corner.y));
+        }
+        AABB2D::new(min.x, min.y, max.x, max.y)
+    }
+
+    /// Moves the camera part of the way to the target, easing in smoothly. The rate is how quickly
+    /// the camera catches up, where after one second only 1 / e^rate of the distance is left. The
+    /// delta is the update's delta in seconds, so the motion is the same at any frame rate.
+    pub fn follow(&mut self, target: Vector2<f32>, rate: f32, delta: f32) {
+        self.position = self.position.lerp(target, 1.0 - (-rate * delta).exp());
+    }
+
+    /// Multiplies the zoom, keeping the world position under the screen position in place, like
+    /// zooming toward the cursor.
+    pub fn zoom_at(&mut self, screen: Vector2<f32>, factor: f32) {
+        // Layer transforms snap to whole pixels, so this is worked out without the snapping.
+        let rotation = Basis2::from_angle(Rad(-core::f32::consts::PI * 2.0 * self.rotation));
+        let before = rotation.rotate_vector(screen.div_element_wise(self.zoom));
+        self.zoom *= factor;
+        let after = rotation.rotate_vector(screen.div_element_wise(self.zoom));
+        self.position += before - after;
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vector2<f32>, b: Vector2<f32>) -> bool {
+        (a - b).magnitude() < 0.01
+    }
+
+    #[test]
+    fn conversions() {
+        let mut camera = Camera::new(Vector2::new(800.0, 600.0));
+        camera.position = Vector2::new(100.0, 50.0);
+        camera.zoom = Vector2::new(2.0, 2.0);
+        assert!(close(camera.world_to_screen(Vector2::new(100.0, 50.0)), Vector2::zero()));
+        assert!(close(camera.screen_to_world(Vector2::new(400.0, 300.0)), Vector2::new(300.0, 200.0)));
+        let bounds = camera.visible_bounds();
+        assert!(close(Vector2::new(bounds.min.x, bounds.min.y), Vector2::new(-100.0, -100.0)));
+
+        let cursor = Vector2::new(200.0, -100.0);
+        let under = camera.screen_to_world(cursor);
+        camera.zoom_at(cursor, 1.5);
+        // Within a pixel, since transforms snap to whole pixels.
+        assert!((camera.screen_to_world(cursor) - under).magnitude() <= 1.001 / camera.zoom.x);
+    }
+
+    #[test]
+    fn follow() {
+        let mut camera = Camera::new(Vector2::new(800.0, 600.0));
+        camera.follow(Vector2::new(100.0, 0.0), core::f32::consts::LN_2, 1.0);
+        assert!(close(camera.position, Vector2::new(50.0, 0.0)));
+    }
+}
@@ -42,8 +42,21 @@ impl RGBA8 {
             a: alpha,
         }
     }
+
+    /// Blends each channel from this color toward the other, where 0 is this color and 1 is the
+    /// other.
+    pub fn lerp(self, other: RGBA8, t: f32) -> RGBA8 {
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        RGBA8::new_raw(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            channel(self.a, other.a),
+        )
+    }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<Vector4<f32>> for RGBA8 {
     fn into(self) -> Vector4<f32> {
         Vector4::new(
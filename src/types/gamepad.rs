@@ -0,0 +1,88 @@
+/// How a gamepad axis's raw value is shaped before it's reported.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GamepadAxisSettings {
+    /// Values closer to rest than this, from [0, 1), are reported as 0. This hides stick drift.
+    pub deadzone: f32,
+    /// Values at least this far from rest, from (deadzone, 1], are reported as fully pressed. This
+    /// helps sticks that never quite reach their edge.
+    pub saturation: f32,
+    /// The curve applied to the range between the dead zone and saturation.
+    pub curve: ResponseCurve,
+}
+
+impl Default for GamepadAxisSettings {
+    fn default() -> GamepadAxisSettings {
+        GamepadAxisSettings {
+            deadzone: 0.1,
+            saturation: 1.0,
+            curve: ResponseCurve::Linear,
+        }
+    }
+}
+
+impl GamepadAxisSettings {
+    /// Shapes a raw axis value from [-1, 1]. Values inside the dead zone become 0, and the range
+    /// between the dead zone and saturation is rescaled to [0, 1] and run through the curve, so the
+    /// output has no jump at either edge. The sign is kept.
+    pub fn apply(&self, value: f32) -> f32 {
+        let deadzone = self.deadzone.clamp(0.0, 0.99);
+        let saturation = self.saturation.clamp(deadzone + 0.01, 1.0);
+        let magnitude = value.abs();
+        if magnitude <= deadzone {
+            return 0.0;
+        }
+        let scaled = ((magnitude - deadzone) / (saturation - deadzone)).min(1.0);
+        value.signum() * self.curve.apply(scaled)
+    }
+}
+
+/// A response curve for gamepad axes. Steeper curves give finer control near rest, like for
+/// aiming, while still reaching 1 at the edge.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ResponseCurve {
+    /// The output matches the input.
+    Linear,
+    /// The output is the input squared.
+    Quadratic,
+    /// The output is the input cubed.
+    Cubic,
+    /// The output is the input raised to the given exponent. Exponents below 1 make the axis more
+    /// sensitive near rest.
+    Power(f32),
+}
+
+impl ResponseCurve {
+    /// Applies the curve to a value from [0, 1].
+    pub fn apply(&self, value: f32) -> f32 {
+        match *self {
+            ResponseCurve::Linear => value,
+            ResponseCurve::Quadratic => value * value,
+            ResponseCurve::Cubic => value * value * value,
+            ResponseCurve::Power(exponent) => value.powf(exponent.max(0.01)),
+        }
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shaping() {
+        let settings = GamepadAxisSettings {
+            deadzone: 0.2,
+            saturation: 0.8,
+            curve: ResponseCurve::Quadratic,
+        };
+        assert_eq!(settings.apply(0.1), 0.0);
+        assert_eq!(settings.apply(-0.2), 0.0);
+        assert!((settings.apply(0.5) - 0.25).abs() < 0.0001);
+        assert!((settings.apply(-0.5) + 0.25).abs() < 0.0001);
+        assert_eq!(settings.apply(0.9), 1.0);
+        assert_eq!(settings.apply(-1.0), -1.0);
+    }
+}
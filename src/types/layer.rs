@@ -1,3 +1,5 @@
+use super::colors::{BLACK, WHITE};
+use super::RGBA8;
 use cgmath::*;
 
 /// Utility type to create simple transformation matrices.
@@ -5,31 +7,244 @@ use cgmath::*;
 pub struct LayerTransform {
     /// The translation of the layer.
     pub translation: Vector2<f32>,
-    /// The zoom level of the layer. This is 1.0 by default, meaning 1 pixel takes up 1x1 pixels on
-    /// screen.
-    pub scale: f32,
+    /// The zoom level of the layer on each axis. This is 1.0 on both axes by default, meaning 1
+    /// pixel takes up 1x1 pixels on screen. Different values on each axis stretch the layer.
+    pub scale: Vector2<f32>,
     /// Rotation is measured in turns from [0, 1). Values outside of the range are wrapped into the
     /// range. For example, 1.75 is wrapped into 0.75, -0.4 is wrapped into 0.6.
     pub rotation: f32,
+    /// The point the layer zooms and rotates around, in screen coordinates like the positions in
+    /// cursor events. Setting this to the cursor's position zooms toward the cursor. The default is
+    /// the center of the screen.
+    pub pivot: Vector2<f32>,
+}
+
+impl Default for LayerTransform {
+    fn default() -> LayerTransform {
+        LayerTransform::new()
+    }
 }
 
 impl LayerTransform {
     pub fn new() -> LayerTransform {
         LayerTransform {
             translation: Vector2::new(0.0, 0.0),
-            scale: 1.0,
+            scale: Vector2::new(1.0, 1.0),
             rotation: 0.0,
+            pivot: Vector2::new(0.0, 0.0),
         }
     }
 
+    /// Sets the same zoom level on both axes.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = Vector2::new(scale, scale);
+    }
+
     /// Creates a new transform matix based on the parameters of the LayerTransform. The transform
-    /// matrix is built in this order: Scale * Translation * Rotation.
+    /// matrix is built in this order: Pivot * Scale * Rotation * -Pivot * Translation.
     pub fn to_matrix(&self) -> Matrix4<f32> {
         let mut translation = self.translation;
-        translation.x = (translation.x * self.scale).floor() / self.scale;
-        translation.y = (translation.y * self.scale).floor() / self.scale;
-        Matrix4::from_scale(self.scale)
-            * Matrix4::from_translation(translation.extend(0.0))
+        translation.x = (translation.x * self.scale.x).floor() / self.scale.x;
+        translation.y = (translation.y * self.scale.y).floor() / self.scale.y;
+        Matrix4::from_translation(self.pivot.extend(0.0))
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, 1.0)
             * Matrix4::from_angle_z(Rad(core::f32::consts::PI * 2.0 * self.rotation))
+            * Matrix4::from_translation((translation - self.pivot).extend(0.0))
+    }
+
+    /// Converts a position in screen coordinates, like the positions in cursor and touch events,
+    /// into the coordinates of a layer using this transform.
+    pub fn screen_to_layer(&self, pos: Vector2<f32>) -> Vector2<f32> {
+        match self.to_matrix().invert() {
+            Some(inverse) => (inverse * pos.extend(0.0).extend(1.0)).truncate().truncate(),
+            None => pos,
+        }
+    }
+}
+
+/// Color adjustments applied to everything drawn by a layer, like dimming the world at night or
+/// desaturating a background behind a menu. They're applied in this order: Saturation, Curve,
+/// Multiply.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColorGrade {
+    /// Color every pixel is multiplied by, including alpha. The default is white, which leaves
+    /// colors unchanged.
+    pub multiply: RGBA8,
+    /// How saturated colors are, where 0 is grayscale and 1 is unchanged. Values above 1
+    /// oversaturate. The default is 1.
+    pub saturation: f32,
+    /// Tint that maps each pixel's brightness onto a gradient. The default is None.
+    pub curve: Option<TintCurve>,
+}
+
+impl Default for ColorGrade {
+    fn default() -> ColorGrade {
+        ColorGrade {
+            multiply: WHITE,
+            saturation: 1.0,
+            curve: None,
+        }
+    }
+}
+
+impl ColorGrade {
+    /// A grade that only multiplies colors.
+    pub fn tint(multiply: RGBA8) -> ColorGrade {
+        ColorGrade {
+            multiply,
+            ..ColorGrade::default()
+        }
+    }
+}
+
+/// A gradient from a shadow color to a highlight color. Dark pixels take on the shadow color and
+/// bright pixels take on the highlight color.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TintCurve {
+    /// Color that black maps to. The default is black.
+    pub shadows: RGBA8,
+    /// Color that white maps to. The default is white.
+    pub highlights: RGBA8,
+    /// How much of the gradient is blended over the original color, from 0 to 1. The default is 1.
+    pub strength: f32,
+}
+
+impl Default for TintCurve {
+    fn default() -> TintCurve {
+        TintCurve {
+            shadows: BLACK,
+            highlights: WHITE,
+            strength: 1.0,
+        }
+    }
+}
+
+impl TintCurve {
+    pub fn new(shadows: RGBA8, highlights: RGBA8, strength: f32) -> TintCurve {
+        TintCurve {
+            shadows,
+            highlights,
+            strength,
+        }
+    }
+}
+
+/// Where a screen space layer's origin sits, see `Layer::set_screen_space`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ScreenOrigin {
+    /// The center of the window, like other layers.
+    #[default]
+    Center,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl ScreenOrigin {
+    /// The origin's position in normalized device coordinates, from -1 to 1 on each axis.
+    pub(crate) fn ndc(self) -> Vector2<f32> {
+        match self {
+            ScreenOrigin::Center => Vector2::new(0.0, 0.0),
+            ScreenOrigin::TopLeft => Vector2::new(-1.0, 1.0),
+            ScreenOrigin::TopRight => Vector2::new(1.0, 1.0),
+            ScreenOrigin::BottomLeft => Vector2::new(-1.0, -1.0),
+            ScreenOrigin::BottomRight => Vector2::new(1.0, -1.0),
+        }
+    }
+}
+
+/// What a layer holds, see `Layer::info`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LayerInfo {
+    /// Number of sprites set with `set_sprites`.
+    pub sprites: usize,
+    /// Number of sprites set with `set_gradient_sprites`.
+    pub gradient_sprites: usize,
+    /// Number of sprites added with `sprite_create`.
+    pub created_sprites: usize,
+    /// Number of sprites submitted through `sprite_sender`, as of the last draw.
+    pub submitted_sprites: usize,
+    /// Number of instances set with `set_instances`.
+    pub instances: usize,
+    /// Number of sprites across every alternate set from `set_sprites_lod`.
+    pub lod_sprites: usize,
+    /// Bytes of GPU buffer memory held for the sprites, gradient sprites, created sprites,
+    /// submitted sprites, instances, and alternate sets. Buffers keep their memory when cleared, until they're set again.
+    pub gpu_bytes: usize,
+    /// The frame the contents last changed on, counted like `Engine::frame_number`. None if they
+    /// never have.
+    pub updated_frame: Option<u64>,
+}
+
+/// The order a layer draws its sprites in. In a sorted layer, sprites at the same depth overlap in
+/// draw order, with later sprites on top.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum SpriteSort {
+    /// Sprites are drawn in the order they were set.
+    #[default]
+    None,
+    /// Sprites are drawn back to front by depth, so translucent sprites blend over what's behind
+    /// them.
+    Depth,
+    /// Sprites are drawn back to front by depth, and sprites at the same depth are drawn from the
+    /// highest Y to the lowest, so sprites lower on the screen overlap the ones above them, like in
+    /// a top-down game.
+    YSort,
+}
+
+impl SpriteSort {
+    /// Sorts the items by their positions, keeping the set order for ties. Returns None if the
+    /// items don't need sorting.
+    pub(crate) fn sorted<T: Copy>(self, items: &[T], pos: impl Fn(&T) -> Vector3<f32>) -> Option<Vec<T>> {
+        let mut items = match self {
+            SpriteSort::None => return None,
+            _ => items.to_vec(),
+        };
+        items.sort_by(|a, b| {
+            let (a, b) = (pos(a), pos(b));
+            let depth = a.z.total_cmp(&b.z);
+            match self {
+                SpriteSort::YSort => depth.then(b.y.total_cmp(&a.y)),
+                _ => depth,
+            }
+        });
+        Some(items)
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort() {
+        let items = [Vector3::new(0.0, 5.0, 1.0), Vector3::new(1.0, 2.0, 0.0), Vector3::new(2.0, 9.0, 0.0)];
+        let order = |sort: SpriteSort| {
+            sort.sorted(&items, |pos| *pos).map(|items| items.iter().map(|pos| pos.x).collect::<Vec<_>>())
+        };
+        assert_eq!(order(SpriteSort::None), None);
+        assert_eq!(order(SpriteSort::Depth), Some(vec![1.0, 2.0, 0.0]));
+        assert_eq!(order(SpriteSort::YSort), Some(vec![2.0, 1.0, 0.0]));
+    }
+
+    #[test]
+    fn pivot() {
+        let mut transform = LayerTransform::new();
+        transform.translation = Vector2::new(10.0, 20.0);
+        transform.pivot = Vector2::new(100.0, -50.0);
+        let under = transform.screen_to_layer(transform.pivot);
+        transform.scale = Vector2::new(2.0, 4.0);
+        transform.rotation = 0.25;
+        let zoomed = transform.screen_to_layer(transform.pivot);
+        assert!((under - zoomed).magnitude() < 0.001);
+        assert!(
+            (transform.screen_to_layer(Vector2::new(100.0, -46.0)) - Vector2::new(91.0, -70.0)).magnitude()
+                < 0.001
+        );
     }
 }
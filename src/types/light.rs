@@ -0,0 +1,75 @@
+use super::*;
+use cgmath::*;
+
+/// A light drawn by a light map. Positions and distances are in the coordinate space of the light
+/// map, which matches a layer using the same transform.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Light {
+    /// The position of the light.
+    pub pos: Vector2<f32>,
+    /// The color of the light. The alpha channel scales the light's intensity.
+    pub color: RGBA8,
+    /// How far the light reaches in pixels. Light falls off to nothing at this distance.
+    pub radius: f32,
+    /// The direction a cone light points. Direction is measured in turns from [0, 1), where 0
+    /// points right. This is ignored for point lights.
+    pub direction: f32,
+    /// The full width of a cone light, measured in turns. A spread of 1 or more makes a point
+    /// light that shines in every direction.
+    pub spread: f32,
+}
+
+impl Default for Light {
+    fn default() -> Light {
+        Light::point(Vector2::new(0.0, 0.0), colors::WHITE, 100.0)
+    }
+}
+
+impl Light {
+    /// Creates a light that shines in every direction.
+    pub fn point(pos: Vector2<f32>, color: RGBA8, radius: f32) -> Light {
+        Light {
+            pos,
+            color,
+            radius,
+            direction: 0.0,
+            spread: 1.0,
+        }
+    }
+
+    /// Creates a light that shines in a cone. Direction and spread are measured in turns.
+    pub fn cone(pos: Vector2<f32>, color: RGBA8, radius: f32, direction: f32, spread: f32) -> Light {
+        Light {
+            pos,
+            color,
+            radius,
+            direction,
+            spread,
+        }
+    }
+}
+
+/// A line segment that blocks light and casts shadows in a light map.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Occluder {
+    pub start: Vector2<f32>,
+    pub end: Vector2<f32>,
+}
+
+impl Occluder {
+    pub fn new(start: Vector2<f32>, end: Vector2<f32>) -> Occluder {
+        Occluder {
+            start,
+            end,
+        }
+    }
+
+    /// Creates the four edges of a rectangle, for blocking light with box shaped geometry.
+    pub fn rect(pos: Vector2<f32>, size: Vector2<f32>) -> [Occluder; 4] {
+        let a = pos;
+        let b = pos + Vector2::new(size.x, 0.0);
+        let c = pos + size;
+        let d = pos + Vector2::new(0.0, size.y);
+        [Occluder::new(a, b), Occluder::new(b, c), Occluder::new(c, d), Occluder::new(d, a)]
+    }
+}
@@ -0,0 +1,26 @@
+use super::*;
+use cgmath::*;
+
+/// A corner of a triangle in a mesh.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MeshVertex {
+    /// Position of the vertex. The Z coordinate represents depth, like a sprite's. Units are
+    /// measured in pixels.
+    pub pos: Vector3<f32>,
+    /// Where the vertex samples the mesh's texture, from (0, 0) at the texture's top left to
+    /// (1, 1) at its bottom right.
+    pub uv: Vector2<f32>,
+    /// Color multiplier to apply to the vertex. Colors blend smoothly across each triangle.
+    pub color: RGBA8,
+}
+
+impl MeshVertex {
+    pub fn new(pos: Vector3<f32>, uv: Vector2<f32>, color: RGBA8) -> MeshVertex {
+        MeshVertex {
+            pos,
+            uv,
+            color,
+        }
+    }
+}
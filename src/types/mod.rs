@@ -1,13 +1,41 @@
+mod animation;
+mod camera;
 mod color;
+mod gamepad;
 mod layer;
+mod light;
+mod mesh;
+mod nine_slice;
+mod particle;
+mod photo;
+mod post;
+mod primitive;
+mod quality;
+mod shape;
 mod sprite;
+mod sprite_group;
+mod stats;
 mod text;
 mod texture;
 mod window;
 
+pub use animation::*;
+pub use camera::*;
 pub use color::*;
+pub use gamepad::*;
 pub use layer::*;
+pub use light::*;
+pub use mesh::*;
+pub use nine_slice::*;
+pub use particle::*;
+pub use photo::*;
+pub use post::*;
+pub use primitive::*;
+pub use quality::*;
+pub use shape::*;
 pub use sprite::*;
+pub use sprite_group::*;
+pub use stats::*;
 pub use text::*;
 pub use texture::*;
 pub use window::*;
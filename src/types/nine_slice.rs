@@ -0,0 +1,165 @@
+use super::*;
+use crate::texture::PIXEL_SIZE;
+use cgmath::*;
+
+/// A texture split into a 3x3 grid by its borders, for panels and buttons that stretch without
+/// distorting their edges. The corners are drawn at their size in pixels, the edges stretch along
+/// one axis, and the center stretches along both. The nine pieces are appended into the same
+/// buffer as the rest of a layer's sprites.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NineSlice {
+    /// Position of the bottom left corner. The Z coordinate represents depth, like a sprite's.
+    /// Units are measured in pixels.
+    pub pos: Vector3<f32>,
+    /// Size to stretch to. If it's smaller than the borders, the borders are shrunk to fit. Units
+    /// are measured in pixels.
+    pub size: Vector2<f32>,
+    pub texture: Texture,
+    /// Color multiplier to apply to every piece. The default is white.
+    pub color: RGBA8,
+    /// Width of the left border, in pixels of the texture.
+    pub left: u16,
+    /// Width of the right border, in pixels of the texture.
+    pub right: u16,
+    /// Height of the top border, in pixels of the texture.
+    pub top: u16,
+    /// Height of the bottom border, in pixels of the texture.
+    pub bottom: u16,
+}
+
+impl Default for NineSlice {
+    fn default() -> NineSlice {
+        NineSlice {
+            pos: Vector3::new(0.0, 0.0, 0.0),
+            size: Vector2::new(100.0, 100.0),
+            texture: Texture::default(),
+            color: colors::WHITE,
+            left: 0,
+            right: 0,
+            top: 0,
+            bottom: 0,
+        }
+    }
+}
+
+impl NineSlice {
+    /// Creates a nine slice with the same border on every side.
+    pub fn new(pos: Vector3<f32>, size: Vector2<f32>, texture: Texture, border: u16) -> NineSlice {
+        NineSlice {
+            pos,
+            size,
+            texture,
+            left: border,
+            right: border,
+            top: border,
+            bottom: border,
+            ..NineSlice::default()
+        }
+    }
+
+    /// Sets the width of each border, in pixels of the texture.
+    pub fn set_borders(&mut self, left: u16, right: u16, top: u16, bottom: u16) {
+        self.left = left;
+        self.right = right;
+        self.top = top;
+        self.bottom = bottom;
+    }
+
+    /// Splits the texture into sprites. This function appends sprites to the end of the output
+    /// buffer. Pieces with no area, like the edges when a border is 0, are skipped.
+    pub fn append(&self, output: &mut Vec<Sprite>) {
+        let uv = self.texture.0;
+        let texture_size = Vector2::new(
+            (uv.y as f32 - uv.x as f32).abs() / PIXEL_SIZE as f32,
+            (uv.w as f32 - uv.z as f32).abs() / PIXEL_SIZE as f32,
+        );
+        // Columns from the left and rows from the bottom, as (offset, size) in texture pixels.
+        let columns = slices(self.left as f32, self.right as f32, texture_size.x);
+        let rows = slices(self.bottom as f32, self.top as f32, texture_size.y);
+        let column_sizes = stretch(self.left as f32, self.right as f32, self.size.x);
+        let row_sizes = stretch(self.bottom as f32, self.top as f32, self.size.y);
+
+        output.reserve(9);
+        let mut y = self.pos.y;
+        for (row, &(row_offset, row_size)) in rows.iter().enumerate() {
+            let height = row_sizes[row];
+            let mut x = self.pos.x;
+            for (column, &(column_offset, column_size)) in columns.iter().enumerate() {
+                let width = column_sizes[column];
+                if width >= 1.0 && height >= 1.0 && column_size > 0.0 && row_size > 0.0 {
+                    // Rows are counted from the bottom, and the texture's V is measured from the top.
+                    let u = |offset: f32| lerp_u16(uv.x, uv.y, offset / texture_size.x);
+                    let v = |offset: f32| lerp_u16(uv.w, uv.z, offset / texture_size.y);
+                    let texture = Texture(Vector4::new(
+                        u(column_offset),
+                        u(column_offset + column_size),
+                        v(row_offset + row_size),
+                        v(row_offset),
+                    ));
+                    let pos = Vector3::new(x, y, self.pos.z);
+                    output.push(Sprite::new(pos, Vector2::new(width, height), texture, self.color, 0.0));
+                }
+                x += width;
+            }
+            y += height;
+        }
+    }
+}
+
+/// Splits a length of texture into its start border, middle, and end border, as offsets and sizes.
+/// Borders are clamped to the texture.
+fn slices(start: f32, end: f32, length: f32) -> [(f32, f32); 3] {
+    let start = start.min(length);
+    let end = end.min(length - start);
+    [(0.0, start), (start, length - start - end), (length - end, end)]
+}
+
+/// Sizes the start border, middle, and end border to fill the length, shrinking the borders if
+/// they don't fit.
+fn stretch(start: f32, end: f32, length: f32) -> [f32; 3] {
+    let length = length.max(0.0);
+    let fit = if start + end > length {
+        length / (start + end)
+    } else {
+        1.0
+    };
+    let (start, end) = (start * fit, end * fit);
+    [start, length - start - end, end]
+}
+
+fn lerp_u16(a: u16, b: u16, t: f32) -> u16 {
+    (a as f32 + (b as f32 - a as f32) * t).round() as u16
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pieces() {
+        // A 32x32 texture in the atlas.
+        let texture = Texture(Vector4::new(0, 32 * PIXEL_SIZE as u16, 0, 32 * PIXEL_SIZE as u16));
+        let mut slice = NineSlice::new(Vector3::new(10.0, 20.0, 0.0), Vector2::new(100.0, 50.0), texture, 8);
+        slice.bottom = 0;
+        let mut sprites = Vec::new();
+        slice.append(&mut sprites);
+        assert_eq!(sprites.len(), 6);
+
+        // Middle row, left column.
+        assert_eq!(sprites[0].pos, Vector3::new(10.0, 20.0, 0.0));
+        assert_eq!(sprites[0].size, Vector2::new(8, 42));
+        let pixel = PIXEL_SIZE as u16;
+        assert_eq!(sprites[0].texture.0, Vector4::new(0, 8 * pixel, 8 * pixel, 32 * pixel));
+        // Top row, right column.
+        assert_eq!(sprites[5].pos, Vector3::new(102.0, 62.0, 0.0));
+        assert_eq!(sprites[5].size, Vector2::new(8, 8));
+        assert_eq!(sprites[5].texture.0, Vector4::new(24 * pixel, 32 * pixel, 0, 8 * pixel));
+
+        // Borders shrink to fit.
+        assert_eq!(stretch(8.0, 8.0, 4.0), [2.0, 0.0, 2.0]);
+    }
+}
@@ -0,0 +1,83 @@
+use super::colors::WHITE;
+use super::*;
+use cgmath::*;
+
+/// Configuration settings for a particle emitter.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParticleSettings {
+    /// Texture to apply to each particle. The default is a plain white texture.
+    pub texture: Texture,
+    /// Number of particles spawned per second while emitting. The default is 100.
+    pub rate: f32,
+    /// Maximum number of live particles. Spawning stops while the emitter is at the limit. The
+    /// default is 1000.
+    pub max_particles: usize,
+    /// How long each particle lives in seconds. The default is 1.
+    pub lifetime: f32,
+    /// Initial velocity of each particle. Units are measured in pixels per second. The default is
+    /// 100 pixels per second upward.
+    pub velocity: Vector2<f32>,
+    /// Random variation applied to the initial velocity. Each axis varies by up to this much in
+    /// either direction. The default is 50 in both axes.
+    pub velocity_variance: Vector2<f32>,
+    /// Acceleration applied to each particle. Units are measured in pixels per second squared.
+    /// The default is none.
+    pub gravity: Vector2<f32>,
+    /// Color of a particle when spawned. The default is white.
+    pub color_start: RGBA8,
+    /// Color of a particle at the end of its life. Colors are interpolated in between. The default
+    /// is transparent white.
+    pub color_end: RGBA8,
+    /// Size of a particle when spawned. Units are measured in pixels. The default is 8x8.
+    pub size_start: Vector2<f32>,
+    /// Size of a particle at the end of its life. Sizes are interpolated in between. The default is
+    /// 8x8.
+    pub size_end: Vector2<f32>,
+}
+
+impl Default for ParticleSettings {
+    fn default() -> ParticleSettings {
+        ParticleSettings {
+            texture: Texture::default(),
+            rate: 100.0,
+            max_particles: 1000,
+            lifetime: 1.0,
+            velocity: Vector2::new(0.0, 100.0),
+            velocity_variance: Vector2::new(50.0, 50.0),
+            gravity: Vector2::new(0.0, 0.0),
+            color_start: WHITE,
+            color_end: RGBA8::new_raw(255, 255, 255, 0),
+            size_start: Vector2::new(8.0, 8.0),
+            size_end: Vector2::new(8.0, 8.0),
+        }
+    }
+}
+
+/// Configuration settings for floating text, like damage numbers.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FloatingTextSettings {
+    /// How long each text lives in seconds. The default is 1.
+    pub lifetime: f32,
+    /// How far each text moves over its lifetime. It starts fast and slows to a stop. Units are
+    /// measured in pixels. The default is 48 pixels upward.
+    pub rise: Vector2<f32>,
+    /// Fraction of the lifetime, from 0 to 1, after which the text fades out. The default is 0.5.
+    pub fade_start: f32,
+    /// Scale multiplier the text starts at before shrinking back to its own scale, to make it pop
+    /// in. 1 disables the pop. The default is 1.5.
+    pub pop: f32,
+    /// How long the pop takes to shrink back in seconds. The default is 0.15.
+    pub pop_duration: f32,
+}
+
+impl Default for FloatingTextSettings {
+    fn default() -> FloatingTextSettings {
+        FloatingTextSettings {
+            lifetime: 1.0,
+            rise: Vector2::new(0.0, 48.0),
+            fade_start: 0.5,
+            pop: 1.5,
+            pop_duration: 0.15,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::input::KeyboardButton;
+use cgmath::*;
+use std::path::PathBuf;
+
+/// Configuration for the engine's built-in photo mode. While photo mode is active, time is paused,
+/// the view can be panned by dragging with the left cursor button and zoomed with the cursor
+/// wheel, and layers marked as photo hidden aren't drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhotoModeSettings {
+    /// Key that enters and leaves photo mode.
+    pub toggle_key: KeyboardButton,
+    /// Key that saves a screenshot. This works outside of photo mode too.
+    pub capture_key: KeyboardButton,
+    /// How far the view can be panned from its origin along each axis, in pixels.
+    pub pan_limit: Vector2<f32>,
+    /// The smallest zoom allowed.
+    pub zoom_min: f32,
+    /// The largest zoom allowed.
+    pub zoom_max: f32,
+    /// Directory screenshots are saved in. It's created if it doesn't exist.
+    pub directory: PathBuf,
+}
+
+impl Default for PhotoModeSettings {
+    fn default() -> PhotoModeSettings {
+        PhotoModeSettings {
+            toggle_key: KeyboardButton::F10,
+            capture_key: KeyboardButton::F12,
+            pan_limit: Vector2::new(512.0, 512.0),
+            zoom_min: 0.5,
+            zoom_max: 4.0,
+            directory: PathBuf::from("screenshots"),
+        }
+    }
+}
+
+/// How frames sent to a video encoder are timed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VideoTiming {
+    /// Every presented frame becomes one video frame, and updates are sent with a fixed delta of
+    /// one video frame while recording. The video plays smoothly even if the game runs slower than
+    /// real time while capturing.
+    Fixed,
+    /// Video frames follow the wall clock. Frames are repeated or dropped so the video keeps pace
+    /// with the game, and update deltas aren't changed.
+    RealTime,
+}
+
+/// Configuration for piping presented frames into an external video encoder, like ffmpeg. Frames
+/// are written to the encoder's standard input as raw RGBA pixels, so no image files are written.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VideoSettings {
+    /// The encoder to run. The default is "ffmpeg", found through the PATH.
+    pub program: PathBuf,
+    /// File the encoder writes the video to. It's overwritten if it exists.
+    pub output: PathBuf,
+    /// Frames per second of the video. The default is 60.
+    pub frame_rate: u32,
+    /// How frames are timed. The default is Fixed.
+    pub timing: VideoTiming,
+    /// Encoder arguments placed between the input and the output file. The default encodes H.264
+    /// at a high quality, padding the frame to an even size.
+    pub arguments: Vec<String>,
+}
+
+impl Default for VideoSettings {
+    fn default() -> VideoSettings {
+        VideoSettings {
+            program: PathBuf::from("ffmpeg"),
+            output: PathBuf::from("capture.mp4"),
+            frame_rate: 60,
+            timing: VideoTiming::Fixed,
+            arguments: ["-c:v", "libx264", "-preset", "slow", "-crf", "16", "-pix_fmt", "yuv420p", "-vf"]
+                .iter()
+                .map(|argument| argument.to_string())
+                .chain(core::iter::once("pad=ceil(iw/2)*2:ceil(ih/2)*2".to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Configuration for keeping the last few seconds of presented frames, so a clip can be saved on
+/// demand with `Engine::window_replay_save`, like for sharing gameplay or attaching to a bug
+/// report. Kept frames take 4 bytes per pixel after downscaling, so a 1920x1080 window with the
+/// defaults keeps about 155 MB.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ReplaySettings {
+    /// How many seconds of frames to keep. The default is 5.
+    pub seconds: f32,
+    /// Frames kept per second. Presented frames are skipped to keep this pace. The default is 15.
+    pub frame_rate: u32,
+    /// Each frame's width and height are divided by this, averaging the pixels. The default is 2.
+    pub downscale: u32,
+}
+
+impl Default for ReplaySettings {
+    fn default() -> ReplaySettings {
+        ReplaySettings {
+            seconds: 5.0,
+            frame_rate: 15,
+            downscale: 2,
+        }
+    }
+}
+
+/// How a replay is saved.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReplayFormat {
+    /// A looping animated GIF. Colors are dithered to a fixed palette of 252 colors.
+    Gif,
+    /// A directory of numbered PNG images, like for editing or encoding the clip elsewhere.
+    Png,
+}
@@ -0,0 +1,34 @@
+use cgmath::*;
+
+/// Token to reference a post processing pass with.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PostPassToken {
+    key: usize,
+}
+
+impl PostPassToken {
+    pub(crate) fn new(key: usize) -> PostPassToken {
+        PostPassToken {
+            key,
+        }
+    }
+
+    pub(crate) fn key(&self) -> usize {
+        self.key
+    }
+}
+
+/// A value that can be assigned to a shader uniform.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UniformValue {
+    /// Assigns to a `float` uniform.
+    Float(f32),
+    /// Assigns to a `vec2` uniform.
+    Vec2(Vector2<f32>),
+    /// Assigns to a `vec3` uniform.
+    Vec3(Vector3<f32>),
+    /// Assigns to a `vec4` uniform.
+    Vec4(Vector4<f32>),
+    /// Assigns to an `int` uniform.
+    Int(i32),
+}
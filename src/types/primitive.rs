@@ -0,0 +1,132 @@
+use super::*;
+use cgmath::*;
+
+/// How the corners of a polyline are joined.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    /// The outer edges are extended until they meet in a point. Very sharp corners would make a
+    /// long spike, so they're beveled instead.
+    Miter,
+    /// The outer corners are connected with a straight edge.
+    Bevel,
+    /// The outer corners are connected with an arc.
+    Round,
+}
+
+/// Colored geometry drawn by a primitive layer. Positions and thicknesses are in the
+/// coordinate space of the primitive layer, which matches a layer using the same transform.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Primitive {
+    /// A straight line with square ends that stop at the points.
+    Line {
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+        thickness: f32,
+        color: RGBA8,
+    },
+    /// Lines connecting the points in order, joined at each corner. Closed polylines also connect
+    /// the last point back to the first.
+    Polyline {
+        points: Vec<Vector2<f32>>,
+        thickness: f32,
+        join: LineJoin,
+        closed: bool,
+        color: RGBA8,
+    },
+    /// A filled convex polygon. Points can be in either winding order. Concave polygons aren't
+    /// filled correctly.
+    Polygon {
+        points: Vec<Vector2<f32>>,
+        color: RGBA8,
+    },
+    /// A filled circle with a smooth edge.
+    Circle {
+        center: Vector2<f32>,
+        radius: f32,
+        color: RGBA8,
+    },
+    /// The outline of a circle with a smooth edge. The outline is drawn inside the radius.
+    Ring {
+        center: Vector2<f32>,
+        radius: f32,
+        thickness: f32,
+        color: RGBA8,
+    },
+    /// A rectangle with rounded corners and smooth edges. The position is the bottom left corner,
+    /// like sprites. The corner radius is limited to half the shorter side. A thickness of 0 fills
+    /// the rectangle, and anything more draws only an outline of that thickness along the inside of
+    /// the edge.
+    RoundedRect {
+        pos: Vector2<f32>,
+        size: Vector2<f32>,
+        corner_radius: f32,
+        thickness: f32,
+        color: RGBA8,
+    },
+}
+
+impl Primitive {
+    /// Creates a line with square ends.
+    pub fn line(start: Vector2<f32>, end: Vector2<f32>, thickness: f32, color: RGBA8) -> Primitive {
+        Primitive::Line {
+            start,
+            end,
+            thickness,
+            color,
+        }
+    }
+
+    /// Creates an open polyline with mitered corners.
+    pub fn polyline(points: &[Vector2<f32>], thickness: f32, color: RGBA8) -> Primitive {
+        Primitive::Polyline {
+            points: points.to_vec(),
+            thickness,
+            join: LineJoin::Miter,
+            closed: false,
+            color,
+        }
+    }
+
+    /// Creates a filled convex polygon.
+    pub fn polygon(points: &[Vector2<f32>], color: RGBA8) -> Primitive {
+        Primitive::Polygon {
+            points: points.to_vec(),
+            color,
+        }
+    }
+
+    /// Creates a filled circle.
+    pub fn circle(center: Vector2<f32>, radius: f32, color: RGBA8) -> Primitive {
+        Primitive::Circle {
+            center,
+            radius,
+            color,
+        }
+    }
+
+    /// Creates the outline of a circle.
+    pub fn ring(center: Vector2<f32>, radius: f32, thickness: f32, color: RGBA8) -> Primitive {
+        Primitive::Ring {
+            center,
+            radius,
+            thickness,
+            color,
+        }
+    }
+
+    /// Creates a filled rectangle with rounded corners.
+    pub fn rounded_rect(
+        pos: Vector2<f32>,
+        size: Vector2<f32>,
+        corner_radius: f32,
+        color: RGBA8,
+    ) -> Primitive {
+        Primitive::RoundedRect {
+            pos,
+            size,
+            corner_radius,
+            thickness: 0.0,
+            color,
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::types::TextureFilter;
+
+/// Rendering settings that trade image quality for speed. These can be changed at any time, and
+/// presets set all of them together.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct QualitySettings {
+    /// If multisample anti-aliasing smooths the edges of sprites. This only has an effect when the
+    /// window was created with `WindowSettings::multisampling` above 0. On the web, multisampling
+    /// can't be turned off after the window is created.
+    pub multisampling: bool,
+    /// If bright parts of the frame glow into their surroundings. Bloom is applied to the window
+    /// before any post processing passes.
+    pub bloom: bool,
+    /// The fraction of the resolution light maps are computed at, from 0.1 to 1. Lower values
+    /// are faster, with softer shadow edges.
+    pub light_resolution: f32,
+    /// Multiplies the max particles of every emitter. Emitters keep at least one particle.
+    pub particle_limit: f32,
+    /// The most expensive texture filter layers may use. Layers that ask for a more expensive
+    /// filter use this one instead.
+    pub texture_filter: TextureFilter,
+}
+
+impl Default for QualitySettings {
+    /// Everything at full quality, except bloom which is off.
+    fn default() -> QualitySettings {
+        QualitySettings {
+            multisampling: true,
+            bloom: false,
+            light_resolution: 1.0,
+            particle_limit: 1.0,
+            texture_filter: TextureFilter::Anisotropic,
+        }
+    }
+}
+
+/// Presets for the quality settings, meant to back a single graphics option.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QualityPreset {
+    /// No multisampling or bloom, lights at a quarter resolution, a quarter of the particles, and
+    /// at most linear filtering.
+    Low,
+    /// Multisampling, lights at half resolution, half of the particles, and at most trilinear
+    /// filtering.
+    Medium,
+    /// Everything at full quality, with bloom.
+    High,
+}
+
+impl QualityPreset {
+    /// The settings the preset applies.
+    pub fn settings(self) -> QualitySettings {
+        match self {
+            QualityPreset::Low => QualitySettings {
+                multisampling: false,
+                bloom: false,
+                light_resolution: 0.25,
+                particle_limit: 0.25,
+                texture_filter: TextureFilter::Linear,
+            },
+            QualityPreset::Medium => QualitySettings {
+                multisampling: true,
+                bloom: false,
+                light_resolution: 0.5,
+                particle_limit: 0.5,
+                texture_filter: TextureFilter::Trilinear,
+            },
+            QualityPreset::High => QualitySettings {
+                bloom: true,
+                ..QualitySettings::default()
+            },
+        }
+    }
+}
+
+impl From<QualityPreset> for QualitySettings {
+    fn from(preset: QualityPreset) -> QualitySettings {
+        preset.settings()
+    }
+}
@@ -0,0 +1,83 @@
+use super::*;
+use cgmath::*;
+
+/// A basic shape that shapes are built from. Positions and distances are in the coordinate space
+/// of the shape layer, which matches a layer using the same transform.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ShapePrimitive {
+    Circle {
+        center: Vector2<f32>,
+        radius: f32,
+    },
+    /// A box, optionally with rounded corners. Rotation is measured in turns around the center.
+    Box {
+        center: Vector2<f32>,
+        size: Vector2<f32>,
+        corner_radius: f32,
+        rotation: f32,
+    },
+    /// A line segment with round ends, covering everything within the radius of the segment.
+    Capsule {
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+        radius: f32,
+    },
+}
+
+/// How a primitive is combined with the primitives before it in a shape.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShapeOperation {
+    /// Adds the primitive to the shape.
+    Union,
+    /// Cuts the primitive out of the shape.
+    Subtract,
+    /// Keeps only where the shape and the primitive overlap.
+    Intersect,
+}
+
+/// A filled shape drawn by a shape layer, built by combining primitives in order. Shapes are
+/// drawn from distance functions, so their edges are smooth at any scale.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shape {
+    /// Primitives in the order they're combined. The first primitive's operation is ignored.
+    pub primitives: Vec<(ShapeOperation, ShapePrimitive)>,
+    /// Color inside the shape.
+    pub fill: RGBA8,
+    /// Color of the border, drawn along the inside of the shape's edge.
+    pub border_color: RGBA8,
+    /// Width of the border in pixels. The default is 0, meaning no border.
+    pub border_width: f32,
+}
+
+impl Shape {
+    /// Creates a shape from a single primitive, without a border.
+    pub fn new(primitive: ShapePrimitive, fill: RGBA8) -> Shape {
+        Shape {
+            primitives: vec![(ShapeOperation::Union, primitive)],
+            fill,
+            border_color: fill,
+            border_width: 0.0,
+        }
+    }
+
+    /// Adds the primitive to the shape.
+    pub fn union(&mut self, primitive: ShapePrimitive) {
+        self.primitives.push((ShapeOperation::Union, primitive));
+    }
+
+    /// Cuts the primitive out of the shape.
+    pub fn subtract(&mut self, primitive: ShapePrimitive) {
+        self.primitives.push((ShapeOperation::Subtract, primitive));
+    }
+
+    /// Keeps only where the shape and the primitive overlap.
+    pub fn intersect(&mut self, primitive: ShapePrimitive) {
+        self.primitives.push((ShapeOperation::Intersect, primitive));
+    }
+
+    /// Sets the border drawn along the inside of the shape's edge.
+    pub fn set_border(&mut self, width: f32, color: RGBA8) {
+        self.border_width = width;
+        self.border_color = color;
+    }
+}
@@ -1,5 +1,7 @@
 use super::colors::WHITE;
 use super::*;
+use crate::math::AABB2D;
+use crate::utility::pool::PoolHandle;
 use cgmath::*;
 
 /// Configuration settings for a sprite.
@@ -17,6 +19,54 @@ pub struct Sprite {
     pub color: RGBA8,
     /// Rotation of the sprite. Units are 1/65536th of a turn.
     pub rotation: u16,
+    /// If the sprite should be drawn. Hidden sprites stay in the layer's buffer but are skipped by
+    /// the vertex shader, so they can be toggled without rebuilding the sprite list. Sprites with a
+    /// fully transparent color are skipped the same way. The default is true.
+    pub visible: bool,
+    /// Built-in effect applied to the sprite. The default is None.
+    pub effect: SpriteEffect,
+    /// How far along the effect is. Units are 1/65535th, from 0 (no effect) to 65535 (full
+    /// effect). Use `set_effect` to set this from a float.
+    pub effect_progress: u16,
+    /// Moves the sprite toward the viewer when it's depth tested, without changing its Z. Decals
+    /// at the same Z as the sprite under them, like a splat on a floor tile, can be given a small
+    /// bias so they always win the depth test instead of flickering. Each step is 1/65536th of the
+    /// depth range, so a bias of 1 is enough to break a tie. Negative values move the sprite away.
+    /// The default is 0.
+    pub depth_bias: i16,
+    /// Stretches the sprite around its center without changing its size, like for squash and
+    /// stretch. The default is 1 on both axes.
+    pub scale: Vector2<f32>,
+    /// Mirrors the texture horizontally. The default is false.
+    pub flip_x: bool,
+    /// Mirrors the texture vertically. The default is false.
+    pub flip_y: bool,
+}
+
+/// A built-in per-sprite effect, driven by the sprite's effect progress.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum SpriteEffect {
+    /// No effect.
+    #[default]
+    None = 0,
+    /// Pixels disappear in a noise pattern, with a bright edge along the boundary. The sprite is
+    /// fully gone at full progress.
+    Dissolve = 1,
+    /// The sprite is erased clockwise from the top, like a cooldown indicator. The sprite is fully
+    /// gone at full progress.
+    RadialWipe = 2,
+    /// The sprite's color is blended toward white, keeping its alpha. The sprite is solid white at
+    /// full progress.
+    Flash = 3,
+    /// The texture's alpha is a signed distance field, where 0.5 is the shape's edge, and the
+    /// sprite is drawn as a solid shape in its color. Edges stay sharp at any scale. Distance field
+    /// text uses this. Progress isn't used.
+    DistanceField = 4,
+    /// Like DistanceField, but the shape's edge fades out past the edge instead of being sharp.
+    /// Progress is how far the fade reaches, from the edge at 0 to the edge of the field at 1.
+    /// Distance field text shadows use this.
+    DistanceFieldShadow = 5,
 }
 
 impl Default for Sprite {
@@ -27,6 +77,13 @@ impl Default for Sprite {
             texture: Texture::default(),
             color: WHITE,
             rotation: 0,
+            visible: true,
+            effect: SpriteEffect::None,
+            effect_progress: 0,
+            depth_bias: 0,
+            scale: Vector2::new(1.0, 1.0),
+            flip_x: false,
+            flip_y: false,
         }
     }
 }
@@ -53,6 +110,13 @@ impl Sprite {
             texture,
             color,
             rotation: (rotation.fract() * 65536.0) as u16,
+            visible: true,
+            effect: SpriteEffect::None,
+            effect_progress: 0,
+            depth_bias: 0,
+            scale: Vector2::new(1.0, 1.0),
+            flip_x: false,
+            flip_y: false,
         }
     }
 
@@ -71,6 +135,186 @@ impl Sprite {
             texture,
             color,
             rotation,
+            visible: true,
+            effect: SpriteEffect::None,
+            effect_progress: 0,
+            depth_bias: 0,
+            scale: Vector2::new(1.0, 1.0),
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    /// Shows or hides the sprite. Hidden sprites are not drawn, but keep their place in the layer.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Mirrors the texture horizontally, vertically, or both.
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+    }
+
+    /// Sets the built-in effect and its progress. Progress is clamped to [0, 1].
+    pub fn set_effect(&mut self, effect: SpriteEffect, progress: f32) {
+        self.effect = effect;
+        self.effect_progress = (progress.clamp(0.0, 1.0) * 65535.0).round() as u16;
+    }
+
+    /// Gets the area the sprite covers after its scale and rotation are applied, in the same
+    /// coordinates as its position.
+    pub fn bounds(&self) -> AABB2D {
+        let half = Vector2::new(self.size.x as f32, self.size.y as f32) / 2.0;
+        let center = self.pos.truncate() + half;
+        let half = Vector2::new(half.x * self.scale.x.abs(), half.y * self.scale.y.abs());
+        let turn = self.rotation as f32 / 65536.0 * core::f32::consts::TAU;
+        let (sin, cos) = (turn.sin().abs(), turn.cos().abs());
+        let extent = Vector2::new(cos * half.x + sin * half.y, sin * half.x + cos * half.y);
+        AABB2D::new(center.x - extent.x, center.y - extent.y, center.x + extent.x, center.y + extent.y)
+    }
+}
+
+/// A sprite with a color multiplier for each corner. Colors blend smoothly across the sprite, for
+/// gradients and edges that fade out, and are multiplied with the sprite's color. Gradient sprites
+/// are set on a layer with `Layer::set_gradient_sprites`, so plain sprites don't pay for the extra
+/// colors.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GradientSprite {
+    pub sprite: Sprite,
+    /// Color multipliers in the order bottom left, bottom right, top left, top right. The default
+    /// is white for every corner.
+    pub corner_colors: [RGBA8; 4],
+}
+
+impl Default for GradientSprite {
+    fn default() -> GradientSprite {
+        GradientSprite::new(Sprite::default(), [WHITE; 4])
+    }
+}
+
+impl GradientSprite {
+    /// Creates a gradient sprite, with corner colors in the order bottom left, bottom right, top
+    /// left, top right.
+    pub fn new(sprite: Sprite, corner_colors: [RGBA8; 4]) -> GradientSprite {
+        GradientSprite {
+            sprite,
+            corner_colors,
+        }
+    }
+
+    /// Creates a gradient sprite that blends from one color at the top to another at the bottom.
+    pub fn vertical(sprite: Sprite, top: RGBA8, bottom: RGBA8) -> GradientSprite {
+        GradientSprite::new(sprite, [bottom, bottom, top, top])
+    }
+
+    /// Creates a gradient sprite that blends from one color on the left to another on the right.
+    pub fn horizontal(sprite: Sprite, left: RGBA8, right: RGBA8) -> GradientSprite {
+        GradientSprite::new(sprite, [left, right, left, right])
+    }
+}
+
+/// Token to reference a sprite created with `Layer::sprite_create`. Tokens can't be copied, so a
+/// removed sprite's token can't be used again.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SpriteToken {
+    /// Identifies the layer the sprite was created on.
+    layer: u32,
+    handle: PoolHandle,
+}
+
+impl SpriteToken {
+    pub(crate) fn new(layer: u32, handle: PoolHandle) -> SpriteToken {
+        SpriteToken {
+            layer,
+            handle,
+        }
+    }
+
+    pub(crate) fn layer(&self) -> u32 {
+        self.layer
+    }
+
+    pub(crate) fn handle(&self) -> PoolHandle {
+        self.handle
+    }
+}
+
+/// The parts of a sprite that change between instances of a shared template, see
+/// `Layer::set_instances`. Instances are 20 bytes against a full sprite's 48, so they're
+/// cheaper to upload for large numbers of identical sprites like bullets.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpriteInstance {
+    /// Position of the instance. The X and Y coordinates represent the bottom left corner of the
+    /// sprite. The Z coordinate represents sprite depth. Units are measured in pixels.
+    pub pos: Vector3<f32>,
+    /// Color multiplier to apply to the instance. The default is white.
+    pub color: RGBA8,
+    /// Rotation of the instance. Units are 1/65536th of a turn.
+    pub rotation: u16,
+}
+
+impl Default for SpriteInstance {
+    fn default() -> SpriteInstance {
+        SpriteInstance {
+            pos: Vector3::new(0.0, 0.0, 0.0),
+            color: WHITE,
+            rotation: 0,
         }
     }
 }
+
+impl SpriteInstance {
+    /// Creates a new instance. Rotation is measured in turns from [0, 1). Values outside of the
+    /// range are wrapped into the range.
+    pub fn new(pos: Vector3<f32>, color: RGBA8, rotation: f32) -> SpriteInstance {
+        SpriteInstance {
+            pos,
+            color,
+            rotation: (rotation.fract() * 65536.0) as u16,
+        }
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::BLACK;
+
+    #[test]
+    fn bounds() {
+        let mut sprite = Sprite::new(
+            Vector3::new(10.0, 20.0, 0.0),
+            Vector2::new(40.0, 20.0),
+            Texture::default(),
+            WHITE,
+            0.0,
+        );
+        assert_eq!(sprite.bounds(), AABB2D::new(10.0, 20.0, 50.0, 40.0));
+
+        sprite.scale = Vector2::new(-0.5, 2.0);
+        assert_eq!(sprite.bounds(), AABB2D::new(20.0, 10.0, 40.0, 50.0));
+
+        sprite.scale = Vector2::new(1.0, 1.0);
+        sprite.rotation = 16384;
+        let bounds = sprite.bounds();
+        assert!((bounds.min - Vector2::new(20.0, 10.0)).magnitude() < 0.001);
+        assert!((bounds.max - Vector2::new(40.0, 50.0)).magnitude() < 0.001);
+    }
+
+    #[test]
+    fn layout() {
+        // Corner colors live in gradient sprites, so plain sprites stay small.
+        assert_eq!(core::mem::size_of::<Sprite>(), 48);
+        assert_eq!(core::mem::size_of::<GradientSprite>(), 64);
+        assert_eq!(core::mem::size_of::<SpriteInstance>(), 20);
+        let gradient = GradientSprite::vertical(Sprite::default(), WHITE, BLACK);
+        assert_eq!(gradient.corner_colors, [BLACK, BLACK, WHITE, WHITE]);
+    }
+}
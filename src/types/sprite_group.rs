@@ -0,0 +1,65 @@
+use super::*;
+use cgmath::*;
+
+/// Sprites positioned relative to a shared origin, for objects made of several sprites. Moving,
+/// rotating, or scaling the group applies to every sprite in it, and the transformed sprites are
+/// appended into the same buffer as the rest of a layer's sprites.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpriteGroup {
+    /// The sprites in the group. Positions are relative to the group's origin.
+    pub sprites: Vec<Sprite>,
+    /// The position of the group's origin.
+    pub translation: Vector2<f32>,
+    /// Rotation around the group's origin. Rotation is measured in turns from [0, 1). Values
+    /// outside of the range are wrapped into the range. For example, 1.75 is wrapped into 0.75,
+    /// -0.4 is wrapped into 0.6.
+    pub rotation: f32,
+    /// Scale around the group's origin. This is 1.0 by default.
+    pub scale: f32,
+}
+
+impl Default for SpriteGroup {
+    fn default() -> SpriteGroup {
+        SpriteGroup::new(Vec::new())
+    }
+}
+
+impl SpriteGroup {
+    pub fn new(sprites: Vec<Sprite>) -> SpriteGroup {
+        SpriteGroup {
+            sprites,
+            translation: Vector2::new(0.0, 0.0),
+            rotation: 0.0,
+            scale: 1.0,
+        }
+    }
+
+    /// Sets the translation, rotation, and scale of the group at once.
+    pub fn set_transform(&mut self, translation: Vector2<f32>, rotation: f32, scale: f32) {
+        self.translation = translation;
+        self.rotation = rotation;
+        self.scale = scale;
+    }
+
+    /// Transforms the group's sprites. This function appends sprites to the end of the output
+    /// buffer.
+    pub fn append(&self, output: &mut Vec<Sprite>) {
+        let rotation = self.rotation.fract();
+        let rotation_raw = (rotation * 65536.0) as i32 as u16;
+        let matrix = Matrix2::from_angle(Rad(core::f32::consts::PI * 2.0 * rotation)) * self.scale;
+        output.reserve(self.sprites.len());
+        for sprite in &self.sprites {
+            // Sprites rotate around their center, so the center is what gets transformed.
+            let local_size = Vector2::new(sprite.size.x as f32, sprite.size.y as f32);
+            let center = sprite.pos.truncate() + local_size / 2.0;
+            let size = local_size * self.scale;
+            let pos = self.translation + matrix * center - size / 2.0;
+            output.push(Sprite {
+                pos: pos.extend(sprite.pos.z),
+                size: Vector2::new(size.x as u16, size.y as u16),
+                rotation: sprite.rotation.wrapping_add(rotation_raw),
+                ..*sprite
+            });
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use core::time::Duration;
+
+/// A snapshot of how the engine is performing, for profiling and debug displays. Frame times come
+/// from `Engine::frame_history`, and draw counts are from the last presented frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EngineStats {
+    /// Frames per second, from the average frame time.
+    pub fps: f32,
+    /// The average frame time.
+    pub frame_time: Duration,
+    /// The frame time half of recent frames are at or under.
+    pub frame_time_p50: Duration,
+    /// The frame time 99% of recent frames are at or under.
+    pub frame_time_p99: Duration,
+    /// Draw calls made for the last frame, including post processing.
+    pub draw_calls: u32,
+    /// Sprites, instances, and glyphs drawn in the last frame.
+    pub sprites: u32,
+    /// Width and height of the texture atlas in pixels.
+    pub atlas_size: u32,
+    /// The fraction of the texture atlas that textures take up, from 0 to 1.
+    pub atlas_occupancy: f32,
+    /// How long the GPU took for each layer and post processing pass of a recent frame, in the
+    /// order they were drawn. GPU timings arrive a few frames late. Empty unless GPU timing is
+    /// enabled with `Engine::stats_gpu_timing` and supported.
+    pub gpu_timings: Vec<GpuTiming>,
+    /// How long the CPU spent in each phase of the last frame, in the order they run. Input
+    /// includes the window events handled since the frame before.
+    pub phase_timings: Vec<PhaseTiming>,
+}
+
+/// A phase of the engine's frame loop.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FramePhase {
+    /// Converting window, device, gamepad, and file events into input messages, including the time
+    /// the game spends handling them.
+    Input,
+    /// Advancing particles, animations, and floating text.
+    Simulation,
+    /// The game's Update message, where it sets up sprites and uploads them.
+    Update,
+    /// Drawing the layers, post processing, and presenting the frame.
+    Draw,
+}
+
+impl FramePhase {
+    /// Every phase, in the order they run.
+    pub const ALL: [FramePhase; 4] =
+        [FramePhase::Input, FramePhase::Simulation, FramePhase::Update, FramePhase::Draw];
+}
+
+/// How long the CPU spent in a phase of a frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PhaseTiming {
+    pub phase: FramePhase,
+    pub time: Duration,
+}
+
+/// Sent to the span hook when a phase starts or ends, see `Engine::stats_span_hook`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpanEvent {
+    Begin,
+    /// Contains how long the phase took.
+    End(Duration),
+}
+
+/// What a GPU timing measured.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GpuPass {
+    /// Drawing a layer's sprites, by the layer's index among the live layers, in the order they
+    /// were created.
+    Layer(usize),
+    /// A post processing pass, by its index among the enabled passes. When no pass is enabled but
+    /// the frame still goes through post processing, like for a virtual resolution, the copy to
+    /// the window is pass 0.
+    Post(usize),
+}
+
+/// How long the GPU took for part of a frame. Comparing the total against the frame time shows
+/// if a game is limited by the GPU, like by fill rate, or by the CPU.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GpuTiming {
+    pub pass: GpuPass,
+    pub time: Duration,
+}
+
+impl EngineStats {
+    /// Formats the stats as lines of text, like for the stats overlay.
+    pub fn lines(&self) -> Vec<String> {
+        let millis = |time: Duration| time.as_secs_f32() * 1000.0;
+        let mut lines = vec![
+            format!("{:.0} fps", self.fps),
+            format!(
+                "{:.2} ms avg, {:.2} ms p50, {:.2} ms p99",
+                millis(self.frame_time),
+                millis(self.frame_time_p50),
+                millis(self.frame_time_p99)
+            ),
+            format!("{} draw calls, {} sprites", self.draw_calls, self.sprites),
+            format!("{0}x{0} atlas, {1:.0}% used", self.atlas_size, self.atlas_occupancy * 100.0),
+        ];
+        if !self.phase_timings.is_empty() {
+            let phases: Vec<String> = self
+                .phase_timings
+                .iter()
+                .map(|timing| format!("{:?} {:.2}", timing.phase, millis(timing.time)))
+                .collect();
+            lines.push(format!("{} ms cpu", phases.join(", ")));
+        }
+        if !self.gpu_timings.is_empty() {
+            lines.push(format!("{:.2} ms gpu", millis(self.gpu_time())));
+        }
+        lines
+    }
+
+    /// The total of the GPU timings.
+    pub fn gpu_time(&self) -> Duration {
+        self.gpu_timings.iter().map(|timing| timing.time).sum()
+    }
+}
@@ -1,31 +1,37 @@
-use super::colors::BLACK;
+use super::colors::*;
 use super::RGBA8;
 use cgmath::*;
+use core::ops::Range;
 
-/// Token to reference a font with.
-#[derive(Copy, Clone, Debug, PartialEq)]
+/// Token to reference a font with. The default token is the engine font.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct FontToken {
     key: usize,
+    bitmap: bool,
 }
 
 impl FontToken {
     pub(crate) fn new(key: usize) -> FontToken {
         FontToken {
             key,
+            bitmap: false,
+        }
+    }
+
+    pub(crate) fn new_bitmap(key: usize) -> FontToken {
+        FontToken {
+            key,
+            bitmap: true,
         }
     }
 
     pub(crate) fn key(&self) -> usize {
         self.key
     }
-}
 
-impl Default for FontToken {
-    // The engine default font.
-    fn default() -> FontToken {
-        FontToken {
-            key: 0,
-        }
+    /// If the font is a bitmap font.
+    pub fn is_bitmap(&self) -> bool {
+        self.bitmap
     }
 }
 
@@ -46,6 +52,56 @@ pub struct Text {
     pub color: RGBA8,
     /// Font to use for the text. This is the engine font by default.
     pub font: FontToken,
+    /// Box drawn behind the text, sized to fit it. The default is None.
+    pub background: Option<TextBackground>,
+    /// Number of characters to show, counted from the start of the string. The full string is
+    /// still laid out, so revealed characters don't move as more are shown. If this is set to None,
+    /// every character is shown. The default is None.
+    pub reveal: Option<usize>,
+    /// Spans of the string that can be found with hit testing, like links or hoverable keywords.
+    /// The default is empty.
+    pub links: Vec<TextLink>,
+    /// Spans of the string drawn with a different color, scale, or font than the rest of the text.
+    /// Where spans overlap, later spans win. `set_markup` can fill this from tags in the string.
+    /// The default is empty.
+    pub spans: Vec<TextSpan>,
+    /// Draws glyphs from signed distance fields instead of bitmaps rasterized at the text's scale.
+    /// Distance field glyphs stay sharp when their layer is zoomed or rotated, at the cost of
+    /// slightly rounder corners. Glyph sprites use the DistanceField effect. The default is false.
+    pub distance_field: bool,
+    /// Shadow or glow drawn behind distance field text, for keeping text readable over busy or
+    /// bright scenes. Text without distance fields has no shadow. The default is None.
+    pub shadow: Option<TextShadow>,
+    /// Spaces glyphs by their exact advances instead of advances rounded up to whole pixels, so
+    /// spacing stays even as the text scales. Best paired with `TextHinting::None` for large or
+    /// animated text. Bitmap fonts always use their exact advances. The default is false.
+    pub subpixel: bool,
+    /// How glyphs are snapped to the pixel grid. The default is `TextHinting::Full`.
+    pub hinting: TextHinting,
+}
+
+/// How glyphs are snapped to the pixel grid. Snapped glyphs are crisp at small sizes, but jump a
+/// pixel at a time when the text moves. Fonts' own hinting instructions aren't used.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextHinting {
+    /// Glyphs are drawn exactly where they're laid out, so moving text slides smoothly.
+    None,
+    /// Baselines are snapped to whole pixels, keeping the tops and bottoms of letters sharp, while
+    /// glyphs keep their exact horizontal positions.
+    Light,
+    /// Baselines and the left edge of each glyph are snapped to whole pixels.
+    Full,
+}
+
+impl TextHinting {
+    /// Snaps the bottom left corner of a glyph.
+    pub(crate) fn snap(self, pos: Vector2<f32>) -> Vector2<f32> {
+        match self {
+            TextHinting::None => pos,
+            TextHinting::Light => Vector2::new(pos.x, pos.y.round()),
+            TextHinting::Full => Vector2::new(pos.x.round(), pos.y.round()),
+        }
+    }
 }
 
 impl Default for Text {
@@ -57,6 +113,14 @@ impl Default for Text {
             scale: 24,
             color: BLACK,
             font: FontToken::default(),
+            background: None,
+            reveal: None,
+            links: Vec::new(),
+            spans: Vec::new(),
+            distance_field: false,
+            shadow: None,
+            subpixel: false,
+            hinting: TextHinting::Full,
         }
     }
 }
@@ -77,11 +141,313 @@ impl Text {
             scale,
             color,
             font,
+            background: None,
+            reveal: None,
+            links: Vec::new(),
+            spans: Vec::new(),
+            distance_field: false,
+            shadow: None,
+            subpixel: false,
+            hinting: TextHinting::Full,
         }
     }
 
     pub fn set_string(&mut self, string: &str) {
         self.string.clear();
-        self.string.push_str(&string);
+        self.string.push_str(string);
+    }
+
+    /// Sets the string from markup, replacing the spans with the styles its tags describe. Colors
+    /// are set with a name like `<red>`, a hex code like `<#FF8000>` or `<#FF800080>`, or
+    /// `<color=red>`. Scale is set with `<scale=32>`. Any closing tag, like `</red>` or `</>`,
+    /// ends the most recently opened tag, and tags still open at the end cover the rest of the
+    /// string. Write `<<` for a literal `<`. Tags that aren't recognized are kept as text.
+    pub fn set_markup(&mut self, markup: &str) {
+        self.string.clear();
+        self.spans.clear();
+        let mut open: Vec<usize> = Vec::new();
+        let mut count = 0;
+        let mut rest = markup;
+        while let Some(start) = rest.find('<') {
+            let before = &rest[..start];
+            self.string.push_str(before);
+            count += before.chars().count();
+            rest = &rest[start..];
+            if let Some(after) = rest.strip_prefix("<<") {
+                self.string.push('<');
+                count += 1;
+                rest = after;
+                continue;
+            }
+            let end = match rest.find('>') {
+                Some(end) => end,
+                None => break,
+            };
+            let tag = &rest[1..end];
+            if tag.starts_with('/') {
+                if let Some(index) = open.pop() {
+                    self.spans[index].end = count;
+                }
+            } else if let Some(mut span) = TextSpan::from_tag(tag) {
+                span.start = count;
+                open.push(self.spans.len());
+                self.spans.push(span);
+            } else {
+                self.string.push_str(&rest[..=end]);
+                count += rest[..=end].chars().count();
+            }
+            rest = &rest[end + 1..];
+        }
+        self.string.push_str(rest);
+        count += rest.chars().count();
+        for index in open {
+            self.spans[index].end = count;
+        }
+        self.spans.retain(|span| span.start < span.end);
+    }
+
+    /// The color, scale, and font of the character at the index, after applying spans.
+    pub(crate) fn style_at(&self, index: usize) -> (RGBA8, u32, FontToken) {
+        let mut style = (self.color, self.scale, self.font);
+        for span in self.spans.iter().filter(|span| span.start <= index && index < span.end) {
+            style =
+                (span.color.unwrap_or(style.0), span.scale.unwrap_or(style.1), span.font.unwrap_or(style.2));
+        }
+        style
+    }
+
+    /// Sets how many characters are shown for a typewriter effect that has been running for the
+    /// given number of seconds, revealing characters at the given rate per second.
+    pub fn set_reveal_time(&mut self, seconds: f32, characters_per_second: f32) {
+        self.reveal = Some((seconds * characters_per_second).max(0.0) as usize);
+    }
+
+    /// If every character in the string is shown.
+    pub fn is_revealed(&self) -> bool {
+        match self.reveal {
+            Some(reveal) => reveal >= self.string.chars().count(),
+            None => true,
+        }
+    }
+}
+
+/// Configuration settings for a box drawn behind text. The box is drawn just behind the text, at a
+/// depth 0.001 lower than the text.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextBackground {
+    /// Color of the box.
+    pub color: RGBA8,
+    /// Space between the text and the edges of the box. Units are measured in pixels.
+    pub padding: f32,
+    /// Radius of the box's corners. This is limited to half the box's smaller side. Units are
+    /// measured in pixels.
+    pub corner_radius: f32,
+}
+
+impl TextBackground {
+    pub fn new(color: RGBA8, padding: f32, corner_radius: f32) -> TextBackground {
+        TextBackground {
+            color,
+            padding,
+            corner_radius,
+        }
+    }
+}
+
+/// Configuration settings for a shadow drawn behind distance field text. Each glyph's shadow is
+/// computed from its distance field, so it costs one extra sprite per glyph. The shadow is drawn at
+/// a depth 0.0005 lower than the text, in front of any background.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextShadow {
+    /// Color of the shadow. Its alpha is the shadow's opacity at the glyph's edge.
+    pub color: RGBA8,
+    /// How far the shadow is moved from the text. Units are measured in pixels. A zero offset with
+    /// some spread makes a glow.
+    pub offset: Vector2<f32>,
+    /// How far the shadow fades out past the glyph's edge, from 0 to 1. At 0 the shadow has a hard
+    /// edge, and at 1 it fades out over the distance field's padding, about an eighth of the text's
+    /// scale.
+    pub spread: f32,
+}
+
+impl TextShadow {
+    pub fn new(color: RGBA8, offset: Vector2<f32>, spread: f32) -> TextShadow {
+        TextShadow {
+            color,
+            offset,
+            spread,
+        }
+    }
+}
+
+/// An interactive span of text. Use `Engine::text_hit_test` to find the link under a position.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextLink {
+    /// Index of the first character in the span.
+    pub start: usize,
+    /// Index one past the last character in the span.
+    pub end: usize,
+    /// Identifies the link when it's hit. Ids don't need to be unique.
+    pub id: u32,
+}
+
+impl TextLink {
+    pub fn new(range: Range<usize>, id: u32) -> TextLink {
+        TextLink {
+            start: range.start,
+            end: range.end,
+            id,
+        }
+    }
+}
+
+/// The laid out size of text, found with `Engine::text_measure`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextMetrics {
+    /// Bottom left corner of the box around every line. Units are measured in pixels.
+    pub pos: Vector2<f32>,
+    /// Size of the box around every line. Units are measured in pixels.
+    pub size: Vector2<f32>,
+    /// Lines from top to bottom.
+    pub lines: Vec<TextLineMetrics>,
+}
+
+/// The laid out size of a single line of text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLineMetrics {
+    /// Characters on the line, including the whitespace or newline that ended it.
+    pub range: Range<usize>,
+    /// Bottom left corner of the line, from its descent to its ascent. Units are measured in
+    /// pixels.
+    pub pos: Vector2<f32>,
+    /// Size of the line. The width covers each character's advance. Units are measured in pixels.
+    pub size: Vector2<f32>,
+    /// Height of the baseline. Units are measured in pixels.
+    pub baseline: f32,
+}
+
+/// Where a laid out character is, found with `Engine::text_glyphs`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextGlyph {
+    /// Index of the character in the string, counted in characters.
+    pub index: usize,
+    /// Bytes of the character in the string.
+    pub bytes: Range<usize>,
+    /// Bottom left corner of the character's box. Units are measured in pixels.
+    pub pos: Vector2<f32>,
+    /// Size of the character's box. The box covers the character's advance horizontally and its
+    /// line's ascent to descent vertically, so neighboring boxes touch. Newlines have no width.
+    /// Units are measured in pixels.
+    pub size: Vector2<f32>,
+    /// Height of the line's baseline. Characters on the same line share a baseline. Units are
+    /// measured in pixels.
+    pub baseline: f32,
+    /// If the character is laid out right to left.
+    pub rtl: bool,
+}
+
+/// A styled span of text. Styles left as None use the text's own style.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextSpan {
+    /// Index of the first character in the span.
+    pub start: usize,
+    /// Index one past the last character in the span.
+    pub end: usize,
+    /// Color of the span's characters.
+    pub color: Option<RGBA8>,
+    /// Scale of the span's characters.
+    pub scale: Option<u32>,
+    /// Font of the span's characters.
+    pub font: Option<FontToken>,
+}
+
+impl TextSpan {
+    /// Creates a span over the characters that doesn't change any style yet.
+    pub fn new(range: Range<usize>) -> TextSpan {
+        TextSpan {
+            start: range.start,
+            end: range.end,
+            color: None,
+            scale: None,
+            font: None,
+        }
+    }
+
+    /// Creates a span that only changes color.
+    pub fn colored(range: Range<usize>, color: RGBA8) -> TextSpan {
+        TextSpan {
+            color: Some(color),
+            ..TextSpan::new(range)
+        }
+    }
+
+    /// Parses the inside of a markup tag, returning an empty span with its style.
+    fn from_tag(tag: &str) -> Option<TextSpan> {
+        let mut span = TextSpan::new(0..0);
+        if let Some(scale) = tag.strip_prefix("scale=") {
+            span.scale = Some(scale.trim().parse().ok()?);
+        } else {
+            span.color = Some(parse_color(tag.strip_prefix("color=").unwrap_or(tag).trim())?);
+        }
+        Some(span)
+    }
+}
+
+/// Parses a color name or a hex code in the form #RRGGBB or #RRGGBBAA.
+fn parse_color(value: &str) -> Option<RGBA8> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+        let alpha = if hex.len() == 8 {
+            channel(6)?
+        } else {
+            255
+        };
+        return Some(RGBA8::new_raw(channel(0)?, channel(2)?, channel(4)?, alpha));
+    }
+    let color = match value.to_ascii_lowercase().as_str() {
+        "red" => RED,
+        "purple" => PURPLE,
+        "blue" => BLUE,
+        "green" => GREEN,
+        "yellow" => YELLOW,
+        "orange" => ORANGE,
+        "magenta" => MAGENTA,
+        "white" => WHITE,
+        "black" => BLACK,
+        _ => return None,
+    };
+    Some(color)
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markup() {
+        let mut text = Text::default();
+        text.set_markup("Pick up the <red>key <scale=32>now</></red>, a<<b <wave>!");
+
+        assert_eq!(text.string, "Pick up the key now, a<b <wave>!");
+        assert_eq!(
+            text.spans,
+            vec![
+                TextSpan::colored(12..19, RED),
+                TextSpan {
+                    scale: Some(32),
+                    ..TextSpan::new(16..19)
+                },
+            ]
+        );
+
+        text.set_markup("<#FF800080>open");
+        assert_eq!(text.spans, vec![TextSpan::colored(0..4, RGBA8::new_raw(255, 128, 0, 128))]);
     }
 }
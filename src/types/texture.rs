@@ -1,10 +1,17 @@
 use crate::texture::PIXEL_SIZE;
 use cgmath::*;
 
-/// Enumeration for all the loadable texture formats. Currently only PNG is supported.
+/// Enumeration for all the loadable texture formats.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum TextureFormat {
     PNG,
+    /// Baseline JPEG, with grayscale or YCbCr color. Progressive JPEG is unsupported.
+    JPEG,
+    /// Uncompressed BMP, including bit fields and palettes.
+    BMP,
+    /// TGA, with or without run length encoding.
+    TGA,
+    QOI,
 }
 
 /// Token to reference a texture with. Has basic configuration settings.
@@ -67,3 +74,61 @@ impl Texture {
         Ok(Texture(subset))
     }
 }
+
+/// How a texture is filtered when it's drawn at a different size than its pixels. Filters are
+/// ordered from cheapest to most expensive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TextureFilter {
+    /// Picks the closest pixel, keeping edges sharp. Best for pixel art.
+    Nearest,
+    /// Blends the closest pixels, smoothing the texture. Pixels from neighboring textures in the
+    /// atlas may bleed into the edges.
+    Linear,
+    /// Like Linear, but also blends between mipmaps so textures drawn well below their size don't
+    /// shimmer. Neighboring textures in the atlas bleed further at smaller sizes. This needs
+    /// mipmaps to be enabled with `Engine::texture_mipmaps`, and is the same as Linear otherwise.
+    /// Render targets have no mipmaps.
+    Trilinear,
+    /// Like Trilinear, but stays sharp when textures are drawn at steep angles or with uneven
+    /// scales. This is the same as Trilinear where anisotropic filtering isn't supported.
+    Anisotropic,
+}
+
+/// How a texture is repeated when a sprite is larger than the texture.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextureWrap {
+    /// The texture is stretched to fill the sprite.
+    Clamp,
+    /// The texture is drawn at its size in pixels and repeated to fill the sprite, starting from
+    /// the sprite's bottom left corner.
+    Repeat,
+    /// Like Repeat, but every other repetition is mirrored so the edges line up.
+    Mirror,
+}
+
+/// Settings for how a layer samples its textures.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextureSampling {
+    /// The default is Nearest.
+    pub filter: TextureFilter,
+    /// The default is Clamp.
+    pub wrap: TextureWrap,
+}
+
+impl Default for TextureSampling {
+    fn default() -> TextureSampling {
+        TextureSampling {
+            filter: TextureFilter::Nearest,
+            wrap: TextureWrap::Clamp,
+        }
+    }
+}
+
+impl TextureSampling {
+    pub fn new(filter: TextureFilter, wrap: TextureWrap) -> TextureSampling {
+        TextureSampling {
+            filter,
+            wrap,
+        }
+    }
+}
@@ -1,3 +1,9 @@
+use crate::math::AABB2D;
+use crate::texture::Image;
+use crate::TextureFormat;
+use cgmath::*;
+use core::fmt;
+
 /// Configuration settings for the window.
 #[derive(Debug, Clone, PartialEq)]
 pub struct WindowSettings {
@@ -7,6 +13,23 @@ pub struct WindowSettings {
     pub display_mode: DisplayMode,
     /// Vsync mode for the window.
     pub vsync: Vsync,
+    /// If the window has the platform's title bar and borders. Without them, window regions can
+    /// be used to move and resize the window. This has no effect on the web.
+    pub decorations: bool,
+    /// Samples per pixel for multisample anti-aliasing, usually 2, 4, or 8. 0 creates the window
+    /// without multisampling, and it can't be turned on later. Multisampling can be turned off and
+    /// on at runtime with `QualitySettings::multisampling`.
+    pub multisampling: u16,
+    /// Width and height of the texture atlas when the engine starts, in pixels. The atlas doubles
+    /// in size whenever a texture doesn't fit, up to the max size. Must be a power of two up to
+    /// 4096.
+    pub atlas_size: u32,
+    /// The largest the texture atlas can grow to, in pixels. Must be a power of two up to 4096,
+    /// and at least the atlas size.
+    pub atlas_max_size: u32,
+    /// The icon shown in the window's title bar and the taskbar. If this is set to None, the
+    /// platform's default icon is used. This has no effect on the web. The default is None.
+    pub icon: Option<WindowIcon>,
 }
 
 impl Default for WindowSettings {
@@ -19,10 +42,135 @@ impl Default for WindowSettings {
                 resizable: true,
             },
             vsync: Vsync::Disabled,
+            decorations: true,
+            multisampling: 0,
+            atlas_size: 1024,
+            atlas_max_size: 4096,
+            icon: None,
         }
     }
 }
 
+/// An icon for the window, as RGBA pixels with the top row first. Icons are usually square, like
+/// 32x32 or 64x64 pixels, and the platform scales them to fit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowIcon {
+    /// Pixels as RGBA bytes, 4 per pixel.
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowIcon {
+    pub fn new(rgba: Vec<u8>, width: u32, height: u32) -> WindowIcon {
+        WindowIcon {
+            rgba,
+            width,
+            height,
+        }
+    }
+
+    /// Decodes an icon from the bytes of an image file, like a PNG.
+    pub fn from_raw(bytes: &[u8], format: TextureFormat) -> WindowIcon {
+        let (rgba, width, height) = decode_rgba(bytes, format);
+        WindowIcon::new(rgba, width, height)
+    }
+}
+
+/// The cursor shown over the window.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WindowCursor {
+    /// One of the platform's cursors.
+    Shape(CursorShape),
+    /// No cursor is shown. The cursor still moves and sends input.
+    Hidden,
+    /// A custom image, drawn as a software cursor. The platform's cursor is hidden and the engine
+    /// draws the image at the cursor's position over everything else, so it follows the cursor a
+    /// frame behind and disappears when the cursor leaves the window. It's drawn at its size in
+    /// pixels, in the same units as cursor positions, and isn't included in screenshots or
+    /// recordings.
+    Image(CursorImage),
+}
+
+impl Default for WindowCursor {
+    fn default() -> WindowCursor {
+        WindowCursor::Shape(CursorShape::Default)
+    }
+}
+
+/// A platform cursor, by what it's used for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum CursorShape {
+    /// The usual arrow.
+    #[default]
+    Default,
+    /// A hand, for links and buttons.
+    Pointer,
+    /// An I-beam, for selecting text.
+    Text,
+    Crosshair,
+    /// For moving something in any direction.
+    Move,
+    /// An open hand, for something that can be dragged.
+    Grab,
+    /// A closed hand, for something being dragged.
+    Grabbing,
+    /// The program is busy and can't be interacted with.
+    Wait,
+    /// The program is busy but can still be interacted with.
+    Progress,
+    Help,
+    /// The action isn't allowed.
+    NotAllowed,
+    /// Resizing left and right.
+    ResizeHorizontal,
+    /// Resizing up and down.
+    ResizeVertical,
+    /// Resizing along the diagonal from the top left to the bottom right.
+    ResizeDiagonal,
+    /// Resizing along the diagonal from the top right to the bottom left.
+    ResizeAntiDiagonal,
+}
+
+/// A custom cursor image, as RGBA pixels with the top row first. It's drawn by the engine as a
+/// software cursor, see `WindowCursor::Image`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CursorImage {
+    /// Pixels as RGBA bytes, 4 per pixel.
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    /// The pixel of the image that sits at the cursor's position, measured from the top left
+    /// corner, like the tip of an arrow.
+    pub hotspot: Vector2<u32>,
+}
+
+impl CursorImage {
+    pub fn new(rgba: Vec<u8>, width: u32, height: u32, hotspot: Vector2<u32>) -> CursorImage {
+        CursorImage {
+            rgba,
+            width,
+            height,
+            hotspot,
+        }
+    }
+
+    /// Decodes a cursor from the bytes of an image file, like a PNG.
+    pub fn from_raw(bytes: &[u8], format: TextureFormat, hotspot: Vector2<u32>) -> CursorImage {
+        let (rgba, width, height) = decode_rgba(bytes, format);
+        CursorImage::new(rgba, width, height, hotspot)
+    }
+}
+
+fn decode_rgba(bytes: &[u8], format: TextureFormat) -> (Vec<u8>, u32, u32) {
+    let image = Image::from_raw(bytes, format);
+    let mut rgba = Vec::with_capacity(image.as_slice().len() * 4);
+    for pixel in image.as_slice() {
+        rgba.extend_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+    }
+    (rgba, image.width(), image.height())
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum DisplayMode {
     /// Normal windowed mode.
@@ -48,3 +196,252 @@ pub enum Vsync {
     /// Vsync will be enabled.
     Enabled,
 }
+
+/// Insets from the edges of the window that content shouldn't be drawn under, like display
+/// notches or TV overscan. Units are measured in logical pixels.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SafeArea {
+    /// Inset from the left edge of the window.
+    pub left: f32,
+    /// Inset from the right edge of the window.
+    pub right: f32,
+    /// Inset from the top edge of the window.
+    pub top: f32,
+    /// Inset from the bottom edge of the window.
+    pub bottom: f32,
+}
+
+impl SafeArea {
+    pub fn new(left: f32, right: f32, top: f32, bottom: f32) -> SafeArea {
+        SafeArea {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+}
+
+/// An area of the window that moves or resizes the window when pressed with the left cursor
+/// button, for windows without decorations.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WindowRegion {
+    /// Bounds of the region in the same coordinates as cursor events.
+    pub bounds: AABB2D,
+    /// What pressing the region does.
+    pub kind: WindowRegionKind,
+}
+
+impl WindowRegion {
+    pub fn new(bounds: AABB2D, kind: WindowRegionKind) -> WindowRegion {
+        WindowRegion {
+            bounds,
+            kind,
+        }
+    }
+}
+
+/// What pressing a window region does.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WindowRegionKind {
+    /// Moves the window, like a title bar.
+    Drag,
+    /// Resizes the window from the given edge.
+    Resize(ResizeEdge),
+}
+
+/// An edge or corner of the window.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResizeEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl ResizeEdge {
+    /// The horizontal and vertical sides moved by the edge. -1 is the left or top side, 1 is the
+    /// right or bottom side, and 0 is neither.
+    pub(crate) fn sides(&self) -> (i32, i32) {
+        match self {
+            ResizeEdge::Left => (-1, 0),
+            ResizeEdge::Right => (1, 0),
+            ResizeEdge::Top => (0, -1),
+            ResizeEdge::Bottom => (0, 1),
+            ResizeEdge::TopLeft => (-1, -1),
+            ResizeEdge::TopRight => (1, -1),
+            ResizeEdge::BottomLeft => (-1, 1),
+            ResizeEdge::BottomRight => (1, 1),
+        }
+    }
+}
+
+/// Where the window sits on the desktop, for reopening it where the user left it. Placements can
+/// be saved as text with `to_string` and read back with `parse`. Units are measured in physical
+/// pixels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowPlacement {
+    /// Name of the monitor the window is on, if the platform names monitors.
+    pub monitor: Option<String>,
+    /// Position of the window's top left corner, including decorations, relative to the top left
+    /// of the monitor.
+    pub position: Vector2<i32>,
+    /// Size of the window, excluding decorations.
+    pub size: Vector2<u32>,
+    /// If the window is maximized. The position and size are where the window returns to when it's
+    /// restored.
+    pub maximized: bool,
+}
+
+impl WindowPlacement {
+    /// Reads a placement written by `to_string`. Returns None if a value is missing or malformed.
+    pub fn parse(text: &str) -> Option<WindowPlacement> {
+        let mut monitor = None;
+        let (mut x, mut y, mut width, mut height, mut maximized) = (None, None, None, None, None);
+        for line in text.lines() {
+            let (key, value) = match line.find('=') {
+                Some(split) => (line[..split].trim(), &line[split + 1..]),
+                None => continue,
+            };
+            match key {
+                "monitor" => monitor = Some(value.to_string()),
+                "x" => x = Some(value.trim().parse().ok()?),
+                "y" => y = Some(value.trim().parse().ok()?),
+                "width" => width = Some(value.trim().parse().ok()?),
+                "height" => height = Some(value.trim().parse().ok()?),
+                "maximized" => maximized = Some(value.trim().parse().ok()?),
+                _ => {}
+            }
+        }
+        Some(WindowPlacement {
+            monitor,
+            position: Vector2::new(x?, y?),
+            size: Vector2::new(width?, height?),
+            maximized: maximized?,
+        })
+    }
+}
+
+impl fmt::Display for WindowPlacement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(monitor) = &self.monitor {
+            writeln!(f, "monitor={}", monitor.replace('\n', " "))?;
+        }
+        writeln!(f, "x={}", self.position.x)?;
+        writeln!(f, "y={}", self.position.y)?;
+        writeln!(f, "width={}", self.size.x)?;
+        writeln!(f, "height={}", self.size.y)?;
+        writeln!(f, "maximized={}", self.maximized)
+    }
+}
+
+/// How a virtual resolution is fit into the window.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScalePolicy {
+    /// Fills the window, stretching the frame if the aspect ratios don't match.
+    Stretch,
+    /// Scales the frame as large as it fits while keeping its aspect ratio. The rest of the window
+    /// is filled with the clear color.
+    Letterbox,
+    /// Scales the frame by the largest whole number that fits, so every virtual pixel covers the
+    /// same number of window pixels. The rest of the window is filled with the clear color. Windows
+    /// smaller than the resolution show it unscaled and cropped.
+    Integer,
+}
+
+/// A fixed size the frame is drawn at before being scaled to fit the window, like 320x180 for a
+/// pixel art game. Layers are laid out in virtual pixels instead of window pixels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VirtualResolution {
+    pub width: u32,
+    pub height: u32,
+    pub policy: ScalePolicy,
+}
+
+impl VirtualResolution {
+    /// Creates a virtual resolution. If the width or height is 0, this function will panic.
+    pub fn new(width: u32, height: u32, policy: ScalePolicy) -> VirtualResolution {
+        if width == 0 || height == 0 {
+            panic!("Neither width or height can be 0.");
+        }
+        VirtualResolution {
+            width,
+            height,
+            policy,
+        }
+    }
+
+    /// The size of the resolution in pixels.
+    pub fn size(&self) -> Vector2<f32> {
+        Vector2::new(self.width as f32, self.height as f32)
+    }
+
+    /// The area of the window the frame is scaled into, as the bottom left corner and size. Units
+    /// are measured in window pixels.
+    pub fn viewport(&self, window: Vector2<f32>) -> (Vector2<f32>, Vector2<f32>) {
+        let size = self.size();
+        let fit = (window.x / size.x).min(window.y / size.y);
+        let area = match self.policy {
+            ScalePolicy::Stretch => return (Vector2::zero(), window),
+            ScalePolicy::Letterbox => (size * fit).map(f32::round),
+            ScalePolicy::Integer => size * fit.floor().max(1.0),
+        };
+        (((window - area) / 2.0).map(f32::floor), area)
+    }
+
+    /// Converts a position in window pixels, measured from the center of the window, into virtual
+    /// pixels measured from the center of the frame.
+    pub fn to_virtual(&self, window: Vector2<f32>, pos: Vector2<f32>) -> Vector2<f32> {
+        let (offset, area) = self.viewport(window);
+        let size = self.size();
+        (pos + window / 2.0 - offset).div_element_wise(area).mul_element_wise(size) - size / 2.0
+    }
+
+    /// Converts a position in virtual pixels, measured from the center of the frame, into window
+    /// pixels measured from the center of the window. The inverse of `to_virtual`.
+    pub fn to_window(&self, window: Vector2<f32>, pos: Vector2<f32>) -> Vector2<f32> {
+        let (offset, area) = self.viewport(window);
+        let size = self.size();
+        (pos + size / 2.0).div_element_wise(size).mul_element_wise(area) + offset - window / 2.0
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtual_viewport() {
+        let window = Vector2::new(1000.0, 700.0);
+        let stretch = VirtualResolution::new(320, 180, ScalePolicy::Stretch);
+        assert_eq!(stretch.viewport(window), (Vector2::new(0.0, 0.0), window));
+        let letterbox = VirtualResolution::new(320, 180, ScalePolicy::Letterbox);
+        assert_eq!(letterbox.viewport(window), (Vector2::new(0.0, 68.0), Vector2::new(1000.0, 563.0)));
+        let integer = VirtualResolution::new(320, 180, ScalePolicy::Integer);
+        assert_eq!(integer.viewport(window), (Vector2::new(20.0, 80.0), Vector2::new(960.0, 540.0)));
+        assert_eq!(integer.to_virtual(window, Vector2::new(0.0, 0.0)), Vector2::new(0.0, 0.0));
+        assert_eq!(integer.to_virtual(window, Vector2::new(480.0, -270.0)), Vector2::new(160.0, -90.0));
+        assert_eq!(integer.to_window(window, Vector2::new(160.0, -90.0)), Vector2::new(480.0, -270.0));
+    }
+
+    #[test]
+    fn placement_text() {
+        let placement = WindowPlacement {
+            monitor: Some(String::from("DELL U2720Q = 2")),
+            position: Vector2::new(-40, 120),
+            size: Vector2::new(1280, 720),
+            maximized: true,
+        };
+        assert_eq!(WindowPlacement::parse(&placement.to_string()), Some(placement));
+        assert_eq!(WindowPlacement::parse("x=1\ny=2\nwidth=3\nmaximized=false"), None);
+        assert_eq!(WindowPlacement::parse("x=1\ny=2\nwidth=3\nheight=four\nmaximized=false"), None);
+    }
+}
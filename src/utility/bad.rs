@@ -12,7 +12,7 @@ pub struct UnsafeShared<T> {
 impl<T> UnsafeShared<T> {
     pub fn new(value: T) -> UnsafeShared<T> {
         let inner = Inner {
-            value: value,
+            value,
             count: 1,
         };
         UnsafeShared {
@@ -41,7 +41,7 @@ impl<T> Drop for UnsafeShared<T> {
         let inner = unsafe { &mut (*self.inner) };
         inner.count -= 1;
         if inner.count == 0 {
-            unsafe { Box::from_raw(self.inner) };
+            unsafe { drop(Box::from_raw(self.inner)) };
         }
     }
 }
@@ -1 +1,3 @@
 pub mod bad;
+pub mod pool;
+pub mod random;
@@ -0,0 +1,208 @@
+/// Refers to a value in a `Pool`. Handles are small and copyable. A handle to a released value
+/// stays invalid even after its slot is reused, since each reuse bumps the slot's generation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PoolHandle {
+    slot: u32,
+    generation: u32,
+}
+
+#[derive(Copy, Clone)]
+struct Slot {
+    /// The index of the slot's value, or None if the slot is free.
+    index: Option<u32>,
+    generation: u32,
+}
+
+/// A pool of values that are acquired and released often, like bullets or particles. Released
+/// slots are reused, so once the pool has grown to its peak size, acquiring and releasing values
+/// doesn't allocate. Values are kept packed in one slice for fast iteration, and releasing a value
+/// moves the last value into its place. With `Pool<Sprite>`, the slice can be passed straight to
+/// `Layer::set_sprites`.
+pub struct Pool<T> {
+    values: Vec<T>,
+    /// The slot of each value.
+    owners: Vec<u32>,
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Pool<T> {
+        Pool::new()
+    }
+}
+
+impl<T> Pool<T> {
+    pub fn new() -> Pool<T> {
+        Pool::with_capacity(0)
+    }
+
+    /// Creates a pool with room for the given number of values before it needs to grow.
+    pub fn with_capacity(capacity: usize) -> Pool<T> {
+        Pool {
+            values: Vec::with_capacity(capacity),
+            owners: Vec::with_capacity(capacity),
+            slots: Vec::with_capacity(capacity),
+            free: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// The number of live values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Adds a value to the pool, reusing a released slot if there is one.
+    pub fn acquire(&mut self, value: T) -> PoolHandle {
+        let index = self.values.len() as u32;
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot as usize].index = Some(index);
+                slot
+            }
+            None => {
+                self.slots.push(Slot {
+                    index: Some(index),
+                    generation: 0,
+                });
+                self.slots.len() as u32 - 1
+            }
+        };
+        self.values.push(value);
+        self.owners.push(slot);
+        PoolHandle {
+            slot,
+            generation: self.slots[slot as usize].generation,
+        }
+    }
+
+    /// Removes the value from the pool and returns it. Returns None if the handle was already
+    /// released.
+    pub fn release(&mut self, handle: PoolHandle) -> Option<T> {
+        let index = self.index_of(handle)?;
+        Some(self.release_index(index))
+    }
+
+    /// If the handle's value is still in the pool.
+    pub fn contains(&self, handle: PoolHandle) -> bool {
+        self.index_of(handle).is_some()
+    }
+
+    pub fn get(&self, handle: PoolHandle) -> Option<&T> {
+        let index = self.index_of(handle)?;
+        Some(&self.values[index])
+    }
+
+    pub fn get_mut(&mut self, handle: PoolHandle) -> Option<&mut T> {
+        let index = self.index_of(handle)?;
+        Some(&mut self.values[index])
+    }
+
+    /// The live values, packed together. The order changes as values are released.
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// Iterates over the live values and their handles.
+    pub fn iter(&self) -> impl Iterator<Item = (PoolHandle, &T)> {
+        let slots = &self.slots;
+        self.owners.iter().zip(&self.values).map(move |(&slot, value)| (Self::handle(slots, slot), value))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (PoolHandle, &mut T)> {
+        let slots = &self.slots;
+        self.owners.iter().zip(&mut self.values).map(move |(&slot, value)| (Self::handle(slots, slot), value))
+    }
+
+    /// Releases every value the predicate returns false for, like bullets that went off screen.
+    pub fn retain<F: FnMut(&mut T) -> bool>(&mut self, mut keep: F) {
+        let mut index = 0;
+        while index < self.values.len() {
+            if keep(&mut self.values[index]) {
+                index += 1;
+            } else {
+                // The last value moves into this index, so it's checked next.
+                self.release_index(index);
+            }
+        }
+    }
+
+    /// Releases every value, keeping the memory for reuse.
+    pub fn clear(&mut self) {
+        while !self.values.is_empty() {
+            self.release_index(self.values.len() - 1);
+        }
+    }
+
+    /// The index of the handle's value in the packed values.
+    pub(crate) fn index_of(&self, handle: PoolHandle) -> Option<usize> {
+        let slot = self.slots.get(handle.slot as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.index.map(|index| index as usize)
+    }
+
+    fn release_index(&mut self, index: usize) -> T {
+        let value = self.values.swap_remove(index);
+        let slot = self.owners.swap_remove(index);
+        let released = &mut self.slots[slot as usize];
+        released.index = None;
+        released.generation = released.generation.wrapping_add(1);
+        self.free.push(slot);
+        if let Some(&moved) = self.owners.get(index) {
+            self.slots[moved as usize].index = Some(index as u32);
+        }
+        value
+    }
+
+    fn handle(slots: &[Slot], slot: u32) -> PoolHandle {
+        PoolHandle {
+            slot,
+            generation: slots[slot as usize].generation,
+        }
+    }
+}
+
+// ////////////////////////////////////////////////////////////////////////////
+// Tests
+// ////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles() {
+        let mut pool = Pool::new();
+        let a = pool.acquire('a');
+        let b = pool.acquire('b');
+        let c = pool.acquire('c');
+        assert_eq!(pool.release(a), Some('a'));
+        assert_eq!(pool.release(a), None);
+        // The last value moves into the released value's place.
+        assert_eq!(pool.as_slice(), &['c', 'b']);
+        assert_eq!(pool.get(c), Some(&'c'));
+
+        // The slot is reused, but the old handle stays released.
+        let d = pool.acquire('d');
+        assert!(!pool.contains(a));
+        assert_eq!(pool.get(d), Some(&'d'));
+        assert_eq!(pool.iter().map(|(handle, _)| handle).collect::<Vec<_>>(), vec![c, b, d]);
+
+        pool.retain(|value| *value != 'c');
+        assert_eq!(pool.as_slice(), &['d', 'b']);
+        assert_eq!(pool.get(b), Some(&'b'));
+        assert_eq!(pool.get(d), Some(&'d'));
+        pool.clear();
+        assert!(pool.is_empty() && !pool.contains(b));
+    }
+}
@@ -0,0 +1,33 @@
+/// Small xorshift generator for effects that need cheap randomness. Not suitable for anything
+/// that needs to be unpredictable.
+pub struct Random {
+    state: u32,
+}
+
+impl Random {
+    pub fn new(seed: u32) -> Random {
+        Random {
+            // Xorshift gets stuck at 0.
+            state: seed | 1,
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Returns a value in [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Returns a value in [-1, 1).
+    pub fn next_signed_f32(&mut self) -> f32 {
+        self.next_f32() * 2.0 - 1.0
+    }
+}